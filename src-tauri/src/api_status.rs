@@ -0,0 +1,244 @@
+use crate::settings::AppSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tracing::{error, info, warn};
+
+/// Default status page (Atlassian Statuspage summary API)
+pub const DEFAULT_STATUS_PAGE_URL: &str = "https://status.anthropic.com/api/v2/summary.json";
+
+/// How often the status page is fetched
+const CHECK_INTERVAL: Duration = Duration::from_secs(120);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An unresolved incident on the status page
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Incident {
+    pub id: String,
+    pub name: String,
+    /// "investigating", "identified", "monitoring"...
+    pub status: String,
+    /// "none", "minor", "major" or "critical"
+    pub impact: String,
+    /// Incident page, if the status page links one
+    pub url: Option<String>,
+    /// RFC 3339
+    pub started_at: String,
+}
+
+/// Overall API health as last reported by the status page
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiStatus {
+    /// "none" when all systems are operational, otherwise "minor", "major"
+    /// or "critical"
+    pub indicator: String,
+    /// e.g. "All Systems Operational"
+    pub description: String,
+    pub incidents: Vec<Incident>,
+}
+
+/// The parts of summary.json we use
+#[derive(Debug, Deserialize)]
+struct Summary {
+    status: SummaryStatus,
+    #[serde(default)]
+    incidents: Vec<SummaryIncident>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SummaryStatus {
+    indicator: String,
+    description: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SummaryIncident {
+    id: String,
+    name: String,
+    status: String,
+    #[serde(default)]
+    impact: String,
+    shortlink: Option<String>,
+    #[serde(default)]
+    created_at: String,
+}
+
+impl From<Summary> for ApiStatus {
+    fn from(summary: Summary) -> Self {
+        Self {
+            indicator: summary.status.indicator,
+            description: summary.status.description,
+            incidents: summary
+                .incidents
+                .into_iter()
+                .map(|incident| Incident {
+                    id: incident.id,
+                    name: incident.name,
+                    status: incident.status,
+                    impact: incident.impact,
+                    url: incident.shortlink,
+                    started_at: incident.created_at,
+                })
+                .collect(),
+        }
+    }
+}
+
+static API_STATUS: OnceLock<Mutex<Option<ApiStatus>>> = OnceLock::new();
+
+fn api_status() -> &'static Mutex<Option<ApiStatus>> {
+    API_STATUS.get_or_init(|| Mutex::new(None))
+}
+
+/// Latest status page reading; None before the first successful check or
+/// when checking is turned off
+pub fn current() -> Option<ApiStatus> {
+    api_status()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+fn fetch(url: &str) -> Result<ApiStatus, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let summary: Summary = client
+        .get(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?
+        .json()
+        .map_err(|e| format!("Unexpected status page response: {}", e))?;
+    Ok(summary.into())
+}
+
+/// Incidents in `next` that weren't open in `previous`
+fn new_incidents<'a>(previous: Option<&ApiStatus>, next: &'a ApiStatus) -> Vec<&'a Incident> {
+    let known: HashSet<&str> = previous
+        .map(|status| status.incidents.iter().map(|i| i.id.as_str()).collect())
+        .unwrap_or_default();
+    next.incidents
+        .iter()
+        .filter(|incident| !known.contains(incident.id.as_str()))
+        .collect()
+}
+
+/// Poll the configured status page in the background. Changes are emitted
+/// as `api-status-changed` and broadcast to WS clients as an "apiStatus"
+/// alert; a newly opened incident also gets a native notification.
+pub fn watch(app: AppHandle, alerts_tx: tokio::sync::broadcast::Sender<String>) {
+    thread::spawn(move || {
+        // Incidents already open at startup show in the banner without a
+        // notification
+        let mut is_first_check = true;
+
+        loop {
+            let url = AppSettings::load().status_page_url;
+            let next = if url.is_empty() {
+                None
+            } else {
+                match fetch(&url) {
+                    Ok(status) => Some(status),
+                    Err(e) => {
+                        // Keep the last reading; a flaky network isn't an outage
+                        warn!("Status page check failed: {}", e);
+                        thread::sleep(CHECK_INTERVAL);
+                        continue;
+                    }
+                }
+            };
+
+            let previous = {
+                let mut current = api_status().lock().unwrap_or_else(|e| e.into_inner());
+                std::mem::replace(&mut *current, next.clone())
+            };
+
+            if previous != next {
+                if let Some(status) = &next {
+                    info!("API status: {} ({})", status.description, status.indicator);
+                    if !is_first_check {
+                        for incident in new_incidents(previous.as_ref(), status) {
+                            notify_incident(&app, incident);
+                        }
+                    }
+                }
+                broadcast(&app, &alerts_tx, next.as_ref());
+            }
+
+            is_first_check = false;
+            thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+fn notify_incident(app: &AppHandle, incident: &Incident) {
+    crate::polling::show_native(
+        app,
+        &AppSettings::load(),
+        "Anthropic API incident",
+        &incident.name,
+        None,
+    );
+}
+
+fn broadcast(
+    app: &AppHandle,
+    alerts_tx: &tokio::sync::broadcast::Sender<String>,
+    status: Option<&ApiStatus>,
+) {
+    if let Err(e) = app.emit("api-status-changed", status) {
+        error!("Failed to emit api-status-changed: {}", e);
+    }
+
+    let ws_alert = serde_json::json!({
+        "kind": "apiStatus",
+        "title": "Anthropic API status",
+        "body": status.map(|s| s.description.as_str()).unwrap_or_default(),
+        "status": status,
+    });
+    if let Ok(json) = serde_json::to_string(&ws_alert) {
+        let _ = alerts_tx.send(json);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_incidents_and_new_incident_detection() {
+        let json = r#"{
+            "page": {"id": "p1", "name": "Anthropic"},
+            "status": {"indicator": "major", "description": "Partial System Outage"},
+            "components": [],
+            "incidents": [
+                {
+                    "id": "inc1",
+                    "name": "Elevated errors on Claude Opus",
+                    "status": "investigating",
+                    "impact": "major",
+                    "shortlink": "https://stspg.io/abc",
+                    "created_at": "2026-02-10T10:00:00.000Z",
+                    "incident_updates": []
+                }
+            ]
+        }"#;
+        let status: ApiStatus = serde_json::from_str::<Summary>(json).unwrap().into();
+        assert_eq!(status.indicator, "major");
+        assert_eq!(
+            status.incidents[0].url.as_deref(),
+            Some("https://stspg.io/abc")
+        );
+
+        assert_eq!(new_incidents(None, &status).len(), 1);
+        assert!(new_incidents(Some(&status), &status).is_empty());
+    }
+}
@@ -1,4 +1,6 @@
+use crate::secrets;
 use rand::Rng;
+use tracing::warn;
 
 /// Generate a random 32-character hex token for WebSocket authentication
 pub fn generate_token() -> String {
@@ -7,6 +9,26 @@ pub fn generate_token() -> String {
     bytes.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
+/// The desktop's auth token, persisted in the OS credential store so paired
+/// clients keep working across restarts. Falls back to a fresh in-memory
+/// token when the store is unavailable.
+pub fn load_or_create_token() -> String {
+    match secrets::get(secrets::AUTH_TOKEN_KEY) {
+        Ok(Some(token)) => return token,
+        Ok(None) => {}
+        Err(e) => {
+            warn!("{}; using a temporary token", e);
+            return generate_token();
+        }
+    }
+
+    let token = generate_token();
+    if let Err(e) = secrets::set(secrets::AUTH_TOKEN_KEY, &token) {
+        warn!("{}; token will change on restart", e);
+    }
+    token
+}
+
 /// Get the local network IP address (for QR code / connection info)
 pub fn get_local_ip() -> String {
     std::net::UdpSocket::bind("0.0.0.0:0")
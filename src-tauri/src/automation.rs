@@ -0,0 +1,44 @@
+//! Actions scripted from outside the app: c9watch:// links (which Shortcuts
+//! and Stream Deck can open) and, on macOS, AppleScript (see `scripting`).
+
+use crate::error::C9Error;
+use crate::settings::AppSettings;
+use crate::store::SessionStore;
+use tracing::{error, info};
+
+/// Sessions from the latest poll as JSON, the same shape the WS API sends
+pub fn sessions_json(store: &SessionStore) -> String {
+    serde_json::to_string(&*store.sessions()).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Bring the terminal or IDE running a session to the front. Focusing can
+/// take a moment (AppleScript, editor CLIs), so it runs on its own thread.
+pub fn focus_session(store: &SessionStore, session_id: &str) -> Result<(), C9Error> {
+    let session = store
+        .sessions()
+        .iter()
+        .find(|session| session.id == session_id)
+        .cloned()
+        .ok_or_else(|| C9Error::NotFound(format!("Session {} is not running", session_id)))?;
+
+    info!("Focusing session {} (PID {})", session.id, session.pid);
+    std::thread::spawn(move || {
+        if let Err(e) = crate::actions::open_session(session.pid, session.project_path) {
+            error!("Failed to focus session {}: {}", session.id, e);
+        }
+    });
+    Ok(())
+}
+
+/// Turn do-not-disturb on or off, or flip it when `enabled` is None.
+/// Returns the new state.
+pub fn set_do_not_disturb(enabled: Option<bool>) -> Result<bool, C9Error> {
+    let mut settings = AppSettings::load();
+    settings.do_not_disturb = enabled.unwrap_or(!settings.do_not_disturb);
+    settings.save()?;
+    info!(
+        "Do not disturb {}",
+        if settings.do_not_disturb { "on" } else { "off" }
+    );
+    Ok(settings.do_not_disturb)
+}
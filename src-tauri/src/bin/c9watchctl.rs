@@ -0,0 +1,288 @@
+//! Command-line client for a running c9watch, over its local control socket
+//! (see `local_socket`). Speaks the same JSON messages as the WS API.
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: c9watchctl <command> [--json]
+
+Commands:
+  list               Print running sessions
+  stop <pid>         Stop a session (add --force to SIGKILL it if SIGTERM isn't enough)
+  open <pid>         Focus the terminal or IDE running a session
+  watch              Print status changes as they happen until interrupted
+
+--json prints the server's JSON instead of a table (one object per line for watch).";
+
+/// Messages the server pushes on its own, which aren't replies to a request
+const PUSHES: &[&str] = &[
+    "sessionsUpdated",
+    "notification",
+    "alert",
+    "conversationUpdated",
+];
+
+struct Args {
+    command: String,
+    pid: Option<u32>,
+    json: bool,
+    force: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut positional = Vec::new();
+    let mut json = false;
+    let mut force = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--force" => force = true,
+            "-h" | "--help" => return Err(String::new()),
+            flag if flag.starts_with('-') => return Err(format!("Unknown option {}", flag)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let command = positional.next().ok_or_else(String::new)?;
+    let pid = match command.as_str() {
+        "list" | "watch" => None,
+        "stop" | "open" => {
+            let pid = positional
+                .next()
+                .ok_or_else(|| format!("{} needs a PID", command))?;
+            Some(pid.parse().map_err(|_| format!("Not a PID: {}", pid))?)
+        }
+        other => return Err(format!("Unknown command {}", other)),
+    };
+    if let Some(extra) = positional.next() {
+        return Err(format!("Unexpected argument {}", extra));
+    }
+    Ok(Args {
+        command,
+        pid,
+        json,
+        force,
+    })
+}
+
+/// One connection to the control socket, one JSON message per line
+struct Client {
+    reader: BufReader<Box<dyn Read>>,
+    writer: Box<dyn Write>,
+}
+
+impl Client {
+    #[cfg(unix)]
+    fn connect() -> io::Result<Self> {
+        let stream =
+            std::os::unix::net::UnixStream::connect(c9watch_lib::local_socket::socket_path())?;
+        Ok(Self {
+            reader: BufReader::new(Box::new(stream.try_clone()?)),
+            writer: Box::new(stream),
+        })
+    }
+
+    #[cfg(windows)]
+    fn connect() -> io::Result<Self> {
+        let pipe = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(c9watch_lib::local_socket::PIPE_NAME)?;
+        Ok(Self {
+            reader: BufReader::new(Box::new(pipe.try_clone()?)),
+            writer: Box::new(pipe),
+        })
+    }
+
+    fn send(&mut self, msg: &Value) -> Result<(), String> {
+        writeln!(self.writer, "{}", msg)
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| format!("Lost connection to c9watch: {}", e))
+    }
+
+    /// Next message from the server, or None once it closed the connection
+    fn receive(&mut self) -> Result<Option<Value>, String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => Ok(None),
+            Ok(_) => serde_json::from_str(&line)
+                .map(Some)
+                .map_err(|e| format!("Unexpected reply from c9watch: {}", e)),
+            Err(e) => Err(format!("Lost connection to c9watch: {}", e)),
+        }
+    }
+
+    /// Send a request and wait for its reply, skipping pushes in between
+    fn request(&mut self, msg: Value) -> Result<Value, String> {
+        self.send(&msg)?;
+        loop {
+            let reply = self.receive()?.ok_or("c9watch closed the connection")?;
+            match reply["type"].as_str() {
+                Some("error") => {
+                    return Err(format!(
+                        "{} ({})",
+                        reply["message"].as_str().unwrap_or("Request failed"),
+                        reply["code"].as_str().unwrap_or("other")
+                    ))
+                }
+                Some(kind) if PUSHES.contains(&kind) => continue,
+                _ => return Ok(reply.get("data").cloned().unwrap_or(reply)),
+            }
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("c9watchctl: {}\n", message);
+            }
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut client = match Client::connect() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("c9watchctl: can't reach c9watch (is it running?): {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(&mut client, &args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("c9watchctl: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(client: &mut Client, args: &Args) -> Result<(), String> {
+    match args.command.as_str() {
+        "list" => {
+            let sessions = client.request(json!({ "type": "getSessions" }))?;
+            if args.json {
+                println!("{}", sessions);
+            } else {
+                print_table(&sessions);
+            }
+        }
+        "stop" => {
+            let result = client.request(json!({
+                "type": "stopSession",
+                "pid": args.pid,
+                "force": args.force,
+            }))?;
+            if args.json {
+                println!("{}", result);
+            } else {
+                match result["outcome"].as_str() {
+                    Some("stillRunning") => {
+                        return Err(
+                            "Still running after SIGTERM; run again with --force".to_string()
+                        )
+                    }
+                    outcome => println!("{}", outcome.unwrap_or("stopped")),
+                }
+            }
+        }
+        "open" => {
+            let sessions = client.request(json!({ "type": "getSessions" }))?;
+            let session = sessions
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|session| session["pid"].as_u64() == args.pid.map(u64::from))
+                .ok_or_else(|| format!("No session with PID {}", args.pid.unwrap_or_default()))?;
+            client.request(json!({
+                "type": "openSession",
+                "pid": args.pid,
+                "projectPath": session["projectPath"],
+            }))?;
+        }
+        "watch" => watch(client, args.json)?,
+        _ => unreachable!("checked in parse_args"),
+    }
+    Ok(())
+}
+
+/// Print every status change (or every session list with --json) until the
+/// connection closes
+fn watch(client: &mut Client, json: bool) -> Result<(), String> {
+    let mut previous: HashMap<String, String> = HashMap::new();
+    let mut first = true;
+    let initial = client.request(json!({ "type": "getSessions" }))?;
+    let mut handle = |sessions: &Value| {
+        if json {
+            println!("{}", sessions);
+            return;
+        }
+        let mut current = HashMap::new();
+        for session in sessions.as_array().into_iter().flatten() {
+            let id = session["id"].as_str().unwrap_or_default().to_string();
+            let status = session["status"].as_str().unwrap_or_default().to_string();
+            let before = previous.remove(&id);
+            if first || before.as_deref() != Some(status.as_str()) {
+                println!(
+                    "{}  {:>7}  {:<16} {}",
+                    chrono::Local::now().format("%H:%M:%S"),
+                    session["pid"],
+                    status,
+                    display_name(session)
+                );
+            }
+            current.insert(id, status);
+        }
+        for id in previous.keys() {
+            println!("{}  ended {}", chrono::Local::now().format("%H:%M:%S"), id);
+        }
+        previous = current;
+        first = false;
+    };
+
+    handle(&initial);
+    while let Some(msg) = client.receive()? {
+        if msg["type"] == "sessionsUpdated" {
+            handle(&msg["data"]);
+        }
+    }
+    Ok(())
+}
+
+fn display_name(session: &Value) -> &str {
+    session["customTitle"]
+        .as_str()
+        .or_else(|| session["sessionName"].as_str())
+        .unwrap_or_default()
+}
+
+fn print_table(sessions: &Value) {
+    let sessions = sessions.as_array().cloned().unwrap_or_default();
+    if sessions.is_empty() {
+        println!("No running sessions");
+        return;
+    }
+    println!("{:>7}  {:<16} {:<24} NAME", "PID", "STATUS", "PROJECT");
+    for session in &sessions {
+        let project = session["projectPath"]
+            .as_str()
+            .and_then(|path| std::path::Path::new(path).file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        println!(
+            "{:>7}  {:<16} {:<24} {}",
+            session["pid"],
+            session["status"].as_str().unwrap_or_default(),
+            project,
+            display_name(session)
+        );
+    }
+}
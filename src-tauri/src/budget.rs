@@ -0,0 +1,286 @@
+use crate::settings::AppSettings;
+use crate::usage::{format_token_count, generate_report_between, UsageReport};
+use chrono::{Datelike, Duration as ChronoDuration, Local, NaiveDate};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Usage is re-aggregated at most this often (it scans transcripts on disk)
+const CHECK_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Percent-of-budget thresholds that trigger an alert
+const THRESHOLDS: [u32; 2] = [80, 100];
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Day,
+    Week,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetMetric {
+    Tokens,
+    Cost,
+}
+
+/// A budget threshold that was crossed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetAlert {
+    pub period: BudgetPeriod,
+    pub metric: BudgetMetric,
+    pub threshold_percent: u32,
+    pub used: f64,
+    pub limit: f64,
+}
+
+impl BudgetAlert {
+    pub fn title(&self) -> String {
+        let period = match self.period {
+            BudgetPeriod::Day => "Daily",
+            BudgetPeriod::Week => "Weekly",
+        };
+        if self.threshold_percent >= 100 {
+            format!("{} budget exceeded", period)
+        } else {
+            format!("{} budget at {}%", period, self.threshold_percent)
+        }
+    }
+
+    pub fn body(&self) -> String {
+        match self.metric {
+            BudgetMetric::Tokens => format!(
+                "💸 {} of {} tokens used",
+                format_token_count(self.used as u64),
+                format_token_count(self.limit as u64)
+            ),
+            BudgetMetric::Cost => format!("💸 ~${:.2} of ${:.2} spent", self.used, self.limit),
+        }
+    }
+}
+
+/// Watches aggregated usage against the budgets in settings and reports
+/// each threshold at most once per day/week
+pub struct BudgetMonitor {
+    last_check: Option<Instant>,
+    /// Keys like "Day:2026-02-10:Tokens:80" for alerts already fired
+    alerted: HashSet<String>,
+}
+
+impl BudgetMonitor {
+    pub fn new() -> Self {
+        Self {
+            last_check: None,
+            alerted: HashSet::new(),
+        }
+    }
+
+    /// Check budgets if due, returning any newly crossed thresholds
+    pub fn check(&mut self, settings: &AppSettings) -> Vec<BudgetAlert> {
+        let has_daily =
+            settings.daily_token_budget.is_some() || settings.daily_cost_budget.is_some();
+        let has_weekly =
+            settings.weekly_token_budget.is_some() || settings.weekly_cost_budget.is_some();
+        if !has_daily && !has_weekly {
+            return Vec::new();
+        }
+
+        if let Some(last) = self.last_check {
+            if last.elapsed() < CHECK_INTERVAL {
+                return Vec::new();
+            }
+        }
+        self.last_check = Some(Instant::now());
+
+        let today = Local::now().date_naive();
+        let week_start = start_of_week(today);
+        let mut alerts = Vec::new();
+
+        if has_daily {
+            let report = generate_report_between(today, today);
+            alerts.extend(self.evaluate(
+                BudgetPeriod::Day,
+                today,
+                &report,
+                settings.daily_token_budget,
+                settings.daily_cost_budget,
+            ));
+        }
+        if has_weekly {
+            let report = generate_report_between(week_start, today);
+            alerts.extend(self.evaluate(
+                BudgetPeriod::Week,
+                week_start,
+                &report,
+                settings.weekly_token_budget,
+                settings.weekly_cost_budget,
+            ));
+        }
+
+        alerts
+    }
+
+    fn evaluate(
+        &mut self,
+        period: BudgetPeriod,
+        period_start: NaiveDate,
+        report: &UsageReport,
+        token_budget: Option<u64>,
+        cost_budget: Option<f64>,
+    ) -> Vec<BudgetAlert> {
+        let mut checks = Vec::new();
+        if let Some(limit) = token_budget {
+            checks.push((
+                BudgetMetric::Tokens,
+                report.tokens.total() as f64,
+                limit as f64,
+            ));
+        }
+        if let Some(limit) = cost_budget {
+            checks.push((BudgetMetric::Cost, report.cost_usd, limit));
+        }
+
+        let mut alerts = Vec::new();
+        for (metric, used, limit) in checks {
+            if limit <= 0.0 {
+                continue;
+            }
+            let percent = used / limit * 100.0;
+
+            // Only report the highest threshold crossed, but mark lower ones as
+            // alerted too so jumping straight past 100% doesn't also fire 80%
+            let mut highest = None;
+            for threshold in THRESHOLDS {
+                if percent < threshold as f64 {
+                    continue;
+                }
+                let key = format!("{:?}:{}:{:?}:{}", period, period_start, metric, threshold);
+                if self.alerted.insert(key) {
+                    highest = Some(threshold);
+                }
+            }
+
+            if let Some(threshold_percent) = highest {
+                alerts.push(BudgetAlert {
+                    period,
+                    metric,
+                    threshold_percent,
+                    used,
+                    limit,
+                });
+            }
+        }
+
+        alerts
+    }
+}
+
+impl Default for BudgetMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Monday of the week containing `date`
+fn start_of_week(date: NaiveDate) -> NaiveDate {
+    date - ChronoDuration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage::TokenCounts;
+
+    fn report_with_tokens(output_tokens: u64) -> UsageReport {
+        UsageReport {
+            tokens: TokenCounts {
+                output_tokens,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_start_of_week_is_monday() {
+        // 2026-02-12 is a Thursday
+        assert_eq!(start_of_week(date("2026-02-12")), date("2026-02-09"));
+        assert_eq!(start_of_week(date("2026-02-09")), date("2026-02-09"));
+    }
+
+    #[test]
+    fn test_thresholds_fire_once() {
+        let mut monitor = BudgetMonitor::new();
+        let day = date("2026-02-10");
+
+        let below = monitor.evaluate(
+            BudgetPeriod::Day,
+            day,
+            &report_with_tokens(700),
+            Some(1000),
+            None,
+        );
+        assert!(below.is_empty());
+
+        let warn = monitor.evaluate(
+            BudgetPeriod::Day,
+            day,
+            &report_with_tokens(850),
+            Some(1000),
+            None,
+        );
+        assert_eq!(warn.len(), 1);
+        assert_eq!(warn[0].threshold_percent, 80);
+
+        let again = monitor.evaluate(
+            BudgetPeriod::Day,
+            day,
+            &report_with_tokens(900),
+            Some(1000),
+            None,
+        );
+        assert!(again.is_empty());
+
+        let over = monitor.evaluate(
+            BudgetPeriod::Day,
+            day,
+            &report_with_tokens(1200),
+            Some(1000),
+            None,
+        );
+        assert_eq!(over.len(), 1);
+        assert_eq!(over[0].threshold_percent, 100);
+    }
+
+    #[test]
+    fn test_jumping_past_100_reports_only_exceeded() {
+        let mut monitor = BudgetMonitor::new();
+        let day = date("2026-02-10");
+
+        let alerts = monitor.evaluate(
+            BudgetPeriod::Day,
+            day,
+            &report_with_tokens(5000),
+            Some(1000),
+            None,
+        );
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].threshold_percent, 100);
+
+        // A new day resets the alerts
+        let next_day = monitor.evaluate(
+            BudgetPeriod::Day,
+            date("2026-02-11"),
+            &report_with_tokens(900),
+            Some(1000),
+            None,
+        );
+        assert_eq!(next_day.len(), 1);
+    }
+}
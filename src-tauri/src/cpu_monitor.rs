@@ -0,0 +1,133 @@
+use crate::polling::Session;
+use crate::settings::AppSettings;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// CPU use that counts as a pegged core. An agent waiting on the API sits
+/// near zero, so this only holds for long while something is spinning.
+const PEGGED_PERCENT: f32 = 90.0;
+
+/// A session whose process has kept a core busy for too long
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CpuAlert {
+    pub session_id: String,
+    pub session_name: String,
+    pub pid: u32,
+    pub cpu_percent: f32,
+    pub minutes: u32,
+}
+
+impl CpuAlert {
+    pub fn title(&self) -> String {
+        format!("{} is using a full CPU core", self.session_name)
+    }
+
+    pub fn body(&self) -> String {
+        format!(
+            "🔥 {:.0}% CPU for {} minutes; the CLI may be stuck (PID {})",
+            self.cpu_percent, self.minutes, self.pid
+        )
+    }
+}
+
+/// Watches each session's CPU use and reports one that stays pegged for
+/// `cpu_alert_minutes`, once per busy stretch
+#[derive(Default)]
+pub struct CpuMonitor {
+    /// When each session's current busy stretch started
+    busy_since: HashMap<String, Instant>,
+    /// Sessions already alerted about in their current busy stretch
+    alerted: HashSet<String>,
+}
+
+impl CpuMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record this cycle's CPU use, returning sessions that just crossed the limit
+    pub fn check(
+        &mut self,
+        sessions: &[Session],
+        settings: &AppSettings,
+        now: Instant,
+    ) -> Vec<CpuAlert> {
+        let Some(minutes) = settings.cpu_alert_minutes.filter(|&m| m > 0) else {
+            self.busy_since.clear();
+            self.alerted.clear();
+            return Vec::new();
+        };
+        let limit = Duration::from_secs(minutes as u64 * 60);
+
+        let mut alerts = Vec::new();
+        for session in sessions {
+            if session.cpu_percent < PEGGED_PERCENT {
+                self.busy_since.remove(&session.id);
+                self.alerted.remove(&session.id);
+                continue;
+            }
+            let since = *self.busy_since.entry(session.id.clone()).or_insert(now);
+            if now.duration_since(since) >= limit && self.alerted.insert(session.id.clone()) {
+                alerts.push(CpuAlert {
+                    session_id: session.id.clone(),
+                    session_name: session.session_name.clone(),
+                    pid: session.pid,
+                    cpu_percent: session.cpu_percent,
+                    minutes,
+                });
+            }
+        }
+
+        // Forget sessions that have ended
+        let current: HashSet<&str> = sessions.iter().map(|s| s.id.as_str()).collect();
+        self.busy_since
+            .retain(|id, _| current.contains(id.as_str()));
+        self.alerted.retain(|id| current.contains(id.as_str()));
+
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polling::tests::test_session;
+    use crate::session::SessionStatus;
+
+    #[test]
+    fn test_alerts_once_per_busy_stretch() {
+        let settings = AppSettings {
+            cpu_alert_minutes: Some(5),
+            ..Default::default()
+        };
+        let mut session = test_session(SessionStatus::Working, 0);
+        session.cpu_percent = 99.5;
+        let mut monitor = CpuMonitor::new();
+        let start = Instant::now();
+        let at = |minutes: u64| start + Duration::from_secs(minutes * 60);
+
+        assert!(monitor
+            .check(&[session.clone()], &settings, at(0))
+            .is_empty());
+        assert!(monitor
+            .check(&[session.clone()], &settings, at(4))
+            .is_empty());
+        let alerts = monitor.check(&[session.clone()], &settings, at(5));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].minutes, 5);
+        assert!(monitor
+            .check(&[session.clone()], &settings, at(9))
+            .is_empty());
+
+        // Settling down starts a new stretch
+        let mut quiet = session.clone();
+        quiet.cpu_percent = 2.0;
+        assert!(monitor.check(&[quiet], &settings, at(10)).is_empty());
+        assert!(monitor
+            .check(&[session.clone()], &settings, at(11))
+            .is_empty());
+        assert_eq!(monitor.check(&[session], &settings, at(16)).len(), 1);
+    }
+}
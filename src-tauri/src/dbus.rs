@@ -0,0 +1,189 @@
+use crate::error::C9Error;
+use crate::polling::Session;
+use crate::store::SessionStore;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use zbus::object_server::SignalEmitter;
+use zbus::{fdo, interface};
+
+/// Well-known bus name, also used as the interface name
+pub const SERVICE_NAME: &str = "org.c9watch.Monitor";
+pub const OBJECT_PATH: &str = "/org/c9watch/Monitor";
+
+/// `org.c9watch.Monitor` on the session bus, for desktop extensions (GNOME
+/// Shell, Waybar, Polybar) that would rather not speak WebSocket
+struct Monitor {
+    store: Arc<SessionStore>,
+}
+
+#[interface(name = "org.c9watch.Monitor")]
+impl Monitor {
+    /// Sessions from the latest poll, as the same JSON the WS API sends
+    async fn list_sessions(&self) -> fdo::Result<String> {
+        let sessions = self.store.current().await;
+        serde_json::to_string(&*sessions).map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Number of sessions per status, e.g. {"Working": 2, "NeedsPermission": 1}
+    async fn counts(&self) -> HashMap<String, u32> {
+        let mut counts = HashMap::new();
+        for session in self.store.current().await.iter() {
+            *counts.entry(status_name(session)).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// SIGTERM the session's process and wait for it to exit (SIGKILL too
+    /// with `force`). Returns the StopResult as JSON.
+    async fn stop(&self, pid: u32, force: bool) -> fdo::Result<String> {
+        // Only a monitored session, not any PID the caller names
+        self.session(pid)?;
+        let result =
+            tauri::async_runtime::spawn_blocking(move || crate::actions::stop_session(pid, force))
+                .await
+                .map_err(|e| fdo::Error::Failed(e.to_string()))??;
+        self.store.request_refresh();
+        serde_json::to_string(&result).map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Focus the terminal or IDE window running the session
+    async fn open(&self, pid: u32) -> fdo::Result<()> {
+        let project_path = self.session(pid)?.project_path;
+        tauri::async_runtime::spawn_blocking(move || {
+            crate::actions::open_session(pid, project_path)
+        })
+        .await
+        .map_err(|e| fdo::Error::Failed(e.to_string()))??;
+        Ok(())
+    }
+
+    /// A session's status changed. `previous` is empty for a new session
+    /// and `status` is empty once it has ended.
+    #[zbus(signal)]
+    async fn status_changed(
+        emitter: &SignalEmitter<'_>,
+        session_id: &str,
+        pid: u32,
+        status: &str,
+        previous: &str,
+    ) -> zbus::Result<()>;
+}
+
+impl Monitor {
+    /// The session with this PID from the latest poll
+    fn session(&self, pid: u32) -> Result<Session, C9Error> {
+        self.store
+            .sessions()
+            .iter()
+            .find(|session| session.pid == pid)
+            .cloned()
+            .ok_or_else(|| C9Error::NotFound(format!("No session with PID {}", pid)))
+    }
+}
+
+impl From<C9Error> for fdo::Error {
+    fn from(error: C9Error) -> Self {
+        match error {
+            C9Error::NotFound(message) => fdo::Error::UnknownObject(message),
+            C9Error::PermissionDenied(message) => fdo::Error::AccessDenied(message),
+            C9Error::ParseError(message) => fdo::Error::InvalidArgs(message),
+            C9Error::Io(e) => fdo::Error::IOError(e.to_string()),
+            error => fdo::Error::Failed(error.to_string()),
+        }
+    }
+}
+
+/// Status as it appears in the JSON, e.g. "NeedsPermission"
+fn status_name(session: &Session) -> String {
+    serde_json::to_value(&session.status)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Claim the bus name and emit `StatusChanged` after every poll. Logs and
+/// gives up if there's no session bus (e.g. a headless machine).
+pub fn start(store: Arc<SessionStore>) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve(store).await {
+            error!("DBus service unavailable: {}", e);
+        }
+    });
+}
+
+async fn serve(store: Arc<SessionStore>) -> zbus::Result<()> {
+    let monitor = Monitor {
+        store: store.clone(),
+    };
+    let connection = zbus::connection::Builder::session()?
+        .name(SERVICE_NAME)?
+        .serve_at(OBJECT_PATH, monitor)?
+        .build()
+        .await?;
+    info!("DBus service {} ready", SERVICE_NAME);
+
+    let interface = connection
+        .object_server()
+        .interface::<_, Monitor>(OBJECT_PATH)
+        .await?;
+    let mut snapshots = store.subscribe();
+    // Session ID -> (PID, status name) as of the last poll
+    let mut previous: HashMap<String, (u32, String)> = HashMap::new();
+
+    while snapshots.changed().await.is_ok() {
+        let sessions = snapshots.borrow_and_update().sessions.clone();
+        let mut current = HashMap::new();
+        for session in sessions.iter() {
+            let status = status_name(session);
+            let before = previous
+                .remove(&session.id)
+                .map(|(_, status)| status)
+                .unwrap_or_default();
+            if before != status {
+                let emitted = Monitor::status_changed(
+                    interface.signal_emitter(),
+                    &session.id,
+                    session.pid,
+                    &status,
+                    &before,
+                )
+                .await;
+                log_emit_failure(emitted);
+            }
+            current.insert(session.id.clone(), (session.pid, status));
+        }
+        // Whatever is left didn't show up in this poll
+        for (session_id, (pid, before)) in previous {
+            let emitted =
+                Monitor::status_changed(interface.signal_emitter(), &session_id, pid, "", &before)
+                    .await;
+            log_emit_failure(emitted);
+        }
+        previous = current;
+    }
+    Ok(())
+}
+
+/// A signal that couldn't be sent (e.g. the bus is briefly congested) is
+/// dropped; the next poll's changes still go out
+fn log_emit_failure(emitted: zbus::Result<()>) {
+    if let Err(e) = emitted {
+        warn!("Failed to emit StatusChanged: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_map_to_fdo_errors() {
+        let not_found = fdo::Error::from(C9Error::NotFound("gone".to_string()));
+        assert!(matches!(not_found, fdo::Error::UnknownObject(message) if message == "gone"));
+        let denied = fdo::Error::from(C9Error::PermissionDenied("no".to_string()));
+        assert!(matches!(denied, fdo::Error::AccessDenied(_)));
+        let other = fdo::Error::from(C9Error::ProcessError("kill failed".to_string()));
+        assert!(matches!(other, fdo::Error::Failed(_)));
+    }
+}
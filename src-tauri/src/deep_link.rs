@@ -0,0 +1,129 @@
+use crate::store::SessionStore;
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+/// URL scheme registered for the app (see `plugins.deep-link` in tauri.conf.json)
+pub const SCHEME: &str = "c9watch";
+
+/// Where a c9watch:// URL points
+#[derive(Debug, PartialEq)]
+pub enum DeepLink {
+    /// `c9watch://session/<id>`
+    Session(String),
+    /// `c9watch://focus/<id>`: bring the session's terminal to the front
+    /// without showing c9watch
+    Focus(String),
+    /// `c9watch://dnd` toggles do-not-disturb, `c9watch://dnd/on` and
+    /// `c9watch://dnd/off` set it
+    DoNotDisturb(Option<bool>),
+}
+
+/// Parse a c9watch:// URL; None for other schemes or unknown paths
+pub fn parse(url: &str) -> Option<DeepLink> {
+    let rest = url.strip_prefix(SCHEME)?.strip_prefix("://")?;
+    // Ignore any query or fragment a chat app may have tacked on
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let mut parts = rest.trim_end_matches('/').split('/');
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some("session"), Some(id), None) if !id.is_empty() => {
+            Some(DeepLink::Session(id.to_string()))
+        }
+        (Some("focus"), Some(id), None) if !id.is_empty() => Some(DeepLink::Focus(id.to_string())),
+        (Some("dnd"), None, None) => Some(DeepLink::DoNotDisturb(None)),
+        (Some("dnd"), Some("on"), None) => Some(DeepLink::DoNotDisturb(Some(true))),
+        (Some("dnd"), Some("off"), None) => Some(DeepLink::DoNotDisturb(Some(false))),
+        _ => None,
+    }
+}
+
+/// Session a link asked for before the frontend was listening (the app was
+/// launched by the link)
+static PENDING_SESSION: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+
+fn pending_session() -> &'static Mutex<Option<String>> {
+    PENDING_SESSION.get_or_init(|| Mutex::new(None))
+}
+
+/// Take the session a launch link pointed at, if the frontend hasn't yet
+pub fn take_pending_session() -> Option<String> {
+    pending_session()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+}
+
+/// Bring the main window forward and point it at the linked session.
+/// `at_launch` links are held for the frontend to pick up once it loads.
+pub fn open(app: &AppHandle, url: &str, at_launch: bool) {
+    let Some(link) = parse(url) else {
+        warn!("Ignoring unrecognized link: {}", url);
+        return;
+    };
+    info!("Opening link: {}", url);
+
+    match link {
+        DeepLink::Session(session_id) => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            if let Some(popover) = app.get_webview_window(crate::popover::LABEL) {
+                let _ = popover.hide();
+            }
+
+            if at_launch {
+                *pending_session().lock().unwrap_or_else(|e| e.into_inner()) = Some(session_id);
+            } else if let Err(e) = app.emit("open-session-link", session_id) {
+                warn!("Failed to emit open-session-link: {}", e);
+            }
+        }
+        // Automation links act in the background and leave c9watch where it is
+        DeepLink::Focus(session_id) => {
+            let store = app.state::<Arc<SessionStore>>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                // A link that launched the app arrives before the first poll
+                store.current().await;
+                if let Err(e) = crate::automation::focus_session(&store, &session_id) {
+                    warn!("{}", e);
+                }
+            });
+        }
+        DeepLink::DoNotDisturb(enabled) => {
+            if let Err(e) = crate::automation::set_do_not_disturb(enabled) {
+                warn!("Failed to change do not disturb: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_session_links() {
+        assert_eq!(
+            parse("c9watch://session/abc-123"),
+            Some(DeepLink::Session("abc-123".to_string()))
+        );
+        assert_eq!(
+            parse("c9watch://session/abc-123/?utm_source=slack"),
+            Some(DeepLink::Session("abc-123".to_string()))
+        );
+        assert_eq!(parse("c9watch://session/"), None);
+        assert_eq!(
+            parse("c9watch://focus/abc-123"),
+            Some(DeepLink::Focus("abc-123".to_string()))
+        );
+        assert_eq!(parse("c9watch://dnd"), Some(DeepLink::DoNotDisturb(None)));
+        assert_eq!(
+            parse("c9watch://dnd/off"),
+            Some(DeepLink::DoNotDisturb(Some(false)))
+        );
+        assert_eq!(parse("c9watch://dnd/maybe"), None);
+        assert_eq!(parse("c9watch://settings"), None);
+        assert_eq!(parse("https://session/abc-123"), None);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::environment::find_claude_binary;
+use crate::network::AccessPolicy;
+use crate::settings::AppSettings;
+use chrono::Utc;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tauri::plugin::PermissionState;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One self-diagnostic check and, when it didn't pass, how to fix it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticCheck {
+    /// Stable identifier the UI can key on (e.g. "claudeBinary")
+    pub id: &'static str,
+    pub title: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl DiagnosticCheck {
+    fn pass(id: &'static str, title: &'static str, detail: String) -> Self {
+        Self {
+            id,
+            title,
+            status: CheckStatus::Pass,
+            detail,
+            fix: None,
+        }
+    }
+
+    fn problem(
+        id: &'static str,
+        title: &'static str,
+        status: CheckStatus,
+        detail: String,
+        fix: &str,
+    ) -> Self {
+        Self {
+            id,
+            title,
+            status,
+            detail,
+            fix: Some(fix.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsReport {
+    /// False if any check failed (warnings don't count)
+    pub ok: bool,
+    pub checks: Vec<DiagnosticCheck>,
+    /// RFC 3339
+    pub generated_at: String,
+}
+
+/// Run every check. `server_running` says whether our own web server holds
+/// the port in `access` (in which case it can't be re-bound to test it).
+pub fn run_diagnostics(
+    app: &AppHandle,
+    access: &AccessPolicy,
+    server_running: bool,
+) -> DiagnosticsReport {
+    let home = dirs::home_dir().unwrap_or_default();
+    let claude_dir = home.join(".claude");
+
+    let checks = vec![
+        check_claude_binary(),
+        check_projects_dir(&claude_dir.join("projects")),
+        check_json_file(
+            "claudeSettings",
+            "Claude Code settings.json",
+            &claude_dir.join("settings.json"),
+            |content| serde_json::from_str::<serde_json::Value>(content).map(|_| ()),
+        ),
+        check_json_file(
+            "appSettings",
+            "c9watch settings",
+            &claude_dir.join("session-monitor-settings.json"),
+            |content| serde_json::from_str::<AppSettings>(content).map(|_| ()),
+        ),
+        check_notifications(app),
+        check_server_port(access, server_running),
+    ];
+
+    DiagnosticsReport {
+        ok: checks.iter().all(|c| c.status != CheckStatus::Fail),
+        checks,
+        generated_at: Utc::now().to_rfc3339(),
+    }
+}
+
+fn check_claude_binary() -> DiagnosticCheck {
+    const ID: &str = "claudeBinary";
+    const TITLE: &str = "Claude Code CLI";
+    match find_claude_binary() {
+        Some(path) => DiagnosticCheck::pass(ID, TITLE, format!("Found at {}", path.display())),
+        None => DiagnosticCheck::problem(
+            ID,
+            TITLE,
+            CheckStatus::Fail,
+            "The claude binary was not found on PATH or in common install locations".to_string(),
+            "Install Claude Code (npm install -g @anthropic-ai/claude-code) or make sure it is on your PATH",
+        ),
+    }
+}
+
+fn check_projects_dir(projects_dir: &Path) -> DiagnosticCheck {
+    const ID: &str = "projectsDir";
+    const TITLE: &str = "Session transcripts";
+    match fs::read_dir(projects_dir) {
+        Ok(entries) => {
+            let count = entries.filter_map(|e| e.ok()).filter(|e| e.path().is_dir()).count();
+            DiagnosticCheck::pass(
+                ID,
+                TITLE,
+                format!("{} readable ({} projects)", projects_dir.display(), count),
+            )
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => DiagnosticCheck::problem(
+            ID,
+            TITLE,
+            CheckStatus::Warn,
+            format!("{} does not exist yet", projects_dir.display()),
+            "Run a Claude Code session once so it creates its projects directory",
+        ),
+        Err(e) => DiagnosticCheck::problem(
+            ID,
+            TITLE,
+            CheckStatus::Fail,
+            format!("Cannot read {}: {}", projects_dir.display(), e),
+            "Check the directory's permissions (on macOS, grant c9watch Full Disk Access if prompted)",
+        ),
+    }
+}
+
+/// A missing file is fine (defaults apply); an unparseable one is not
+fn check_json_file(
+    id: &'static str,
+    title: &'static str,
+    path: &Path,
+    parse: fn(&str) -> serde_json::Result<()>,
+) -> DiagnosticCheck {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return DiagnosticCheck::pass(
+                id,
+                title,
+                format!("{} not present (using defaults)", path.display()),
+            );
+        }
+        Err(e) => {
+            return DiagnosticCheck::problem(
+                id,
+                title,
+                CheckStatus::Fail,
+                format!("Cannot read {}: {}", path.display(), e),
+                "Check the file's permissions",
+            );
+        }
+    };
+
+    match parse(&content) {
+        Ok(()) => DiagnosticCheck::pass(id, title, format!("{} parses", path.display())),
+        Err(e) => DiagnosticCheck::problem(
+            id,
+            title,
+            CheckStatus::Fail,
+            format!("{} is not valid: {}", path.display(), e),
+            "Fix the JSON syntax error, or move the file aside to fall back to defaults",
+        ),
+    }
+}
+
+fn check_notifications(app: &AppHandle) -> DiagnosticCheck {
+    const ID: &str = "notifications";
+    const TITLE: &str = "Notification permission";
+    match app.notification().permission_state() {
+        Ok(PermissionState::Granted) => {
+            DiagnosticCheck::pass(ID, TITLE, "Notifications are allowed".to_string())
+        }
+        Ok(PermissionState::Denied) => DiagnosticCheck::problem(
+            ID,
+            TITLE,
+            CheckStatus::Warn,
+            "Notifications are blocked".to_string(),
+            "Allow notifications for c9watch in your system settings",
+        ),
+        Ok(_) => DiagnosticCheck::problem(
+            ID,
+            TITLE,
+            CheckStatus::Warn,
+            "Notification permission has not been granted yet".to_string(),
+            "Accept the notification prompt, or enable c9watch in your system settings",
+        ),
+        Err(e) => DiagnosticCheck::problem(
+            ID,
+            TITLE,
+            CheckStatus::Warn,
+            format!("Could not read notification permission: {}", e),
+            "Check notification settings for c9watch in your system settings",
+        ),
+    }
+}
+
+fn check_server_port(access: &AccessPolicy, server_running: bool) -> DiagnosticCheck {
+    const ID: &str = "serverPort";
+    const TITLE: &str = "Web server port";
+    let addr = access.bind_addr;
+
+    if server_running {
+        return DiagnosticCheck::pass(ID, TITLE, format!("Listening on {}", addr));
+    }
+    match std::net::TcpListener::bind(addr) {
+        Ok(_) => DiagnosticCheck::problem(
+            ID,
+            TITLE,
+            CheckStatus::Warn,
+            format!("{} is free but the server is not running", addr),
+            "Restart the server from settings",
+        ),
+        Err(e) => DiagnosticCheck::problem(
+            ID,
+            TITLE,
+            CheckStatus::Fail,
+            format!("Cannot bind {}: {}", addr, e),
+            "Another program is using this port, or the bind address in settings is not on this machine",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_file_check() {
+        let dir = std::env::temp_dir().join(format!("c9watch-doctor-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let parse = |content: &str| serde_json::from_str::<serde_json::Value>(content).map(|_| ());
+        let missing = check_json_file("claudeSettings", "t", &dir.join("missing.json"), parse);
+        assert_eq!(missing.status, CheckStatus::Pass);
+
+        let broken = dir.join("broken.json");
+        fs::write(&broken, "{ not json").unwrap();
+        let check = check_json_file("claudeSettings", "t", &broken, parse);
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.fix.is_some());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::session::diagnostics::SCHEMA_CLAUDE_VERSION;
+use serde::Serialize;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+#[cfg(windows)]
+const CLAUDE_BINARY: &str = "claude.exe";
+#[cfg(not(windows))]
+const CLAUDE_BINARY: &str = "claude";
+
+/// Detected once per run; the CLI rarely changes while c9watch is open
+static ENVIRONMENT: OnceLock<EnvironmentInfo> = OnceLock::new();
+
+pub fn environment_info() -> &'static EnvironmentInfo {
+    ENVIRONMENT.get_or_init(EnvironmentInfo::detect)
+}
+
+/// Detect the installation and log anything that needs the user's attention
+pub fn check_installation() {
+    let info = environment_info();
+    match (&info.claude_path, &info.claude_version) {
+        (None, _) => warn!("Claude Code CLI not found on PATH or in common install locations"),
+        (Some(path), None) => warn!(
+            "Found Claude Code at {} but could not read its version",
+            path
+        ),
+        (Some(path), Some(version)) => info!("Found Claude Code {} at {}", version, path),
+    }
+    if info.newer_than_schema {
+        warn!(
+            "Claude Code {} is newer than the session format c9watch was built against ({}); some details may not be shown",
+            info.claude_version.as_deref().unwrap_or_default(),
+            info.schema_version
+        );
+    }
+}
+
+/// The Claude Code installation c9watch is monitoring
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentInfo {
+    pub app_version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub claude_path: Option<String>,
+    /// As reported by `claude --version`, e.g. "2.0.14"
+    pub claude_version: Option<String>,
+    /// Claude Code version (major.minor) the session parser was built against
+    pub schema_version: String,
+    /// The installed CLI is newer than `schema_version`, so its session files
+    /// may contain fields or entry types c9watch doesn't understand yet
+    pub newer_than_schema: bool,
+}
+
+impl EnvironmentInfo {
+    fn detect() -> Self {
+        let claude_path = find_claude_binary();
+        let claude_version = claude_path.as_deref().and_then(claude_version);
+        let newer_than_schema = claude_version
+            .as_deref()
+            .and_then(parse_version)
+            .map(|(major, minor, _)| (major, minor) > SCHEMA_CLAUDE_VERSION)
+            .unwrap_or(false);
+
+        Self {
+            app_version: env!("CARGO_PKG_VERSION"),
+            os: env::consts::OS,
+            arch: env::consts::ARCH,
+            claude_path: claude_path.map(|p| p.to_string_lossy().to_string()),
+            claude_version,
+            schema_version: format!("{}.{}", SCHEMA_CLAUDE_VERSION.0, SCHEMA_CLAUDE_VERSION.1),
+            newer_than_schema,
+        }
+    }
+}
+
+/// Install locations checked after PATH. Apps launched from Finder/Dock get a
+/// minimal PATH that usually misses these.
+fn fallback_locations() -> Vec<PathBuf> {
+    let mut locations = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        locations.push(home.join(".claude").join("local").join(CLAUDE_BINARY));
+        locations.push(home.join(".local").join("bin").join(CLAUDE_BINARY));
+        locations.push(home.join(".npm-global").join("bin").join(CLAUDE_BINARY));
+        locations.push(home.join(".bun").join("bin").join(CLAUDE_BINARY));
+    }
+    #[cfg(unix)]
+    {
+        locations.push(PathBuf::from("/opt/homebrew/bin").join(CLAUDE_BINARY));
+        locations.push(PathBuf::from("/usr/local/bin").join(CLAUDE_BINARY));
+    }
+    locations
+}
+
+/// Locate the `claude` CLI on PATH or in a common install location
+pub fn find_claude_binary() -> Option<PathBuf> {
+    let on_path = env::var_os("PATH")
+        .map(|path| env::split_paths(&path).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|dir| dir.join(CLAUDE_BINARY));
+
+    on_path
+        .chain(fallback_locations())
+        .find(|candidate| candidate.is_file())
+}
+
+/// Run `claude --version`, whose output looks like "2.0.14 (Claude Code)"
+fn claude_version(binary: &Path) -> Option<String> {
+    let output = Command::new(binary).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.split_whitespace().next().map(|v| v.to_string())
+}
+
+/// "2.0.14" -> (2, 0, 14); a missing patch component counts as 0
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version.trim_start_matches('v').split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    let patch = parts
+        .next()
+        .and_then(|p| {
+            p.split(|c: char| !c.is_ascii_digit())
+                .next()
+                .and_then(|d| d.parse().ok())
+        })
+        .unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("2.0.14"), Some((2, 0, 14)));
+        assert_eq!(parse_version("v1.3"), Some((1, 3, 0)));
+        assert_eq!(parse_version("2.1.0-beta.1"), Some((2, 1, 0)));
+        assert_eq!(parse_version("unknown"), None);
+    }
+}
@@ -0,0 +1,90 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use thiserror::Error;
+
+/// Error returned by Tauri commands and sent in WS `error` messages.
+///
+/// Serializes as `{ "code": "notFound", "message": "..." }` so clients can
+/// tell the kinds apart without matching on message text.
+#[derive(Debug, Error)]
+pub enum C9Error {
+    /// A session, message, transcript or process that doesn't exist
+    #[error("{0}")]
+    NotFound(String),
+
+    /// Not allowed by the OS or by c9watch's own settings
+    #[error("{0}")]
+    PermissionDenied(String),
+
+    /// Input that couldn't be understood: a malformed request, transcript or
+    /// settings bundle
+    #[error("{0}")]
+    ParseError(String),
+
+    /// Signalling, focusing or typing into an agent process failed
+    #[error("{0}")]
+    ProcessError(String),
+
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    /// Anything not given a more specific kind yet
+    #[error("{0}")]
+    Other(String),
+}
+
+impl C9Error {
+    /// Machine-readable kind, stable across releases
+    pub fn code(&self) -> &'static str {
+        match self {
+            C9Error::NotFound(_) => "notFound",
+            C9Error::PermissionDenied(_) => "permissionDenied",
+            C9Error::ParseError(_) => "parseError",
+            C9Error::ProcessError(_) => "processError",
+            C9Error::Io(_) => "io",
+            C9Error::Other(_) => "other",
+        }
+    }
+}
+
+/// Most helpers still report failures as plain strings
+impl From<String> for C9Error {
+    fn from(message: String) -> Self {
+        C9Error::Other(message)
+    }
+}
+
+impl From<&str> for C9Error {
+    fn from(message: &str) -> Self {
+        C9Error::Other(message.to_string())
+    }
+}
+
+impl Serialize for C9Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("C9Error", 2)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_with_code() {
+        let error = C9Error::NotFound("Session abc not found".to_string());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "code": "notFound", "message": "Session abc not found" })
+        );
+
+        let io = C9Error::from(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "denied",
+        ));
+        assert_eq!(io.code(), "io");
+        assert_eq!(C9Error::from("boom".to_string()).code(), "other");
+    }
+}
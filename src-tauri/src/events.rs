@@ -0,0 +1,204 @@
+use crate::polling::Session;
+use crate::session::SessionStatus;
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+/// Global event log, fed by the polling loop
+static EVENTS: OnceLock<EventLog> = OnceLock::new();
+
+pub fn events() -> &'static EventLog {
+    EVENTS.get_or_init(EventLog::default)
+}
+
+/// Events kept in memory; older ones are dropped
+const CAPACITY: usize = 1000;
+
+/// Events returned when the caller doesn't pick a limit
+pub const DEFAULT_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EventKind {
+    /// A session moved between statuses, appeared (`from` is None) or ended
+    /// (`to` is None)
+    StatusChanged,
+    /// A native/web notification went out for the session
+    Notification,
+}
+
+/// Something that happened to a session, for a "while you were away" view
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEvent {
+    /// RFC 3339
+    pub time: String,
+    pub kind: EventKind,
+    pub session_id: String,
+    /// Custom title if set, otherwise the session name
+    pub session_name: String,
+    pub project_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<SessionStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<SessionStatus>,
+    /// Notification body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl SessionEvent {
+    fn new(kind: EventKind, session: &Session) -> Self {
+        Self {
+            time: Utc::now().to_rfc3339(),
+            kind,
+            session_id: session.id.clone(),
+            session_name: session
+                .custom_title
+                .clone()
+                .unwrap_or_else(|| session.session_name.clone()),
+            project_path: session.project_path.clone(),
+            from: None,
+            to: None,
+            message: None,
+        }
+    }
+}
+
+/// Last status and display details of a session, to describe it once gone
+#[derive(Debug)]
+struct Seen {
+    status: SessionStatus,
+    event: SessionEvent,
+}
+
+#[derive(Debug, Default)]
+struct EventState {
+    events: VecDeque<SessionEvent>,
+    /// Sessions as of the last poll; None until the first one
+    seen: Option<HashMap<String, Seen>>,
+}
+
+/// Recent status transitions and notifications across all sessions,
+/// in memory only
+#[derive(Debug, Default)]
+pub struct EventLog {
+    state: Mutex<EventState>,
+}
+
+impl EventLog {
+    fn push(events: &mut VecDeque<SessionEvent>, event: SessionEvent) {
+        if events.len() == CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+
+    /// Record how this poll's sessions differ from the last one's. The first
+    /// poll only establishes what's running.
+    pub fn observe(&self, sessions: &[Session]) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let state = &mut *state;
+        let first = state.seen.is_none();
+        let mut previous = state.seen.take().unwrap_or_default();
+        let mut current = HashMap::new();
+
+        for session in sessions {
+            let before = previous.remove(&session.id).map(|seen| seen.status);
+            if !first && before.as_ref() != Some(&session.status) {
+                Self::push(
+                    &mut state.events,
+                    SessionEvent {
+                        from: before,
+                        to: Some(session.status.clone()),
+                        ..SessionEvent::new(EventKind::StatusChanged, session)
+                    },
+                );
+            }
+            current.insert(
+                session.id.clone(),
+                Seen {
+                    status: session.status.clone(),
+                    event: SessionEvent::new(EventKind::StatusChanged, session),
+                },
+            );
+        }
+
+        // Whatever is left didn't show up in this poll
+        for (_, seen) in previous {
+            Self::push(
+                &mut state.events,
+                SessionEvent {
+                    time: Utc::now().to_rfc3339(),
+                    from: Some(seen.status),
+                    ..seen.event
+                },
+            );
+        }
+        state.seen = Some(current);
+    }
+
+    pub fn record_notification(&self, session: &Session, body: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        Self::push(
+            &mut state.events,
+            SessionEvent {
+                to: Some(session.status.clone()),
+                message: Some(body.to_string()),
+                ..SessionEvent::new(EventKind::Notification, session)
+            },
+        );
+    }
+
+    /// The last `limit` events, newest first
+    pub fn recent(&self, limit: usize) -> Vec<SessionEvent> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .events
+            .iter()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polling::tests::test_session;
+
+    #[test]
+    fn test_observe_records_transitions_newest_first() {
+        let log = EventLog::default();
+        let mut session = test_session(SessionStatus::Working, 0);
+        log.observe(&[session.clone()]);
+        assert!(log.recent(10).is_empty());
+
+        session.status = SessionStatus::NeedsPermission;
+        log.observe(&[session.clone()]);
+        log.record_notification(&session, "Needs permission for Bash");
+        log.observe(&[]);
+
+        let recent = log.recent(10);
+        assert_eq!(recent.len(), 3);
+        // Ended
+        assert_eq!(recent[0].from, Some(SessionStatus::NeedsPermission));
+        assert_eq!(recent[0].to, None);
+        assert!(matches!(recent[1].kind, EventKind::Notification));
+        assert_eq!(recent[2].from, Some(SessionStatus::Working));
+        assert_eq!(recent[2].to, Some(SessionStatus::NeedsPermission));
+        assert_eq!(log.recent(1).len(), 1);
+    }
+
+    #[test]
+    fn test_log_is_capped() {
+        let log = EventLog::default();
+        let session = test_session(SessionStatus::Working, 0);
+        for _ in 0..CAPACITY + 5 {
+            log.record_notification(&session, "Finished working");
+        }
+        assert_eq!(log.recent(usize::MAX).len(), CAPACITY);
+    }
+}
@@ -0,0 +1,165 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// How long a working tree's status is reused before running git again.
+/// Several sessions often share a cwd, and a poll runs every few seconds.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Longest a `git status` may run before it's killed and the repository is
+/// treated as unknown until the next refresh, e.g. on a huge or networked tree
+const GIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a running `git status` is checked for exit
+const GIT_POLL: Duration = Duration::from_millis(20);
+
+/// Working-tree state of a session's repository
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GitStatus {
+    /// Tracked files with staged or unstaged changes, including conflicts.
+    /// Untracked files aren't counted; finding them walks the whole tree.
+    pub changed_files: u32,
+    /// Commits not on the upstream branch; None without an upstream
+    pub ahead: Option<u32>,
+    /// Upstream commits not yet merged; None without an upstream
+    pub behind: Option<u32>,
+}
+
+struct CachedStatus {
+    checked_at: Instant,
+    /// None when the directory isn't in a git repository
+    status: Option<GitStatus>,
+}
+
+static GIT_STATUS_CACHE: OnceLock<Mutex<HashMap<PathBuf, CachedStatus>>> = OnceLock::new();
+
+fn cache() -> &'static Mutex<HashMap<PathBuf, CachedStatus>> {
+    GIT_STATUS_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Status of the repository containing `cwd`, refreshed at most every
+/// REFRESH_INTERVAL. None if `cwd` isn't in a repository or git failed.
+pub fn git_status(cwd: &Path) -> Option<GitStatus> {
+    {
+        let cache = cache().lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cached) = cache.get(cwd) {
+            if cached.checked_at.elapsed() < REFRESH_INTERVAL {
+                return cached.status.clone();
+            }
+        }
+    }
+
+    // Without the lock held, so a slow repository doesn't hold up the others
+    let status = read_git_status(cwd);
+    let mut cache = cache().lock().unwrap_or_else(|e| e.into_inner());
+    // Drop directories no session has asked about in a while
+    cache.retain(|_, cached| cached.checked_at.elapsed() < REFRESH_INTERVAL * 10);
+    cache.insert(
+        cwd.to_path_buf(),
+        CachedStatus {
+            checked_at: Instant::now(),
+            status: status.clone(),
+        },
+    );
+    status
+}
+
+fn read_git_status(cwd: &Path) -> Option<GitStatus> {
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(cwd)
+        // Don't take index.lock to refresh stat info; the agent may be using git
+        .args([
+            "--no-optional-locks",
+            "status",
+            "--porcelain=v2",
+            "--branch",
+            "--untracked-files=no",
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| debug!("git status failed in {}: {}", cwd.display(), e))
+        .ok()?;
+
+    // Read on another thread so a full pipe can't stall git while we wait
+    let mut stdout = child.stdout.take()?;
+    let reader = std::thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+
+    let deadline = Instant::now() + GIT_TIMEOUT;
+    let exit = loop {
+        match child.try_wait() {
+            Ok(Some(exit)) => break exit,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(GIT_POLL),
+            Ok(None) => {
+                debug!("git status timed out in {}", cwd.display());
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+            Err(e) => {
+                debug!("git status failed in {}: {}", cwd.display(), e);
+                return None;
+            }
+        }
+    };
+    let output = reader.join().ok()?.ok()?;
+    if !exit.success() {
+        return None;
+    }
+    Some(parse_porcelain_v2(&String::from_utf8_lossy(&output)))
+}
+
+/// Parse `git status --porcelain=v2 --branch` output
+fn parse_porcelain_v2(output: &str) -> GitStatus {
+    let mut status = GitStatus::default();
+    for line in output.lines() {
+        if let Some(ab) = line.strip_prefix("# branch.ab ") {
+            // "+<ahead> -<behind>"
+            let mut counts = ab.split_whitespace();
+            status.ahead = counts
+                .next()
+                .and_then(|a| a.trim_start_matches('+').parse().ok());
+            status.behind = counts
+                .next()
+                .and_then(|b| b.trim_start_matches('-').parse().ok());
+        } else if line.starts_with("1 ") || line.starts_with("2 ") || line.starts_with("u ") {
+            status.changed_files += 1;
+        }
+    }
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_porcelain_v2() {
+        let output = "# branch.oid 1234abcd\n\
+                      # branch.head main\n\
+                      # branch.upstream origin/main\n\
+                      # branch.ab +2 -1\n\
+                      1 .M N... 100644 100644 100644 aaaa bbbb src/lib.rs\n\
+                      2 R. N... 100644 100644 100644 aaaa bbbb R100 new.rs\told.rs\n\
+                      u UU N... 100644 100644 100644 100644 aaaa bbbb cccc conflict.rs\n\
+                      ? notes.txt\n\
+                      ! target/\n";
+        let status = parse_porcelain_v2(output);
+        assert_eq!(status.changed_files, 3);
+        assert_eq!((status.ahead, status.behind), (Some(2), Some(1)));
+
+        let clean = parse_porcelain_v2("# branch.oid 1234abcd\n# branch.head scratch\n");
+        assert_eq!(clean, GitStatus::default());
+    }
+}
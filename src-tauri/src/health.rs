@@ -0,0 +1,151 @@
+use crate::polling::Session;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Global health state, fed by the polling loop and the web server
+static HEALTH: OnceLock<HealthMonitor> = OnceLock::new();
+
+pub fn health() -> &'static HealthMonitor {
+    HEALTH.get_or_init(HealthMonitor::new)
+}
+
+/// Polling counts as stalled when no cycle has succeeded for this long
+/// (the loop runs every few seconds)
+const STALE_AFTER: Duration = Duration::from_secs(30);
+
+#[derive(Default)]
+struct PollState {
+    last_success: Option<(Instant, DateTime<Utc>)>,
+    last_cycle_ms: u64,
+    last_error: Option<String>,
+    consecutive_errors: u32,
+    sessions_by_status: BTreeMap<String, u32>,
+}
+
+/// Tracks whether c9watch itself is working: polling progress, connected
+/// clients and uptime
+pub struct HealthMonitor {
+    started: Instant,
+    clients: AtomicUsize,
+    poll: Mutex<PollState>,
+}
+
+/// Decrements the connected client count when the connection ends
+pub struct ClientGuard;
+
+impl Drop for ClientGuard {
+    fn drop(&mut self) {
+        health().clients.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    /// "ok", "starting" (no poll cycle yet) or "degraded" (polling stalled)
+    pub status: &'static str,
+    pub version: &'static str,
+    pub uptime_seconds: u64,
+    pub connected_clients: usize,
+    /// RFC 3339 time of the last successful poll cycle
+    pub last_poll: Option<String>,
+    pub last_poll_seconds_ago: Option<u64>,
+    pub last_poll_duration_ms: u64,
+    pub last_poll_error: Option<String>,
+    pub consecutive_poll_errors: u32,
+    pub session_count: u32,
+    /// Active sessions per status (e.g. "Working": 2)
+    pub sessions_by_status: BTreeMap<String, u32>,
+}
+
+impl HealthMonitor {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            clients: AtomicUsize::new(0),
+            poll: Mutex::new(PollState::default()),
+        }
+    }
+
+    pub fn record_poll_success(&self, sessions: &[Session], cycle: Duration) {
+        let mut by_status = BTreeMap::new();
+        for session in sessions {
+            let status = serde_json::to_value(&session.status)
+                .ok()
+                .and_then(|v| v.as_str().map(|s| s.to_string()))
+                .unwrap_or_default();
+            *by_status.entry(status).or_insert(0) += 1;
+        }
+
+        let mut poll = self.poll.lock().unwrap_or_else(|e| e.into_inner());
+        poll.last_success = Some((Instant::now(), Utc::now()));
+        poll.last_cycle_ms = cycle.as_millis() as u64;
+        poll.consecutive_errors = 0;
+        poll.sessions_by_status = by_status;
+    }
+
+    pub fn record_poll_error(&self, error: String) {
+        let mut poll = self.poll.lock().unwrap_or_else(|e| e.into_inner());
+        poll.last_error = Some(error);
+        poll.consecutive_errors += 1;
+    }
+
+    /// Count a WebSocket client for as long as the returned guard lives
+    pub fn client_connected(&self) -> ClientGuard {
+        self.clients.fetch_add(1, Ordering::Relaxed);
+        ClientGuard
+    }
+
+    pub fn report(&self) -> HealthReport {
+        let poll = self.poll.lock().unwrap_or_else(|e| e.into_inner());
+        let since_last = poll.last_success.map(|(at, _)| at.elapsed());
+        let status = match since_last {
+            None => "starting",
+            Some(elapsed) if elapsed > STALE_AFTER => "degraded",
+            Some(_) => "ok",
+        };
+
+        HealthReport {
+            status,
+            version: env!("CARGO_PKG_VERSION"),
+            uptime_seconds: self.started.elapsed().as_secs(),
+            connected_clients: self.clients.load(Ordering::Relaxed),
+            last_poll: poll.last_success.map(|(_, at)| at.to_rfc3339()),
+            last_poll_seconds_ago: since_last.map(|d| d.as_secs()),
+            last_poll_duration_ms: poll.last_cycle_ms,
+            last_poll_error: poll.last_error.clone(),
+            consecutive_poll_errors: poll.consecutive_errors,
+            session_count: poll.sessions_by_status.values().sum(),
+            sessions_by_status: poll.sessions_by_status.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_tracks_poll_cycles() {
+        let monitor = HealthMonitor::new();
+        assert_eq!(monitor.report().status, "starting");
+
+        monitor.record_poll_error("boom".to_string());
+        monitor.record_poll_error("boom again".to_string());
+        let report = monitor.report();
+        assert_eq!(report.status, "starting");
+        assert_eq!(report.consecutive_poll_errors, 2);
+        assert_eq!(report.last_poll_error.as_deref(), Some("boom again"));
+
+        monitor.record_poll_success(&[], Duration::from_millis(12));
+        let report = monitor.report();
+        assert_eq!(report.status, "ok");
+        assert_eq!(report.consecutive_poll_errors, 0);
+        assert_eq!(report.last_poll_duration_ms, 12);
+        assert_eq!(report.session_count, 0);
+    }
+}
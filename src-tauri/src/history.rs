@@ -0,0 +1,346 @@
+use crate::error::C9Error;
+use crate::polling::Session;
+use crate::settings::AppSettings;
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+/// Global history store, loaded from disk on first use
+static HISTORY: OnceLock<HistoryStore> = OnceLock::new();
+
+pub fn history() -> &'static HistoryStore {
+    HISTORY.get_or_init(HistoryStore::load)
+}
+
+/// How often accumulated history is written back to disk
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often the retention settings are applied to the in-memory history
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Time a single session spent in each status on a given (local) day
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailySessionRecord {
+    pub project_path: String,
+    #[serde(default)]
+    pub working_seconds: u64,
+    #[serde(default)]
+    pub idle_seconds: u64,
+    #[serde(default)]
+    pub needs_permission_seconds: u64,
+}
+
+/// On-disk format of the history file
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryData {
+    /// Local date ("YYYY-MM-DD") -> session ID -> record
+    #[serde(default)]
+    days: BTreeMap<String, HashMap<String, DailySessionRecord>>,
+    /// Local date of the Monday the last weekly digest was sent for
+    #[serde(default)]
+    last_weekly_digest: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct HistoryState {
+    data: HistoryData,
+    /// Last cumulative (working, idle, needs_permission) seconds seen per session,
+    /// used to turn the tracker's running totals into per-day increments
+    last_totals: HashMap<String, (u64, u64, u64)>,
+    dirty: bool,
+    last_saved: Option<Instant>,
+    last_pruned: Option<Instant>,
+}
+
+/// What `HistoryStore::vacuum` removed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VacuumResult {
+    pub days_removed: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// Drop days older than `oldest_kept`, then the oldest remaining days until
+/// the serialized history fits in `max_bytes` (the newest day is always kept).
+/// Returns how many days were dropped.
+fn prune(data: &mut HistoryData, oldest_kept: Option<NaiveDate>, max_bytes: Option<u64>) -> usize {
+    let before = data.days.len();
+    if let Some(oldest_kept) = oldest_kept {
+        data.days = data
+            .days
+            .split_off(&oldest_kept.format("%Y-%m-%d").to_string());
+    }
+
+    if let Some(max_bytes) = max_bytes {
+        let mut size = serde_json::to_vec(data).map(|v| v.len()).unwrap_or(0) as u64;
+        while size > max_bytes && data.days.len() > 1 {
+            let Some((date, sessions)) = data.days.pop_first() else {
+                break;
+            };
+            // `"date":{...},`
+            let entry =
+                serde_json::to_vec(&sessions).map(|v| v.len()).unwrap_or(0) + date.len() + 4;
+            size = size.saturating_sub(entry as u64);
+        }
+    }
+
+    before - data.days.len()
+}
+
+/// Persistent per-day record of how much time each session spent in each status
+#[derive(Debug, Default)]
+pub struct HistoryStore {
+    state: Mutex<HistoryState>,
+}
+
+impl HistoryStore {
+    fn load() -> Self {
+        let data = fs::read_to_string(Self::get_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            state: Mutex::new(HistoryState {
+                data,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn get_path() -> PathBuf {
+        let home = dirs::home_dir().expect("Failed to get home directory");
+        home.join(".claude").join("session-monitor-history.json")
+    }
+
+    /// Fold one poll cycle's worth of session durations into today's records
+    pub fn record_cycle(&self, sessions: &[Session]) {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let state = &mut *state;
+
+        for session in sessions {
+            let totals = (
+                session.working_seconds,
+                session.idle_seconds,
+                session.needs_permission_seconds,
+            );
+            // The first sighting only establishes a baseline; durations tracked
+            // before a restart of c9watch would otherwise be counted twice.
+            let previous = state
+                .last_totals
+                .insert(session.id.clone(), totals)
+                .unwrap_or(totals);
+
+            let delta = (
+                totals.0.saturating_sub(previous.0),
+                totals.1.saturating_sub(previous.1),
+                totals.2.saturating_sub(previous.2),
+            );
+
+            let record = state
+                .data
+                .days
+                .entry(today.clone())
+                .or_default()
+                .entry(session.id.clone())
+                .or_insert_with(|| DailySessionRecord {
+                    project_path: session.project_path.clone(),
+                    ..Default::default()
+                });
+            record.working_seconds += delta.0;
+            record.idle_seconds += delta.1;
+            record.needs_permission_seconds += delta.2;
+            state.dirty = true;
+        }
+
+        state
+            .last_totals
+            .retain(|id, _| sessions.iter().any(|s| &s.id == id));
+    }
+
+    /// Write the history file if it changed and the save interval has elapsed
+    pub fn flush(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let due = state
+            .last_saved
+            .map(|t| t.elapsed() >= SAVE_INTERVAL)
+            .unwrap_or(true);
+        if !state.dirty || !due {
+            return;
+        }
+
+        if let Err(e) = Self::write(&mut state) {
+            error!("Failed to save history: {}", e);
+        }
+    }
+
+    /// Apply the retention settings, at most once per prune interval. The
+    /// dropped days are written out with the next flush.
+    pub fn apply_retention(&self, settings: &AppSettings) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let due = state
+            .last_pruned
+            .map(|t| t.elapsed() >= PRUNE_INTERVAL)
+            .unwrap_or(true);
+        if !due {
+            return;
+        }
+        state.last_pruned = Some(Instant::now());
+
+        let removed = prune(
+            &mut state.data,
+            Self::oldest_kept(settings),
+            Self::max_bytes(settings),
+        );
+        if removed > 0 {
+            info!("Pruned {} days of usage history", removed);
+            state.dirty = true;
+        }
+    }
+
+    /// Apply the retention settings and rewrite the history file right away
+    pub fn vacuum(&self, settings: &AppSettings) -> Result<VacuumResult, C9Error> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let bytes_before = fs::metadata(Self::get_path()).map(|m| m.len()).unwrap_or(0);
+
+        let days_removed = prune(
+            &mut state.data,
+            Self::oldest_kept(settings),
+            Self::max_bytes(settings),
+        );
+        state.last_pruned = Some(Instant::now());
+        let bytes_after = Self::write(&mut state)?;
+
+        info!(
+            "Vacuumed usage history: {} days removed, {} -> {} bytes",
+            days_removed, bytes_before, bytes_after
+        );
+        Ok(VacuumResult {
+            days_removed,
+            bytes_before,
+            bytes_after,
+        })
+    }
+
+    fn oldest_kept(settings: &AppSettings) -> Option<NaiveDate> {
+        settings
+            .history_retention_days
+            .map(|days| Local::now().date_naive() - chrono::Duration::days(i64::from(days)))
+    }
+
+    fn max_bytes(settings: &AppSettings) -> Option<u64> {
+        settings
+            .history_max_mb
+            .map(|mb| mb.saturating_mul(1024 * 1024))
+    }
+
+    /// Write the history file, returning its size
+    fn write(state: &mut HistoryState) -> Result<u64, C9Error> {
+        let path = Self::get_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string(&state.data)
+            .map_err(|e| C9Error::Other(format!("Failed to serialize history: {}", e)))?;
+        // Don't retry every cycle if the disk is full
+        state.dirty = false;
+        state.last_saved = Some(Instant::now());
+        fs::write(&path, &content)?;
+        Ok(content.len() as u64)
+    }
+
+    /// All records for local dates in `[start, end]`, as (date, session_id, record)
+    pub fn records_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<(String, String, DailySessionRecord)> {
+        let start = start.format("%Y-%m-%d").to_string();
+        let end = end.format("%Y-%m-%d").to_string();
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        state
+            .data
+            .days
+            .range(start..=end)
+            .flat_map(|(date, sessions)| {
+                sessions
+                    .iter()
+                    .map(move |(id, record)| (date.clone(), id.clone(), record.clone()))
+            })
+            .collect()
+    }
+
+    pub fn last_weekly_digest(&self) -> Option<String> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.data.last_weekly_digest.clone()
+    }
+
+    pub fn set_last_weekly_digest(&self, date: String) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.data.last_weekly_digest = Some(date);
+        state.dirty = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(sessions: usize) -> HashMap<String, DailySessionRecord> {
+        (0..sessions)
+            .map(|i| {
+                (
+                    format!("session-{}", i),
+                    DailySessionRecord {
+                        project_path: "/code/c9watch".to_string(),
+                        working_seconds: 60,
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_prune_by_age() {
+        let mut data = HistoryData::default();
+        for d in ["2026-01-01", "2026-02-01", "2026-03-01"] {
+            data.days.insert(d.to_string(), day(1));
+        }
+
+        assert_eq!(prune(&mut data, Some(date("2026-02-01")), None), 1);
+        let kept: Vec<&str> = data.days.keys().map(String::as_str).collect();
+        assert_eq!(kept, ["2026-02-01", "2026-03-01"]);
+    }
+
+    #[test]
+    fn test_prune_by_size_keeps_newest() {
+        let mut data = HistoryData::default();
+        for d in ["2026-03-01", "2026-03-02", "2026-03-03"] {
+            data.days.insert(d.to_string(), day(20));
+        }
+        let one_day = serde_json::to_vec(&day(20)).unwrap().len() as u64;
+
+        let removed = prune(&mut data, None, Some(one_day * 2));
+        assert_eq!(removed, 2);
+        assert!(data.days.contains_key("2026-03-03"));
+
+        // Never drops the last remaining day, however small the limit
+        assert_eq!(prune(&mut data, None, Some(0)), 0);
+        assert_eq!(data.days.len(), 1);
+    }
+}
@@ -2,31 +2,136 @@
 #[cfg(not(mobile))]
 pub mod actions;
 #[cfg(not(mobile))]
+pub mod api_status;
+#[cfg(not(mobile))]
 pub mod auth;
 #[cfg(not(mobile))]
+pub mod automation;
+#[cfg(not(mobile))]
+pub mod budget;
+#[cfg(not(mobile))]
+pub mod cpu_monitor;
+#[cfg(target_os = "linux")]
+pub mod dbus;
+#[cfg(not(mobile))]
+pub mod deep_link;
+#[cfg(not(mobile))]
+pub mod doctor;
+#[cfg(not(mobile))]
+pub mod environment;
+#[cfg(not(mobile))]
+pub mod events;
+#[cfg(not(mobile))]
+pub mod git_status;
+#[cfg(not(mobile))]
+pub mod health;
+#[cfg(not(mobile))]
+pub mod history;
+#[cfg(not(mobile))]
+pub mod local_socket;
+#[cfg(not(mobile))]
+pub mod logging;
+#[cfg(not(mobile))]
+pub mod metadata;
+#[cfg(not(mobile))]
+pub mod network;
+#[cfg(not(mobile))]
+pub mod notification_actions;
+#[cfg(not(mobile))]
+pub mod open_files;
+#[cfg(not(mobile))]
+pub mod pairing;
+#[cfg(not(mobile))]
 pub mod polling;
 #[cfg(not(mobile))]
+pub mod popover;
+#[cfg(not(mobile))]
+pub mod power;
+#[cfg(not(mobile))]
+pub mod providers;
+#[cfg(not(mobile))]
+pub mod quick_switch;
+#[cfg(not(mobile))]
+pub mod quicklist;
+#[cfg(not(mobile))]
+pub mod rate_limit;
+#[cfg(not(mobile))]
+pub mod redact;
+#[cfg(target_os = "macos")]
+pub mod scripting;
+#[cfg(not(mobile))]
+pub mod search;
+#[cfg(not(mobile))]
+pub mod secrets;
+#[cfg(not(mobile))]
+pub mod settings;
+#[cfg(not(mobile))]
+pub mod settings_bundle;
+#[cfg(not(mobile))]
+pub mod share;
+#[cfg(not(mobile))]
+pub mod snooze;
+#[cfg(not(mobile))]
+pub mod stats;
+#[cfg(not(mobile))]
+pub mod status_override;
+#[cfg(not(mobile))]
+pub mod store;
+#[cfg(not(mobile))]
+pub mod telemetry;
+#[cfg(not(mobile))]
+pub mod tracking;
+#[cfg(not(mobile))]
+pub mod transcript;
+#[cfg(not(mobile))]
+pub mod usage;
+#[cfg(not(mobile))]
 pub mod web_server;
+#[cfg(not(mobile))]
+pub mod widget;
+#[cfg(not(mobile))]
+pub mod window_state;
 
 // Shared modules (types used by both desktop and mobile builds)
+pub mod error;
 pub mod session;
 
+// Mobile-only modules
+#[cfg(mobile)]
+pub mod mobile_connection;
+#[cfg(mobile)]
+pub mod mobile_notifications;
+
 #[cfg(not(mobile))]
-use actions::{open_session as open_session_action, stop_session as stop_session_action};
+use actions::{
+    open_session as open_session_action, stop_session as stop_session_action, StopResult,
+};
+pub use error::C9Error;
 #[cfg(not(mobile))]
-use polling::{detect_and_enrich_sessions, start_polling, Session};
+use polling::{start_polling, Session};
 use serde::Serialize;
-use session::{extract_messages, parse_all_entries, MessageType};
+use session::{preview_tool_results, ConversationFilter};
+pub use session::{BranchPoint, ConversationMessage};
 #[cfg(not(mobile))]
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 #[cfg(not(mobile))]
 use std::time::Duration;
 #[cfg(not(mobile))]
+use store::SessionStore;
+#[cfg(not(mobile))]
 use tauri::{
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Emitter,
+    Emitter, WebviewUrl, WebviewWindowBuilder,
 };
 use tauri::{AppHandle, Manager};
+#[cfg(not(mobile))]
+use tauri_plugin_clipboard_manager::ClipboardExt;
+#[cfg(not(mobile))]
+use tauri_plugin_deep_link::DeepLinkExt;
+#[cfg(not(mobile))]
+use tauri_plugin_opener::OpenerExt;
+#[cfg(not(mobile))]
+use tracing::error;
 
 // ── Shared types ────────────────────────────────────────────────────
 
@@ -36,15 +141,9 @@ use tauri::{AppHandle, Manager};
 pub struct Conversation {
     pub session_id: String,
     pub messages: Vec<ConversationMessage>,
-}
-
-/// Individual message in a conversation
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ConversationMessage {
-    pub timestamp: String,
-    pub message_type: MessageType,
-    pub content: String,
+    /// Places the session was rewound, with the alternative shown at each
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<BranchPoint>,
 }
 
 // ── Desktop-only commands ───────────────────────────────────────────
@@ -57,100 +156,517 @@ fn greet(name: &str) -> String {
 
 #[cfg(not(mobile))]
 #[tauri::command]
-async fn get_sessions() -> Result<Vec<Session>, String> {
-    polling::detect_and_enrich_sessions()
+async fn get_sessions(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    refresh: Option<bool>,
+) -> Result<Vec<Session>, C9Error> {
+    // Normally the latest poll; `refresh` polls again first
+    let sessions = if refresh.unwrap_or(false) {
+        store.refresh().await
+    } else {
+        store.current().await
+    };
+    Ok(sessions.to_vec())
 }
 
-/// Core logic for getting conversation data (shared by Tauri command and WS handler)
+/// Fuzzy-match running sessions for the quick switcher, best first
 #[cfg(not(mobile))]
-pub fn get_conversation_data(session_id: &str) -> Result<Conversation, String> {
-    let home_dir = dirs::home_dir().ok_or("Failed to get home directory")?;
-    let claude_projects_dir = home_dir.join(".claude").join("projects");
+#[tauri::command]
+async fn query_sessions(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    text: String,
+) -> Result<Vec<quick_switch::SessionMatch>, C9Error> {
+    Ok(quick_switch::query(store.current().await.to_vec(), &text))
+}
 
-    let entries = std::fs::read_dir(&claude_projects_dir)
-        .map_err(|e| format!("Failed to read projects directory: {}", e))?;
+/// Full-text search over every transcript, newest sessions first
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn search_conversations(
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<search::SearchHit>, C9Error> {
+    Ok(search::search_conversations(
+        &query,
+        limit.unwrap_or(search::DEFAULT_LIMIT),
+    ))
+}
 
-    let session_filename = format!("{}.jsonl", session_id);
+/// Core logic for getting conversation data (shared by Tauri command and WS handler)
+#[cfg(not(mobile))]
+pub fn get_conversation_data(
+    store: &SessionStore,
+    session_id: &str,
+) -> Result<Conversation, C9Error> {
+    conversation_on_branch(store, session_id, None)
+}
 
-    for entry in entries.flatten() {
-        let project_path = entry.path();
-        if !project_path.is_dir() {
-            continue;
+/// Conversation along the branch through `through` (any entry uuid), or the
+/// branch the session is on now
+#[cfg(not(mobile))]
+pub fn conversation_on_branch(
+    store: &SessionStore,
+    session_id: &str,
+    through: Option<&str>,
+) -> Result<Conversation, C9Error> {
+    // Running sessions of any agent are read by the provider that found them
+    let running = store
+        .transcripts()
+        .and_then(|transcripts| transcripts.conversation(session_id, through));
+    let (messages, branches) = match running {
+        Some(conversation) => conversation?,
+        None => {
+            let session_file = providers::claude::find_transcript(session_id)?;
+            providers::claude::read_branch(&session_file, through).map_err(C9Error::ParseError)?
         }
+    };
 
-        let session_file = project_path.join(&session_filename);
-        if session_file.exists() {
-            let entries = parse_all_entries(&session_file)
-                .map_err(|e| format!("Failed to parse session file: {}", e))?;
+    Ok(Conversation {
+        branches,
+        ..to_conversation(session_id, messages)
+    })
+}
 
-            let messages = extract_messages(&entries);
+/// Conversation as sent to viewers: long tool results cut to a preview, then
+/// the caller's filter applied
+#[cfg(not(mobile))]
+pub fn conversation_payload(
+    store: &SessionStore,
+    session_id: &str,
+    filter: Option<ConversationFilter>,
+) -> Result<Conversation, C9Error> {
+    let through = filter.as_ref().and_then(|filter| filter.branch.as_deref());
+    let mut conversation = conversation_on_branch(store, session_id, through)?;
+    preview_tool_results(&mut conversation.messages);
+    if let Some(filter) = filter {
+        conversation.messages = filter.apply(conversation.messages);
+    }
+    Ok(conversation)
+}
 
-            let conversation_messages: Vec<ConversationMessage> = messages
-                .into_iter()
-                .map(|(timestamp, msg_type, content)| ConversationMessage {
-                    timestamp,
-                    message_type: msg_type,
-                    content,
+/// Full content of one message, for previews cut short in the payload.
+/// Looked up by transcript entry uuid, so only Claude sessions have one; an
+/// entry with several tool results also needs the result's `tool_use_id`.
+#[cfg(not(mobile))]
+pub fn message_content(
+    store: &SessionStore,
+    session_id: &str,
+    uuid: &str,
+    tool_use_id: Option<&str>,
+) -> Result<String, C9Error> {
+    conversation_on_branch(store, session_id, Some(uuid))?
+        .messages
+        .into_iter()
+        .find(|message| {
+            message.uuid.as_deref() == Some(uuid)
+                && tool_use_id.is_none_or(|id| {
+                    message
+                        .tool_result
+                        .as_ref()
+                        .is_some_and(|result| result.tool_use_id == id)
                 })
-                .collect();
+        })
+        .map(|message| message.content)
+        .ok_or_else(|| {
+            C9Error::NotFound(format!(
+                "Message {} not found in session {}",
+                uuid, session_id
+            ))
+        })
+}
 
-            return Ok(Conversation {
-                session_id: session_id.to_string(),
-                messages: conversation_messages,
-            });
+/// Open a Claude session's transcript with the default app for .jsonl files
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn open_session_file(app: AppHandle, session_id: String) -> Result<(), C9Error> {
+    session::validate_session_id(&session_id)?;
+    let path = providers::claude::find_transcript(&session_id)?;
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| C9Error::Other(e.to_string()))
+}
+
+/// Show a Claude session's transcript in Finder/Explorer/the file manager
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn reveal_session_file(app: AppHandle, session_id: String) -> Result<(), C9Error> {
+    session::validate_session_id(&session_id)?;
+    let path = providers::claude::find_transcript(&session_id)?;
+    app.opener()
+        .reveal_item_in_dir(&path)
+        .map_err(|e| C9Error::Other(e.to_string()))
+}
+
+/// Put a session's transcript on the clipboard as Markdown or plain text
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn copy_conversation(
+    app: AppHandle,
+    store: tauri::State<'_, Arc<SessionStore>>,
+    session_id: String,
+    format: transcript::TranscriptFormat,
+) -> Result<(), C9Error> {
+    session::validate_session_id(&session_id)?;
+    let conversation = get_conversation_data(&store, &session_id)?;
+    app.clipboard()
+        .write_text(transcript::render(&conversation, format))
+        .map_err(|e| C9Error::Other(e.to_string()))
+}
+
+/// Publish a read-only snapshot of a session's transcript on the web server
+/// for `ttl` seconds (default an hour)
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn share_conversation(
+    info: tauri::State<'_, Mutex<ServerInfo>>,
+    store: tauri::State<'_, Arc<SessionStore>>,
+    session_id: String,
+    ttl: Option<u64>,
+) -> Result<share::ShareLink, C9Error> {
+    session::validate_session_id(&session_id)?;
+    let (base_url, mobile_access) = {
+        let info = info.lock().unwrap_or_else(|e| e.into_inner());
+        (
+            format!("http://{}:{}", info.local_ip, info.port),
+            info.mobile_access,
+        )
+    };
+    if !mobile_access {
+        return Err(C9Error::PermissionDenied(
+            "Turn on mobile access to share links; the server only listens on localhost"
+                .to_string(),
+        ));
+    }
+
+    let mut conversation = get_conversation_data(&store, &session_id)?;
+    if let Some(redactor) = redact::current() {
+        for message in &mut conversation.messages {
+            if let std::borrow::Cow::Owned(masked) = redactor.redact(&message.content) {
+                message.content = masked;
+            }
         }
     }
+    let ttl = ttl.map(Duration::from_secs).unwrap_or(share::DEFAULT_TTL);
+    Ok(share::create(&conversation, &base_url, ttl))
+}
 
-    Err(format!(
-        "Session {} not found in any project directory",
-        session_id
-    ))
+#[cfg(not(mobile))]
+fn to_conversation(session_id: &str, messages: Vec<ConversationMessage>) -> Conversation {
+    Conversation {
+        session_id: session_id.to_string(),
+        messages,
+        branches: Vec::new(),
+    }
 }
 
 #[cfg(not(mobile))]
 #[tauri::command]
-async fn get_conversation(session_id: String) -> Result<Conversation, String> {
-    get_conversation_data(&session_id)
+async fn get_conversation(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    session_id: String,
+    filter: Option<ConversationFilter>,
+) -> Result<Conversation, C9Error> {
+    session::validate_session_id(&session_id)?;
+    conversation_payload(&store, &session_id, filter)
 }
 
 #[cfg(not(mobile))]
 #[tauri::command]
-async fn stop_session(app: AppHandle, pid: u32) -> Result<(), String> {
-    stop_session_action(pid)?;
-    std::thread::sleep(Duration::from_millis(300));
+async fn get_message_content(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    session_id: String,
+    uuid: String,
+    tool_use_id: Option<String>,
+) -> Result<String, C9Error> {
+    session::validate_session_id(&session_id)?;
+    message_content(&store, &session_id, &uuid, tool_use_id.as_deref())
+}
 
-    if let Ok(sessions) = detect_and_enrich_sessions() {
-        let _ = app.emit("sessions-updated", &sessions);
-    }
-    Ok(())
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn stop_session(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    pid: u32,
+    force: Option<bool>,
+) -> Result<StopResult, C9Error> {
+    // Waiting for the process to exit blocks; keep it off the async workers
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        stop_session_action(pid, force.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    // The polling loop picks up the change and broadcasts it
+    store.request_refresh();
+    Ok(result)
 }
 
 #[cfg(not(mobile))]
 #[tauri::command]
-async fn open_session(pid: u32, project_path: String) -> Result<(), String> {
+async fn open_session(pid: u32, project_path: String) -> Result<(), C9Error> {
     open_session_action(pid, project_path)
 }
 
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn respond_to_permission(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    pid: u32,
+    approve: bool,
+    tool_name: Option<String>,
+) -> Result<(), C9Error> {
+    // Focusing and typing into the terminal blocks; keep it off the async workers
+    let store = Arc::clone(&store);
+    tauri::async_runtime::spawn_blocking(move || {
+        actions::respond_to_permission(&store, pid, approve, tool_name.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn quit_session_gracefully(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    pid: u32,
+) -> Result<(), C9Error> {
+    let store = Arc::clone(&store);
+    tauri::async_runtime::spawn_blocking(move || actions::quit_session_gracefully(&store, pid))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
 #[cfg(not(mobile))]
 #[tauri::command]
 async fn rename_session(
-    app: AppHandle,
+    store: tauri::State<'_, Arc<SessionStore>>,
     session_id: String,
     new_name: String,
-) -> Result<(), String> {
-    let mut custom_titles = session::CustomTitles::load();
-    custom_titles.set(session_id, new_name);
-    custom_titles.save()?;
+) -> Result<(), C9Error> {
+    metadata::rename(&store, session_id, new_name)
+}
 
-    if let Ok(sessions) = detect_and_enrich_sessions() {
-        let _ = app.emit("sessions-updated", &sessions);
-    }
+/// Show `status` for a session instead of the detected one, until `ttl`
+/// seconds pass (default an hour) or the detected status changes
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn override_status(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    session_id: String,
+    status: session::SessionStatus,
+    ttl: Option<u64>,
+) -> Result<(), C9Error> {
+    metadata::override_status(&store, &session_id, status, ttl)
+}
+
+/// Hold back a session's notifications for `minutes` (0 wakes it). Returns
+/// when notifications resume, as RFC 3339.
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn snooze_session(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    session_id: String,
+    minutes: u32,
+) -> Result<Option<String>, C9Error> {
+    let until = metadata::snooze(&store, &session_id, minutes)?;
+    Ok(until.map(|until| until.to_rfc3339()))
+}
+
+/// Attach a note to a session; blank text removes it
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn set_note(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    session_id: String,
+    text: String,
+) -> Result<(), C9Error> {
+    metadata::set_note(&store, session_id, text)
+}
+
+/// Give a session a color label; None removes it
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn set_session_color(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    session_id: String,
+    color: Option<session::SessionColor>,
+) -> Result<(), C9Error> {
+    metadata::set_color(&store, session_id, color)
+}
+
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_ignore_list() -> Result<session::IgnoreList, C9Error> {
+    Ok(session::IgnoreList::load())
+}
+
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn set_ignore_list(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    ignore_list: session::IgnoreList,
+) -> Result<(), C9Error> {
+    metadata::set_ignore_list(&store, ignore_list)
+}
+
+/// Hide a single session from the monitor and its notifications
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn ignore_session(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    session_id: String,
+) -> Result<(), C9Error> {
+    metadata::ignore(&store, session_id)
+}
+
+/// Get the recorded status transitions for a session (for the activity strip)
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_session_timeline(
+    session_id: String,
+) -> Result<Vec<tracking::StatusTransition>, C9Error> {
+    session::validate_session_id(&session_id)?;
+    tracking::tracker()
+        .timeline(&session_id)
+        .ok_or_else(|| C9Error::NotFound(format!("Session {} is not being tracked", session_id)))
+}
+
+/// Per-tool call counts, durations and repeated failures for a session
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_session_stats(session_id: String) -> Result<stats::SessionStats, C9Error> {
+    session::validate_session_id(&session_id)?;
+    stats::session_stats(&session_id)
+}
+
+/// Files the session modified through its edit tools, with change counts
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_session_changes(session_id: String) -> Result<Vec<stats::FileChange>, C9Error> {
+    session::validate_session_id(&session_id)?;
+    stats::session_changes(&session_id)
+}
+
+/// The session's current TodoWrite list
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_todos(session_id: String) -> Result<Vec<session::TodoItem>, C9Error> {
+    session::validate_session_id(&session_id)?;
+    Ok(session::load_todos(&session_id))
+}
+
+/// Aggregate sessions, working time, tokens, and estimated cost per project per day
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_usage_report(range: usage::ReportRange) -> Result<usage::UsageReport, C9Error> {
+    Ok(usage::generate_report(range))
+}
+
+/// Apply the history retention settings now and compact the history file
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn vacuum_history() -> Result<history::VacuumResult, C9Error> {
+    history::history().vacuum(&settings::AppSettings::load())
+}
+
+/// Per-file JSONL parse results (failures, lenient recoveries, unknown fields)
+/// from the most recent parse of each session file
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_parse_diagnostics() -> Result<Vec<session::FileParseStats>, C9Error> {
+    Ok(session::diagnostics().snapshot())
+}
+
+/// Run a tool call through the loaded permission rules without it happening,
+/// to see why c9watch thinks it does or doesn't need approval
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn check_permission(
+    tool_name: String,
+    input: serde_json::Value,
+) -> Result<session::PermissionCheck, C9Error> {
+    Ok(session::get_permission_checker().explain(&tool_name, &input))
+}
+
+/// The allow/deny rules c9watch loaded from ~/.claude/settings.json
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_permission_rules() -> Result<session::PermissionRules, C9Error> {
+    Ok(session::get_permission_checker().rules())
+}
+
+/// Add an allow rule to ~/.claude/settings.json, e.g. `Bash(cargo test:*)`.
+/// Blanket Bash rules and rules containing shell operators are refused.
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn add_allow_rule(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    pattern: String,
+) -> Result<session::PermissionRules, C9Error> {
+    let path = session::permissions::settings_path()
+        .ok_or_else(|| C9Error::Other("Could not determine home directory".to_string()))?;
+    session::permissions::add_allow_rule(&path, &pattern)?;
+    session::reload_permission_checker();
+
+    // The polling loop picks up the change and broadcasts it
+    store.request_refresh();
+    Ok(session::get_permission_checker().rules())
+}
+
+/// The last `limit` (default 100) status transitions and notifications across
+/// all sessions, newest first
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_recent_events(limit: Option<usize>) -> Result<Vec<events::SessionEvent>, C9Error> {
+    Ok(events::events().recent(limit.unwrap_or(events::DEFAULT_LIMIT)))
+}
+
+/// Latest Anthropic status page reading, null until the first check
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_api_status() -> Result<Option<api_status::ApiStatus>, C9Error> {
+    Ok(api_status::current())
+}
+
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_settings() -> Result<settings::AppSettings, C9Error> {
+    Ok(settings::AppSettings::load())
+}
+
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn update_settings(settings: settings::AppSettings) -> Result<(), C9Error> {
+    redact::validate_patterns(&settings.redaction_patterns).map_err(C9Error::ParseError)?;
+    settings.save()?;
+    redact::configure(&settings);
+    Ok(())
+}
+
+/// Settings, custom names/titles, notes, colors and ignore list as one JSON bundle
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn export_settings() -> Result<String, C9Error> {
+    Ok(settings_bundle::export()?)
+}
+
+/// Replace the current setup with a bundle from `export_settings`
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn import_settings(
+    store: tauri::State<'_, Arc<SessionStore>>,
+    bundle: String,
+) -> Result<(), C9Error> {
+    settings_bundle::import(&bundle)?;
+
+    // The polling loop picks up the change and broadcasts it
+    store.request_refresh();
     Ok(())
 }
 
 /// Get the terminal title for a session (iTerm2 only, macOS)
 #[tauri::command]
-async fn get_terminal_title(pid: u32) -> Result<Option<String>, String> {
+async fn get_terminal_title(pid: u32) -> Result<Option<String>, C9Error> {
     #[cfg(target_os = "macos")]
     {
         Ok(actions::get_iterm2_session_title(pid))
@@ -165,18 +681,57 @@ async fn get_terminal_title(pid: u32) -> Result<Option<String>, String> {
 /// Show and focus the main application window
 #[cfg(not(mobile))]
 #[tauri::command]
-async fn show_main_window(app: AppHandle) -> Result<(), String> {
+async fn show_main_window(app: AppHandle) -> Result<(), C9Error> {
     if let Some(window) = app.get_webview_window("main") {
         window.show().map_err(|e| e.to_string())?;
         window.set_focus().map_err(|e| e.to_string())?;
 
-        if let Some(popover) = app.get_webview_window("popover") {
+        if let Some(popover) = app.get_webview_window(popover::LABEL) {
             let _ = popover.hide();
         }
     }
     Ok(())
 }
 
+/// Open (or bring forward) a small always-on-top window following one session
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn open_session_window(app: AppHandle, session_id: String) -> Result<(), C9Error> {
+    // Also keeps the window label to the characters labels allow
+    session::validate_session_id(&session_id)?;
+
+    let label = format!("session-{}", session_id);
+    if let Some(window) = app.get_webview_window(&label) {
+        window.show().map_err(|e| e.to_string())?;
+        window.set_focus().map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+
+    let url = WebviewUrl::App(format!("session?id={}", session_id).into());
+    WebviewWindowBuilder::new(&app, label, url)
+        .title("c9watch")
+        .inner_size(380.0, 460.0)
+        .min_inner_size(280.0, 200.0)
+        .always_on_top(true)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Keep the popover up when it loses focus (and above other windows)
+#[cfg(not(mobile))]
+#[tauri::command]
+fn set_popover_pinned(app: AppHandle, pinned: bool) -> Result<(), C9Error> {
+    Ok(popover::set_pinned(&app, pinned)?)
+}
+
+/// Whether the popover is pinned (restored across launches)
+#[cfg(not(mobile))]
+#[tauri::command]
+fn get_popover_pinned() -> bool {
+    popover::is_pinned()
+}
+
 /// Server connection info for the mobile client
 #[cfg(not(mobile))]
 #[derive(Debug, Clone, Serialize)]
@@ -186,66 +741,291 @@ pub struct ServerInfo {
     pub port: u16,
     pub local_ip: String,
     pub ws_url: String,
+    /// False when the server only listens on localhost
+    pub mobile_access: bool,
+}
+
+#[cfg(not(mobile))]
+impl ServerInfo {
+    pub fn new(token: String, port: u16, local_ip: String, mobile_access: bool) -> Self {
+        Self {
+            ws_url: format!("ws://{}:{}/ws?token={}", local_ip, port, token),
+            token,
+            port,
+            local_ip,
+            mobile_access,
+        }
+    }
+}
+
+/// Session id from a c9watch://session link that arrived before the window was ready
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn take_pending_session_link() -> Result<Option<String>, C9Error> {
+    Ok(deep_link::take_pending_session())
 }
 
+/// Kept behind a mutex because the advertised address changes when the
+/// machine switches networks (see `network::watch_advertised_address`)
 #[cfg(not(mobile))]
 #[tauri::command]
-async fn get_server_info(info: tauri::State<'_, ServerInfo>) -> Result<ServerInfo, String> {
-    Ok(ServerInfo {
-        token: info.token.clone(),
-        port: info.port,
-        local_ip: info.local_ip.clone(),
-        ws_url: info.ws_url.clone(),
+async fn get_server_info(info: tauri::State<'_, Mutex<ServerInfo>>) -> Result<ServerInfo, C9Error> {
+    Ok(info.lock().unwrap_or_else(|e| e.into_inner()).clone())
+}
+
+/// Recent connections and requests the web server turned away (bad tokens,
+/// disallowed addresses, rate limiting), newest first
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_rejected_connections() -> Result<Vec<rate_limit::RejectedAttempt>, C9Error> {
+    Ok(rate_limit::rate_limiter().rejected_attempts())
+}
+
+/// Default and maximum number of lines returned by `get_recent_logs`
+#[cfg(not(mobile))]
+const DEFAULT_LOG_LINES: usize = 500;
+#[cfg(not(mobile))]
+const MAX_LOG_LINES: usize = 5000;
+
+/// Tail of the app's log files, for attaching to bug reports
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_recent_logs(lines: Option<usize>) -> Result<Vec<String>, C9Error> {
+    Ok(logging::recent_logs(
+        lines.unwrap_or(DEFAULT_LOG_LINES).min(MAX_LOG_LINES),
+    )?)
+}
+
+/// Restart the web server so changed bind/LAN settings take effect without
+/// quitting the app. Connected clients are closed and reconnect.
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn restart_server(
+    app: AppHandle,
+    runtime: tauri::State<'_, web_server::ServerRuntime>,
+    info: tauri::State<'_, Mutex<ServerInfo>>,
+) -> Result<ServerInfo, C9Error> {
+    let settings = settings::AppSettings::load();
+    let access = network::AccessPolicy::from_settings(&settings, web_server::WS_PORT);
+    runtime.restart(access.clone()).await?;
+
+    let updated = {
+        let mut info = info.lock().unwrap_or_else(|e| e.into_inner());
+        *info = ServerInfo::new(
+            info.token.clone(),
+            info.port,
+            network::advertised_ip(&access, &settings),
+            access.is_public(),
+        );
+        info.clone()
+    };
+    let _ = app.emit("server-info-changed", &updated);
+    Ok(updated)
+}
+
+/// Installed Claude Code version and whether the parser supports it
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn get_environment_info() -> Result<environment::EnvironmentInfo, C9Error> {
+    Ok(environment::environment_info().clone())
+}
+
+/// Self-check of everything c9watch depends on, with fix suggestions
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn run_diagnostics(
+    app: AppHandle,
+    runtime: tauri::State<'_, web_server::ServerRuntime>,
+) -> Result<doctor::DiagnosticsReport, C9Error> {
+    let server_running = runtime.is_running().await;
+    let access = runtime.access();
+    Ok(doctor::run_diagnostics(&app, &access, server_running))
+}
+
+/// Interfaces that can be chosen as the advertised address in settings
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn list_network_interfaces() -> Result<Vec<network::NetworkInterface>, C9Error> {
+    Ok(network::list_interfaces())
+}
+
+/// A one-time code the mobile client exchanges for its own credential
+#[cfg(not(mobile))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairingCode {
+    pub code: String,
+    pub expires_in_seconds: u64,
+    /// URL to encode in the QR code (opens the web client and pairs)
+    pub pair_url: String,
+}
+
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn create_pairing_code(
+    info: tauri::State<'_, Mutex<ServerInfo>>,
+) -> Result<PairingCode, C9Error> {
+    let info = info.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    if !info.mobile_access {
+        return Err(C9Error::PermissionDenied(
+            "Mobile access is disabled in settings".to_string(),
+        ));
+    }
+    let code = pairing::pairing().create_code();
+    Ok(PairingCode {
+        pair_url: format!("http://{}:{}/?pair={}", info.local_ip, info.port, code),
+        code,
+        expires_in_seconds: pairing::CODE_TTL.as_secs(),
     })
 }
 
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn list_paired_devices() -> Result<Vec<pairing::DeviceInfo>, C9Error> {
+    Ok(pairing::pairing().list_devices())
+}
+
+#[cfg(not(mobile))]
+#[tauri::command]
+async fn revoke_device(device_id: String) -> Result<(), C9Error> {
+    pairing::pairing().revoke(&device_id)
+}
+
 // ── App entry point ─────────────────────────────────────────────────
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let builder = tauri::Builder::default().plugin(tauri_plugin_opener::init());
+    let builder = tauri::Builder::default();
+
+    // Must be the first plugin: a second launch (e.g. opening a c9watch://
+    // link) hands its arguments to this instance and exits
+    #[cfg(not(mobile))]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    }));
+
+    let builder = builder.plugin(tauri_plugin_opener::init());
 
     // Desktop: full setup with all plugins and commands
     #[cfg(not(mobile))]
     let builder = builder
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_notification::init())
+        .on_window_event(|window, event| {
+            popover::handle_window_event(window, event);
+            window_state::handle_window_event(window, event);
+        })
         .setup(|app| {
+            // ── Logging ─────────────────────────────────────────
+            match app.path().app_log_dir() {
+                Ok(log_dir) => logging::init(&log_dir),
+                Err(e) => eprintln!("Failed to resolve log directory: {}", e),
+            }
+
+            window_state::restore(app.handle());
+
+            // Spawning `claude --version` can take a moment; don't block startup
+            std::thread::spawn(environment::check_installation);
+
+            // Published by the polling loop; managed before anything that
+            // reads it, including links handled at launch
+            let store = Arc::new(SessionStore::new());
+            app.manage(store.clone());
+            #[cfg(target_os = "macos")]
+            scripting::register(store.clone());
+
+            // ── Deep links ──────────────────────────────────────
+            // Bundles register the scheme at install time; dev builds and
+            // AppImages on Linux/Windows need it registered at runtime
+            #[cfg(any(target_os = "linux", windows))]
+            if let Err(e) = app.deep_link().register_all() {
+                tracing::warn!("Failed to register {}:// links: {}", deep_link::SCHEME, e);
+            }
+            let link_app = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    deep_link::open(&link_app, url.as_str(), false);
+                }
+            });
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                for url in urls {
+                    deep_link::open(app.handle(), url.as_str(), true);
+                }
+            }
+
             // ── WebSocket server ────────────────────────────────
-            let token = auth::generate_token();
-            let local_ip = auth::get_local_ip();
+            let token = auth::load_or_create_token();
             let port = web_server::WS_PORT;
+            let settings = settings::AppSettings::load();
+            let access = network::AccessPolicy::from_settings(&settings, port);
+            let local_ip = network::advertised_ip(&access, &settings);
 
-            let ws_url = format!("ws://{}:{}/ws?token={}", local_ip, port, token);
-            let http_url = format!("http://{}:{}/?token={}", local_ip, port, token);
+            if access.is_public() {
+                // The QR carries a short-lived pairing code, never the token itself
+                let pair_code = pairing::pairing().create_code();
+                let pair_url = format!("http://{}:{}/?pair={}", local_ip, port, pair_code);
 
-            eprintln!("\n[c9watch] Mobile connection ready");
-            eprintln!("[c9watch] Token: {}", token);
-            eprintln!("[c9watch] URL:   {}\n", http_url);
-            qr2term::print_qr(&http_url).ok();
-            eprintln!();
+                eprintln!("\n[c9watch] Mobile connection ready");
+                eprintln!(
+                    "[c9watch] Pairing code: {} (valid {}s)",
+                    pair_code,
+                    pairing::CODE_TTL.as_secs()
+                );
+                eprintln!("[c9watch] URL:   {}\n", pair_url);
+                qr2term::print_qr(&pair_url).ok();
+                eprintln!();
+            } else {
+                eprintln!("\n[c9watch] Mobile access disabled; listening on localhost only\n");
+            }
 
             let (sessions_tx, _rx) = tokio::sync::broadcast::channel::<String>(16);
             let (notifications_tx, _nrx) = tokio::sync::broadcast::channel::<String>(16);
+            let (alerts_tx, _arx) = tokio::sync::broadcast::channel::<String>(16);
+
+            let server_info = ServerInfo::new(token.clone(), port, local_ip, access.is_public());
+            app.manage(Mutex::new(server_info));
 
-            let server_info = ServerInfo {
-                token: token.clone(),
-                port,
-                local_ip: local_ip.clone(),
-                ws_url,
-            };
-            app.manage(server_info);
-
-            let ws_state = Arc::new(web_server::WsState {
-                auth_token: token,
-                sessions_tx: sessions_tx.clone(),
-                notifications_tx: notifications_tx.clone(),
+            app.manage(web_server::ServerRuntime::new(
+                token,
+                access.clone(),
+                store.clone(),
+                sessions_tx.clone(),
+                notifications_tx.clone(),
+                alerts_tx.clone(),
+            ));
+            let server_app = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let runtime = server_app.state::<web_server::ServerRuntime>();
+                if let Err(e) = runtime.restart(access).await {
+                    error!("{}", e);
+                }
             });
-            tauri::async_runtime::spawn(web_server::start_server(ws_state));
+            local_socket::start(app.state::<web_server::ServerRuntime>().local_state());
+            network::watch_advertised_address(app.handle().clone());
+
+            if settings.otlp_receiver {
+                telemetry::start_receiver();
+            }
+
+            api_status::watch(app.handle().clone(), alerts_tx.clone());
+
+            #[cfg(target_os = "linux")]
+            dbus::start(store.clone());
 
             // ── Polling loop ────────────────────────────────────
-            start_polling(app.handle().clone(), sessions_tx, notifications_tx);
+            start_polling(
+                app.handle().clone(),
+                store,
+                sessions_tx,
+                notifications_tx,
+                alerts_tx,
+            );
 
             // ── Tray icon ───────────────────────────────────────
             let app_handle = app.handle().clone();
@@ -257,10 +1037,15 @@ pub fn run() {
                     if let TrayIconEvent::Click {
                         button: MouseButton::Left,
                         button_state: MouseButtonState::Up,
+                        rect,
                         ..
                     } = event
                     {
-                        if let Some(window) = app_handle.get_webview_window("main") {
+                        if app_handle.get_webview_window(popover::LABEL).is_some() {
+                            if let Err(e) = popover::show_at_tray(&app_handle, rect) {
+                                error!("Failed to show popover: {}", e);
+                            }
+                        } else if let Some(window) = app_handle.get_webview_window("main") {
                             let _ = window.show();
                             let _ = window.set_focus();
                         }
@@ -273,18 +1058,73 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             greet,
             get_sessions,
+            query_sessions,
+            search_conversations,
             get_conversation,
+            get_message_content,
+            copy_conversation,
+            share_conversation,
             stop_session,
             open_session,
+            open_session_file,
+            reveal_session_file,
+            respond_to_permission,
+            quit_session_gracefully,
             rename_session,
+            set_note,
+            set_session_color,
+            override_status,
+            snooze_session,
+            get_ignore_list,
+            set_ignore_list,
+            ignore_session,
+            get_session_timeline,
+            get_session_stats,
+            get_session_changes,
+            get_todos,
+            get_usage_report,
+            vacuum_history,
+            get_parse_diagnostics,
+            get_api_status,
+            get_recent_events,
+            check_permission,
+            get_permission_rules,
+            add_allow_rule,
+            get_settings,
+            update_settings,
+            export_settings,
+            import_settings,
             get_terminal_title,
             show_main_window,
-            get_server_info
+            open_session_window,
+            set_popover_pinned,
+            get_popover_pinned,
+            take_pending_session_link,
+            get_server_info,
+            list_network_interfaces,
+            get_rejected_connections,
+            restart_server,
+            get_recent_logs,
+            run_diagnostics,
+            get_environment_info,
+            create_pairing_code,
+            list_paired_devices,
+            revoke_device
         ]);
 
-    // Mobile: minimal shell (all communication via WebSocket from the frontend)
+    // Mobile: minimal shell. The frontend talks to the desktop over its own
+    // WebSocket; a Rust-side connection survives the WebView being suspended,
+    // keeps the latest sessions and posts notifications in the background.
     #[cfg(mobile)]
-    let builder = builder.setup(|_app| Ok(()));
+    let builder = builder
+        .plugin(tauri_plugin_notification::init())
+        .setup(|_app| Ok(()))
+        .invoke_handler(tauri::generate_handler![
+            mobile_connection::connect_desktop,
+            mobile_connection::disconnect_desktop,
+            mobile_connection::get_desktop_snapshot,
+            mobile_notifications::set_app_foreground
+        ]);
 
     builder
         .run(tauri::generate_context!())
@@ -0,0 +1,275 @@
+use crate::web_server::{serve_connection, Encoding, Frame, ServerMsg, Transport, WsState};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tracing::{error, info, warn};
+
+/// Named pipe the control API listens on (Windows)
+#[cfg(windows)]
+pub const PIPE_NAME: &str = r"\\.\pipe\c9watch";
+
+/// Owner-only DACL for the pipe: full access for its owner (this user),
+/// protected from inherited entries, so no one else can open it
+#[cfg(windows)]
+const PIPE_SDDL: &str = "D:P(A;;GA;;;OW)";
+
+/// Unix socket the control API listens on. It sits in a directory only this
+/// user can enter, so only this user can connect and no token is needed.
+#[cfg(unix)]
+pub fn socket_path() -> std::path::PathBuf {
+    let home = dirs::home_dir().expect("Failed to get home directory");
+    home.join(".claude").join("c9watch").join("c9watch.sock")
+}
+
+/// The WS protocol over a byte stream: one JSON message per line each way.
+/// MessagePack isn't offered since it can't be split into lines.
+struct LineTransport<S> {
+    stream: BufReader<S>,
+    /// Bytes of a line still being read, kept across cancelled reads
+    pending: Vec<u8>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> LineTransport<S> {
+    fn new(stream: S) -> Self {
+        Self {
+            stream: BufReader::new(stream),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> Transport for LineTransport<S> {
+    const BINARY: bool = false;
+
+    async fn next_frame(&mut self) -> Option<Frame> {
+        loop {
+            // `read_until` keeps partial input in `pending` if the read is
+            // cancelled by a push going out
+            match self.stream.read_until(b'\n', &mut self.pending).await {
+                Ok(0) | Err(_) => return None,
+                Ok(_) => {}
+            }
+            let line = String::from_utf8_lossy(&self.pending).trim().to_string();
+            self.pending.clear();
+            if !line.is_empty() {
+                return Some(Frame::Text(line));
+            }
+        }
+    }
+
+    async fn send_msg(&mut self, msg: &ServerMsg, _encoding: Encoding) -> bool {
+        let mut line = serde_json::to_vec(msg).unwrap_or_default();
+        line.push(b'\n');
+        let stream = self.stream.get_mut();
+        stream.write_all(&line).await.is_ok() && stream.flush().await.is_ok()
+    }
+
+    async fn close(&mut self) {
+        let _ = self.stream.get_mut().shutdown().await;
+    }
+}
+
+/// Serve the control API on the local socket until the app exits
+pub fn start(state: Arc<WsState>) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = listen(state).await {
+            error!("Local control socket: {}", e);
+        }
+    });
+}
+
+/// Create the socket's directory, or take over an existing one, as this
+/// user's alone (0700). The socket is bound inside it, so there's no moment
+/// when it is reachable by others, as there would be between bind and chmod.
+#[cfg(unix)]
+fn private_dir(dir: &std::path::Path) -> Result<(), String> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let metadata = std::fs::symlink_metadata(dir)
+        .map_err(|e| format!("Failed to inspect {}: {}", dir.display(), e))?;
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let uid = unsafe { libc::geteuid() };
+    if !metadata.is_dir() || metadata.uid() != uid {
+        return Err(format!(
+            "{} isn't a directory owned by this user",
+            dir.display()
+        ));
+    }
+    if metadata.mode() & 0o777 != 0o700 {
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Failed to restrict {}: {}", dir.display(), e))?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn listen(state: Arc<WsState>) -> Result<(), String> {
+    let path = socket_path();
+    if let Some(dir) = path.parent() {
+        private_dir(dir)?;
+    }
+    // Left behind by a previous run that didn't exit cleanly
+    if path.exists() {
+        std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove stale {}: {}", path.display(), e))?;
+    }
+    let listener = tokio::net::UnixListener::bind(&path)
+        .map_err(|e| format!("Failed to bind {}: {}", path.display(), e))?;
+    info!("Control API listening on {}", path.display());
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let state = state.clone();
+                tauri::async_runtime::spawn(async move {
+                    serve_connection(LineTransport::new(stream), state).await;
+                });
+            }
+            Err(e) => warn!("Failed to accept control connection: {}", e),
+        }
+    }
+}
+
+/// A security descriptor parsed from SDDL, freed on drop
+#[cfg(windows)]
+struct PipeSecurity {
+    descriptor: windows_sys::Win32::Security::PSECURITY_DESCRIPTOR,
+}
+
+// The descriptor is a private heap allocation only read through this value
+#[cfg(windows)]
+unsafe impl Send for PipeSecurity {}
+
+#[cfg(windows)]
+impl PipeSecurity {
+    fn new(sddl: &str) -> Result<Self, String> {
+        use windows_sys::Win32::Security::Authorization::{
+            ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+        };
+
+        let wide: Vec<u16> = sddl.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut descriptor = std::ptr::null_mut();
+        // SAFETY: `wide` is NUL-terminated and outlives the call; the
+        // descriptor it allocates is freed in Drop
+        let ok = unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                wide.as_ptr(),
+                SDDL_REVISION_1,
+                &mut descriptor,
+                std::ptr::null_mut(),
+            )
+        };
+        if ok == 0 {
+            return Err(format!(
+                "Failed to build the pipe's security descriptor: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(Self { descriptor })
+    }
+
+    /// Create a pipe instance carrying this descriptor
+    fn create(
+        &self,
+        options: &tokio::net::windows::named_pipe::ServerOptions,
+    ) -> Result<tokio::net::windows::named_pipe::NamedPipeServer, String> {
+        use windows_sys::Win32::Security::SECURITY_ATTRIBUTES;
+
+        let mut attributes = SECURITY_ATTRIBUTES {
+            nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+            lpSecurityDescriptor: self.descriptor,
+            bInheritHandle: 0,
+        };
+        // SAFETY: `attributes` points at a valid descriptor for the call
+        unsafe {
+            options.create_with_security_attributes_raw(
+                PIPE_NAME,
+                &mut attributes as *mut SECURITY_ATTRIBUTES as *mut std::ffi::c_void,
+            )
+        }
+        .map_err(|e| format!("Failed to create {}: {}", PIPE_NAME, e))
+    }
+}
+
+#[cfg(windows)]
+impl Drop for PipeSecurity {
+    fn drop(&mut self) {
+        // SAFETY: allocated by ConvertStringSecurityDescriptorToSecurityDescriptorW
+        unsafe {
+            windows_sys::Win32::Foundation::LocalFree(self.descriptor);
+        }
+    }
+}
+
+#[cfg(windows)]
+async fn listen(state: Arc<WsState>) -> Result<(), String> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    // Remote clients are refused, and an explicit DACL limits local ones to
+    // this user. The default descriptor would also let in LocalSystem,
+    // administrators and, for reading, Everyone.
+    let security = PipeSecurity::new(PIPE_SDDL)?;
+    let mut options = ServerOptions::new();
+    options.reject_remote_clients(true);
+    let mut server = security.create(options.clone().first_pipe_instance(true))?;
+    info!("Control API listening on {}", PIPE_NAME);
+
+    loop {
+        let accepted = server.connect().await;
+        // Open the next instance before handing this one off, so there's
+        // always one for a client to connect to
+        let connected = std::mem::replace(&mut server, security.create(&options)?);
+        if let Err(e) = accepted {
+            warn!("Failed to accept control connection: {}", e);
+            continue;
+        }
+
+        let state = state.clone();
+        tauri::async_runtime::spawn(async move {
+            serve_connection(LineTransport::new(connected), state).await;
+        });
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+async fn listen(_state: Arc<WsState>) -> Result<(), String> {
+    Err("not supported on this platform".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    #[test]
+    fn test_line_transport_frames() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let (server, mut client) = tokio::io::duplex(1024);
+            let mut transport = LineTransport::new(server);
+
+            client
+                .write_all(b"\n{\"type\":\"getSessions\"}\n")
+                .await
+                .unwrap();
+            match transport.next_frame().await {
+                Some(Frame::Text(text)) => assert_eq!(text, r#"{"type":"getSessions"}"#),
+                _ => panic!("expected a text frame"),
+            }
+
+            assert!(transport.send_msg(&ServerMsg::Ok, Encoding::Json).await);
+            let mut reply = vec![0; 14];
+            client.read_exact(&mut reply).await.unwrap();
+            assert_eq!(reply, b"{\"type\":\"ok\"}\n");
+
+            drop(client);
+            assert!(transport.next_frame().await.is_none());
+        });
+    }
+}
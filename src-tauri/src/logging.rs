@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, EnvFilter};
+
+/// Directory the rotated log files are written to, set once by `init`
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Keeps the background log writer alive (and flushing) for the app's lifetime
+static WORKER_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+const LOG_FILE_PREFIX: &str = "c9watch";
+const LOG_FILE_SUFFIX: &str = "log";
+
+/// Daily log files kept before the oldest is deleted
+const MAX_LOG_FILES: usize = 7;
+
+/// Log to stderr and to daily-rotated files in `log_dir`. The level defaults
+/// to `info` and can be overridden with `RUST_LOG`.
+pub fn init(log_dir: &Path) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let stderr_layer = fmt::layer().with_writer(std::io::stderr);
+
+    let file_layer = match RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir)
+    {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let _ = WORKER_GUARD.set(guard);
+            let _ = LOG_DIR.set(log_dir.to_path_buf());
+            Some(fmt::layer().with_writer(writer).with_ansi(false))
+        }
+        Err(e) => {
+            eprintln!("Failed to open log directory {}: {}", log_dir.display(), e);
+            None
+        }
+    };
+
+    let _ = tracing_subscriber::registry()
+        .with(filter)
+        .with(stderr_layer)
+        .with(file_layer)
+        .try_init();
+}
+
+/// The last `lines` lines across the most recent log files, oldest first
+pub fn recent_logs(lines: usize) -> Result<Vec<String>, String> {
+    let dir = LOG_DIR
+        .get()
+        .ok_or_else(|| "File logging is not enabled".to_string())?;
+
+    // Rotated files are named c9watch.YYYY-MM-DD.log, so name order is date order
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read log directory: {}", e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.starts_with(LOG_FILE_PREFIX) && name.ends_with(LOG_FILE_SUFFIX))
+                .unwrap_or(false)
+        })
+        .collect();
+    files.sort();
+
+    let mut collected: Vec<String> = Vec::new();
+    for path in files.iter().rev() {
+        let needed = lines - collected.len();
+        let mut chunk = crate::session::parser::read_last_n_lines(path, needed)?;
+        chunk.append(&mut collected);
+        collected = chunk;
+        if collected.len() >= lines {
+            break;
+        }
+    }
+
+    Ok(collected)
+}
@@ -0,0 +1,121 @@
+//! Edits to what the user attaches to sessions: titles, notes, colors,
+//! snoozes, status overrides and the ignore list.
+//!
+//! The Tauri commands and the WS handlers both go through here, so an edit
+//! from either surface triggers a poll, and the polling loop re-emits the
+//! session list to the desktop window and every WS client.
+
+use crate::error::C9Error;
+use crate::session::{
+    validate_session_id, CustomTitles, IgnoreList, SessionColor, SessionColors, SessionNotes,
+    SessionStatus,
+};
+use crate::settings::AppSettings;
+use crate::store::SessionStore;
+use chrono::{DateTime, Utc};
+use tracing::{debug, warn};
+
+/// Poll now so the edit shows up everywhere instead of on the next interval
+fn changed(store: &SessionStore) {
+    store.request_refresh();
+}
+
+pub fn rename(store: &SessionStore, session_id: String, new_name: String) -> Result<(), C9Error> {
+    validate_session_id(&session_id)?;
+    let title = new_name.trim().to_string();
+    let mut custom_titles = CustomTitles::load();
+    custom_titles.set(session_id.clone(), title.clone());
+    custom_titles.save()?;
+    if AppSettings::load().sync_terminal_title && !title.is_empty() {
+        if let Some(session) = store.sessions().iter().find(|s| s.id == session_id) {
+            sync_terminal_title(session.pid, title);
+        }
+    }
+    changed(store);
+    Ok(())
+}
+
+/// Rename the session's terminal tab too. Off the caller's thread, since
+/// AppleScript and terminal CLIs can take a while; a terminal that won't
+/// take the title isn't worth failing the rename over.
+fn sync_terminal_title(pid: u32, title: String) {
+    std::thread::spawn(
+        move || match crate::actions::set_terminal_title(pid, &title) {
+            Ok(true) => {}
+            Ok(false) => debug!("Terminal of PID {} has no title API", pid),
+            Err(e) => warn!("Failed to set terminal title for PID {}: {}", pid, e),
+        },
+    );
+}
+
+/// Show `status` instead of the detected one until `ttl` seconds pass
+/// (default an hour) or the detected status changes
+pub fn override_status(
+    store: &SessionStore,
+    session_id: &str,
+    status: SessionStatus,
+    ttl: Option<u64>,
+) -> Result<(), C9Error> {
+    validate_session_id(session_id)?;
+    crate::status_override::overrides().set(
+        session_id,
+        status,
+        ttl.unwrap_or(crate::status_override::DEFAULT_TTL_SECS),
+    );
+    changed(store);
+    Ok(())
+}
+
+/// Hold back notifications for `minutes` (0 wakes the session). Returns when
+/// they resume.
+pub fn snooze(
+    store: &SessionStore,
+    session_id: &str,
+    minutes: u32,
+) -> Result<Option<DateTime<Utc>>, C9Error> {
+    validate_session_id(session_id)?;
+    let until = crate::snooze::snoozes().snooze(session_id, minutes);
+    changed(store);
+    Ok(until)
+}
+
+/// Blank text removes the note
+pub fn set_note(store: &SessionStore, session_id: String, text: String) -> Result<(), C9Error> {
+    validate_session_id(&session_id)?;
+    let mut notes = SessionNotes::load();
+    notes.set(session_id, text);
+    notes.save()?;
+    changed(store);
+    Ok(())
+}
+
+/// None removes the color
+pub fn set_color(
+    store: &SessionStore,
+    session_id: String,
+    color: Option<SessionColor>,
+) -> Result<(), C9Error> {
+    validate_session_id(&session_id)?;
+    let mut colors = SessionColors::load();
+    colors.set(session_id, color);
+    colors.save()?;
+    changed(store);
+    Ok(())
+}
+
+pub fn ignore(store: &SessionStore, session_id: String) -> Result<(), C9Error> {
+    validate_session_id(&session_id)?;
+    let mut ignore_list = IgnoreList::load();
+    ignore_list.ignore_session(session_id);
+    ignore_list.save()?;
+    changed(store);
+    Ok(())
+}
+
+/// Replace the whole ignore list
+pub fn set_ignore_list(store: &SessionStore, ignore_list: IgnoreList) -> Result<(), C9Error> {
+    ignore_list.validate().map_err(C9Error::ParseError)?;
+    ignore_list.save()?;
+    changed(store);
+    Ok(())
+}
@@ -0,0 +1,224 @@
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{header, HeaderValue};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{info, warn};
+
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// How often to ping the desktop
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Silence after which the socket is assumed dead, e.g. after the phone
+/// switched from Wi-Fi to cellular and the old TCP connection went nowhere
+const KEEPALIVE_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// What the Rust side last heard from the desktop. Survives the WebView
+/// being suspended, so the UI can repaint immediately on resume.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DesktopSnapshot {
+    pub connected: bool,
+    /// Latest session list, as sent in `sessions`/`sessionsUpdated`
+    pub sessions: Option<serde_json::Value>,
+    /// RFC 3339 time `sessions` arrived
+    pub updated_at: Option<String>,
+}
+
+struct Connection {
+    task: JoinHandle<()>,
+    wake: Arc<Notify>,
+}
+
+static CONNECTION: OnceLock<Mutex<Option<Connection>>> = OnceLock::new();
+static SNAPSHOT: OnceLock<Mutex<DesktopSnapshot>> = OnceLock::new();
+
+fn connection() -> &'static Mutex<Option<Connection>> {
+    CONNECTION.get_or_init(|| Mutex::new(None))
+}
+
+fn snapshot() -> &'static Mutex<DesktopSnapshot> {
+    SNAPSHOT.get_or_init(|| Mutex::new(DesktopSnapshot::default()))
+}
+
+/// Keep a socket open to the desktop at `url` (the stored WS URL, token
+/// included), reconnecting with backoff. Replaces any previous connection.
+///
+/// Emits `desktop-sessions-updated` and `desktop-connection-changed`, and
+/// hands notifications to `mobile_notifications`.
+#[tauri::command]
+pub fn connect_desktop(app: AppHandle, url: String) {
+    let mut connection = connection().lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(previous) = connection.take() {
+        previous.task.abort();
+    }
+    *snapshot().lock().unwrap_or_else(|e| e.into_inner()) = DesktopSnapshot::default();
+
+    let wake = Arc::new(Notify::new());
+    *connection = Some(Connection {
+        task: tauri::async_runtime::spawn(run(app, url, wake.clone())),
+        wake,
+    });
+}
+
+#[tauri::command]
+pub fn disconnect_desktop() {
+    if let Some(running) = connection()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+    {
+        running.task.abort();
+    }
+    *snapshot().lock().unwrap_or_else(|e| e.into_inner()) = DesktopSnapshot::default();
+}
+
+#[tauri::command]
+pub fn get_desktop_snapshot() -> DesktopSnapshot {
+    snapshot().lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Drop the current socket (if any) and reconnect without waiting out the
+/// backoff
+pub fn reconnect_now() {
+    if let Some(connection) = connection()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+    {
+        connection.wake.notify_one();
+    }
+}
+
+async fn run(app: AppHandle, url: String, wake: Arc<Notify>) {
+    let mut delay = MIN_RECONNECT_DELAY;
+    loop {
+        match connect(&app, &url, &wake).await {
+            // Was connected and closed cleanly, or asked to reconnect
+            Ok(()) => delay = MIN_RECONNECT_DELAY,
+            Err(e) => warn!("Desktop connection: {}", e),
+        }
+        set_connected(&app, false);
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+            _ = wake.notified() => delay = MIN_RECONNECT_DELAY,
+        }
+    }
+}
+
+/// One connection, from the handshake until it drops. The token is sent
+/// again on every connect, so a new network path is simply a new login.
+async fn connect(app: &AppHandle, url: &str, wake: &Notify) -> Result<(), String> {
+    // Native clients can send the token as a header instead of in the URL
+    let (bare_url, token) = split_token(url);
+    let mut request = bare_url.into_client_request().map_err(|e| e.to_string())?;
+    if let Some(token) = token {
+        let value =
+            HeaderValue::from_str(&format!("Bearer {}", token)).map_err(|e| e.to_string())?;
+        request.headers_mut().insert(header::AUTHORIZATION, value);
+    }
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(|e| e.to_string())?;
+    info!("Connected to desktop");
+    set_connected(app, true);
+
+    // Updates are only pushed on change; start from a full list
+    socket
+        .send(Message::Text(r#"{"type":"getSessions"}"#.into()))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut keepalive = tokio::time::interval(KEEPALIVE_INTERVAL);
+    let mut last_heard = Instant::now();
+    loop {
+        tokio::select! {
+            message = socket.next() => {
+                let Some(message) = message else {
+                    return Ok(());
+                };
+                last_heard = Instant::now();
+                if let Message::Text(text) = message.map_err(|e| e.to_string())? {
+                    handle_message(app, &text);
+                }
+            }
+            _ = keepalive.tick() => {
+                if last_heard.elapsed() > KEEPALIVE_TIMEOUT {
+                    return Err("Desktop stopped responding".to_string());
+                }
+                socket
+                    .send(Message::Ping(Vec::new()))
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            _ = wake.notified() => {
+                let _ = socket.close(None).await;
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn handle_message(app: &AppHandle, text: &str) {
+    let Ok(msg) = serde_json::from_str::<serde_json::Value>(text) else {
+        return;
+    };
+    match msg["type"].as_str() {
+        Some("sessions") | Some("sessionsUpdated") => {
+            {
+                let mut snapshot = snapshot().lock().unwrap_or_else(|e| e.into_inner());
+                snapshot.sessions = Some(msg["data"].clone());
+                snapshot.updated_at = Some(chrono::Utc::now().to_rfc3339());
+            }
+            let _ = app.emit("desktop-sessions-updated", &msg["data"]);
+        }
+        Some("notification") => crate::mobile_notifications::notify(app, &msg["data"]),
+        _ => {}
+    }
+}
+
+fn set_connected(app: &AppHandle, connected: bool) {
+    let changed = {
+        let mut snapshot = snapshot().lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::replace(&mut snapshot.connected, connected) != connected
+    };
+    if changed {
+        let _ = app.emit("desktop-connection-changed", connected);
+    }
+}
+
+/// Split `?token=` off a WS URL
+fn split_token(url: &str) -> (String, Option<String>) {
+    let Some((base, query)) = url.split_once('?') else {
+        return (url.to_string(), None);
+    };
+
+    let mut token = None;
+    let rest: Vec<&str> = query
+        .split('&')
+        .filter(|pair| match pair.strip_prefix("token=") {
+            Some(value) => {
+                token = Some(value.to_string());
+                false
+            }
+            None => true,
+        })
+        .collect();
+
+    if rest.is_empty() {
+        (base.to_string(), token)
+    } else {
+        (format!("{}?{}", base, rest.join("&")), token)
+    }
+}
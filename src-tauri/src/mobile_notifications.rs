@@ -0,0 +1,37 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+use tracing::warn;
+
+/// Whether the WebView is on screen. It shows its own in-app toasts then,
+/// so local notifications are only posted while the app is in the background.
+static FOREGROUND: AtomicBool = AtomicBool::new(true);
+
+/// Called by the frontend on `visibilitychange`
+#[tauri::command]
+pub fn set_app_foreground(foreground: bool) {
+    let was_foreground = FOREGROUND.swap(foreground, Ordering::Relaxed);
+    // The socket may have died while suspended (or the network changed);
+    // don't wait for the keepalive to notice
+    if foreground && !was_foreground {
+        crate::mobile_connection::reconnect_now();
+    }
+}
+
+/// Post a desktop `notification` message as a local notification, unless
+/// the app is in the foreground.
+///
+/// This is not push: Android keeps the process, and so the socket, alive for
+/// a while after the app is backgrounded, but iOS suspends it within seconds
+/// unless the OS grants background time. Alerts sent while the socket is
+/// down are not replayed.
+pub fn notify(app: &AppHandle, data: &serde_json::Value) {
+    if FOREGROUND.load(Ordering::Relaxed) {
+        return;
+    }
+    let title = data["title"].as_str().unwrap_or("c9watch");
+    let body = data["body"].as_str().unwrap_or_default();
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        warn!("Failed to show notification: {}", e);
+    }
+}
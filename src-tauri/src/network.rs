@@ -0,0 +1,297 @@
+use crate::settings::AppSettings;
+use crate::web_server::ServerRuntime;
+use crate::ServerInfo;
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{error, info, warn};
+
+/// How often the advertised address is re-checked (network switches, VPN up/down)
+const ADDRESS_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// An IP range like "192.168.1.0/24" or "fd00::/8". A bare address is a /32 (/128).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let network: IpAddr = addr
+            .parse()
+            .map_err(|_| format!("Invalid IP address in range: {}", s))?;
+        let max = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(p) => p
+                .parse::<u8>()
+                .ok()
+                .filter(|p| *p <= max)
+                .ok_or_else(|| format!("Invalid prefix length in range: {}", s))?,
+            None => max,
+        };
+        Ok(Self { network, prefix })
+    }
+}
+
+impl Cidr {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        // Peers on a dual-stack socket show up as ::ffff:a.b.c.d
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Where the web server listens and who may talk to it, derived from settings
+#[derive(Debug, Clone)]
+pub struct AccessPolicy {
+    pub bind_addr: SocketAddr,
+    /// Empty means any peer that can reach `bind_addr`
+    allowed: Vec<Cidr>,
+}
+
+impl AccessPolicy {
+    pub fn from_settings(settings: &AppSettings, port: u16) -> Self {
+        let ip = if !settings.mobile_access {
+            // Localhost-only mode: never reachable from the LAN
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        } else if let Some(addr) = settings.bind_address.as_deref() {
+            addr.trim().parse().unwrap_or_else(|_| {
+                warn!("Invalid bind address {:?}, using localhost", addr);
+                IpAddr::V4(Ipv4Addr::LOCALHOST)
+            })
+        } else {
+            // [::] accepts both IPv4 and IPv6 (localhost can resolve to ::1)
+            IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+        };
+
+        let allowed = settings
+            .allowed_cidrs
+            .iter()
+            .filter_map(|range| match range.parse::<Cidr>() {
+                Ok(cidr) => Some(cidr),
+                Err(e) => {
+                    warn!("Ignoring allowed range: {}", e);
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            bind_addr: SocketAddr::new(ip, port),
+            allowed,
+        }
+    }
+
+    /// Whether the server is reachable from other machines at all
+    pub fn is_public(&self) -> bool {
+        !self.bind_addr.ip().is_loopback()
+    }
+
+    /// Loopback is always allowed; anything else must match an allowed range
+    /// when ranges are configured
+    pub fn allows(&self, peer: IpAddr) -> bool {
+        peer.to_canonical().is_loopback()
+            || self.allowed.is_empty()
+            || self.allowed.iter().any(|cidr| cidr.contains(peer))
+    }
+}
+
+/// Broad category of a network interface, guessed from its name and address
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum InterfaceKind {
+    Wifi,
+    Ethernet,
+    Vpn,
+    Other,
+}
+
+/// A non-loopback interface address the server could advertise
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkInterface {
+    pub name: String,
+    pub address: String,
+    pub kind: InterfaceKind,
+    /// The address outbound traffic currently uses (the automatic choice)
+    pub is_default: bool,
+}
+
+/// IPv4 addresses of all non-loopback interfaces, default route first
+pub fn list_interfaces() -> Vec<NetworkInterface> {
+    let default_ip = crate::auth::get_local_ip();
+    let mut interfaces: Vec<NetworkInterface> = if_addrs::get_if_addrs()
+        .unwrap_or_else(|e| {
+            error!("Failed to list interfaces: {}", e);
+            Vec::new()
+        })
+        .into_iter()
+        .filter(|iface| !iface.is_loopback())
+        // Phones can't use IPv6 link-local addresses in a URL without a zone id
+        .filter(|iface| iface.ip().is_ipv4())
+        .map(|iface| {
+            let ip = iface.ip();
+            NetworkInterface {
+                kind: classify(&iface.name, ip),
+                address: ip.to_string(),
+                is_default: ip.to_string() == default_ip,
+                name: iface.name,
+            }
+        })
+        .collect();
+    interfaces.sort_by(|a, b| b.is_default.cmp(&a.is_default).then(a.name.cmp(&b.name)));
+    interfaces
+}
+
+/// Interface name prefixes used to guess an interface's kind
+const VPN_PREFIXES: &[&str] = &[
+    "utun",
+    "tun",
+    "tap",
+    "wg",
+    "tailscale",
+    "zt",
+    "ppp",
+    "ipsec",
+];
+const WIFI_PREFIXES: &[&str] = &["wl", "wi-fi", "wifi", "wireless", "airport"];
+const ETHERNET_PREFIXES: &[&str] = &["en", "eth", "ethernet"];
+
+fn classify(name: &str, ip: IpAddr) -> InterfaceKind {
+    let name = name.to_lowercase();
+    let has_prefix = |prefixes: &[&str]| prefixes.iter().any(|p| name.starts_with(p));
+    // Tailscale hands out addresses from the CGNAT range
+    let tailscale: Cidr = "100.64.0.0/10".parse().expect("valid range");
+
+    if tailscale.contains(ip) || has_prefix(VPN_PREFIXES) {
+        InterfaceKind::Vpn
+    } else if has_prefix(WIFI_PREFIXES) {
+        InterfaceKind::Wifi
+    } else if has_prefix(ETHERNET_PREFIXES) {
+        InterfaceKind::Ethernet
+    } else {
+        InterfaceKind::Other
+    }
+}
+
+/// The address to put in connection URLs and the QR code
+pub fn advertised_ip(access: &AccessPolicy, settings: &AppSettings) -> String {
+    let bind_ip = access.bind_addr.ip();
+    if !access.is_public() || !bind_ip.is_unspecified() {
+        return bind_ip.to_string();
+    }
+    if let Some(name) = settings.advertised_interface.as_deref() {
+        if let Some(iface) = list_interfaces().into_iter().find(|i| i.name == name) {
+            return iface.address;
+        }
+    }
+    crate::auth::get_local_ip()
+}
+
+/// Keep `ServerInfo` in sync with the advertised address and tell the UI
+/// (which regenerates the QR code) when it changes
+pub fn watch_advertised_address(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(ADDRESS_CHECK_INTERVAL);
+        refresh_advertised_address(&app);
+    });
+}
+
+/// Re-resolve the advertised address now, e.g. after waking from sleep on
+/// a different network
+pub fn refresh_advertised_address(app: &AppHandle) {
+    let access = app.state::<ServerRuntime>().access();
+    let ip = advertised_ip(&access, &AppSettings::load());
+    let state = app.state::<Mutex<ServerInfo>>();
+    let info = {
+        let mut info = state.lock().unwrap_or_else(|e| e.into_inner());
+        if info.local_ip == ip {
+            return;
+        }
+        info!("Advertised address changed: {} -> {}", info.local_ip, ip);
+        *info = ServerInfo::new(info.token.clone(), info.port, ip, info.mobile_access);
+        info.clone()
+    };
+    if let Err(e) = app.emit("server-info-changed", &info) {
+        error!("Failed to emit server-info-changed: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_cidr_contains() {
+        let lan: Cidr = "192.168.1.0/24".parse().unwrap();
+        assert!(lan.contains(ip("192.168.1.42")));
+        assert!(lan.contains(ip("::ffff:192.168.1.42")));
+        assert!(!lan.contains(ip("192.168.2.1")));
+
+        let all: Cidr = "0.0.0.0/0".parse().unwrap();
+        assert!(all.contains(ip("8.8.8.8")));
+
+        let ula: Cidr = "fd00::/8".parse().unwrap();
+        assert!(ula.contains(ip("fd12:3456::1")));
+        assert!(!ula.contains(ip("fe80::1")));
+
+        let single: Cidr = "10.0.0.5".parse().unwrap();
+        assert!(single.contains(ip("10.0.0.5")));
+        assert!(!single.contains(ip("10.0.0.6")));
+
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+        assert!("not-an-ip/8".parse::<Cidr>().is_err());
+    }
+
+    #[test]
+    fn test_classify_interfaces() {
+        assert_eq!(classify("wlan0", ip("192.168.1.5")), InterfaceKind::Wifi);
+        assert_eq!(classify("eth0", ip("10.0.0.5")), InterfaceKind::Ethernet);
+        assert_eq!(classify("utun3", ip("10.0.0.5")), InterfaceKind::Vpn);
+        assert_eq!(classify("en5", ip("100.101.102.103")), InterfaceKind::Vpn);
+        assert_eq!(classify("bridge0", ip("10.0.0.5")), InterfaceKind::Other);
+    }
+
+    #[test]
+    fn test_policy_from_settings() {
+        let mut settings = AppSettings {
+            allowed_cidrs: vec!["192.168.1.0/24".to_string()],
+            ..Default::default()
+        };
+        let policy = AccessPolicy::from_settings(&settings, 9210);
+        assert!(policy.is_public());
+        assert!(policy.allows(ip("127.0.0.1")));
+        assert!(policy.allows(ip("192.168.1.7")));
+        assert!(!policy.allows(ip("10.0.0.7")));
+
+        settings.mobile_access = false;
+        let policy = AccessPolicy::from_settings(&settings, 9210);
+        assert!(!policy.is_public());
+        assert_eq!(policy.bind_addr, "127.0.0.1:9210".parse().unwrap());
+    }
+}
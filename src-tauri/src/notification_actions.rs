@@ -0,0 +1,136 @@
+use crate::polling::Session;
+use crate::session::SessionStatus;
+use crate::store::SessionStore;
+use std::sync::Arc;
+use std::thread;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tracing::{debug, error};
+
+/// What the user did with an actionable notification
+#[derive(Debug, PartialEq)]
+enum Response {
+    Open,
+    Approve,
+    Dismissed,
+}
+
+/// Show a notification with an "Open" button, plus "Approve" when the
+/// session is waiting on a permission prompt, and act on the answer.
+///
+/// The notification plugin only supports actions on mobile, so this talks to
+/// the platform directly. Returns false where that isn't available and the
+/// caller should show a plain notification instead.
+pub fn show(app: &AppHandle, title: &str, body: &str, session: &Session) -> bool {
+    if !cfg!(any(target_os = "macos", target_os = "linux")) {
+        return false;
+    }
+
+    let app = app.clone();
+    let title = title.to_string();
+    let body = body.to_string();
+    let pid = session.pid;
+    let project_path = session.project_path.clone();
+    let needs_permission = session.status == SessionStatus::NeedsPermission;
+    let tool_name = session.pending_tool_name.clone();
+
+    // Waiting for the answer blocks until the notification is clicked or dismissed
+    thread::spawn(move || {
+        let response = match show_and_wait(&title, &body, needs_permission) {
+            Ok(response) => response,
+            Err(e) => {
+                error!("Actionable notification failed, showing a plain one: {}", e);
+                if let Err(e) = app
+                    .notification()
+                    .builder()
+                    .title(&title)
+                    .body(&body)
+                    .show()
+                {
+                    error!("Failed to show notification: {}", e);
+                }
+                return;
+            }
+        };
+
+        debug!("Notification for PID {}: {:?}", pid, response);
+        let result = match response {
+            Response::Open => crate::actions::open_session(pid, project_path),
+            // The prompt may have been answered while the notification was up
+            Response::Approve => crate::actions::respond_to_permission(
+                &app.state::<Arc<SessionStore>>(),
+                pid,
+                true,
+                tool_name.as_deref(),
+            ),
+            Response::Dismissed => Ok(()),
+        };
+        if let Err(e) = result {
+            error!("Notification action failed: {}", e);
+        }
+    });
+    true
+}
+
+#[cfg(target_os = "macos")]
+fn show_and_wait(title: &str, body: &str, needs_permission: bool) -> Result<Response, String> {
+    use mac_notification_sys::{MainButton, Notification, NotificationResponse};
+
+    static SET_APPLICATION: std::sync::Once = std::sync::Once::new();
+    SET_APPLICATION.call_once(|| {
+        // Fails harmlessly if the plugin already registered the bundle
+        let _ = mac_notification_sys::set_application("com.minchenlee.c9watch");
+    });
+
+    let permission_actions = ["Approve", "Open"];
+    let main_button = if needs_permission {
+        MainButton::DropdownActions("Respond", &permission_actions)
+    } else {
+        MainButton::SingleAction("Open")
+    };
+
+    let response = Notification::new()
+        .title(title)
+        .message(body)
+        .main_button(main_button)
+        .wait_for_click(true)
+        .send()
+        .map_err(|e| e.to_string())?;
+
+    Ok(match response {
+        NotificationResponse::ActionButton(action) if action == "Approve" => Response::Approve,
+        NotificationResponse::ActionButton(_) | NotificationResponse::Click => Response::Open,
+        _ => Response::Dismissed,
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn show_and_wait(title: &str, body: &str, needs_permission: bool) -> Result<Response, String> {
+    let mut notification = notify_rust::Notification::new();
+    notification
+        .appname("c9watch")
+        .summary(title)
+        .body(body)
+        // "default" is a click on the notification itself
+        .action("default", "Open")
+        .action("open", "Open");
+    if needs_permission {
+        notification.action("approve", "Approve");
+    }
+
+    let handle = notification.show().map_err(|e| e.to_string())?;
+    let mut response = Response::Dismissed;
+    handle.wait_for_action(|action| {
+        response = match action {
+            "approve" => Response::Approve,
+            "default" | "open" => Response::Open,
+            _ => Response::Dismissed,
+        };
+    });
+    Ok(response)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn show_and_wait(_title: &str, _body: &str, _needs_permission: bool) -> Result<Response, String> {
+    Err("Notification actions are not supported on this platform".to_string())
+}
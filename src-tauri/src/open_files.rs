@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+/// Files a process currently has open, from procfs on Linux and `lsof`
+/// elsewhere. Empty when the process is gone or the lookup isn't available.
+#[cfg(target_os = "linux")]
+pub fn open_files(pid: u32) -> Vec<PathBuf> {
+    let Ok(fds) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+        return Vec::new();
+    };
+    fds.flatten()
+        .filter_map(|fd| std::fs::read_link(fd.path()).ok())
+        .filter(|path| path.is_absolute())
+        .collect()
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn open_files(pid: u32) -> Vec<PathBuf> {
+    // -a: only this pid; -Fn: one "n<path>" line per file; -w: no warnings
+    let Ok(output) = std::process::Command::new("lsof")
+        .args(["-a", "-w", "-p", &pid.to_string(), "-Fn"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    parse_lsof(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(unix))]
+pub fn open_files(_pid: u32) -> Vec<PathBuf> {
+    Vec::new()
+}
+
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+fn parse_lsof(output: &str) -> Vec<PathBuf> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix('n'))
+        .filter(|path| path.starts_with('/'))
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lsof_field_output() {
+        let output = "p4242\nfcwd\nn/Users/me/project\nf12\nn/Users/me/.claude/projects/-Users-me-project/abc.jsonl\nf13\nnlocalhost:9210->localhost:50000\n";
+        assert_eq!(
+            parse_lsof(output),
+            vec![
+                PathBuf::from("/Users/me/project"),
+                PathBuf::from("/Users/me/.claude/projects/-Users-me-project/abc.jsonl"),
+            ]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_open_files_sees_own_handle() {
+        let path = std::env::temp_dir().join(format!("c9watch-open-{}.jsonl", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        let open = open_files(std::process::id());
+        drop(file);
+        let _ = std::fs::remove_file(&path);
+        assert!(open.contains(&path));
+    }
+}
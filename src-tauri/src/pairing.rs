@@ -0,0 +1,293 @@
+use crate::secrets;
+use chrono::Utc;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{error, warn};
+
+/// Global pairing state shared by the Tauri commands and the web server
+static PAIRING: OnceLock<Pairing> = OnceLock::new();
+
+pub fn pairing() -> &'static Pairing {
+    PAIRING.get_or_init(Pairing::load)
+}
+
+/// How long a pairing code can be redeemed after it is shown
+pub const CODE_TTL: Duration = Duration::from_secs(120);
+
+/// Wrong guesses allowed before every outstanding code is invalidated
+const MAX_FAILED_ATTEMPTS: u32 = 5;
+
+/// Devices authenticate on every widget and quick-list poll, so `last_seen`
+/// is only written to disk this often
+const LAST_SEEN_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A device that exchanged a pairing code for its own credential.
+/// This is what's written to disk; the credential itself lives in the OS
+/// credential store and is only kept here when that store is unavailable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PairedDevice {
+    id: String,
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    token: Option<String>,
+    paired_at: String,
+    #[serde(default)]
+    last_seen: Option<String>,
+}
+
+/// Paired device as shown in the UI (without its credential)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceInfo {
+    pub id: String,
+    pub name: String,
+    pub paired_at: String,
+    pub last_seen: Option<String>,
+}
+
+/// Credential handed to a device after a successful pairing
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceCredential {
+    pub device_id: String,
+    pub token: String,
+}
+
+#[derive(Default)]
+struct PairingState {
+    /// Outstanding code -> expiry
+    codes: HashMap<String, Instant>,
+    failed_attempts: u32,
+    devices: Vec<PairedDevice>,
+    /// Device id -> credential, loaded from the credential store at startup
+    tokens: HashMap<String, String>,
+    /// When `authenticate` last wrote the devices file
+    last_seen_saved: Option<Instant>,
+}
+
+/// Short-lived numeric pairing codes and the long-lived device credentials
+/// they are exchanged for, so the main auth token never has to leave the desktop
+pub struct Pairing {
+    state: Mutex<PairingState>,
+}
+
+impl Pairing {
+    fn load() -> Self {
+        let mut devices: Vec<PairedDevice> = fs::read_to_string(Self::get_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        let mut tokens = HashMap::new();
+        let mut migrated = false;
+        for device in &mut devices {
+            let key = secrets::device_key(&device.id);
+            let token = match device.token.clone() {
+                // Plaintext credential (older file, or the store was down when
+                // it was issued): move it into the credential store if we can
+                Some(token) => {
+                    if secrets::set(&key, &token).is_ok() {
+                        device.token = None;
+                        migrated = true;
+                    }
+                    Some(token)
+                }
+                None => secrets::get(&key).unwrap_or_else(|e| {
+                    warn!("{}", e);
+                    None
+                }),
+            };
+            if let Some(token) = token {
+                tokens.insert(device.id.clone(), token);
+            }
+        }
+        if migrated {
+            if let Err(e) = Self::save(&devices) {
+                error!("Failed to save devices: {}", e);
+            }
+        }
+
+        Self {
+            state: Mutex::new(PairingState {
+                devices,
+                tokens,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn get_path() -> PathBuf {
+        let home = dirs::home_dir().expect("Failed to get home directory");
+        home.join(".claude").join("session-monitor-devices.json")
+    }
+
+    fn save(devices: &[PairedDevice]) -> Result<(), String> {
+        let path = Self::get_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(devices).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| e.to_string())
+    }
+
+    /// Generate a new 6-digit code, valid for `CODE_TTL`
+    pub fn create_code(&self) -> String {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        state.codes.retain(|_, expires| *expires > now);
+
+        let mut rng = rand::thread_rng();
+        let code = loop {
+            let candidate = format!("{:06}", rng.gen_range(0..1_000_000));
+            if !state.codes.contains_key(&candidate) {
+                break candidate;
+            }
+        };
+        state.codes.insert(code.clone(), now + CODE_TTL);
+        code
+    }
+
+    /// Exchange a pairing code for a new device credential. Codes are single use.
+    pub fn redeem(&self, code: &str, device_name: &str) -> Result<DeviceCredential, String> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let valid = matches!(state.codes.remove(code), Some(expires) if expires > Instant::now());
+        if !valid {
+            state.failed_attempts += 1;
+            if state.failed_attempts >= MAX_FAILED_ATTEMPTS {
+                // Too many guesses: make the attacker start over against fresh codes
+                state.codes.clear();
+                state.failed_attempts = 0;
+            }
+            return Err("Invalid or expired pairing code".to_string());
+        }
+        state.failed_attempts = 0;
+
+        let device_id: String = (0..8)
+            .map(|_| format!("{:02x}", rand::thread_rng().gen::<u8>()))
+            .collect();
+        let name = device_name.trim();
+        let token = crate::auth::generate_token();
+        let stored = match secrets::set(&secrets::device_key(&device_id), &token) {
+            Ok(()) => None,
+            Err(e) => {
+                warn!("{}; keeping credential in the devices file", e);
+                Some(token.clone())
+            }
+        };
+        let device = PairedDevice {
+            id: device_id.clone(),
+            name: if name.is_empty() {
+                "Mobile device".to_string()
+            } else {
+                name.chars().take(64).collect()
+            },
+            token: stored,
+            paired_at: Utc::now().to_rfc3339(),
+            last_seen: None,
+        };
+        state.tokens.insert(device_id.clone(), token.clone());
+        state.devices.push(device);
+        Self::save(&state.devices)?;
+
+        Ok(DeviceCredential { device_id, token })
+    }
+
+    /// Whether `token` belongs to a paired device (and record that it was
+    /// seen; persisted at most every `LAST_SEEN_SAVE_INTERVAL`)
+    pub fn authenticate(&self, token: &str) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(device_id) = state
+            .tokens
+            .iter()
+            .find(|(_, t)| t.as_str() == token)
+            .map(|(id, _)| id.clone())
+        else {
+            return false;
+        };
+        let Some(device) = state.devices.iter_mut().find(|d| d.id == device_id) else {
+            return false;
+        };
+        device.last_seen = Some(Utc::now().to_rfc3339());
+        let due = state
+            .last_seen_saved
+            .is_none_or(|saved| saved.elapsed() >= LAST_SEEN_SAVE_INTERVAL);
+        if due {
+            state.last_seen_saved = Some(Instant::now());
+            if let Err(e) = Self::save(&state.devices) {
+                error!("Failed to save devices: {}", e);
+            }
+        }
+        true
+    }
+
+    pub fn list_devices(&self) -> Vec<DeviceInfo> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .devices
+            .iter()
+            .map(|d| DeviceInfo {
+                id: d.id.clone(),
+                name: d.name.clone(),
+                paired_at: d.paired_at.clone(),
+                last_seen: d.last_seen.clone(),
+            })
+            .collect()
+    }
+
+    /// Remove a device; its credential stops working for new connections
+    pub fn revoke(&self, device_id: &str) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let before = state.devices.len();
+        state.devices.retain(|d| d.id != device_id);
+        if state.devices.len() == before {
+            return Err(format!("Device {} not found", device_id));
+        }
+        state.tokens.remove(device_id);
+        if let Err(e) = secrets::delete(&secrets::device_key(device_id)) {
+            warn!("{}", e);
+        }
+        Self::save(&state.devices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty() -> Pairing {
+        Pairing {
+            state: Mutex::new(PairingState::default()),
+        }
+    }
+
+    #[test]
+    fn test_codes_are_six_digits_and_expire() {
+        let pairing = empty();
+        let code = pairing.create_code();
+        assert_eq!(code.len(), 6);
+        assert!(code.chars().all(|c| c.is_ascii_digit()));
+
+        pairing.state.lock().unwrap().codes.insert(
+            "123456".to_string(),
+            Instant::now() - Duration::from_secs(1),
+        );
+        assert!(pairing.redeem("123456", "phone").is_err());
+    }
+
+    #[test]
+    fn test_too_many_failures_invalidate_codes() {
+        let pairing = empty();
+        let code = pairing.create_code();
+        for _ in 0..MAX_FAILED_ATTEMPTS {
+            let _ = pairing.redeem("not-a-code", "phone");
+        }
+        assert!(pairing.redeem(&code, "phone").is_err());
+    }
+}
@@ -1,215 +1,479 @@
-use crate::session::{
-    determine_status, get_pending_tool_name, parse_last_n_entries, parse_sessions_index,
-    SessionDetector, SessionStatus,
-};
-use chrono::{DateTime, Utc};
+use crate::budget::{BudgetAlert, BudgetMonitor};
+use crate::cpu_monitor::{CpuAlert, CpuMonitor};
+use crate::git_status::{git_status, GitStatus};
+use crate::power::SleepDetector;
+use crate::providers::{AgentRegistry, SessionSnapshot};
+use crate::session::parser::truncate_string;
+use crate::session::{load_todos, PermissionMode, SessionStatus, TodoItem};
+use crate::settings::AppSettings;
+use crate::stats::McpServerUsage;
+use crate::store::SessionStore;
+use crate::telemetry::{telemetry, SessionTelemetry};
+use chrono::{Datelike, Local, Utc, Weekday};
 use serde::Serialize;
 use std::collections::hash_map::DefaultHasher;
-use std::collections::{HashMap, HashSet};
-use std::fs::File;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::hash::{Hash, Hasher};
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use std::thread;
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_notification::NotificationExt;
+use tracing::{error, info, warn};
 
 /// Combined session information for the frontend
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Session {
     pub id: String,
+    /// Which agent CLI runs the session (e.g. "claude", "codex")
+    pub agent: String,
     pub pid: u32,
     pub session_name: String,
     pub custom_title: Option<String>,
+    /// User's note on the session (see `set_note`)
+    pub note: Option<String>,
+    /// User's color label (see `set_session_color`)
+    pub color: Option<crate::session::SessionColor>,
     pub project_path: String,
     pub git_branch: Option<String>,
+    /// Uncommitted changes and ahead/behind counts of the cwd's repository;
+    /// None outside a repository
+    pub git_status: Option<GitStatus>,
     pub first_prompt: String,
     pub summary: Option<String>,
+    /// Earlier sessions this one was resumed from, oldest first
+    pub lineage: Vec<String>,
     pub message_count: u32,
     pub modified: String,
     pub status: SessionStatus,
+    /// `status` was set by hand (see `override_status`) rather than detected
+    pub status_overridden: bool,
+    /// RFC 3339 time the session's notifications resume (see `snooze_session`)
+    pub snoozed_until: Option<String>,
     pub latest_message: String,
     pub pending_tool_name: Option<String>,
+    /// What the pending tool wants to do: the command, file or arguments
+    pub pending_tool_input: Option<String>,
+    /// Seconds since the claude process started
+    pub duration_seconds: u64,
+    /// Seconds observed in each status while c9watch was running
+    pub working_seconds: u64,
+    pub idle_seconds: u64,
+    pub needs_permission_seconds: u64,
+    /// Estimated share of the context window in use (0-100), from the latest
+    /// assistant message's usage
+    pub context_percent: Option<u32>,
+    /// Exact usage, API errors and tool timings from Claude Code's
+    /// OpenTelemetry export, when the OTLP receiver is enabled
+    pub telemetry: Option<SessionTelemetry>,
+    /// The session's TodoWrite list (empty if it has none)
+    pub todos: Vec<TodoItem>,
+    /// Plan mode, accept-edits or skip-permissions, when the agent records it
+    pub permission_mode: Option<PermissionMode>,
+    /// Model used for the latest reply (e.g. "opus-4.5")
+    pub model: Option<String>,
+    /// MCP servers the session has called, with error counts
+    pub mcp_servers: Vec<McpServerUsage>,
+    /// CPU use of the agent process since the last poll, in percent of one core
+    pub cpu_percent: f32,
+    /// Resident memory of the agent process
+    pub memory_bytes: u64,
 }
 
 /// Start the background polling loop
 ///
-/// This function spawns a background thread that:
-/// 1. Detects active Claude sessions every 2-3 seconds
+/// This function spawns an async task that, every few seconds:
+/// 1. Detects active Claude sessions, on a blocking thread since that reads
+///    the process table and transcripts
 /// 2. Enriches them with status information
 /// 3. Tracks status transitions and fires notifications
-/// 4. Emits "sessions-updated" events to the frontend
-/// 5. Broadcasts session data to WebSocket clients
+/// 4. Publishes them to the `SessionStore`
+/// 5. Emits "sessions-updated" events to the frontend
+/// 6. Broadcasts session data to WebSocket clients
+/// 7. Checks usage budgets and broadcasts alerts
+///
+/// `SessionStore::request_refresh` cuts the wait short.
 pub fn start_polling(
     app: AppHandle,
+    store: Arc<SessionStore>,
     sessions_tx: tokio::sync::broadcast::Sender<String>,
     notifications_tx: tokio::sync::broadcast::Sender<String>,
+    alerts_tx: tokio::sync::broadcast::Sender<String>,
 ) {
-    thread::spawn(move || {
-        let app_handle = Arc::new(app);
-        let poll_interval = Duration::from_millis(3500);
-
-        // Create detector once and reuse across poll cycles
-        let mut detector = match SessionDetector::new() {
-            Ok(d) => d,
+    tauri::async_runtime::spawn(async move {
+        // Create the registry once and reuse its process scanner across poll cycles
+        let registry = match AgentRegistry::new() {
+            Ok(r) => r,
             Err(e) => {
-                eprintln!("[polling] Failed to create session detector: {}", e);
+                error!("Failed to create session detector: {}", e);
                 return;
             }
         };
+        store.set_transcripts(registry.transcripts());
+        let poller = Arc::new(Mutex::new(Poller::new(
+            app.clone(),
+            registry,
+            store.clone(),
+            sessions_tx.clone(),
+            notifications_tx,
+            alerts_tx,
+        )));
 
-        // Track previous status for each session
-        let previous_status: Arc<Mutex<HashMap<String, SessionStatus>>> =
-            Arc::new(Mutex::new(HashMap::new()));
+        let mut sleep_detector = SleepDetector::default();
 
-        // Track last notification time per session to prevent duplicates.
-        // If status flickers (Working → Ready → Working → Ready), this cooldown
-        // ensures we don't fire the same notification twice within a short window.
-        let mut last_notification_time: HashMap<String, Instant> = HashMap::new();
-        let notification_cooldown = Duration::from_secs(30);
+        loop {
+            let cycle = poller.clone();
+            let any_working = tauri::async_runtime::spawn_blocking(move || {
+                cycle.lock().unwrap_or_else(|e| e.into_inner()).run_cycle()
+            })
+            .await
+            .unwrap_or_else(|e| {
+                error!("Poll cycle failed: {}", e);
+                true
+            });
 
-        // Track if this is the first poll cycle
-        let mut is_first_cycle = true;
+            let observed = is_observed(&app, &sessions_tx);
+            let interval = poll_interval(
+                AppSettings::load().adaptive_polling && crate::power::on_battery(),
+                observed,
+                any_working,
+            );
+            if wait_for_next_poll(&app, &store, &sessions_tx, interval, &mut sleep_detector).await {
+                // Statuses changed while asleep; re-seed them rather than
+                // notifying about every transition, push a fresh list right
+                // away and pick up a network change
+                info!("System woke from sleep, re-polling");
+                poller.lock().unwrap_or_else(|e| e.into_inner()).reset();
+                let app = app.clone();
+                tauri::async_runtime::spawn_blocking(move || {
+                    crate::network::refresh_advertised_address(&app)
+                });
+            }
+        }
+    });
+}
 
-        loop {
-            // Detect and enrich sessions
-            match detect_and_enrich_sessions_with_detector(&mut detector) {
-                Ok(sessions) => {
-                    // Track current session IDs to clean up stale entries
-                    let current_session_ids: HashSet<String> =
-                        sessions.iter().map(|s| s.id.clone()).collect();
-
-                    // Process status transitions and fire notifications
-                    match previous_status.lock() {
-                        Ok(mut prev_status_map) => {
-                            if is_first_cycle {
-                                // First cycle: seed the map without notifications
-                                for session in &sessions {
-                                    prev_status_map
-                                        .insert(session.id.clone(), session.status.clone());
-                                }
-                                is_first_cycle = false;
-                            } else {
-                                // Check for status transitions
-                                for session in &sessions {
-                                    if let Some(prev_status) = prev_status_map.get(&session.id) {
-                                        // Check for notification-worthy transitions
-                                        let should_notify = match (prev_status, &session.status) {
-                                            (
-                                                SessionStatus::Working,
-                                                SessionStatus::NeedsPermission,
-                                            ) => true,
-                                            (
-                                                SessionStatus::Working,
-                                                SessionStatus::WaitingForInput,
-                                            ) => true,
-                                            _ => false,
-                                        };
-
-                                        if should_notify {
-                                            // Check cooldown to prevent duplicate notifications
-                                            // from status flickering across poll cycles
-                                            let on_cooldown = last_notification_time
-                                                .get(&session.id)
-                                                .map(|t| t.elapsed() < notification_cooldown)
-                                                .unwrap_or(false);
-
-                                            if !on_cooldown {
-                                                fire_notification(
-                                                    &app_handle,
-                                                    &notifications_tx,
-                                                    &session.id,
-                                                    &session.first_prompt,
-                                                    &session.session_name,
-                                                    &session.status,
-                                                    session.pending_tool_name.as_deref(),
-                                                    session.pid,
-                                                    &session.project_path,
-                                                );
-                                                last_notification_time
-                                                    .insert(session.id.clone(), Instant::now());
-                                            }
-                                        }
-                                    }
-
-                                    // Update the status map
-                                    prev_status_map
-                                        .insert(session.id.clone(), session.status.clone());
+/// State carried from one poll cycle to the next
+struct Poller {
+    app_handle: AppHandle,
+    registry: AgentRegistry,
+    store: Arc<SessionStore>,
+    sessions_tx: tokio::sync::broadcast::Sender<String>,
+    notifications_tx: tokio::sync::broadcast::Sender<String>,
+    alerts_tx: tokio::sync::broadcast::Sender<String>,
+
+    /// Previous status of each session
+    previous_status: HashMap<String, SessionStatus>,
+
+    /// Last notification time per session to prevent duplicates.
+    /// If status flickers (Working → Ready → Working → Ready), this cooldown
+    /// ensures we don't fire the same notification twice within a short window.
+    last_notification_time: HashMap<String, Instant>,
+
+    /// Whether this is the first poll cycle (or the first after a sleep)
+    is_first_cycle: bool,
+
+    budget_monitor: BudgetMonitor,
+    cpu_monitor: CpuMonitor,
+
+    /// Fingerprint and time of the last sessions-updated broadcast
+    last_broadcast: Option<(u64, Instant)>,
+
+    /// Whether any session was working at the last successful poll
+    any_working: bool,
+
+    notification_grouper: NotificationGrouper,
+}
+
+/// Minimum time between two notifications for the same session
+const NOTIFICATION_COOLDOWN: Duration = Duration::from_secs(30);
+
+impl Poller {
+    fn new(
+        app_handle: AppHandle,
+        registry: AgentRegistry,
+        store: Arc<SessionStore>,
+        sessions_tx: tokio::sync::broadcast::Sender<String>,
+        notifications_tx: tokio::sync::broadcast::Sender<String>,
+        alerts_tx: tokio::sync::broadcast::Sender<String>,
+    ) -> Self {
+        Self {
+            app_handle,
+            registry,
+            store,
+            sessions_tx,
+            notifications_tx,
+            alerts_tx,
+            previous_status: HashMap::new(),
+            last_notification_time: HashMap::new(),
+            is_first_cycle: true,
+            budget_monitor: BudgetMonitor::new(),
+            cpu_monitor: CpuMonitor::new(),
+            last_broadcast: None,
+            any_working: true,
+            notification_grouper: NotificationGrouper::default(),
+        }
+    }
+
+    /// Re-seed statuses without notifying, and broadcast on the next cycle
+    fn reset(&mut self) {
+        self.is_first_cycle = true;
+        self.last_broadcast = None;
+    }
+
+    /// Run one poll cycle, returning whether any session is working
+    fn run_cycle(&mut self) -> bool {
+        let cycle_started = Instant::now();
+
+        // Detect and enrich sessions
+        match detect_and_enrich_sessions_with_registry(&mut self.registry) {
+            Ok(sessions) => {
+                self.any_working = sessions.iter().any(|s| {
+                    matches!(s.status, SessionStatus::Working | SessionStatus::Connecting)
+                });
+
+                // Track current session IDs to clean up stale entries
+                let current_session_ids: HashSet<String> =
+                    sessions.iter().map(|s| s.id.clone()).collect();
+
+                // Process status transitions and fire notifications
+                if self.is_first_cycle {
+                    // First cycle: seed the map without notifications
+                    for session in &sessions {
+                        self.previous_status
+                            .insert(session.id.clone(), session.status.clone());
+                    }
+                    self.is_first_cycle = false;
+                } else {
+                    // Check for status transitions
+                    for session in &sessions {
+                        if let Some(prev_status) = self.previous_status.get(&session.id) {
+                            // Check for notification-worthy transitions
+                            let should_notify = match (prev_status, &session.status) {
+                                (SessionStatus::Working, SessionStatus::NeedsPermission) => true,
+                                (SessionStatus::Working, SessionStatus::WaitingForInput) => true,
+                                _ => false,
+                            };
+
+                            // A status the user set by hand is no news to them,
+                            // and snoozed sessions stay quiet until they wake
+                            if should_notify
+                                && !session.status_overridden
+                                && session.snoozed_until.is_none()
+                            {
+                                // Check cooldown to prevent duplicate notifications
+                                // from status flickering across poll cycles
+                                let on_cooldown = self
+                                    .last_notification_time
+                                    .get(&session.id)
+                                    .map(|t| t.elapsed() < NOTIFICATION_COOLDOWN)
+                                    .unwrap_or(false);
+
+                                if !on_cooldown {
+                                    self.notification_grouper.push(session.clone());
+                                    self.last_notification_time
+                                        .insert(session.id.clone(), Instant::now());
                                 }
                             }
-
-                            // Clean up disappeared sessions
-                            prev_status_map.retain(|id, _| current_session_ids.contains(id));
-                            last_notification_time.retain(|id, _| current_session_ids.contains(id));
                         }
-                        Err(poisoned) => {
-                            eprintln!("[polling] Mutex poisoned, recovering...");
-                            let mut prev_status_map = poisoned.into_inner();
-                            prev_status_map.clear(); // Clear stale state
-
-                            // Seed the map with current sessions (no notifications after recovery)
-                            for session in &sessions {
-                                prev_status_map.insert(session.id.clone(), session.status.clone());
-                            }
-                            is_first_cycle = false; // Mark as initialized
+
+                        // Update the status map
+                        self.previous_status
+                            .insert(session.id.clone(), session.status.clone());
+                    }
+                }
+
+                // Clean up disappeared sessions
+                self.previous_status
+                    .retain(|id, _| current_session_ids.contains(id));
+                self.last_notification_time
+                    .retain(|id, _| current_session_ids.contains(id));
+                crate::tracking::tracker().retain(&current_session_ids);
+                crate::status_override::overrides().retain(&current_session_ids);
+                crate::snooze::snoozes().retain(&current_session_ids);
+
+                crate::widget::update(&sessions);
+
+                // Held transitions may be stale by now
+                self.notification_grouper.refresh(&sessions);
+                for notification in self.notification_grouper.flush(Instant::now()) {
+                    match notification {
+                        GroupedNotification::Single(session) => {
+                            fire_notification(&self.app_handle, &self.notifications_tx, &session)
                         }
+                        GroupedNotification::Summary(sessions) => fire_summary_notification(
+                            &self.app_handle,
+                            &self.notifications_tx,
+                            &sessions,
+                            &AppSettings::load(),
+                            crate::actions::is_session_focused,
+                        ),
                     }
+                }
+
+                // Only wake the WebView and WS clients when something changed,
+                // plus a periodic refresh so duration counters keep moving
+                let fingerprint = sessions_fingerprint(&sessions);
+                let changed = self
+                    .last_broadcast
+                    .map(|(previous, at)| {
+                        previous != fingerprint || at.elapsed() >= DURATION_REFRESH_INTERVAL
+                    })
+                    .unwrap_or(true);
+
+                if changed {
+                    self.last_broadcast = Some((fingerprint, Instant::now()));
 
                     // Emit event to Tauri frontend
-                    if let Err(e) = app_handle.emit("sessions-updated", &sessions) {
-                        eprintln!("Failed to emit sessions-updated event: {}", e);
+                    if let Err(e) = self.app_handle.emit("sessions-updated", &sessions) {
+                        error!("Failed to emit sessions-updated event: {}", e);
                     }
 
                     // Broadcast to WebSocket clients
                     if let Ok(json) = serde_json::to_string(&sessions) {
-                        let _ = sessions_tx.send(json);
+                        let _ = self.sessions_tx.send(json);
                     }
                 }
-                Err(e) => {
-                    eprintln!("Error detecting sessions: {}", e);
-                    // Continue polling even on error
+
+                // Fold this cycle into the persistent usage history
+                let history = crate::history::history();
+                history.record_cycle(&sessions);
+                history.flush();
+                crate::events::events().observe(&sessions);
+
+                let settings = AppSettings::load();
+                history.apply_retention(&settings);
+                crate::redact::configure(&settings);
+                maybe_send_weekly_digest(&self.app_handle, &settings);
+                for alert in self.budget_monitor.check(&settings) {
+                    fire_budget_alert(&self.app_handle, &self.alerts_tx, &alert, &settings);
+                }
+                for alert in self.cpu_monitor.check(&sessions, &settings, Instant::now()) {
+                    fire_cpu_alert(&self.app_handle, &self.alerts_tx, &alert, &settings);
                 }
-            }
 
-            thread::sleep(poll_interval);
+                crate::health::health().record_poll_success(&sessions, cycle_started.elapsed());
+                self.store.publish(sessions, cycle_started);
+            }
+            Err(e) => {
+                error!("Error detecting sessions: {}", e);
+                crate::health::health().record_poll_error(e.to_string());
+                // Continue polling even on error
+            }
         }
-    });
+
+        self.any_working
+    }
 }
 
-/// Checks if a file was modified within the last N seconds
-fn is_file_recently_modified(path: &Path, seconds: u64) -> bool {
-    std::fs::metadata(path)
-        .and_then(|m| m.modified())
-        .ok()
-        .map(|modified| {
-            modified
-                .elapsed()
-                .map(|elapsed| elapsed.as_secs() < seconds)
-                .unwrap_or(false)
+/// Poll interval while someone is watching or the machine is on AC power
+const POLL_INTERVAL: Duration = Duration::from_millis(3500);
+/// On battery with no window open and no WS client connected
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(15);
+/// As IDLE_POLL_INTERVAL, when no session is working either
+const QUIET_POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// How often a stretched wait checks whether someone started watching
+const WAIT_SLICE: Duration = Duration::from_millis(500);
+
+/// Whether a c9watch window is on screen or a WS client is connected
+fn is_observed(app: &AppHandle, sessions_tx: &tokio::sync::broadcast::Sender<String>) -> bool {
+    sessions_tx.receiver_count() > 0
+        || app.webview_windows().values().any(|window| {
+            window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false)
         })
-        .unwrap_or(false)
+}
+
+/// Time until the next poll. Polling only slows down to save battery when
+/// nobody would see the result right away.
+fn poll_interval(save_battery: bool, observed: bool, any_working: bool) -> Duration {
+    match (save_battery && !observed, any_working) {
+        (false, _) => POLL_INTERVAL,
+        (true, true) => IDLE_POLL_INTERVAL,
+        (true, false) => QUIET_POLL_INTERVAL,
+    }
+}
+
+/// Sleep until the next poll, cutting a stretched wait short as soon as a
+/// window opens or a WS client connects, and any wait when a refresh is
+/// requested. Returns true, early, if the system slept meanwhile.
+async fn wait_for_next_poll(
+    app: &AppHandle,
+    store: &SessionStore,
+    sessions_tx: &tokio::sync::broadcast::Sender<String>,
+    interval: Duration,
+    sleep_detector: &mut SleepDetector,
+) -> bool {
+    let stretched = interval > POLL_INTERVAL;
+    let slice = if stretched { WAIT_SLICE } else { interval };
+    let started = Instant::now();
+    while started.elapsed() < interval {
+        tokio::select! {
+            _ = tokio::time::sleep(slice.min(interval.saturating_sub(started.elapsed()))) => {}
+            _ = store.refresh_requested() => return false,
+        }
+        if sleep_detector.woke() {
+            return true;
+        }
+        if stretched && is_observed(app, sessions_tx) {
+            return false;
+        }
+    }
+    false
+}
+
+/// How often the session list is re-broadcast even if only durations changed
+const DURATION_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Session fields that change every cycle and shouldn't count as a change
+const TICKING_FIELDS: &[&str] = &[
+    "durationSeconds",
+    "workingSeconds",
+    "idleSeconds",
+    "needsPermissionSeconds",
+    "cpuPercent",
+    "memoryBytes",
+];
+
+/// Hash of the session list, ignoring the duration counters and resource use
+fn sessions_fingerprint(sessions: &[Session]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for session in sessions {
+        let mut value = serde_json::to_value(session).unwrap_or_default();
+        if let Some(object) = value.as_object_mut() {
+            for field in TICKING_FIELDS {
+                object.remove(*field);
+            }
+        }
+        value.to_string().hash(&mut hasher);
+    }
+    hasher.finish()
 }
 
 /// Detect sessions and enrich them with status and conversation data
 pub fn detect_and_enrich_sessions() -> Result<Vec<Session>, String> {
-    let mut detector = SessionDetector::new()
-        .map_err(|e| format!("Failed to create session detector: {}", e))?;
-    detect_and_enrich_sessions_with_detector(&mut detector)
+    let mut registry =
+        AgentRegistry::new().map_err(|e| format!("Failed to create session detector: {}", e))?;
+    detect_and_enrich_sessions_with_registry(&mut registry)
 }
 
-/// Detect sessions using an existing detector (avoids recreating System each call)
-fn detect_and_enrich_sessions_with_detector(detector: &mut SessionDetector) -> Result<Vec<Session>, String> {
-    let detected_sessions = detector
+/// Detect sessions using an existing registry (avoids recreating System each call)
+fn detect_and_enrich_sessions_with_registry(
+    registry: &mut AgentRegistry,
+) -> Result<Vec<Session>, String> {
+    let detected_sessions = registry
         .detect_sessions()
         .map_err(|e| format!("Failed to detect sessions: {}", e))?;
 
     let custom_names = crate::session::CustomNames::load();
     let custom_titles = crate::session::CustomTitles::load();
+    let notes = crate::session::SessionNotes::load();
+    let colors = crate::session::SessionColors::load();
+    let ignore_list = crate::session::IgnoreList::load();
+    let settings = AppSettings::load();
     let mut sessions = Vec::new();
     let mut seen_ids: HashSet<String> = HashSet::new();
+    let mut watched_files: HashSet<PathBuf> = HashSet::new();
 
     for detected in detected_sessions {
         // Get session ID - if not found, skip this session
@@ -226,231 +490,301 @@ fn detect_and_enrich_sessions_with_detector(detector: &mut SessionDetector) -> R
         }
         seen_ids.insert(session_id.clone());
 
-        // Try to parse sessions-index.json to get basic info (optional)
-        let index_path = detected.project_path.join("sessions-index.json");
-        let sessions_index = parse_sessions_index(&index_path).ok();
-
-        // Find the matching entry in the index (if index exists)
-        let session_entry = sessions_index.as_ref().and_then(|index| {
-            index
-                .entries
-                .iter()
-                .find(|entry| entry.session_id == session_id)
-        });
-
-        let (first_prompt, summary, message_count, modified, git_branch) = match session_entry {
-            Some(entry) => (
-                entry.first_prompt.clone(),
-                entry.summary.clone(),
-                entry.message_count,
-                entry.modified.clone(),
-                Some(entry.git_branch.clone()),
-            ),
-            None => {
-                // Session not in index or index doesn't exist - use fallback values
-                let session_file_path = detected.project_path.join(format!("{}.jsonl", session_id));
-
-                // Try to get first prompt from JSONL file
-                let first_prompt = get_first_prompt_from_jsonl(&session_file_path)
-                    .unwrap_or_else(|| "(Active session)".to_string());
-
-                // Count messages in the file
-                let message_count = count_messages_in_jsonl(&session_file_path);
-
-                // Get file modification time
-                let modified = std::fs::metadata(&session_file_path)
-                    .and_then(|m| m.modified())
-                    .ok()
-                    .map(|t| {
-                        let datetime: DateTime<Utc> = t.into();
-                        datetime.to_rfc3339()
-                    })
-                    .unwrap_or_default();
+        // Skip sessions the user has hidden (also keeps them out of notifications)
+        if ignore_list.is_ignored(&session_id, &detected.cwd.to_string_lossy()) {
+            continue;
+        }
 
-                (first_prompt, None, message_count, modified, None)
-            }
-        };
+        watched_files.insert(detected.transcript_path.clone());
 
-        // Parse the session JSONL file to determine status and get latest message
-        let session_file_path = detected.project_path.join(format!("{}.jsonl", session_id));
-        let entries = match parse_last_n_entries(&session_file_path, 20) {
-            Ok(entries) => entries,
-            Err(e) => {
-                eprintln!(
-                    "Failed to parse session file for {}: {}. Using fallback status.",
-                    session_id, e
-                );
-                vec![]
-            }
+        let SessionSnapshot {
+            first_prompt,
+            summary,
+            message_count,
+            modified,
+            git_branch,
+            status,
+            latest_message,
+            pending_tool_name,
+            pending_tool_input,
+            context_percent,
+            permission_mode,
+            model,
+            mcp_servers,
+        } = match registry.read_session(&detected) {
+            Some(snapshot) => snapshot,
+            None => continue,
         };
 
-        let status = if entries.is_empty() {
+        // Empty sessions (0 messages) are mostly ones the user immediately
+        // left with /resume, so they're hidden unless asked for; a freshly
+        // launched agent also has none until the first prompt
+        if message_count == 0 && !settings.show_empty_sessions {
+            continue;
+        }
+        let status = if message_count == 0 {
             SessionStatus::Connecting
         } else {
-            let raw_status = determine_status(&entries);
-            // Override WaitingForInput if the JSONL file was recently modified.
-            // This catches progress entries (bash_progress, thinking updates) that
-            // don't get parsed as meaningful entries but indicate active work.
-            //
-            // Why 8 seconds? Polling runs every 3.5s, Claude writes progress every 1-3s
-            // during active work. 8s provides buffer for gaps without delaying "Ready"
-            // transition when work truly finishes.
-            if raw_status == SessionStatus::WaitingForInput
-                && is_file_recently_modified(&session_file_path, 8)
-            {
-                SessionStatus::Working
-            } else {
-                raw_status
-            }
+            status
         };
 
-        let latest_message = get_latest_message_from_entries(&entries);
-        let pending_tool_name = get_pending_tool_name(&entries);
-
-        // Skip empty sessions (0 messages) - these are likely sessions where user
-        // immediately used /resume to switch to a different session
-        if message_count == 0 {
-            continue;
-        }
-
         // Use custom name if available, otherwise use detected project name
         let session_name = custom_names
             .get(&session_id)
             .cloned()
             .unwrap_or(detected.project_name);
 
+        let lineage = if detected.agent == "claude" {
+            crate::session::lineage(&detected.transcript_path)
+        } else {
+            Vec::new()
+        };
+
         // Get custom title if available
         let custom_title = custom_titles.get(&session_id).cloned();
+        let note = notes.get(&session_id).cloned();
+        let color = colors.get(&session_id);
+
+        let (status, status_overridden) =
+            crate::status_override::overrides().apply(&session_id, status);
+
+        let snoozed_until = crate::snooze::snoozes()
+            .snoozed_until(&session_id)
+            .map(|until| until.to_rfc3339());
+
+        // Accumulate per-status time and compute process uptime
+        let durations = crate::tracking::tracker().observe(&session_id, &status);
+        let duration_seconds = (Utc::now().timestamp() as u64).saturating_sub(detected.start_time);
+        let session_telemetry = telemetry().session(&session_id);
+        let todos = load_todos(&session_id);
+        let usage = registry.usage(detected.pid);
 
         sessions.push(Session {
             id: session_id,
+            agent: detected.agent,
             pid: detected.pid,
             session_name,
             custom_title,
+            note,
+            color,
             project_path: detected.cwd.to_string_lossy().to_string(),
             git_branch,
-            first_prompt,
+            git_status: git_status(&detected.cwd),
+            first_prompt: truncate_string(&first_prompt, settings.prompt_preview_chars()),
             summary,
+            lineage,
             message_count,
             modified,
             status,
-            latest_message,
+            status_overridden,
+            snoozed_until,
+            latest_message: truncate_string(&latest_message, settings.message_preview_chars()),
             pending_tool_name,
+            pending_tool_input,
+            duration_seconds,
+            working_seconds: durations.working,
+            idle_seconds: durations.idle,
+            needs_permission_seconds: durations.needs_permission,
+            context_percent,
+            telemetry: session_telemetry,
+            todos,
+            permission_mode,
+            model,
+            mcp_servers,
+            // One decimal is plenty and keeps the broadcast JSON short
+            cpu_percent: (usage.cpu_percent * 10.0).round() / 10.0,
+            memory_bytes: usage.memory_bytes,
         });
     }
 
+    // Forget per-file state for sessions that have gone away
+    registry.retain(&watched_files);
+
     Ok(sessions)
 }
 
-/// Extract the first user prompt from a session JSONL file
-fn get_first_prompt_from_jsonl(path: &Path) -> Option<String> {
-    let file = File::open(path).ok()?;
-    let reader = BufReader::new(file);
-
-    for line in reader.lines().take(50) {
-        if let Ok(line) = line {
-            if let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) {
-                // Check if this is a user message
-                if value.get("type").and_then(|t| t.as_str()) == Some("user") {
-                    // Try to get the message content
-                    if let Some(message) = value.get("message") {
-                        if let Some(content) = message.get("content") {
-                            // Content can be a string or array
-                            if let Some(text) = content.as_str() {
-                                return Some(truncate_string(text, 100));
-                            } else if let Some(arr) = content.as_array() {
-                                // Find the first text block
-                                for item in arr {
-                                    if item.get("type").and_then(|t| t.as_str()) == Some("text") {
-                                        if let Some(text) =
-                                            item.get("text").and_then(|t| t.as_str())
-                                        {
-                                            return Some(truncate_string(text, 100));
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+/// Notifications sent within this window count as one burst
+const GROUP_WINDOW: Duration = Duration::from_secs(10);
+
+/// More notifications than this within GROUP_WINDOW are sent as one summary
+const GROUP_THRESHOLD: usize = 2;
+
+enum GroupedNotification {
+    Single(Session),
+    Summary(Vec<Session>),
+}
+
+/// Coalesces bursts of status transitions. Up to GROUP_THRESHOLD
+/// notifications per window go out one by one; past that they are held and
+/// sent as a single summary once the window has passed.
+#[derive(Default)]
+struct NotificationGrouper {
+    /// When each notification or summary in the current window went out
+    sent: VecDeque<Instant>,
+    /// Transitions waiting to be sent
+    held: Vec<Session>,
+}
+
+impl NotificationGrouper {
+    fn push(&mut self, session: Session) {
+        self.held.push(session);
     }
 
-    None
+    /// Bring held transitions up to date with this cycle's sessions, dropping
+    /// those that have ended or gone back to work since
+    fn refresh(&mut self, current: &[Session]) {
+        self.held = self
+            .held
+            .drain(..)
+            .filter_map(|held| current.iter().find(|s| s.id == held.id).cloned())
+            .filter(|session| {
+                matches!(
+                    session.status,
+                    SessionStatus::WaitingForInput | SessionStatus::NeedsPermission
+                )
+            })
+            .collect();
+    }
+
+    /// Notifications that are due now
+    fn flush(&mut self, now: Instant) -> Vec<GroupedNotification> {
+        self.sent
+            .retain(|sent_at| now.duration_since(*sent_at) < GROUP_WINDOW);
+        if self.held.is_empty() {
+            return Vec::new();
+        }
+
+        if self.sent.len() + self.held.len() <= GROUP_THRESHOLD {
+            self.sent.extend(std::iter::repeat_n(now, self.held.len()));
+            return self
+                .held
+                .drain(..)
+                .map(GroupedNotification::Single)
+                .collect();
+        }
+
+        // Over the limit: wait for the window to clear, then summarize
+        // everything held so far
+        if !self.sent.is_empty() {
+            return Vec::new();
+        }
+        self.sent.push_back(now);
+        if self.held.len() == 1 {
+            return vec![GroupedNotification::Single(self.held.remove(0))];
+        }
+        vec![GroupedNotification::Summary(std::mem::take(&mut self.held))]
+    }
 }
 
-/// Truncate a string to a maximum length (character-safe for UTF-8)
-fn truncate_string(s: &str, max_chars: usize) -> String {
-    let char_count = s.chars().count();
-    if char_count <= max_chars {
-        s.to_string()
-    } else {
-        let truncated: String = s.chars().take(max_chars).collect();
-        format!("{}...", truncated)
+/// e.g. "3 sessions finished, 1 needs permission"
+fn summary_body(sessions: &[Session]) -> String {
+    let finished = sessions
+        .iter()
+        .filter(|s| s.status == SessionStatus::WaitingForInput)
+        .count();
+    let needs_permission = sessions
+        .iter()
+        .filter(|s| s.status == SessionStatus::NeedsPermission)
+        .count();
+
+    let mut parts = Vec::new();
+    match finished {
+        0 => {}
+        1 => parts.push("1 session finished".to_string()),
+        n => parts.push(format!("{} sessions finished", n)),
     }
+    match needs_permission {
+        0 => {}
+        1 => parts.push("1 needs permission".to_string()),
+        n => parts.push(format!("{} need permission", n)),
+    }
+    parts.join(", ")
+}
+
+/// Where native banners go; the app's notification center outside tests
+pub(crate) trait NativeNotifier {
+    /// Show a banner with Open/Approve buttons for `session`. False where
+    /// that isn't available and a plain banner should be shown instead.
+    fn show_actionable(&self, title: &str, body: &str, session: &Session) -> bool;
+
+    fn show(&self, title: &str, body: &str) -> Result<(), String>;
 }
 
-/// Extract the latest message content from session entries
-fn get_latest_message_from_entries(entries: &[crate::session::parser::SessionEntry]) -> String {
-    if entries.is_empty() {
-        return String::new();
+impl NativeNotifier for AppHandle {
+    fn show_actionable(&self, title: &str, body: &str, session: &Session) -> bool {
+        crate::notification_actions::show(self, title, body, session)
     }
 
-    // Iterate backwards to find the last user or assistant message
-    for entry in entries.iter().rev() {
-        match entry {
-            crate::session::parser::SessionEntry::User { message, .. } => {
-                // Skip tool result entries - only show actual user prompts
-                if message.is_tool_result {
-                    continue;
-                }
-                return truncate_string(&message.content, 200);
-            }
-            crate::session::parser::SessionEntry::Assistant { message, .. } => {
-                // For assistant, try to find the last text block
-                for content in message.content.iter().rev() {
-                    match content {
-                        crate::session::parser::MessageContent::Text { text } => {
-                            return truncate_string(text, 200);
-                        }
-                        crate::session::parser::MessageContent::Thinking { thinking, .. } => {
-                            return truncate_string(thinking, 200);
-                        }
-                        crate::session::parser::MessageContent::ToolUse { name, .. } => {
-                            return format!("Executing {}...", name);
-                        }
-                        _ => continue,
-                    }
-                }
-            }
-            _ => continue,
-        }
+    fn show(&self, title: &str, body: &str) -> Result<(), String> {
+        self.notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show()
+            .map_err(|e| e.to_string())
     }
+}
 
-    String::new()
+/// Show a native banner unless Do Not Disturb is on. Every native
+/// notification goes through here; web clients are notified regardless,
+/// since they may be on another device. Returns false if it was held back.
+pub(crate) fn show_native(
+    notifier: &impl NativeNotifier,
+    settings: &AppSettings,
+    title: &str,
+    body: &str,
+    session: Option<&Session>,
+) -> bool {
+    if settings.do_not_disturb {
+        return false;
+    }
+    // Prefer a notification with Open/Approve buttons; the plugin's plain
+    // one is the fallback.
+    // Note: Notifications work in production builds (.app) but may not appear in dev mode
+    if session.is_some_and(|session| notifier.show_actionable(title, body, session)) {
+        return true;
+    }
+    if let Err(e) = notifier.show(title, body) {
+        error!("Failed to show notification \"{}\": {}", title, e);
+    }
+    true
 }
 
-/// Count user/assistant messages in a JSONL file
-fn count_messages_in_jsonl(path: &Path) -> u32 {
-    let file = match File::open(path) {
-        Ok(f) => f,
-        Err(_) => return 0,
-    };
-    let reader = BufReader::new(file);
-    let mut count = 0u32;
-
-    for line in reader.lines().flatten() {
-        if let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) {
-            if let Some(msg_type) = value.get("type").and_then(|t| t.as_str()) {
-                if msg_type == "user" || msg_type == "assistant" {
-                    count += 1;
-                }
-            }
-        }
+/// Fire one notification standing in for several transitions. The native
+/// banner leaves out sessions whose terminal the user is looking at
+/// (`is_focused`, by pid), as single notifications do.
+fn fire_summary_notification(
+    notifier: &impl NativeNotifier,
+    notifications_tx: &tokio::sync::broadcast::Sender<String>,
+    sessions: &[Session],
+    settings: &AppSettings,
+    is_focused: impl Fn(u32) -> bool,
+) {
+    let title = "c9watch";
+    let body = summary_body(sessions);
+
+    let unseen: Vec<Session> = sessions
+        .iter()
+        .filter(|session| !(settings.suppress_when_focused && is_focused(session.pid)))
+        .cloned()
+        .collect();
+    if !unseen.is_empty() {
+        show_native(notifier, settings, title, &summary_body(&unseen), None);
     }
 
-    count
+    for session in sessions {
+        crate::events::events().record_notification(session, &body);
+    }
+
+    let session_ids: Vec<&str> = sessions.iter().map(|s| s.id.as_str()).collect();
+    let ws_notification = serde_json::json!({
+        "title": title,
+        "body": body,
+        "sessionIds": session_ids,
+    });
+    if let Ok(json) = serde_json::to_string(&ws_notification) {
+        let _ = notifications_tx.send(json);
+    }
 }
 
 /// Notification metadata for click-to-focus
@@ -468,22 +802,31 @@ struct NotificationMetadata {
 fn fire_notification(
     app_handle: &AppHandle,
     notifications_tx: &tokio::sync::broadcast::Sender<String>,
-    session_id: &str,
-    first_prompt: &str,
-    session_name: &str,
-    status: &SessionStatus,
-    pending_tool_name: Option<&str>,
-    pid: u32,
-    project_path: &str,
+    session: &Session,
 ) {
-    // Truncate title to 60 characters
-    let title = truncate_string(first_prompt, 60);
+    let session_id = session.id.as_str();
+    let session_name = session.session_name.as_str();
+    let pid = session.pid;
+    let settings = AppSettings::load();
+
+    let title = truncate_string(&session.first_prompt, settings.notification_title_chars());
 
     // Build the body based on the status
-    let body = match status {
+    let body = match session.status {
         SessionStatus::NeedsPermission => {
-            let tool_name = pending_tool_name.unwrap_or("unknown tool");
-            format!("🔐 {}: Needs permission for {}", session_name, tool_name)
+            let tool_name = session
+                .pending_tool_name
+                .as_deref()
+                .unwrap_or("unknown tool");
+            match session.pending_tool_input.as_deref() {
+                Some(input) => format!(
+                    "🔐 {}: Needs permission for {}\n{}",
+                    session_name,
+                    tool_name,
+                    truncate_string(input, settings.prompt_preview_chars())
+                ),
+                None => format!("🔐 {}: Needs permission for {}", session_name, tool_name),
+            }
         }
         SessionStatus::WaitingForInput => {
             format!("✅ {}: Finished working", session_name)
@@ -491,34 +834,31 @@ fn fire_notification(
         _ => return, // Should not happen based on the caller's logic
     };
 
+    crate::events::events().record_notification(session, &body);
+
     // Generate a stable i32 ID from the session_id string using hash
     let mut hasher = DefaultHasher::new();
     session_id.hash(&mut hasher);
     let notification_id = (hasher.finish() as i32).abs();
 
-    // Fire native notification via Tauri plugin
-    // Note: Notifications work in production builds (.app) but may not appear in dev mode
-    if let Err(e) = app_handle
-        .notification()
-        .builder()
-        .title(&title)
-        .body(&body)
-        .show()
-    {
-        eprintln!("[notification] Failed to show notification: {}", e);
-    }
-
-    // Emit event with session metadata for click-to-focus handling
-    let metadata = NotificationMetadata {
-        notification_id,
-        session_id: session_id.to_string(),
-        pid,
-        project_path: project_path.to_string(),
-        title: title.clone(),
-    };
+    // The user is already looking at the session's terminal; a banner is
+    // noise. Web clients are still notified since they may be on another
+    // device.
+    let is_focused = settings.suppress_when_focused && crate::actions::is_session_focused(pid);
+
+    if !is_focused && show_native(app_handle, &settings, &title, &body, Some(session)) {
+        // Emit event with session metadata for click-to-focus handling
+        let metadata = NotificationMetadata {
+            notification_id,
+            session_id: session_id.to_string(),
+            pid,
+            project_path: session.project_path.clone(),
+            title: title.clone(),
+        };
 
-    if let Err(e) = app_handle.emit("notification-fired", &metadata) {
-        eprintln!("Failed to emit notification-fired event: {}", e);
+        if let Err(e) = app_handle.emit("notification-fired", &metadata) {
+            error!("Failed to emit notification-fired event: {}", e);
+        }
     }
 
     // Broadcast to WebSocket clients for web notifications
@@ -533,8 +873,90 @@ fn fire_notification(
     }
 }
 
+/// Send the weekly usage digest on Mondays, if enabled and not already sent today
+fn maybe_send_weekly_digest(app_handle: &AppHandle, settings: &AppSettings) {
+    if !settings.weekly_digest {
+        return;
+    }
+
+    let today = Local::now().date_naive();
+    if today.weekday() != Weekday::Mon {
+        return;
+    }
+
+    let today_str = today.format("%Y-%m-%d").to_string();
+    let history = crate::history::history();
+    if history.last_weekly_digest().as_deref() == Some(today_str.as_str()) {
+        return;
+    }
+
+    // Report on the previous Monday through Sunday
+    let report = crate::usage::generate_report_between(
+        today - chrono::Duration::days(7),
+        today - chrono::Duration::days(1),
+    );
+    let body = format!(
+        "📊 Last week: {} sessions, {:.1}h working, {} tokens (~${:.2})",
+        report.sessions_run,
+        report.working_seconds as f64 / 3600.0,
+        crate::usage::format_token_count(report.tokens.total()),
+        report.cost_usd
+    );
+
+    // Held back during Do Not Disturb; it goes out on a later poll that day
+    if show_native(app_handle, settings, "c9watch weekly digest", &body, None) {
+        history.set_last_weekly_digest(today_str);
+    }
+}
+
+/// Fire a native notification and broadcast a WS alert for a crossed budget threshold
+fn fire_budget_alert(
+    app_handle: &AppHandle,
+    alerts_tx: &tokio::sync::broadcast::Sender<String>,
+    alert: &BudgetAlert,
+    settings: &AppSettings,
+) {
+    let title = alert.title();
+    let body = alert.body();
+
+    show_native(app_handle, settings, &title, &body, None);
+
+    let ws_alert = serde_json::json!({
+        "kind": "budget",
+        "title": title,
+        "body": body,
+        "budget": alert,
+    });
+    if let Ok(json) = serde_json::to_string(&ws_alert) {
+        let _ = alerts_tx.send(json);
+    }
+}
+
+/// Fire a native notification and broadcast a WS alert for a session stuck on the CPU
+fn fire_cpu_alert(
+    app_handle: &AppHandle,
+    alerts_tx: &tokio::sync::broadcast::Sender<String>,
+    alert: &CpuAlert,
+    settings: &AppSettings,
+) {
+    let title = alert.title();
+    let body = alert.body();
+
+    show_native(app_handle, settings, &title, &body, None);
+
+    let ws_alert = serde_json::json!({
+        "kind": "cpu",
+        "title": title,
+        "body": body,
+        "cpu": alert,
+    });
+    if let Ok(json) = serde_json::to_string(&ws_alert) {
+        let _ = alerts_tx.send(json);
+    }
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
     #[test]
@@ -555,4 +977,174 @@ mod tests {
             }
         }
     }
+
+    pub(crate) fn test_session(status: SessionStatus, working_seconds: u64) -> Session {
+        Session {
+            id: "s1".to_string(),
+            agent: "claude".to_string(),
+            pid: 1,
+            session_name: "project".to_string(),
+            custom_title: None,
+            note: None,
+            color: None,
+            project_path: "/tmp/project".to_string(),
+            git_branch: None,
+            git_status: None,
+            first_prompt: "hello".to_string(),
+            summary: None,
+            lineage: Vec::new(),
+            message_count: 2,
+            modified: "2026-02-10T10:00:00Z".to_string(),
+            status,
+            status_overridden: false,
+            snoozed_until: None,
+            latest_message: String::new(),
+            pending_tool_name: None,
+            pending_tool_input: None,
+            duration_seconds: working_seconds,
+            working_seconds,
+            idle_seconds: 0,
+            needs_permission_seconds: 0,
+            context_percent: None,
+            telemetry: None,
+            todos: Vec::new(),
+            permission_mode: None,
+            model: None,
+            mcp_servers: Vec::new(),
+            cpu_percent: 0.0,
+            memory_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_poll_interval_stretches_only_when_unobserved_on_battery() {
+        assert_eq!(poll_interval(false, false, false), POLL_INTERVAL);
+        assert_eq!(poll_interval(true, true, false), POLL_INTERVAL);
+        assert_eq!(poll_interval(true, false, true), IDLE_POLL_INTERVAL);
+        assert_eq!(poll_interval(true, false, false), QUIET_POLL_INTERVAL);
+    }
+
+    #[test]
+    fn test_notification_burst_is_summarized() {
+        let start = Instant::now();
+        let mut grouper = NotificationGrouper::default();
+
+        grouper.push(test_session(SessionStatus::WaitingForInput, 1));
+        grouper.push(test_session(SessionStatus::WaitingForInput, 1));
+        assert_eq!(grouper.flush(start).len(), 2);
+
+        // Past the threshold within the window: held until it clears
+        grouper.push(test_session(SessionStatus::WaitingForInput, 1));
+        grouper.push(test_session(SessionStatus::NeedsPermission, 1));
+        assert!(grouper.flush(start + Duration::from_secs(3)).is_empty());
+        grouper.push(test_session(SessionStatus::WaitingForInput, 1));
+        assert!(grouper.flush(start + Duration::from_secs(6)).is_empty());
+
+        match grouper
+            .flush(start + GROUP_WINDOW + Duration::from_secs(1))
+            .as_slice()
+        {
+            [GroupedNotification::Summary(sessions)] => {
+                assert_eq!(
+                    summary_body(sessions),
+                    "2 sessions finished, 1 needs permission"
+                );
+            }
+            _ => panic!("expected one summary"),
+        }
+    }
+
+    /// Records banners instead of showing them
+    #[derive(Default)]
+    struct RecordingNotifier {
+        shown: std::cell::RefCell<Vec<String>>,
+    }
+
+    impl NativeNotifier for RecordingNotifier {
+        fn show_actionable(&self, _title: &str, _body: &str, _session: &Session) -> bool {
+            false
+        }
+
+        fn show(&self, _title: &str, body: &str) -> Result<(), String> {
+            self.shown.borrow_mut().push(body.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_summary_respects_do_not_disturb() {
+        let (notifications_tx, mut notifications_rx) = tokio::sync::broadcast::channel(4);
+        let sessions = vec![
+            test_session(SessionStatus::WaitingForInput, 1),
+            test_session(SessionStatus::WaitingForInput, 1),
+        ];
+        let notifier = RecordingNotifier::default();
+        let mut settings = AppSettings {
+            do_not_disturb: true,
+            ..Default::default()
+        };
+
+        fire_summary_notification(&notifier, &notifications_tx, &sessions, &settings, |_| {
+            false
+        });
+        assert!(notifier.shown.borrow().is_empty());
+        // Web clients still hear about it
+        assert!(notifications_rx.try_recv().is_ok());
+
+        settings.do_not_disturb = false;
+        fire_summary_notification(&notifier, &notifications_tx, &sessions, &settings, |_| {
+            false
+        });
+        assert_eq!(*notifier.shown.borrow(), vec!["2 sessions finished"]);
+    }
+
+    #[test]
+    fn test_summary_leaves_out_focused_sessions() {
+        let (notifications_tx, _notifications_rx) = tokio::sync::broadcast::channel(4);
+        let mut watched = test_session(SessionStatus::NeedsPermission, 1);
+        watched.pid = 2;
+        let sessions = vec![test_session(SessionStatus::WaitingForInput, 1), watched];
+        let notifier = RecordingNotifier::default();
+        let settings = AppSettings {
+            suppress_when_focused: true,
+            ..Default::default()
+        };
+
+        fire_summary_notification(&notifier, &notifications_tx, &sessions, &settings, |pid| {
+            pid == 2
+        });
+        assert_eq!(*notifier.shown.borrow(), vec!["1 session finished"]);
+
+        fire_summary_notification(&notifier, &notifications_tx, &sessions, &settings, |_| true);
+        assert_eq!(notifier.shown.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_held_notifications_refresh_to_current_status() {
+        let mut grouper = NotificationGrouper::default();
+        let mut answered = test_session(SessionStatus::NeedsPermission, 1);
+        answered.id = "s2".to_string();
+        let mut ended = test_session(SessionStatus::WaitingForInput, 1);
+        ended.id = "s3".to_string();
+        grouper.push(test_session(SessionStatus::NeedsPermission, 1));
+        grouper.push(answered);
+        grouper.push(ended);
+
+        let mut working = test_session(SessionStatus::Working, 1);
+        working.id = "s2".to_string();
+        grouper.refresh(&[test_session(SessionStatus::WaitingForInput, 1), working]);
+
+        assert_eq!(grouper.held.len(), 1);
+        assert_eq!(grouper.held[0].status, SessionStatus::WaitingForInput);
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_duration_counters() {
+        let before = sessions_fingerprint(&[test_session(SessionStatus::Working, 10)]);
+        let later = sessions_fingerprint(&[test_session(SessionStatus::Working, 20)]);
+        let changed = sessions_fingerprint(&[test_session(SessionStatus::WaitingForInput, 20)]);
+
+        assert_eq!(before, later);
+        assert_ne!(before, changed);
+    }
 }
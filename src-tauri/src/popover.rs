@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager, PhysicalPosition, Rect, Window, WindowEvent};
+
+/// Label of the tray popover window (`src/routes/popover`)
+pub const LABEL: &str = "popover";
+
+/// While pinned, the popover stays up when it loses focus and floats above
+/// other windows, e.g. parked on a second monitor
+static PINNED: AtomicBool = AtomicBool::new(false);
+
+pub fn is_pinned() -> bool {
+    PINNED.load(Ordering::Relaxed)
+}
+
+/// Pin or unpin the popover, applying it to the window if it's open
+pub fn set_pinned(app: &AppHandle, pinned: bool) -> Result<(), String> {
+    if PINNED.swap(pinned, Ordering::Relaxed) != pinned {
+        crate::window_state::set_popover_pinned(pinned);
+    }
+    if let Some(popover) = app.get_webview_window(LABEL) {
+        popover
+            .set_always_on_top(pinned)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Builder `on_window_event` hook: the popover hides when focus moves
+/// elsewhere, like a menu, unless it's pinned
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    if window.label() != LABEL {
+        return;
+    }
+    if let WindowEvent::Focused(false) = event {
+        if !is_pinned() {
+            let _ = window.hide();
+        }
+    }
+}
+
+/// Rectangle in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PixelRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Gap between the tray icon and the popover, in physical pixels
+const TRAY_GAP: i32 = 4;
+
+/// Top-left corner for a `size` popover anchored to `tray` on the monitor
+/// whose work area (the screen minus menu bar, taskbar and dock) is `work`.
+///
+/// The tray's taskbar edge is whichever side of the work area the icon sits
+/// outside of: the popover opens below a top menu bar, above a bottom
+/// taskbar and beside a vertical one, then is clamped onto the work area.
+pub fn place(tray: PixelRect, work: PixelRect, size: (u32, u32)) -> (i32, i32) {
+    let (width, height) = (size.0 as i32, size.1 as i32);
+    let tray_right = tray.x + tray.width as i32;
+    let tray_bottom = tray.y + tray.height as i32;
+    let work_right = work.x + work.width as i32;
+    let work_bottom = work.y + work.height as i32;
+    let center_x = tray.x + tray.width as i32 / 2 - width / 2;
+    let center_y = tray.y + tray.height as i32 / 2 - height / 2;
+
+    let (x, y) = if tray_right <= work.x {
+        (tray_right + TRAY_GAP, center_y)
+    } else if tray.x >= work_right {
+        (tray.x - width - TRAY_GAP, center_y)
+    } else if tray.y + tray.height as i32 / 2 < work.y + work.height as i32 / 2 {
+        // Menu bar or top taskbar; comparing against the middle also covers
+        // auto-hiding bars, whose icons sit inside the work area
+        (center_x, tray_bottom + TRAY_GAP)
+    } else {
+        (center_x, tray.y - height - TRAY_GAP)
+    };
+
+    (
+        x.clamp(work.x, (work_right - width).max(work.x)),
+        y.clamp(work.y, (work_bottom - height).max(work.y)),
+    )
+}
+
+/// Show the popover next to the tray icon at `tray_rect`, on the monitor
+/// that icon is on
+pub fn show_at_tray(app: &AppHandle, tray_rect: Rect) -> Result<(), String> {
+    let popover = app
+        .get_webview_window(LABEL)
+        .ok_or("Popover window not found")?;
+
+    // Tray rects are reported in physical pixels on every platform, so the
+    // scale factor passed here is never applied
+    let position = tray_rect.position.to_physical::<f64>(1.0);
+    let tray_size = tray_rect.size.to_physical::<f64>(1.0);
+    let tray = PixelRect {
+        x: position.x.round() as i32,
+        y: position.y.round() as i32,
+        width: tray_size.width.round() as u32,
+        height: tray_size.height.round() as u32,
+    };
+
+    let center_x = position.x + tray_size.width / 2.0;
+    let center_y = position.y + tray_size.height / 2.0;
+    let monitor = match app.monitor_from_point(center_x, center_y) {
+        Ok(Some(monitor)) => Some(monitor),
+        _ => app.primary_monitor().ok().flatten(),
+    }
+    .ok_or("No monitor found")?;
+
+    let area = monitor.work_area();
+    let work = PixelRect {
+        x: area.position.x,
+        y: area.position.y,
+        width: area.size.width,
+        height: area.size.height,
+    };
+
+    // The window's physical size is in its current monitor's scale; convert
+    // to the target monitor's before placing
+    let window_scale = popover.scale_factor().map_err(|e| e.to_string())?;
+    let logical = popover
+        .outer_size()
+        .map_err(|e| e.to_string())?
+        .to_logical::<f64>(window_scale);
+    let scale = monitor.scale_factor();
+    let size = (
+        (logical.width * scale).round() as u32,
+        (logical.height * scale).round() as u32,
+    );
+
+    let (x, y) = place(tray, work, size);
+    popover
+        .set_position(PhysicalPosition::new(x, y))
+        .map_err(|e| e.to_string())?;
+    popover.show().map_err(|e| e.to_string())?;
+    popover.set_focus().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> PixelRect {
+        PixelRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_place_against_taskbar_edge() {
+        let size = (400, 300);
+
+        // macOS menu bar on a secondary display to the right (2x scale)
+        let work = rect(2560, 50, 5120, 2830);
+        assert_eq!(place(rect(7000, 0, 44, 44), work, size), (6822, 50));
+
+        // Windows bottom taskbar, icon near the right edge: clamped on-screen
+        let work = rect(0, 0, 1920, 1032);
+        assert_eq!(place(rect(1880, 1040, 32, 40), work, size), (1520, 732));
+
+        // Vertical taskbar docked left
+        let work = rect(62, 0, 1858, 1080);
+        assert_eq!(place(rect(0, 900, 62, 40), work, size), (66, 770));
+
+        // Vertical taskbar docked right, on a display left of the primary
+        let work = rect(-1920, 0, 1858, 1080);
+        assert_eq!(place(rect(-62, 100, 62, 40), work, size), (-466, 0));
+    }
+}
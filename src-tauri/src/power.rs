@@ -0,0 +1,92 @@
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long a power source reading is reused
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+static ON_BATTERY: OnceLock<Mutex<Option<(Instant, bool)>>> = OnceLock::new();
+
+/// Whether the machine is running on battery. False on desktops and when the
+/// power source can't be determined.
+pub fn on_battery() -> bool {
+    let mut cached = ON_BATTERY
+        .get_or_init(|| Mutex::new(None))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some((checked_at, on_battery)) = *cached {
+        if checked_at.elapsed() < CHECK_INTERVAL {
+            return on_battery;
+        }
+    }
+    let on_battery = read_on_battery();
+    *cached = Some((Instant::now(), on_battery));
+    on_battery
+}
+
+/// Wall-clock time gained on the monotonic clock that counts as a sleep
+const SLEEP_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// Notices system sleep without OS hooks: the monotonic clock stops while
+/// the machine is suspended, the wall clock keeps going.
+pub struct SleepDetector {
+    wall: SystemTime,
+    monotonic: Instant,
+}
+
+impl Default for SleepDetector {
+    fn default() -> Self {
+        Self {
+            wall: SystemTime::now(),
+            monotonic: Instant::now(),
+        }
+    }
+}
+
+impl SleepDetector {
+    /// Whether the system slept since the previous check
+    pub fn woke(&mut self) -> bool {
+        let wall_elapsed = self.wall.elapsed().unwrap_or_default();
+        let slept = wall_elapsed.saturating_sub(self.monotonic.elapsed()) > SLEEP_THRESHOLD;
+        *self = Self::default();
+        slept
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn read_on_battery() -> bool {
+    // First line: "Now drawing from 'Battery Power'" or "'AC Power'"
+    std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"))
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "linux")]
+fn read_on_battery() -> bool {
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    let read = |path: std::path::PathBuf| {
+        std::fs::read_to_string(path)
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut has_battery = false;
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        match read(path.join("type")).as_str() {
+            // Any connected charger means we're not on battery
+            "Mains" | "USB" if read(path.join("online")) == "1" => return false,
+            "Battery" => has_battery = true,
+            _ => {}
+        }
+    }
+    has_battery
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn read_on_battery() -> bool {
+    false
+}
@@ -0,0 +1,366 @@
+use super::{mtime_secs, AgentProvider, SessionSnapshot, RECENT_ACTIVITY_SECS};
+use crate::session::parser::{read_last_n_lines, truncate_string, MAX_PREVIEW_CHARS};
+use crate::session::{
+    AgentProcess, ConversationMessage, DetectedSession, MessageType, SessionDetectorError,
+    SessionStatus,
+};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+const HISTORY_FILE: &str = ".aider.chat.history.md";
+
+/// Every aider run appends a new section starting with this line
+const SESSION_HEADER: &str = "# aider chat started at ";
+
+/// Lines read from the end of the history file when polling. The file keeps
+/// every chat ever run in the project, so it is never read whole while polling.
+const TAIL_LINES: usize = 2000;
+
+/// Aider: a Python CLI that appends each chat to .aider.chat.history.md in
+/// the repository root
+pub struct AiderProvider;
+
+/// The current (last) chat section of a history file
+#[derive(Debug)]
+struct AiderChat {
+    header: String,
+    /// RFC 3339, from the section header
+    started_at: String,
+    blocks: Vec<(MessageType, String)>,
+}
+
+impl AiderProvider {
+    pub fn new() -> Result<Self, SessionDetectorError> {
+        Ok(Self)
+    }
+}
+
+impl AgentProvider for AiderProvider {
+    fn id(&self) -> &'static str {
+        "aider"
+    }
+
+    fn matches_process(&self, process: &AgentProcess) -> bool {
+        // Usually runs as `python .../bin/aider`
+        process.name == "aider"
+            || process
+                .cmd
+                .iter()
+                .take(2)
+                .any(|arg| Path::new(arg).file_name().and_then(|n| n.to_str()) == Some("aider"))
+    }
+
+    fn locate_sessions(
+        &self,
+        processes: &[&AgentProcess],
+    ) -> Result<Vec<DetectedSession>, SessionDetectorError> {
+        let mut sessions = Vec::new();
+        for proc in processes {
+            let Some(proc_cwd) = &proc.cwd else {
+                continue;
+            };
+            let Some(history) = history_file(proc_cwd, &proc.cmd) else {
+                continue;
+            };
+            // Aider writes the section header on startup, so an older file
+            // belongs to some other (finished) run
+            if mtime_secs(&history) + 5 < proc.start_time {
+                continue;
+            }
+            let Some(chat) = read_current_chat(&history, TAIL_LINES) else {
+                continue;
+            };
+
+            sessions.push(DetectedSession {
+                agent: self.id().to_string(),
+                pid: proc.pid,
+                cwd: proc_cwd.clone(),
+                project_path: history.parent().map(Path::to_path_buf).unwrap_or_default(),
+                session_id: Some(session_id(&history, &chat.header)),
+                transcript_path: history,
+                project_name: proc_cwd
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("unknown")
+                    .to_string(),
+                start_time: proc.start_time,
+            });
+        }
+
+        Ok(sessions)
+    }
+
+    fn read_session(&self, detected: &DetectedSession) -> SessionSnapshot {
+        let path = &detected.transcript_path;
+        let blocks = read_current_chat(path, TAIL_LINES)
+            .map(|chat| chat.blocks)
+            .unwrap_or_default();
+        let quiet_secs = (Utc::now().timestamp() as u64).saturating_sub(mtime_secs(path));
+
+        let conversation: Vec<&(MessageType, String)> = blocks
+            .iter()
+            .filter(|(kind, _)| matches!(kind, MessageType::User | MessageType::Assistant))
+            .collect();
+
+        SessionSnapshot {
+            first_prompt: conversation
+                .iter()
+                .find(|(kind, _)| *kind == MessageType::User)
+                .map(|(_, text)| truncate_string(text, MAX_PREVIEW_CHARS))
+                .unwrap_or_else(|| "(Active session)".to_string()),
+            summary: None,
+            message_count: conversation.len() as u32,
+            modified: fs::metadata(path)
+                .and_then(|m| m.modified())
+                .ok()
+                .map(|t| DateTime::<Utc>::from(t).to_rfc3339())
+                .unwrap_or_default(),
+            git_branch: None,
+            status: determine_status(&blocks, quiet_secs),
+            latest_message: conversation
+                .last()
+                .map(|(_, text)| truncate_string(text, MAX_PREVIEW_CHARS))
+                .unwrap_or_default(),
+            pending_tool_name: None,
+            pending_tool_input: None,
+            context_percent: None,
+            permission_mode: None,
+            model: current_model(&blocks),
+            mcp_servers: Vec::new(),
+        }
+    }
+
+    fn conversation(&self, detected: &DetectedSession) -> Result<Vec<ConversationMessage>, String> {
+        let chat = read_current_chat(&detected.transcript_path, usize::MAX)
+            .ok_or_else(|| "No aider chat found in the history file".to_string())?;
+        // The history file has no per-message times
+        Ok(chat
+            .blocks
+            .into_iter()
+            .map(|(kind, text)| ConversationMessage::new(chat.started_at.clone(), kind, text))
+            .collect())
+    }
+}
+
+/// The history file aider is writing: `--chat-history-file` if given,
+/// otherwise .aider.chat.history.md in the cwd or the enclosing git root
+fn history_file(cwd: &Path, cmd: &[String]) -> Option<PathBuf> {
+    if let Some(pos) = cmd.iter().position(|arg| arg == "--chat-history-file") {
+        return cmd.get(pos + 1).map(|file| cwd.join(file));
+    }
+    if let Some(file) = cmd
+        .iter()
+        .find_map(|arg| arg.strip_prefix("--chat-history-file="))
+    {
+        return Some(cwd.join(file));
+    }
+
+    for dir in cwd.ancestors() {
+        let candidate = dir.join(HISTORY_FILE);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    None
+}
+
+/// Aider sessions have no id of their own; derive a stable one from the
+/// history file and the section's start time
+fn session_id(history: &Path, header: &str) -> String {
+    let digest = Sha256::digest(format!("{}\n{}", history.display(), header).as_bytes());
+    format!("aider-{:x}", digest)[..22].to_string()
+}
+
+fn read_current_chat(path: &Path, max_lines: usize) -> Option<AiderChat> {
+    let lines = match read_last_n_lines(path, max_lines) {
+        Ok(lines) => lines,
+        Err(e) => {
+            warn!("Failed to read aider history {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    parse_current_chat(&lines)
+}
+
+/// Split the last chat section into messages. In the history file user input
+/// is prefixed with "#### ", aider's own output with "> ", and everything else
+/// is the model's reply. A chat longer than `lines` has no header in view;
+/// then all of `lines` is taken as the current chat.
+fn parse_current_chat(lines: &[String]) -> Option<AiderChat> {
+    let (header, body) = match lines
+        .iter()
+        .rposition(|line| line.starts_with(SESSION_HEADER))
+    {
+        Some(start) => (lines[start].clone(), &lines[start + 1..]),
+        None if lines.is_empty() => return None,
+        None => (String::new(), lines),
+    };
+    let started_at = header
+        .strip_prefix(SESSION_HEADER)
+        .and_then(|time| NaiveDateTime::parse_from_str(time.trim(), "%Y-%m-%d %H:%M:%S").ok())
+        .and_then(|t| Local.from_local_datetime(&t).single())
+        .map(|t| t.with_timezone(&Utc).to_rfc3339())
+        .unwrap_or_default();
+
+    let mut blocks: Vec<(MessageType, String)> = Vec::new();
+    // Prompts start after a blank line (or the header) and may run on over
+    // several "####" lines; a "####" heading inside a reply is part of it
+    let mut prompt_may_start = true;
+    for line in body {
+        let in_prompt = matches!(blocks.last(), Some((MessageType::User, _)));
+        let prompt_line = line
+            .strip_prefix("####")
+            .filter(|_| prompt_may_start || in_prompt);
+        prompt_may_start = line.trim().is_empty();
+
+        let (kind, text) = if let Some(text) = prompt_line {
+            (MessageType::User, text.strip_prefix(' ').unwrap_or(text))
+        } else if let Some(text) = line.strip_prefix('>') {
+            (MessageType::System, text.strip_prefix(' ').unwrap_or(text))
+        } else if line.trim().is_empty() {
+            // Blank lines only matter inside a reply
+            if let Some((MessageType::Assistant, text)) = blocks.last_mut() {
+                text.push('\n');
+            }
+            continue;
+        } else {
+            (MessageType::Assistant, line.as_str())
+        };
+
+        match blocks.last_mut() {
+            Some((last_kind, existing)) if *last_kind == kind => {
+                existing.push('\n');
+                existing.push_str(text);
+            }
+            _ => blocks.push((kind, text.to_string())),
+        }
+    }
+
+    for (_, text) in blocks.iter_mut() {
+        *text = text.trim().to_string();
+    }
+    blocks.retain(|(_, text)| !text.is_empty());
+
+    Some(AiderChat {
+        header,
+        started_at,
+        blocks,
+    })
+}
+
+/// Aider prints its model settings at startup ("Main model: ... with diff
+/// edit format") and again after /model
+fn current_model(blocks: &[(MessageType, String)]) -> Option<String> {
+    blocks
+        .iter()
+        .rev()
+        .filter(|(kind, _)| *kind == MessageType::System)
+        .flat_map(|(_, text)| text.lines().rev())
+        .find_map(|line| {
+            let rest = line
+                .strip_prefix("Main model: ")
+                .or_else(|| line.strip_prefix("Model: "))?;
+            rest.split_whitespace().next().map(str::to_string)
+        })
+}
+
+/// Aider records the prompt as soon as it is sent and the reply once it has
+/// finished streaming, so an unanswered prompt means the model is working
+fn determine_status(blocks: &[(MessageType, String)], quiet_secs: u64) -> SessionStatus {
+    match blocks.last() {
+        Some((MessageType::User, _)) => SessionStatus::Working,
+        Some(_) if quiet_secs < RECENT_ACTIVITY_SECS => SessionStatus::Working,
+        _ => SessionStatus::WaitingForInput,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_current_chat_section() {
+        let history = lines(
+            "# aider chat started at 2026-02-09 09:00:00\n\n#### old prompt\n\nold reply\n\n\
+             # aider chat started at 2026-02-10 10:00:00\n\n> Aider v0.80.0\n\n\
+             > Main model: gpt-4o with diff edit format\n\n\
+             #### add a hello function\n#### to main.py\n\nHere is the change.\n\nDone.\n\n\
+             > Applied edit to main.py\n",
+        );
+        let chat = parse_current_chat(&history).unwrap();
+        assert_eq!(chat.header, "# aider chat started at 2026-02-10 10:00:00");
+        let kinds: Vec<&MessageType> = chat.blocks.iter().map(|(kind, _)| kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &MessageType::System,
+                &MessageType::User,
+                &MessageType::Assistant,
+                &MessageType::System
+            ]
+        );
+        assert_eq!(chat.blocks[1].1, "add a hello function\nto main.py");
+        assert_eq!(current_model(&chat.blocks).as_deref(), Some("gpt-4o"));
+        assert_eq!(chat.blocks[2].1, "Here is the change.\n\nDone.");
+        assert_eq!(
+            determine_status(&chat.blocks, 60),
+            SessionStatus::WaitingForInput
+        );
+
+        let asked = lines("# aider chat started at 2026-02-10 10:00:00\n\n#### fix it\n");
+        let chat = parse_current_chat(&asked).unwrap();
+        assert_eq!(determine_status(&chat.blocks, 60), SessionStatus::Working);
+    }
+
+    #[test]
+    fn test_parse_chat_without_header_in_view() {
+        // The tail of a chat too long for its header to be in the window
+        let tail = lines(
+            "rest of a reply\n\n#### explain it\n\nIt works like this:\n\
+             #### Step one\nOpen the file.\n",
+        );
+        let chat = parse_current_chat(&tail).unwrap();
+        assert_eq!(chat.header, "");
+        assert_eq!(chat.started_at, "");
+        let kinds: Vec<&MessageType> = chat.blocks.iter().map(|(kind, _)| kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &MessageType::Assistant,
+                &MessageType::User,
+                &MessageType::Assistant
+            ]
+        );
+        // The heading in the reply doesn't start a new prompt
+        assert_eq!(
+            chat.blocks[2].1,
+            "It works like this:\n#### Step one\nOpen the file."
+        );
+        assert!(parse_current_chat(&[]).is_none());
+    }
+
+    #[test]
+    fn test_history_file_flag() {
+        let cwd = Path::new("/tmp/project");
+        let cmd = vec![
+            "python".to_string(),
+            "aider".to_string(),
+            "--chat-history-file".to_string(),
+            "notes/chat.md".to_string(),
+        ];
+        assert_eq!(
+            history_file(cwd, &cmd),
+            Some(PathBuf::from("/tmp/project/notes/chat.md"))
+        );
+    }
+}
@@ -0,0 +1,975 @@
+use super::{AgentProvider, SessionSnapshot, RECENT_ACTIVITY_SECS};
+use crate::error::C9Error;
+use crate::session::id::{contained_in, is_uuid};
+use crate::session::parser::{
+    truncate_string, MessageContent, SessionEntry, UserContentKind, MAX_PREVIEW_CHARS,
+};
+use crate::session::{
+    branches, determine_status_in_mode, diagnostics, extract_messages, generate_title,
+    get_pending_tool_input_in_mode, get_pending_tool_name_in_mode, get_permission_mode, lineage,
+    parse_all_entries, parse_sessions_index, tail_reader, validate_session_id, AgentProcess,
+    BranchPoint, ConversationMessage, DetectedSession, PermissionMode, SessionDetectorError,
+    SessionStatus,
+};
+use crate::stats::McpScan;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use tracing::warn;
+
+/// Claude Code: `claude` processes whose transcripts live in ~/.claude/projects/
+pub struct ClaudeProvider {
+    claude_projects_dir: PathBuf,
+    /// Last permission mode seen per transcript. Only prompts record it, so
+    /// it scrolls out of the recent entries during long tool runs.
+    permission_modes: Mutex<HashMap<PathBuf, PermissionMode>>,
+    /// MCP calls counted so far per transcript
+    mcp_scans: Mutex<HashMap<PathBuf, McpScan>>,
+}
+
+impl ClaudeProvider {
+    pub fn new() -> Result<Self, SessionDetectorError> {
+        let home_dir = dirs::home_dir().ok_or(SessionDetectorError::HomeDirectoryNotFound)?;
+
+        Ok(Self {
+            claude_projects_dir: home_dir.join(".claude").join("projects"),
+            permission_modes: Mutex::new(HashMap::new()),
+            mcp_scans: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// The session's permission mode, from the recent entries if they
+    /// record one, otherwise as last seen
+    fn permission_mode(&self, path: &Path, entries: &[SessionEntry]) -> Option<PermissionMode> {
+        let mut modes = self
+            .permission_modes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(mode) = get_permission_mode(entries) {
+            modes.insert(path.to_path_buf(), mode);
+        }
+        modes.get(path).copied()
+    }
+
+    /// Find sessions that are likely active based on running process count
+    fn find_active_sessions(
+        &self,
+        processes: &[&AgentProcess],
+        project_dirs: &[PathBuf],
+    ) -> Vec<DetectedSession> {
+        // Collect all session files with their modification times and project path
+        // Tuple: (modified_time, jsonl_path, project_dir, project_path, project_name, has_reliable_path)
+        let mut session_files: Vec<(
+            std::time::SystemTime,
+            PathBuf,
+            PathBuf,
+            PathBuf,
+            String,
+            bool,
+        )> = Vec::new();
+
+        for project_dir in project_dirs {
+            if let Ok(entries) = fs::read_dir(project_dir) {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+
+                    // Check if it's a JSONL file (UUID format, not subagent files)
+                    if path.is_file() && path.extension().map_or(false, |ext| ext == "jsonl") {
+                        // Skip files that don't look like UUIDs (e.g., agent-*.jsonl)
+                        if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                            if stem.starts_with("agent-") {
+                                continue;
+                            }
+                        }
+
+                        if let Ok(metadata) = fs::metadata(&path) {
+                            if let Ok(modified) = metadata.modified() {
+                                // Get session ID and project info
+                                if let Some(session_id) = path
+                                    .file_stem()
+                                    .and_then(|s| s.to_str())
+                                    .map(|s| s.to_string())
+                                {
+                                    // Try to get project info from sessions-index.json
+                                    // This is the ONLY reliable source of project path
+                                    let (project_path, project_name, has_reliable_path) = match self
+                                        .get_project_info_from_index(project_dir, &session_id)
+                                    {
+                                        Some((path, name)) => (path, name, true),
+                                        None => {
+                                            // No reliable path available - use directory name as display only
+                                            // Don't try to decode it (decoding is ambiguous due to dashes)
+                                            let dir_name = project_dir
+                                                .file_name()
+                                                .and_then(|n| n.to_str())
+                                                .unwrap_or("unknown");
+
+                                            // Just use the last segment after splitting on dash as a rough name
+                                            // This is for display only, not for matching
+                                            let name = dir_name
+                                                .rsplit('-')
+                                                .next()
+                                                .unwrap_or("unknown")
+                                                .to_string();
+
+                                            // Use the project_dir as a placeholder (will use fallback PID assignment)
+                                            (project_dir.clone(), name, false)
+                                        }
+                                    };
+
+                                    session_files.push((
+                                        modified,
+                                        path,
+                                        project_dir.clone(),
+                                        project_path,
+                                        project_name,
+                                        has_reliable_path,
+                                    ));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Sort by modification time (most recent first)
+        session_files.sort_by(|a, b| b.0.cmp(&a.0));
+
+        // Process-centric approach: for each process, find its matching session
+        // This ensures we only show sessions that have actual running processes
+        let mut sessions = Vec::new();
+        let mut used_session_ids: std::collections::HashSet<String> =
+            std::collections::HashSet::new();
+
+        // Sort processes by start_time (newest first) to match newest processes first
+        let mut sorted_processes: Vec<&AgentProcess> = processes.to_vec();
+        sorted_processes.sort_by(|a, b| b.start_time.cmp(&a.start_time));
+
+        // With several processes in one directory the newest-file heuristic
+        // can pair them up wrongly, so first bind any process caught with its
+        // transcript open. Claude Code doesn't hold the file open between
+        // writes, so this only settles some of them.
+        let mut processes_per_cwd: HashMap<&Path, usize> = HashMap::new();
+        for proc in &sorted_processes {
+            if let Some(cwd) = &proc.cwd {
+                *processes_per_cwd.entry(cwd.as_path()).or_default() += 1;
+            }
+        }
+        let mut open_transcripts: HashMap<u32, PathBuf> = HashMap::new();
+        for proc in &sorted_processes {
+            let shares_cwd = match &proc.cwd {
+                Some(cwd) => processes_per_cwd[cwd.as_path()] > 1,
+                None => false,
+            };
+            if !shares_cwd {
+                continue;
+            }
+            let open = crate::open_files::open_files(proc.pid);
+            let transcript = session_files
+                .iter()
+                .map(|file| &file.1)
+                .find(|path| open.contains(path));
+            if let Some(transcript) = transcript {
+                if let Some(id) = transcript.file_stem().and_then(|s| s.to_str()) {
+                    used_session_ids.insert(id.to_string());
+                    open_transcripts.insert(proc.pid, transcript.clone());
+                }
+            }
+        }
+
+        for proc in sorted_processes {
+            let proc_cwd = match &proc.cwd {
+                Some(cwd) => cwd,
+                None => continue, // Skip processes without cwd
+            };
+
+            // Encode the process cwd for matching
+            let cwd_str = proc_cwd.to_string_lossy();
+            let encoded_cwd = cwd_str.replace('/', "-").replace('_', "-");
+
+            // Helper closure to check if a session matches the process path
+            let path_matches =
+                |project_dir: &Path, project_path: &Path, has_reliable_path: bool| -> bool {
+                    let dir_name = project_dir
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("");
+
+                    // Method 1: Direct path comparison (exact or subdirectory match)
+                    let direct_match = if has_reliable_path {
+                        proc_cwd == project_path || proc_cwd.starts_with(project_path)
+                    } else {
+                        false
+                    };
+
+                    // Method 2: Encoded path comparison
+                    let encoded_match = dir_name == encoded_cwd;
+
+                    direct_match || encoded_match
+                };
+
+            // Helper closure to check if session is not already used
+            let session_available = |path: &Path| -> bool {
+                match path.file_stem().and_then(|s| s.to_str()) {
+                    Some(id) => !used_session_ids.contains(id),
+                    None => false,
+                }
+            };
+
+            // Find session with activity after process start
+            // Only match sessions that were modified AFTER the process started
+            // This prevents matching a new Claude instance (with no session file yet)
+            // to an older session from the same project directory
+            let open_transcript = open_transcripts.get(&proc.pid);
+            let matching_session = session_files.iter().find(
+                |(modified, path, project_dir, project_path, _, has_reliable_path)| {
+                    if let Some(open) = open_transcript {
+                        return path == open;
+                    }
+                    if !session_available(path) {
+                        return false;
+                    }
+
+                    // Check if the session was modified after the process started
+                    let session_active_after_proc_start =
+                        match modified.duration_since(std::time::UNIX_EPOCH) {
+                            Ok(duration) => {
+                                let session_modified_secs = duration.as_secs();
+                                // Session must have been modified at or after process start (with 5s buffer)
+                                session_modified_secs + 5 >= proc.start_time
+                            }
+                            Err(_) => false,
+                        };
+
+                    session_active_after_proc_start
+                        && path_matches(project_dir, project_path, *has_reliable_path)
+                },
+            );
+
+            if let Some((_, path, project_dir, _, project_name, _)) = matching_session {
+                if let Some(session_id) = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(|s| s.to_string())
+                {
+                    used_session_ids.insert(session_id.clone());
+
+                    sessions.push(DetectedSession {
+                        agent: self.id().to_string(),
+                        pid: proc.pid,
+                        cwd: proc_cwd.clone(),
+                        project_path: project_dir.clone(),
+                        transcript_path: path.clone(),
+                        session_id: Some(session_id),
+                        project_name: project_name.clone(),
+                        start_time: proc.start_time,
+                    });
+                }
+            }
+        }
+
+        sessions
+    }
+
+    /// Get project info from sessions-index.json for a given session ID
+    fn get_project_info_from_index(
+        &self,
+        project_dir: &Path,
+        session_id: &str,
+    ) -> Option<(PathBuf, String)> {
+        let index_path = project_dir.join("sessions-index.json");
+
+        if let Ok(content) = fs::read_to_string(&index_path) {
+            if let Ok(index) = serde_json::from_str::<ProjectIndex>(&content) {
+                if let Some(entries) = &index.entries {
+                    for entry in entries {
+                        if entry.session_id == session_id {
+                            if let Some(proj_path) = &entry.project_path {
+                                let path = PathBuf::from(proj_path);
+                                let name = path
+                                    .file_name()
+                                    .and_then(|n| n.to_str())
+                                    .unwrap_or("unknown")
+                                    .to_string();
+                                return Some((path, name));
+                            }
+                        }
+                    }
+
+                    // If session not found in index, use first entry's project path as fallback
+                    if let Some(first) = entries.first() {
+                        if let Some(proj_path) = &first.project_path {
+                            let path = PathBuf::from(proj_path);
+                            let name = path
+                                .file_name()
+                                .and_then(|n| n.to_str())
+                                .unwrap_or("unknown")
+                                .to_string();
+                            return Some((path, name));
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Enumerates all project directories in ~/.claude/projects/
+    fn enumerate_project_directories(&self) -> Result<Vec<PathBuf>, SessionDetectorError> {
+        let mut project_dirs = Vec::new();
+
+        // Check if the claude projects directory exists
+        if !self.claude_projects_dir.exists() {
+            return Ok(project_dirs);
+        }
+
+        // Read all entries in the projects directory
+        let entries = fs::read_dir(&self.claude_projects_dir)?;
+
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+
+            // Only include directories
+            if path.is_dir() {
+                project_dirs.push(path);
+            }
+        }
+
+        Ok(project_dirs)
+    }
+}
+
+impl AgentProvider for ClaudeProvider {
+    fn id(&self) -> &'static str {
+        "claude"
+    }
+
+    fn matches_process(&self, process: &AgentProcess) -> bool {
+        if process.name.contains("c9watch") {
+            return false;
+        }
+        if process.name.contains("claude") {
+            return true;
+        }
+        // npm and npx installs run as `node .../@anthropic-ai/claude-code/cli.js`
+        // or `node .../bin/claude`
+        process.name.starts_with("node")
+            && process.cmd.iter().skip(1).any(|arg| {
+                arg.contains("@anthropic-ai/claude-code")
+                    || Path::new(arg).file_name().and_then(|n| n.to_str()) == Some("claude")
+            })
+    }
+
+    fn locate_sessions(
+        &self,
+        processes: &[&AgentProcess],
+    ) -> Result<Vec<DetectedSession>, SessionDetectorError> {
+        // Get all session project directories
+        let project_dirs = self.enumerate_project_directories()?;
+
+        // Find recently active sessions and associate them with running processes
+        Ok(self.find_active_sessions(processes, &project_dirs))
+    }
+
+    fn read_session(&self, detected: &DetectedSession) -> SessionSnapshot {
+        let session_id = detected.session_id.as_deref().unwrap_or_default();
+        let session_file_path = &detected.transcript_path;
+        let index_path = detected.project_path.join("sessions-index.json");
+
+        let EnrichedMetadata {
+            first_prompt,
+            summary,
+            message_count,
+            modified,
+            git_branch,
+        } = get_session_metadata(session_id, session_file_path, &index_path);
+
+        // Parse the session JSONL file to determine status and get latest message
+        let entries = match tail_reader().recent_entries(session_file_path, 20) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!(
+                    "Failed to parse session file for {}: {}. Using fallback status.",
+                    session_id, e
+                );
+                vec![]
+            }
+        };
+
+        let permission_mode = self.permission_mode(session_file_path, &entries);
+        let mode = permission_mode.unwrap_or_default();
+
+        let status = if entries.is_empty() {
+            SessionStatus::Connecting
+        } else {
+            let raw_status = determine_status_in_mode(&entries, mode);
+            // Override WaitingForInput if the JSONL file was recently modified.
+            // This catches progress entries (bash_progress, thinking updates) that
+            // don't get parsed as meaningful entries but indicate active work.
+            if raw_status == SessionStatus::WaitingForInput
+                && is_file_recently_modified(session_file_path, RECENT_ACTIVITY_SECS)
+            {
+                SessionStatus::Working
+            } else {
+                raw_status
+            }
+        };
+
+        SessionSnapshot {
+            first_prompt,
+            summary,
+            message_count,
+            modified,
+            git_branch,
+            status,
+            latest_message: get_latest_message_from_entries(&entries),
+            pending_tool_name: get_pending_tool_name_in_mode(&entries, mode),
+            pending_tool_input: get_pending_tool_input_in_mode(&entries, mode),
+            context_percent: get_context_percent_from_entries(&entries),
+            permission_mode,
+            model: get_model_from_entries(&entries),
+            mcp_servers: self
+                .mcp_scans
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry(session_file_path.clone())
+                .or_default()
+                .update(session_file_path),
+        }
+    }
+
+    fn conversation(&self, detected: &DetectedSession) -> Result<Vec<ConversationMessage>, String> {
+        read_branch(&detected.transcript_path, None).map(|(messages, _)| messages)
+    }
+
+    fn conversation_on_branch(
+        &self,
+        detected: &DetectedSession,
+        through: Option<&str>,
+    ) -> Result<(Vec<ConversationMessage>, Vec<BranchPoint>), String> {
+        read_branch(&detected.transcript_path, through)
+    }
+
+    fn retain(&self, watched: &HashSet<PathBuf>) {
+        // Forget tail state and cached metadata for sessions that have gone away
+        tail_reader().retain(watched);
+        self.permission_modes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|path, _| watched.contains(path));
+        self.mcp_scans
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|path, _| watched.contains(path));
+        enrichment_cache()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|path, _| watched.contains(path));
+        lineage::retain(watched);
+        diagnostics().retain(watched);
+    }
+}
+
+/// A transcript's messages along the branch through `through` (any entry
+/// uuid), or the branch the session is on now, with its branch points
+pub fn read_branch(
+    path: &Path,
+    through: Option<&str>,
+) -> Result<(Vec<ConversationMessage>, Vec<BranchPoint>), String> {
+    let mut entries =
+        parse_all_entries(path).map_err(|e| format!("Failed to parse session file: {}", e))?;
+    let branch = branches::resolve(&entries, through);
+    branch.retain(&mut entries);
+    Ok((extract_messages(&entries), branch.points))
+}
+
+/// Transcript of any Claude session, running or not, by session ID
+pub fn find_transcript(session_id: &str) -> Result<PathBuf, C9Error> {
+    validate_session_id(session_id)?;
+    let not_found = || {
+        C9Error::NotFound(format!(
+            "Session {} not found in any project directory",
+            session_id
+        ))
+    };
+    // Other agents' IDs never name a Claude transcript
+    if !is_uuid(session_id) {
+        return Err(not_found());
+    }
+
+    let home_dir = dirs::home_dir().ok_or("Failed to get home directory")?;
+    let claude_projects_dir = home_dir.join(".claude").join("projects");
+
+    let entries = fs::read_dir(&claude_projects_dir)?;
+
+    let session_filename = format!("{}.jsonl", session_id);
+    let session_file = entries
+        .flatten()
+        .map(|entry| entry.path().join(&session_filename))
+        .find(|session_file| session_file.is_file())
+        .ok_or_else(not_found)?;
+    contained_in(&session_file, &claude_projects_dir)
+}
+
+/// Structure of sessions-index.json
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectIndex {
+    #[allow(dead_code)]
+    version: Option<u32>,
+    entries: Option<Vec<ProjectIndexEntry>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectIndexEntry {
+    session_id: String,
+    project_path: Option<String>,
+    #[allow(dead_code)]
+    full_path: Option<String>,
+    #[allow(dead_code)]
+    first_prompt: Option<String>,
+    #[allow(dead_code)]
+    summary: Option<String>,
+    #[allow(dead_code)]
+    message_count: Option<u32>,
+    #[allow(dead_code)]
+    git_branch: Option<String>,
+    #[allow(dead_code)]
+    modified: Option<String>,
+}
+
+/// Checks if a file was modified within the last N seconds
+fn is_file_recently_modified(path: &Path, seconds: u64) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(|modified| {
+            modified
+                .elapsed()
+                .map(|elapsed| elapsed.as_secs() < seconds)
+                .unwrap_or(false)
+        })
+        .unwrap_or(false)
+}
+
+/// Session fields derived from sessions-index.json and the transcript that
+/// only change when one of those files does
+#[derive(Debug, Clone)]
+struct EnrichedMetadata {
+    first_prompt: String,
+    summary: Option<String>,
+    message_count: u32,
+    modified: String,
+    git_branch: Option<String>,
+}
+
+struct CachedMetadata {
+    session_mtime: Option<SystemTime>,
+    index_mtime: Option<SystemTime>,
+    metadata: EnrichedMetadata,
+}
+
+/// Enrichment results keyed by session JSONL path, reused while neither the
+/// transcript nor the project's sessions-index.json has been modified
+static ENRICHMENT_CACHE: OnceLock<Mutex<HashMap<PathBuf, CachedMetadata>>> = OnceLock::new();
+
+fn enrichment_cache() -> &'static Mutex<HashMap<PathBuf, CachedMetadata>> {
+    ENRICHMENT_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn file_mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Get index/transcript metadata for a session, re-reading files only when
+/// their modification time has changed since the last poll
+fn get_session_metadata(
+    session_id: &str,
+    session_file_path: &Path,
+    index_path: &Path,
+) -> EnrichedMetadata {
+    let session_mtime = file_mtime(session_file_path);
+    let index_mtime = file_mtime(index_path);
+
+    {
+        let cache = enrichment_cache().lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cached) = cache.get(session_file_path) {
+            if cached.session_mtime == session_mtime && cached.index_mtime == index_mtime {
+                return cached.metadata.clone();
+            }
+        }
+    }
+
+    let metadata = read_session_metadata(session_id, session_file_path, index_path);
+    enrichment_cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(
+            session_file_path.to_path_buf(),
+            CachedMetadata {
+                session_mtime,
+                index_mtime,
+                metadata: metadata.clone(),
+            },
+        );
+    metadata
+}
+
+fn read_session_metadata(
+    session_id: &str,
+    session_file_path: &Path,
+    index_path: &Path,
+) -> EnrichedMetadata {
+    // Try to parse sessions-index.json to get basic info (optional)
+    let sessions_index = parse_sessions_index(index_path).ok();
+
+    // Find the matching entry in the index (if index exists)
+    let session_entry = sessions_index.as_ref().and_then(|index| {
+        index
+            .entries
+            .iter()
+            .find(|entry| entry.session_id == session_id)
+    });
+
+    let (first_prompt, summary, message_count, modified, git_branch) = match session_entry {
+        Some(entry) => (
+            entry.first_prompt.clone(),
+            entry
+                .summary
+                .clone()
+                .or_else(|| generate_title(session_file_path)),
+            entry.message_count,
+            entry.modified.clone(),
+            Some(entry.git_branch.clone()),
+        ),
+        None => {
+            // Session not in index or index doesn't exist - use fallback values
+
+            // Try to get first prompt from JSONL file
+            let first_prompt = get_first_prompt_from_jsonl(session_file_path)
+                .unwrap_or_else(|| "(Active session)".to_string());
+
+            // Count messages in the file
+            let message_count = count_messages_in_jsonl(session_file_path);
+
+            // Get file modification time
+            let modified = std::fs::metadata(session_file_path)
+                .and_then(|m| m.modified())
+                .ok()
+                .map(|t| {
+                    let datetime: DateTime<Utc> = t.into();
+                    datetime.to_rfc3339()
+                })
+                .unwrap_or_default();
+
+            // Without an index there's no summary either; make one up
+            let summary = generate_title(session_file_path);
+
+            (first_prompt, summary, message_count, modified, None)
+        }
+    };
+
+    EnrichedMetadata {
+        first_prompt,
+        summary,
+        message_count,
+        modified,
+        git_branch,
+    }
+}
+
+/// Extract the first user prompt from a session JSONL file
+fn get_first_prompt_from_jsonl(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    let reader = BufReader::new(file);
+
+    for line in reader.lines().take(50) {
+        if let Ok(line) = line {
+            if let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) {
+                // Check if this is a user message
+                if value.get("type").and_then(|t| t.as_str()) == Some("user") {
+                    // Try to get the message content
+                    if let Some(message) = value.get("message") {
+                        if let Some(content) = message.get("content") {
+                            // Content can be a string or array
+                            if let Some(text) = content.as_str() {
+                                return Some(truncate_string(text, MAX_PREVIEW_CHARS));
+                            } else if let Some(arr) = content.as_array() {
+                                // Find the first text block
+                                for item in arr {
+                                    if item.get("type").and_then(|t| t.as_str()) == Some("text") {
+                                        if let Some(text) =
+                                            item.get("text").and_then(|t| t.as_str())
+                                        {
+                                            return Some(truncate_string(text, MAX_PREVIEW_CHARS));
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Extract the latest message content from session entries
+fn get_latest_message_from_entries(entries: &[SessionEntry]) -> String {
+    if entries.is_empty() {
+        return String::new();
+    }
+
+    // Iterate backwards to find the last user or assistant message
+    for entry in entries.iter().rev() {
+        match entry {
+            SessionEntry::User { message, .. } => {
+                // Skip tool result entries - only show actual user prompts
+                if message.is_tool_result {
+                    continue;
+                }
+                return match message.kind() {
+                    UserContentKind::Prompt => truncate_string(&message.content, MAX_PREVIEW_CHARS),
+                    UserContentKind::SlashCommand { name, args } => {
+                        truncate_string(format!("{} {}", name, args).trim(), MAX_PREVIEW_CHARS)
+                    }
+                    UserContentKind::LocalCommandOutput(output) => {
+                        truncate_string(&output, MAX_PREVIEW_CHARS)
+                    }
+                };
+            }
+            SessionEntry::Assistant { message, .. } => {
+                // For assistant, try to find the last text block
+                for content in message.content.iter().rev() {
+                    match content {
+                        MessageContent::Text { text } => {
+                            return truncate_string(text, MAX_PREVIEW_CHARS);
+                        }
+                        MessageContent::Thinking { thinking, .. } => {
+                            return truncate_string(thinking, MAX_PREVIEW_CHARS);
+                        }
+                        MessageContent::ToolUse { name, .. } => {
+                            return format!("Executing {}...", name);
+                        }
+                        _ => continue,
+                    }
+                }
+            }
+            _ => continue,
+        }
+    }
+
+    String::new()
+}
+
+/// Default context window size for Claude models
+const CONTEXT_WINDOW_TOKENS: u64 = 200_000;
+
+/// Context window for sessions running with the extended 1M-token beta
+const EXTENDED_CONTEXT_WINDOW_TOKENS: u64 = 1_000_000;
+
+/// Estimate how full the context window is from the latest assistant message.
+///
+/// The prompt sent for that message (input + cache read + cache write tokens)
+/// is the live context size. Returns None if no usage data is available.
+fn get_context_percent_from_entries(entries: &[SessionEntry]) -> Option<u32> {
+    let context_tokens = entries.iter().rev().find_map(|entry| match entry {
+        SessionEntry::Assistant { message, .. } => {
+            let usage = message.usage.as_ref()?;
+            let tokens = usage.input_tokens.unwrap_or(0) as u64
+                + usage.cache_creation_input_tokens.unwrap_or(0) as u64
+                + usage.cache_read_input_tokens.unwrap_or(0) as u64;
+            // Synthetic messages (e.g. interruptions) report zero usage
+            (tokens > 0).then_some(tokens)
+        }
+        _ => None,
+    })?;
+
+    // The model ID doesn't say which window is in use, but a prompt larger
+    // than the default window can only mean the extended one
+    let window = if context_tokens > CONTEXT_WINDOW_TOKENS {
+        EXTENDED_CONTEXT_WINDOW_TOKENS
+    } else {
+        CONTEXT_WINDOW_TOKENS
+    };
+
+    Some(((context_tokens * 100) / window).min(100) as u32)
+}
+
+/// Model of the most recent assistant message, shortened for display
+fn get_model_from_entries(entries: &[SessionEntry]) -> Option<String> {
+    entries.iter().rev().find_map(|entry| match entry {
+        // Interruptions and API errors are written as "<synthetic>"
+        SessionEntry::Assistant { message, .. } if !message.model.starts_with('<') => {
+            Some(short_model_name(&message.model))
+        }
+        _ => None,
+    })
+}
+
+/// "claude-opus-4-5-20251101" -> "opus-4.5", "claude-3-5-haiku-20241022" ->
+/// "haiku-3.5". IDs that don't follow either naming scheme are kept as is.
+fn short_model_name(model: &str) -> String {
+    let Some(rest) = model.strip_prefix("claude-") else {
+        return model.to_string();
+    };
+    // Drop the date suffix
+    let parts: Vec<&str> = rest
+        .split('-')
+        .filter(|part| !(part.len() == 8 && part.chars().all(|c| c.is_ascii_digit())))
+        .collect();
+
+    let is_version = |part: &&str| part.chars().all(|c| c.is_ascii_digit());
+    let family: Vec<&str> = parts.iter().copied().filter(|p| !is_version(p)).collect();
+    let version: Vec<&str> = parts.iter().copied().filter(is_version).collect();
+    match (family.as_slice(), version.is_empty()) {
+        ([family], false) => format!("{}-{}", family, version.join(".")),
+        _ => model.to_string(),
+    }
+}
+
+/// Count user/assistant messages in a JSONL file
+fn count_messages_in_jsonl(path: &Path) -> u32 {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(_) => return 0,
+    };
+    let reader = BufReader::new(file);
+    let mut count = 0u32;
+
+    for line in reader.lines().flatten() {
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) {
+            if let Some(msg_type) = value.get("type").and_then(|t| t.as_str()) {
+                if msg_type == "user" || msg_type == "assistant" {
+                    count += 1;
+                }
+            }
+        }
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_transcript_rejects_traversal() {
+        for id in ["../../../etc/passwd", "..", "a/../../b", "x.jsonl\0"] {
+            assert!(matches!(find_transcript(id), Err(C9Error::ParseError(_))));
+        }
+        // Well-formed but not a Claude session ID
+        assert!(matches!(
+            find_transcript("aider-3f2a9c01b7d4e8f6a5"),
+            Err(C9Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_enumerate_project_directories() {
+        let provider = ClaudeProvider::new().unwrap();
+        let result = provider.enumerate_project_directories();
+        assert!(result.is_ok());
+
+        if let Ok(dirs) = result {
+            println!("Found {} project directories", dirs.len());
+        }
+    }
+
+    #[test]
+    fn test_matches_claude_processes_only() {
+        let provider = ClaudeProvider::new().unwrap();
+        let process = |name: &str, cmd: &[&str]| AgentProcess {
+            pid: 1,
+            name: name.to_string(),
+            cmd: cmd.iter().map(|arg| arg.to_string()).collect(),
+            cwd: None,
+            start_time: 0,
+            usage: Default::default(),
+        };
+        assert!(provider.matches_process(&process("claude", &[])));
+        assert!(!provider.matches_process(&process("c9watch-claude", &[])));
+        assert!(!provider.matches_process(&process("node", &[])));
+        assert!(provider.matches_process(&process(
+            "node",
+            &[
+                "node",
+                "/usr/local/lib/node_modules/@anthropic-ai/claude-code/cli.js",
+                "--resume",
+            ],
+        )));
+        assert!(provider.matches_process(&process(
+            "node",
+            &["node", "/home/me/.npm/_npx/1a2b/node_modules/.bin/claude"],
+        )));
+        // Other node tools started in a directory named after claude
+        assert!(!provider.matches_process(&process(
+            "node",
+            &["node", "/home/me/claude-tools/server.js"],
+        )));
+        assert!(!provider.matches_process(&process("node", &["claude"])));
+    }
+
+    fn assistant_entry(input: u32, cache_read: u32) -> SessionEntry {
+        serde_json::from_value(serde_json::json!({
+            "type": "assistant",
+            "uuid": "a1",
+            "timestamp": "2026-02-10T10:00:00Z",
+            "message": {
+                "model": "claude-sonnet-4-5",
+                "id": "msg_1",
+                "role": "assistant",
+                "content": [],
+                "usage": {
+                    "input_tokens": input,
+                    "output_tokens": 10,
+                    "cache_read_input_tokens": cache_read
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_context_percent_uses_latest_assistant_usage() {
+        let entries = vec![
+            assistant_entry(1_000, 9_000),
+            assistant_entry(20_000, 80_000),
+        ];
+        assert_eq!(get_context_percent_from_entries(&entries), Some(50));
+    }
+
+    #[test]
+    fn test_context_percent_extended_window_and_missing_usage() {
+        assert_eq!(get_context_percent_from_entries(&[]), None);
+        let entries = vec![assistant_entry(0, 500_000)];
+        assert_eq!(get_context_percent_from_entries(&entries), Some(50));
+    }
+
+    #[test]
+    fn test_short_model_name() {
+        assert_eq!(short_model_name("claude-opus-4-5-20251101"), "opus-4.5");
+        assert_eq!(short_model_name("claude-sonnet-4-20250514"), "sonnet-4");
+        assert_eq!(short_model_name("claude-3-5-haiku-20241022"), "haiku-3.5");
+        assert_eq!(short_model_name("claude-sonnet-4-5"), "sonnet-4.5");
+        assert_eq!(short_model_name("gpt-5"), "gpt-5");
+
+        let entries = vec![assistant_entry(1_000, 0)];
+        assert_eq!(
+            get_model_from_entries(&entries).as_deref(),
+            Some("sonnet-4.5")
+        );
+    }
+}
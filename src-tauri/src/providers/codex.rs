@@ -0,0 +1,740 @@
+use super::{mtime_secs, AgentProvider, SessionSnapshot, RECENT_ACTIVITY_SECS};
+use crate::session::parser::{read_last_n_lines, truncate_string, MAX_PREVIEW_CHARS};
+use crate::session::{
+    AgentProcess, ConversationMessage, DetectedSession, MessageType, PermissionMode,
+    SessionDetectorError, SessionStatus, ToolCall, ToolResultInfo,
+};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tracing::warn;
+
+/// Rollout lines read from the end of the file to work out the current status
+const TAIL_LINES: usize = 200;
+
+/// OpenAI Codex CLI: `codex` processes whose rollout files live in
+/// ~/.codex/sessions/YYYY/MM/DD/rollout-*.jsonl
+pub struct CodexProvider {
+    sessions_dir: PathBuf,
+    /// The `session_meta` line of each rollout; it never changes once written
+    metas: Mutex<HashMap<PathBuf, RolloutMeta>>,
+    /// Whole-file metadata, re-read only when the rollout's mtime changes
+    summaries: Mutex<HashMap<PathBuf, (Option<SystemTime>, RolloutSummary)>>,
+}
+
+#[derive(Debug, Clone)]
+struct RolloutMeta {
+    id: String,
+    cwd: PathBuf,
+    git_branch: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct RolloutSummary {
+    first_prompt: Option<String>,
+    message_count: u32,
+}
+
+impl CodexProvider {
+    pub fn new() -> Result<Self, SessionDetectorError> {
+        let home_dir = dirs::home_dir().ok_or(SessionDetectorError::HomeDirectoryNotFound)?;
+
+        Ok(Self {
+            sessions_dir: home_dir.join(".codex").join("sessions"),
+            metas: Mutex::new(HashMap::new()),
+            summaries: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Rollout files in the day directories from `since` onwards. Directories
+    /// are named by local date, so a day of slack covers timezone edges.
+    fn rollout_files(&self, since: NaiveDate) -> Result<Vec<PathBuf>, SessionDetectorError> {
+        let mut files = Vec::new();
+        if !self.sessions_dir.exists() {
+            return Ok(files);
+        }
+
+        let pattern = format!("{}/*/*/*", self.sessions_dir.display());
+        let day_dirs = glob::glob(&pattern)
+            .map(|paths| paths.flatten().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        for day_dir in day_dirs {
+            let date = day_dir
+                .strip_prefix(&self.sessions_dir)
+                .ok()
+                .map(|rel| {
+                    rel.components()
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/")
+                })
+                .and_then(|rel| NaiveDate::parse_from_str(&rel, "%Y/%m/%d").ok());
+            if !date.map_or(false, |d| d >= since.pred_opt().unwrap_or(since)) {
+                continue;
+            }
+
+            for entry in fs::read_dir(&day_dir)?.flatten() {
+                let path = entry.path();
+                let is_rollout = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .map_or(false, |n| {
+                        n.starts_with("rollout-") && n.ends_with(".jsonl")
+                    });
+                if is_rollout {
+                    files.push(path);
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn meta(&self, path: &Path) -> Option<RolloutMeta> {
+        let mut metas = self.metas.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(meta) = metas.get(path) {
+            return Some(meta.clone());
+        }
+        let meta = read_rollout_meta(path)?;
+        metas.insert(path.to_path_buf(), meta.clone());
+        Some(meta)
+    }
+
+    fn summary(&self, path: &Path) -> RolloutSummary {
+        let mtime = fs::metadata(path).and_then(|m| m.modified()).ok();
+        let mut summaries = self.summaries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached_mtime, summary)) = summaries.get(path) {
+            if *cached_mtime == mtime {
+                return summary.clone();
+            }
+        }
+        let summary = read_rollout_summary(path);
+        summaries.insert(path.to_path_buf(), (mtime, summary.clone()));
+        summary
+    }
+}
+
+impl AgentProvider for CodexProvider {
+    fn id(&self) -> &'static str {
+        "codex"
+    }
+
+    fn matches_process(&self, process: &AgentProcess) -> bool {
+        // The npm package runs a platform binary such as codex-aarch64-apple-darwin
+        process.name == "codex" || process.name.starts_with("codex-")
+    }
+
+    fn locate_sessions(
+        &self,
+        processes: &[&AgentProcess],
+    ) -> Result<Vec<DetectedSession>, SessionDetectorError> {
+        let earliest_start = processes.iter().map(|p| p.start_time).min().unwrap_or(0);
+        let since = DateTime::<Utc>::from_timestamp(earliest_start as i64, 0)
+            .map(|t| t.with_timezone(&Local).date_naive())
+            .unwrap_or(NaiveDate::MIN);
+
+        // Only rollouts written since the oldest codex process started can
+        // belong to a running one (with the same 5s buffer as Claude)
+        let mut rollouts: Vec<(u64, PathBuf, RolloutMeta)> = Vec::new();
+        for path in self.rollout_files(since)? {
+            let modified = mtime_secs(&path);
+            if modified + 5 < earliest_start {
+                continue;
+            }
+            if let Some(meta) = self.meta(&path) {
+                rollouts.push((modified, path, meta));
+            }
+        }
+        rollouts.sort_by(|a, b| b.0.cmp(&a.0));
+
+        // Match newest processes first to the most recently written rollout
+        // in their working directory
+        let mut sorted_processes: Vec<&AgentProcess> = processes.to_vec();
+        sorted_processes.sort_by(|a, b| b.start_time.cmp(&a.start_time));
+
+        let mut used: HashSet<PathBuf> = HashSet::new();
+        let mut sessions = Vec::new();
+        for proc in sorted_processes {
+            let Some(proc_cwd) = &proc.cwd else {
+                continue;
+            };
+
+            let matching = rollouts.iter().find(|(modified, path, meta)| {
+                !used.contains(path) && modified + 5 >= proc.start_time && &meta.cwd == proc_cwd
+            });
+
+            if let Some((_, path, meta)) = matching {
+                used.insert(path.clone());
+                sessions.push(DetectedSession {
+                    agent: self.id().to_string(),
+                    pid: proc.pid,
+                    cwd: proc_cwd.clone(),
+                    project_path: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+                    transcript_path: path.clone(),
+                    session_id: Some(meta.id.clone()),
+                    project_name: proc_cwd
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("unknown")
+                        .to_string(),
+                    start_time: proc.start_time,
+                });
+            }
+        }
+
+        Ok(sessions)
+    }
+
+    fn read_session(&self, detected: &DetectedSession) -> SessionSnapshot {
+        let path = &detected.transcript_path;
+        let summary = self.summary(path);
+        let git_branch = self.meta(path).and_then(|meta| meta.git_branch);
+
+        let items: Vec<Value> = match read_last_n_lines(path, TAIL_LINES) {
+            Ok(lines) => lines
+                .iter()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect(),
+            Err(e) => {
+                warn!("Failed to read Codex rollout {}: {}", path.display(), e);
+                Vec::new()
+            }
+        };
+
+        let quiet_secs = (Utc::now().timestamp() as u64).saturating_sub(mtime_secs(path));
+        let (status, pending_call) = determine_status(&items, quiet_secs);
+
+        let modified = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .map(|t| DateTime::<Utc>::from(t).to_rfc3339())
+            .unwrap_or_default();
+
+        SessionSnapshot {
+            first_prompt: summary
+                .first_prompt
+                .unwrap_or_else(|| "(Active session)".to_string()),
+            summary: None,
+            message_count: summary.message_count,
+            modified,
+            git_branch,
+            status,
+            latest_message: latest_message(&items),
+            pending_tool_name: pending_call.map(tool_name),
+            pending_tool_input: pending_call.and_then(tool_input),
+            context_percent: context_percent(&items),
+            permission_mode: permission_mode(&items),
+            model: items
+                .iter()
+                .rev()
+                .find(|item| item["type"].as_str() == Some("turn_context"))
+                .and_then(|item| item["payload"]["model"].as_str())
+                .map(str::to_string),
+            mcp_servers: Vec::new(),
+        }
+    }
+
+    fn conversation(&self, detected: &DetectedSession) -> Result<Vec<ConversationMessage>, String> {
+        let file = File::open(&detected.transcript_path).map_err(|e| e.to_string())?;
+        let mut tool_names: HashMap<String, String> = HashMap::new();
+        let mut conversation = Vec::new();
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            if let Ok(item) = serde_json::from_str::<Value>(&line) {
+                conversation.extend(conversation_message(&item, &mut tool_names));
+            }
+        }
+        Ok(conversation)
+    }
+
+    fn retain(&self, watched: &HashSet<PathBuf>) {
+        self.metas
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|path, _| watched.contains(path));
+        self.summaries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|path, _| watched.contains(path));
+    }
+}
+
+/// The `type` of a rollout line and the `type` of its payload
+fn item_types(item: &Value) -> (Option<&str>, Option<&str>) {
+    (item["type"].as_str(), item["payload"]["type"].as_str())
+}
+
+/// Read the `session_meta` line that starts every rollout
+fn read_rollout_meta(path: &Path) -> Option<RolloutMeta> {
+    let file = File::open(path).ok()?;
+    let mut first_line = String::new();
+    BufReader::new(file).read_line(&mut first_line).ok()?;
+
+    let item: Value = serde_json::from_str(&first_line).ok()?;
+    if item["type"].as_str() != Some("session_meta") {
+        return None;
+    }
+    let payload = &item["payload"];
+    Some(RolloutMeta {
+        id: payload["id"].as_str()?.to_string(),
+        cwd: PathBuf::from(payload["cwd"].as_str()?),
+        git_branch: payload["git"]["branch"].as_str().map(|b| b.to_string()),
+    })
+}
+
+/// Text of a `message` payload's input/output text blocks
+fn message_text(payload: &Value) -> String {
+    payload["content"]
+        .as_array()
+        .map(|blocks| {
+            blocks
+                .iter()
+                .filter_map(|block| block["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+/// How the user messages Codex writes itself begin: its environment and the
+/// AGENTS.md instructions (tagged in older versions, a heading in newer ones)
+const INJECTED_CONTEXT_PREFIXES: &[&str] = &[
+    "<environment_context>",
+    "<user_instructions>",
+    "# AGENTS.md instructions for ",
+];
+
+/// Whether a user message was injected by Codex rather than typed; a prompt
+/// that merely starts with some other markup is still the user's
+fn is_injected_context(text: &str) -> bool {
+    let text = text.trim_start();
+    INJECTED_CONTEXT_PREFIXES
+        .iter()
+        .any(|prefix| text.starts_with(prefix))
+}
+
+fn read_rollout_summary(path: &Path) -> RolloutSummary {
+    let mut summary = RolloutSummary::default();
+    let Ok(file) = File::open(path) else {
+        return summary;
+    };
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(item) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if item_types(&item) != (Some("response_item"), Some("message")) {
+            continue;
+        }
+        let payload = &item["payload"];
+        match payload["role"].as_str() {
+            Some("user") => {
+                let text = message_text(payload);
+                if is_injected_context(&text) {
+                    continue;
+                }
+                if summary.first_prompt.is_none() {
+                    summary.first_prompt = Some(truncate_string(text.trim(), MAX_PREVIEW_CHARS));
+                }
+                summary.message_count += 1;
+            }
+            Some("assistant") => summary.message_count += 1,
+            _ => {}
+        }
+    }
+
+    summary
+}
+
+fn tool_name(payload: &Value) -> String {
+    match payload["type"].as_str() {
+        Some("local_shell_call" | "exec_approval_request") => "shell".to_string(),
+        Some("apply_patch_approval_request") => "apply_patch".to_string(),
+        _ => payload["name"].as_str().unwrap_or("tool").to_string(),
+    }
+}
+
+/// The command or patch a tool call wants to run
+fn tool_input(payload: &Value) -> Option<String> {
+    let input = match payload["type"].as_str() {
+        Some("local_shell_call") => shell_command(&payload["action"]["command"]),
+        Some("exec_approval_request") => shell_command(&payload["command"]),
+        Some("apply_patch_approval_request") => {
+            let files: Vec<&str> = payload["changes"]
+                .as_object()?
+                .keys()
+                .map(String::as_str)
+                .collect();
+            Some(files.join(", "))
+        }
+        // apply_patch and other freeform tools
+        Some("custom_tool_call") => payload["input"].as_str().map(str::to_string),
+        _ => {
+            let arguments: Value = serde_json::from_str(payload["arguments"].as_str()?).ok()?;
+            shell_command(&arguments["command"]).or_else(|| {
+                arguments
+                    .as_object()
+                    .filter(|args| !args.is_empty())
+                    .map(|_| arguments.to_string())
+            })
+        }
+    }?;
+    Some(truncate_string(&input, 200))
+}
+
+/// A shell call's argv as one line, without the `bash -lc` wrapper
+fn shell_command(argv: &Value) -> Option<String> {
+    let argv: Vec<&str> = argv.as_array()?.iter().filter_map(|a| a.as_str()).collect();
+    match argv.as_slice() {
+        [shell, flag, script] if shell.ends_with("sh") && flag.starts_with('-') => {
+            Some(script.to_string())
+        }
+        [] => None,
+        args => Some(args.join(" ")),
+    }
+}
+
+/// The latest turn's approval policy in Claude's terms: "never" asks for
+/// nothing, anything else may ask
+fn permission_mode(items: &[Value]) -> Option<PermissionMode> {
+    let policy = items
+        .iter()
+        .rev()
+        .find(|item| item["type"].as_str() == Some("turn_context"))?["payload"]["approval_policy"]
+        .as_str()?;
+    Some(if policy == "never" {
+        PermissionMode::BypassPermissions
+    } else {
+        PermissionMode::Default
+    })
+}
+
+/// Work out the session status from the most recent rollout lines, returning
+/// the approval request being waited on when there is one. `quiet_secs` is
+/// how long ago the rollout was last written.
+///
+/// Only an approval request recorded in the rollout counts as waiting for
+/// permission; a tool call that is merely slow is still working.
+fn determine_status(items: &[Value], quiet_secs: u64) -> (SessionStatus, Option<&Value>) {
+    // Calls that have started running or finished, seen walking backwards
+    let mut answered_calls: HashSet<&str> = HashSet::new();
+    for item in items.iter().rev() {
+        let payload = &item["payload"];
+        match item_types(item) {
+            (Some("event_msg"), Some("task_complete" | "turn_aborted")) => {
+                return (SessionStatus::WaitingForInput, None);
+            }
+            (Some("event_msg"), Some("task_started" | "user_message")) => {
+                return (SessionStatus::Working, None);
+            }
+            (Some("event_msg"), Some("exec_approval_request" | "apply_patch_approval_request")) => {
+                let answered = payload["call_id"]
+                    .as_str()
+                    .map_or(false, |id| answered_calls.contains(id));
+                if answered {
+                    return (SessionStatus::Working, None);
+                }
+                return (SessionStatus::NeedsPermission, Some(payload));
+            }
+            (
+                Some("event_msg"),
+                Some(
+                    "exec_command_begin" | "exec_command_end" | "patch_apply_begin"
+                    | "patch_apply_end",
+                ),
+            )
+            | (Some("response_item"), Some("function_call_output" | "custom_tool_call_output")) => {
+                if let Some(call_id) = payload["call_id"].as_str() {
+                    answered_calls.insert(call_id);
+                }
+            }
+            (
+                Some("response_item"),
+                Some("function_call" | "custom_tool_call" | "local_shell_call"),
+            ) => {
+                return (SessionStatus::Working, None);
+            }
+            (Some("response_item"), Some("message")) => match payload["role"].as_str() {
+                Some("assistant") if quiet_secs >= RECENT_ACTIVITY_SECS => {
+                    return (SessionStatus::WaitingForInput, None);
+                }
+                Some("assistant") | Some("user") => return (SessionStatus::Working, None),
+                _ => continue,
+            },
+            _ => continue,
+        }
+    }
+
+    (SessionStatus::Connecting, None)
+}
+
+/// A rollout line as a viewer message, if it is part of the conversation.
+/// `tool_names` maps call ids to tool names so results can name their tool.
+fn conversation_message(
+    item: &Value,
+    tool_names: &mut HashMap<String, String>,
+) -> Option<ConversationMessage> {
+    if item["type"].as_str() != Some("response_item") {
+        return None;
+    }
+    let timestamp = item["timestamp"].as_str().unwrap_or_default().to_string();
+    let payload = &item["payload"];
+    let call_id = payload["call_id"].as_str().unwrap_or_default();
+
+    match payload["type"].as_str()? {
+        "message" => {
+            let kind = match payload["role"].as_str()? {
+                "user" => MessageType::User,
+                "assistant" => MessageType::Assistant,
+                _ => return None,
+            };
+            let text = message_text(payload);
+            if text.trim().is_empty() || is_injected_context(&text) {
+                return None;
+            }
+            Some(ConversationMessage::new(timestamp, kind, text))
+        }
+        "reasoning" => {
+            let summary: Vec<&str> = payload["summary"]
+                .as_array()?
+                .iter()
+                .filter_map(|part| part["text"].as_str())
+                .collect();
+            (!summary.is_empty()).then(|| {
+                ConversationMessage::new(timestamp, MessageType::Thinking, summary.join("\n\n"))
+            })
+        }
+        "function_call" | "custom_tool_call" | "local_shell_call" => {
+            let name = tool_name(payload);
+            let input = match payload["type"].as_str() {
+                Some("function_call") => payload["arguments"]
+                    .as_str()
+                    .and_then(|arguments| serde_json::from_str(arguments).ok())
+                    .unwrap_or(Value::Null),
+                Some("custom_tool_call") => payload["input"].clone(),
+                _ => payload["action"].clone(),
+            };
+            tool_names.insert(call_id.to_string(), name.clone());
+            let content = match tool_input(payload) {
+                Some(summary) => format!("{}: {}", name, summary),
+                None => name.clone(),
+            };
+            Some(ConversationMessage {
+                tool_call: Some(ToolCall {
+                    id: call_id.to_string(),
+                    name,
+                    input,
+                }),
+                ..ConversationMessage::new(timestamp, MessageType::ToolUse, content)
+            })
+        }
+        "function_call_output" | "custom_tool_call_output" => {
+            // A plain string, or an object with `content` and `success`
+            let output = &payload["output"];
+            let (content, is_error) = match output.as_str() {
+                Some(text) => (text.to_string(), false),
+                None => (
+                    output["content"].as_str().unwrap_or_default().to_string(),
+                    output["success"].as_bool() == Some(false),
+                ),
+            };
+            Some(ConversationMessage {
+                tool_result: Some(ToolResultInfo {
+                    tool_use_id: call_id.to_string(),
+                    tool_name: tool_names.get(call_id).cloned(),
+                    is_error,
+                }),
+                ..ConversationMessage::new(timestamp, MessageType::ToolResult, content)
+            })
+        }
+        _ => None,
+    }
+}
+
+fn latest_message(items: &[Value]) -> String {
+    for item in items.iter().rev() {
+        let payload = &item["payload"];
+        match item_types(item) {
+            (Some("response_item"), Some("message")) => {
+                let text = message_text(payload);
+                if text.trim().is_empty() || is_injected_context(&text) {
+                    continue;
+                }
+                return truncate_string(text.trim(), MAX_PREVIEW_CHARS);
+            }
+            (
+                Some("response_item"),
+                Some("function_call" | "custom_tool_call" | "local_shell_call"),
+            ) => {
+                return format!("Executing {}...", tool_name(payload));
+            }
+            _ => continue,
+        }
+    }
+
+    String::new()
+}
+
+/// Share of the model's context window used by the latest request, from the
+/// most recent `token_count` event
+fn context_percent(items: &[Value]) -> Option<u32> {
+    let info = items.iter().rev().find_map(|item| {
+        let info = &item["payload"]["info"];
+        (item_types(item) == (Some("event_msg"), Some("token_count")) && info.is_object())
+            .then_some(info)
+    })?;
+
+    let tokens = info["last_token_usage"]["input_tokens"].as_u64()?;
+    let window = info["model_context_window"].as_u64().filter(|w| *w > 0)?;
+    Some(((tokens * 100) / window).min(100) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn item(kind: &str, payload: Value) -> Value {
+        json!({ "timestamp": "2026-02-10T10:00:00Z", "type": kind, "payload": payload })
+    }
+
+    fn user(text: &str) -> Value {
+        item(
+            "response_item",
+            json!({ "type": "message", "role": "user", "content": [{ "type": "input_text", "text": text }] }),
+        )
+    }
+
+    fn call(id: &str) -> Value {
+        item(
+            "response_item",
+            json!({
+                "type": "function_call",
+                "name": "shell",
+                "arguments": r#"{"command":["bash","-lc","cargo test"]}"#,
+                "call_id": id
+            }),
+        )
+    }
+
+    #[test]
+    fn test_status_from_turn_events() {
+        let done = vec![
+            user("fix the build"),
+            item("event_msg", json!({ "type": "task_complete" })),
+        ];
+        assert_eq!(
+            determine_status(&done, 60).0,
+            SessionStatus::WaitingForInput
+        );
+
+        let started = vec![
+            user("fix the build"),
+            item("event_msg", json!({ "type": "task_started" })),
+        ];
+        assert_eq!(determine_status(&started, 60).0, SessionStatus::Working);
+
+        assert_eq!(determine_status(&[], 60).0, SessionStatus::Connecting);
+    }
+
+    #[test]
+    fn test_only_recorded_approval_requests_need_permission() {
+        // A long-running call with nothing recorded is still working
+        let pending = vec![user("run tests"), call("c1")];
+        assert_eq!(determine_status(&pending, 600).0, SessionStatus::Working);
+
+        let mut asked = pending.clone();
+        asked.push(item(
+            "event_msg",
+            json!({
+                "type": "exec_approval_request",
+                "call_id": "c1",
+                "command": ["bash", "-lc", "cargo test"]
+            }),
+        ));
+        let (status, request) = determine_status(&asked, 2);
+        assert_eq!(status, SessionStatus::NeedsPermission);
+        assert_eq!(request.map(tool_name).as_deref(), Some("shell"));
+        assert_eq!(request.and_then(tool_input).as_deref(), Some("cargo test"));
+
+        let approved = [
+            asked.clone(),
+            vec![item(
+                "event_msg",
+                json!({ "type": "exec_command_begin", "call_id": "c1" }),
+            )],
+        ]
+        .concat();
+        assert_eq!(determine_status(&approved, 30).0, SessionStatus::Working);
+    }
+
+    #[test]
+    fn test_conversation_messages() {
+        let items = vec![
+            user("<environment_context>cwd</environment_context>"),
+            user("run tests"),
+            call("c1"),
+            item(
+                "response_item",
+                json!({ "type": "function_call_output", "call_id": "c1", "output": "ok" }),
+            ),
+        ];
+        let mut tool_names = HashMap::new();
+        let conversation: Vec<ConversationMessage> = items
+            .iter()
+            .filter_map(|item| conversation_message(item, &mut tool_names))
+            .collect();
+        let kinds: Vec<&MessageType> = conversation.iter().map(|m| &m.message_type).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &MessageType::User,
+                &MessageType::ToolUse,
+                &MessageType::ToolResult
+            ]
+        );
+        assert_eq!(conversation[1].content, "shell: cargo test");
+        assert_eq!(
+            conversation[2]
+                .tool_result
+                .as_ref()
+                .unwrap()
+                .tool_name
+                .as_deref(),
+            Some("shell")
+        );
+    }
+
+    #[test]
+    fn test_injected_context() {
+        assert!(is_injected_context(
+            "<environment_context>cwd</environment_context>"
+        ));
+        assert!(is_injected_context(
+            "# AGENTS.md instructions for /tmp/project\n\n<INSTRUCTIONS>"
+        ));
+        assert!(!is_injected_context("<div> is misaligned, fix the CSS"));
+    }
+
+    #[test]
+    fn test_latest_message_and_context() {
+        let items = vec![
+            user("<environment_context>cwd</environment_context>"),
+            user("add a flag"),
+            item(
+                "event_msg",
+                json!({ "type": "token_count", "info": {
+                    "last_token_usage": { "input_tokens": 68_000, "output_tokens": 500 },
+                    "model_context_window": 272_000
+                } }),
+            ),
+        ];
+        assert_eq!(latest_message(&items), "add a flag");
+        assert_eq!(context_percent(&items), Some(25));
+    }
+}
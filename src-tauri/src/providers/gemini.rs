@@ -0,0 +1,495 @@
+use super::{mtime_secs, AgentProvider, SessionSnapshot, RECENT_ACTIVITY_SECS};
+use crate::session::parser::{truncate_string, MAX_PREVIEW_CHARS};
+use crate::session::{
+    AgentProcess, ConversationMessage, DetectedSession, MessageType, SessionDetectorError,
+    SessionStatus, ToolCall, ToolResultInfo,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tracing::warn;
+
+/// Gemini 2.5 models' context window
+const CONTEXT_WINDOW_TOKENS: u64 = 1_048_576;
+
+/// Google Gemini CLI: `gemini` processes (usually a node script) whose chat
+/// recordings live in ~/.gemini/tmp/<sha256 of project root>/chats/
+pub struct GeminiProvider {
+    tmp_dir: PathBuf,
+    /// Parsed chat files, re-read only when their mtime changes
+    chats: Mutex<HashMap<PathBuf, (Option<SystemTime>, Arc<ChatRecord>)>>,
+}
+
+/// A chat recording (session-*.json)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatRecord {
+    session_id: String,
+    #[serde(default)]
+    messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatMessage {
+    /// RFC 3339
+    #[serde(default)]
+    timestamp: String,
+    /// "user", "gemini", "info" or "error"
+    #[serde(rename = "type")]
+    kind: String,
+    /// A string or a list of parts with `text`
+    #[serde(default)]
+    content: Value,
+    #[serde(default)]
+    tool_calls: Vec<ChatToolCall>,
+    tokens: Option<TokenCounts>,
+    /// On gemini replies
+    model: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatToolCall {
+    #[serde(default)]
+    id: String,
+    name: String,
+    display_name: Option<String>,
+    #[serde(default)]
+    args: Value,
+    /// "success", "error", "cancelled", or an in-progress state
+    #[serde(default)]
+    status: String,
+    /// What the CLI showed for the result; usually text, or an object for
+    /// file diffs
+    #[serde(default)]
+    result_display: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenCounts {
+    #[serde(default)]
+    input: u64,
+}
+
+impl GeminiProvider {
+    pub fn new() -> Result<Self, SessionDetectorError> {
+        let home_dir = dirs::home_dir().ok_or(SessionDetectorError::HomeDirectoryNotFound)?;
+
+        Ok(Self {
+            tmp_dir: home_dir.join(".gemini").join("tmp"),
+            chats: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Chat files for a project, keyed the way Gemini CLI names its
+    /// per-project directories
+    fn chat_files(&self, project_root: &Path) -> Vec<PathBuf> {
+        let hash = format!(
+            "{:x}",
+            Sha256::digest(project_root.to_string_lossy().as_bytes())
+        );
+        let chats_dir = self.tmp_dir.join(hash).join("chats");
+
+        fs::read_dir(chats_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        path.file_name()
+                            .and_then(|n| n.to_str())
+                            .map_or(false, |n| n.starts_with("session-") && n.ends_with(".json"))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn chat(&self, path: &Path) -> Option<Arc<ChatRecord>> {
+        let mtime = fs::metadata(path).and_then(|m| m.modified()).ok();
+        let mut chats = self.chats.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached_mtime, record)) = chats.get(path) {
+            if *cached_mtime == mtime {
+                return Some(Arc::clone(record));
+            }
+        }
+
+        let record = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<ChatRecord>(&content).map_err(|e| e.to_string())
+            });
+        match record {
+            Ok(record) => {
+                let record = Arc::new(record);
+                chats.insert(path.to_path_buf(), (mtime, Arc::clone(&record)));
+                Some(record)
+            }
+            Err(e) => {
+                // The CLI rewrites the whole file on each message, so a read can
+                // land mid-write; keep the last good copy if there is one
+                warn!("Failed to parse Gemini chat {}: {}", path.display(), e);
+                chats.get(path).map(|(_, record)| Arc::clone(record))
+            }
+        }
+    }
+}
+
+impl AgentProvider for GeminiProvider {
+    fn id(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn matches_process(&self, process: &AgentProcess) -> bool {
+        if process.name == "gemini" {
+            return true;
+        }
+        // Installed from npm it runs as `node .../bin/gemini` or
+        // `node .../@google/gemini-cli/...`
+        process.name.starts_with("node")
+            && process.cmd.iter().skip(1).any(|arg| {
+                arg.contains("gemini-cli")
+                    || Path::new(arg).file_name().and_then(|n| n.to_str()) == Some("gemini")
+            })
+    }
+
+    fn locate_sessions(
+        &self,
+        processes: &[&AgentProcess],
+    ) -> Result<Vec<DetectedSession>, SessionDetectorError> {
+        // Newest processes first, each taking the most recently written chat
+        // for its project that was touched after it started
+        let mut sorted_processes: Vec<&AgentProcess> = processes.to_vec();
+        sorted_processes.sort_by(|a, b| b.start_time.cmp(&a.start_time));
+
+        let mut used: HashSet<PathBuf> = HashSet::new();
+        let mut sessions = Vec::new();
+        for proc in sorted_processes {
+            let Some(proc_cwd) = &proc.cwd else {
+                continue;
+            };
+
+            let mut candidates: Vec<(u64, PathBuf)> = self
+                .chat_files(proc_cwd)
+                .into_iter()
+                .filter(|path| !used.contains(path))
+                .map(|path| (mtime_secs(&path), path))
+                .filter(|(modified, _)| modified + 5 >= proc.start_time)
+                .collect();
+            candidates.sort_by(|a, b| b.0.cmp(&a.0));
+
+            let Some((_, path)) = candidates.into_iter().next() else {
+                continue;
+            };
+            let Some(record) = self.chat(&path) else {
+                continue;
+            };
+
+            used.insert(path.clone());
+            sessions.push(DetectedSession {
+                agent: self.id().to_string(),
+                pid: proc.pid,
+                cwd: proc_cwd.clone(),
+                project_path: path
+                    .parent()
+                    .and_then(Path::parent)
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default(),
+                transcript_path: path,
+                session_id: Some(record.session_id.clone()),
+                project_name: proc_cwd
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("unknown")
+                    .to_string(),
+                start_time: proc.start_time,
+            });
+        }
+
+        Ok(sessions)
+    }
+
+    fn read_session(&self, detected: &DetectedSession) -> SessionSnapshot {
+        let path = &detected.transcript_path;
+        let record = self.chat(path);
+        let messages: &[ChatMessage] = record.as_ref().map_or(&[], |r| r.messages.as_slice());
+        let quiet_secs = (Utc::now().timestamp() as u64).saturating_sub(mtime_secs(path));
+        let (status, pending_tool_name) = determine_status(messages, quiet_secs);
+
+        let first_prompt = messages
+            .iter()
+            .find(|m| m.kind == "user")
+            .map(|m| truncate_string(message_text(m).trim(), MAX_PREVIEW_CHARS))
+            .unwrap_or_else(|| "(Active session)".to_string());
+        let message_count = messages
+            .iter()
+            .filter(|m| m.kind == "user" || m.kind == "gemini")
+            .count() as u32;
+        let modified = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .map(|t| DateTime::<Utc>::from(t).to_rfc3339())
+            .unwrap_or_default();
+
+        SessionSnapshot {
+            first_prompt,
+            summary: None,
+            message_count,
+            modified,
+            git_branch: None,
+            status,
+            latest_message: latest_message(messages),
+            pending_tool_name,
+            pending_tool_input: None,
+            context_percent: context_percent(messages),
+            permission_mode: None,
+            model: messages.iter().rev().find_map(|m| m.model.clone()),
+            mcp_servers: Vec::new(),
+        }
+    }
+
+    fn conversation(&self, detected: &DetectedSession) -> Result<Vec<ConversationMessage>, String> {
+        let record = self
+            .chat(&detected.transcript_path)
+            .ok_or_else(|| "Couldn't read the Gemini chat recording".to_string())?;
+        Ok(record
+            .messages
+            .iter()
+            .flat_map(conversation_messages)
+            .collect())
+    }
+
+    fn retain(&self, watched: &HashSet<PathBuf>) {
+        self.chats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|path, _| watched.contains(path));
+    }
+}
+
+fn message_text(message: &ChatMessage) -> String {
+    match &message.content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn is_finished(status: &str) -> bool {
+    matches!(status, "success" | "error" | "cancelled")
+}
+
+/// A recorded message as viewer messages: its text, then each tool call
+/// followed by its result once it has one
+fn conversation_messages(message: &ChatMessage) -> Vec<ConversationMessage> {
+    let text_kind = match message.kind.as_str() {
+        "user" => MessageType::User,
+        "gemini" => MessageType::Assistant,
+        _ => MessageType::System,
+    };
+    let mut messages = Vec::new();
+    let text = message_text(message);
+    if !text.trim().is_empty() {
+        messages.push(ConversationMessage::new(
+            message.timestamp.clone(),
+            text_kind,
+            text,
+        ));
+    }
+
+    for call in &message.tool_calls {
+        let label = tool_label(call);
+        messages.push(ConversationMessage {
+            tool_call: Some(ToolCall {
+                id: call.id.clone(),
+                name: call.name.clone(),
+                input: call.args.clone(),
+            }),
+            ..ConversationMessage::new(
+                message.timestamp.clone(),
+                MessageType::ToolUse,
+                format!("{}: {}", label, call.args),
+            )
+        });
+        if let (true, Some(result)) = (is_finished(&call.status), call.result_display.as_str()) {
+            messages.push(ConversationMessage {
+                tool_result: Some(ToolResultInfo {
+                    tool_use_id: call.id.clone(),
+                    tool_name: Some(label),
+                    is_error: call.status == "error",
+                }),
+                ..ConversationMessage::new(
+                    message.timestamp.clone(),
+                    MessageType::ToolResult,
+                    result.to_string(),
+                )
+            });
+        }
+    }
+    messages
+}
+
+fn tool_label(call: &ChatToolCall) -> String {
+    call.display_name
+        .clone()
+        .unwrap_or_else(|| call.name.clone())
+}
+
+/// Work out the session status from the chat recording, returning the tool
+/// awaiting confirmation when there is one. `quiet_secs` is how long ago the
+/// chat file was last written.
+///
+/// Only a call recorded as `awaiting_approval` is waiting for permission; an
+/// unfinished one is still running, however long it takes. The model always
+/// answers finished tool calls, so a reply whose tools have all run means
+/// another response is on its way; only a plain reply (or a cancelled call)
+/// ends the turn.
+fn determine_status(messages: &[ChatMessage], quiet_secs: u64) -> (SessionStatus, Option<String>) {
+    let Some(last) = messages.iter().rev().find(|m| m.kind != "info") else {
+        return (SessionStatus::Connecting, None);
+    };
+
+    match last.kind.as_str() {
+        "user" => (SessionStatus::Working, None),
+        "gemini" if !last.tool_calls.is_empty() => {
+            if let Some(call) = last.tool_calls.iter().find(|c| !is_finished(&c.status)) {
+                if call.status == "awaiting_approval" {
+                    return (SessionStatus::NeedsPermission, Some(tool_label(call)));
+                }
+                return (SessionStatus::Working, None);
+            }
+            if last.tool_calls.iter().any(|c| c.status == "cancelled") {
+                (SessionStatus::WaitingForInput, None)
+            } else {
+                (SessionStatus::Working, None)
+            }
+        }
+        "gemini" if quiet_secs < RECENT_ACTIVITY_SECS => (SessionStatus::Working, None),
+        _ => (SessionStatus::WaitingForInput, None),
+    }
+}
+
+fn latest_message(messages: &[ChatMessage]) -> String {
+    for message in messages.iter().rev() {
+        if message.kind != "user" && message.kind != "gemini" {
+            continue;
+        }
+        if let Some(call) = message.tool_calls.last() {
+            return format!("Executing {}...", tool_label(call));
+        }
+        let text = message_text(message);
+        if !text.trim().is_empty() {
+            return truncate_string(text.trim(), MAX_PREVIEW_CHARS);
+        }
+    }
+
+    String::new()
+}
+
+fn context_percent(messages: &[ChatMessage]) -> Option<u32> {
+    let tokens = messages
+        .iter()
+        .rev()
+        .find_map(|m| m.tokens.as_ref().map(|t| t.input).filter(|t| *t > 0))?;
+    Some(((tokens * 100) / CONTEXT_WINDOW_TOKENS).min(100) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(value: Value) -> Vec<ChatMessage> {
+        serde_json::from_value::<ChatRecord>(serde_json::json!({
+            "sessionId": "g1",
+            "messages": value
+        }))
+        .unwrap()
+        .messages
+    }
+
+    #[test]
+    fn test_status_from_chat_recording() {
+        let asked = messages(serde_json::json!([{ "type": "user", "content": "refactor io" }]));
+        assert_eq!(determine_status(&asked, 60).0, SessionStatus::Working);
+
+        let answered = messages(serde_json::json!([
+            { "type": "user", "content": "refactor io" },
+            { "type": "gemini", "content": "Done.", "tokens": { "input": 104_857 } }
+        ]));
+        assert_eq!(determine_status(&answered, 2).0, SessionStatus::Working);
+        assert_eq!(
+            determine_status(&answered, 60).0,
+            SessionStatus::WaitingForInput
+        );
+        assert_eq!(latest_message(&answered), "Done.");
+        assert_eq!(context_percent(&answered), Some(10));
+    }
+
+    #[test]
+    fn test_conversation_messages() {
+        let recorded = messages(serde_json::json!([
+            { "type": "user", "content": "list files", "timestamp": "2026-02-10T10:00:00Z" },
+            { "type": "gemini", "content": "", "toolCalls": [
+                { "id": "c1", "name": "run_shell_command", "displayName": "Shell",
+                  "args": { "command": "ls" }, "status": "success", "resultDisplay": "a.txt" }
+            ] }
+        ]));
+        let conversation: Vec<ConversationMessage> =
+            recorded.iter().flat_map(conversation_messages).collect();
+        let kinds: Vec<&MessageType> = conversation.iter().map(|m| &m.message_type).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &MessageType::User,
+                &MessageType::ToolUse,
+                &MessageType::ToolResult
+            ]
+        );
+        assert_eq!(conversation[0].timestamp, "2026-02-10T10:00:00Z");
+        assert_eq!(
+            conversation[1].tool_call.as_ref().unwrap().input["command"],
+            "ls"
+        );
+        assert_eq!(conversation[2].content, "a.txt");
+    }
+
+    #[test]
+    fn test_unfinished_tool_call_needs_approval() {
+        let pending = messages(serde_json::json!([
+            { "type": "user", "content": "delete tmp" },
+            { "type": "gemini", "content": "", "toolCalls": [
+                { "name": "run_shell_command", "displayName": "Shell", "status": "awaiting_approval" }
+            ] }
+        ]));
+        assert_eq!(
+            determine_status(&pending, 0),
+            (SessionStatus::NeedsPermission, Some("Shell".to_string()))
+        );
+
+        // A slow call is running, not waiting on the user
+        let running = messages(serde_json::json!([
+            { "type": "gemini", "content": "", "toolCalls": [
+                { "name": "run_shell_command", "status": "executing" }
+            ] }
+        ]));
+        assert_eq!(determine_status(&running, 600).0, SessionStatus::Working);
+
+        let ran = messages(serde_json::json!([
+            { "type": "gemini", "content": "", "toolCalls": [
+                { "name": "run_shell_command", "status": "success" }
+            ] }
+        ]));
+        assert_eq!(determine_status(&ran, 60).0, SessionStatus::Working);
+    }
+}
@@ -0,0 +1,367 @@
+pub mod aider;
+pub mod claude;
+pub mod codex;
+pub mod gemini;
+pub mod opencode;
+
+use crate::session::{
+    AgentProcess, BranchPoint, ConversationMessage, DetectedSession, PermissionMode, ProcessUsage,
+    SessionDetector, SessionDetectorError, SessionStatus,
+};
+use crate::stats::McpServerUsage;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+use tracing::warn;
+
+/// A transcript written this recently means the agent is still working,
+/// whatever its last entry says.
+///
+/// Why 8 seconds? Polling runs every 3.5s, Claude writes progress every 1-3s
+/// during active work. 8s provides buffer for gaps without delaying "Ready"
+/// transition when work truly finishes.
+const RECENT_ACTIVITY_SECS: u64 = 8;
+
+/// Last modification of a file in seconds since the epoch, 0 if unknown
+fn mtime_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Everything a provider reads from a session's transcript on each poll
+#[derive(Debug, Clone)]
+pub struct SessionSnapshot {
+    pub first_prompt: String,
+    pub summary: Option<String>,
+    pub message_count: u32,
+    /// RFC 3339
+    pub modified: String,
+    pub git_branch: Option<String>,
+    pub status: SessionStatus,
+    pub latest_message: String,
+    pub pending_tool_name: Option<String>,
+    /// What the pending tool wants to do (command, file, arguments)
+    pub pending_tool_input: Option<String>,
+    pub context_percent: Option<u32>,
+    /// None when the agent doesn't record one
+    pub permission_mode: Option<PermissionMode>,
+    /// Model used for the latest reply
+    pub model: Option<String>,
+    /// MCP servers the session has called, by name
+    pub mcp_servers: Vec<McpServerUsage>,
+}
+
+/// One AI coding CLI c9watch can monitor
+///
+/// Polling scans processes once per cycle, hands each provider the processes
+/// it claims, and merges what the providers report into one session list.
+pub trait AgentProvider: Send + Sync {
+    /// Stable identifier, e.g. "claude"
+    fn id(&self) -> &'static str;
+
+    /// Whether a running process belongs to this agent
+    fn matches_process(&self, process: &AgentProcess) -> bool;
+
+    /// Match this agent's running processes to their transcript files
+    fn locate_sessions(
+        &self,
+        processes: &[&AgentProcess],
+    ) -> Result<Vec<DetectedSession>, SessionDetectorError>;
+
+    /// Parse a located session's transcript and work out its status
+    fn read_session(&self, detected: &DetectedSession) -> SessionSnapshot;
+
+    /// The full conversation as (timestamp, type, content), for the viewer
+    fn conversation(
+        &self,
+        _detected: &DetectedSession,
+    ) -> Result<Vec<ConversationMessage>, String> {
+        Err(format!(
+            "Viewing {} conversations is not supported",
+            self.id()
+        ))
+    }
+
+    /// The conversation along the branch through `through` (any entry uuid),
+    /// or the branch the session is on now, with the points it was rewound
+    /// at. Only Claude transcripts branch; other agents' are read whole.
+    fn conversation_on_branch(
+        &self,
+        detected: &DetectedSession,
+        _through: Option<&str>,
+    ) -> Result<(Vec<ConversationMessage>, Vec<BranchPoint>), String> {
+        Ok((self.conversation(detected)?, Vec::new()))
+    }
+
+    /// Drop any per-file caches for transcripts no longer being watched
+    fn retain(&self, _watched: &HashSet<PathBuf>) {}
+}
+
+/// Every supported agent, in the order their sessions are listed
+fn all_providers() -> Result<Vec<Box<dyn AgentProvider>>, SessionDetectorError> {
+    Ok(vec![
+        Box::new(claude::ClaudeProvider::new()?),
+        Box::new(codex::CodexProvider::new()?),
+        Box::new(gemini::GeminiProvider::new()?),
+        Box::new(aider::AiderProvider::new()?),
+        Box::new(opencode::OpenCodeProvider::new()?),
+    ])
+}
+
+/// A registry's providers and its latest detection, shared with commands so
+/// they can reach a session's transcript through the provider that found it
+#[derive(Clone)]
+pub struct Transcripts {
+    providers: Arc<Vec<Box<dyn AgentProvider>>>,
+    /// Sessions found by the most recent detection, by session id
+    detected: Arc<Mutex<HashMap<String, DetectedSession>>>,
+}
+
+impl Transcripts {
+    /// Conversation of a running session, read by the provider that detected
+    /// it. None if the session wasn't seen by the last detection.
+    pub fn conversation(
+        &self,
+        session_id: &str,
+        through: Option<&str>,
+    ) -> Option<Result<(Vec<ConversationMessage>, Vec<BranchPoint>), String>> {
+        let detected = self
+            .detected
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(session_id)
+            .cloned()?;
+        let provider = self
+            .providers
+            .iter()
+            .find(|provider| provider.id() == detected.agent)?;
+        Some(provider.conversation_on_branch(&detected, through))
+    }
+
+    /// Transcript of a running session, as of the last detection
+    pub fn transcript_path(&self, session_id: &str) -> Option<PathBuf> {
+        self.detected
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(session_id)
+            .map(|detected| detected.transcript_path.clone())
+    }
+}
+
+/// The process scanner plus every supported provider, reused across poll
+/// cycles
+pub struct AgentRegistry {
+    detector: SessionDetector,
+    transcripts: Transcripts,
+    /// Resource use of each process at the latest scan
+    usage: HashMap<u32, ProcessUsage>,
+}
+
+impl AgentRegistry {
+    pub fn new() -> Result<Self, SessionDetectorError> {
+        Ok(Self {
+            detector: SessionDetector::new()?,
+            transcripts: Transcripts {
+                providers: Arc::new(all_providers()?),
+                detected: Arc::default(),
+            },
+            usage: HashMap::new(),
+        })
+    }
+
+    /// Handle for reading conversations of the sessions this registry detects
+    pub fn transcripts(&self) -> Transcripts {
+        self.transcripts.clone()
+    }
+
+    /// Detect active sessions of every agent, in provider order
+    pub fn detect_sessions(&mut self) -> Result<Vec<DetectedSession>, SessionDetectorError> {
+        let processes = self.detector.scan_processes();
+        let mut sessions = Vec::new();
+        self.usage.clear();
+
+        for provider in self.transcripts.providers.iter() {
+            let claimed: Vec<&AgentProcess> = processes
+                .iter()
+                .filter(|process| provider.matches_process(process))
+                .collect();
+
+            // If none of this agent's processes are running, skip its transcripts
+            if claimed.is_empty() {
+                continue;
+            }
+            self.usage
+                .extend(claimed.iter().map(|process| (process.pid, process.usage)));
+            // One agent's unreadable transcripts shouldn't hide the others' sessions
+            match provider.locate_sessions(&claimed) {
+                Ok(located) => sessions.extend(located),
+                Err(e) => warn!("Failed to locate {} sessions: {}", provider.id(), e),
+            }
+        }
+
+        *self
+            .transcripts
+            .detected
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = sessions
+            .iter()
+            .filter_map(|s| Some((s.session_id.clone()?, s.clone())))
+            .collect();
+
+        Ok(sessions)
+    }
+
+    /// Read a session with the provider that detected it
+    pub fn read_session(&self, detected: &DetectedSession) -> Option<SessionSnapshot> {
+        self.transcripts
+            .providers
+            .iter()
+            .find(|provider| provider.id() == detected.agent)
+            .map(|provider| provider.read_session(detected))
+    }
+
+    /// CPU and memory use of a detected session's process
+    pub fn usage(&self, pid: u32) -> ProcessUsage {
+        self.usage.get(&pid).copied().unwrap_or_default()
+    }
+
+    pub fn retain(&self, watched: &HashSet<PathBuf>) {
+        for provider in self.transcripts.providers.iter() {
+            provider.retain(watched);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::SessionStore;
+
+    fn entry(kind: &str, uuid: &str, parent: Option<&str>, text: &str) -> String {
+        let message = if kind == "user" {
+            serde_json::json!({ "role": "user", "content": text })
+        } else {
+            serde_json::json!({
+                "model": "claude-sonnet-4-5",
+                "id": format!("msg_{}", uuid),
+                "role": "assistant",
+                "content": [{ "type": "text", "text": text }]
+            })
+        };
+        serde_json::json!({
+            "type": kind,
+            "uuid": uuid,
+            "parentUuid": parent,
+            "timestamp": "2026-01-08T15:23:03.096Z",
+            "message": message
+        })
+        .to_string()
+    }
+
+    /// Store whose last detection found a Claude session writing to `path`
+    fn running_claude_session(session_id: &str, path: &Path) -> SessionStore {
+        let store = SessionStore::new();
+        store.set_transcripts(Transcripts {
+            providers: Arc::new(vec![Box::new(claude::ClaudeProvider::new().unwrap())]),
+            detected: Arc::new(Mutex::new(HashMap::from([(
+                session_id.to_string(),
+                DetectedSession {
+                    agent: "claude".to_string(),
+                    pid: 1,
+                    cwd: std::env::temp_dir(),
+                    project_path: std::env::temp_dir(),
+                    transcript_path: path.to_path_buf(),
+                    session_id: Some(session_id.to_string()),
+                    project_name: "tmp".to_string(),
+                    start_time: 0,
+                },
+            )]))),
+        });
+        store
+    }
+
+    #[test]
+    fn test_running_claude_session_follows_selected_branch() {
+        let session_id = "7f1c2d3e-4a5b-4c6d-8e9f-0a1b2c3d4e5f";
+        let path = std::env::temp_dir().join(format!(
+            "c9watch-running-branch-{}.jsonl",
+            std::process::id()
+        ));
+        let lines = [
+            entry("user", "u1", None, "write the script"),
+            entry("assistant", "a1", Some("u1"), "done"),
+            entry("user", "u2", Some("a1"), "now add tests"),
+            entry("assistant", "a2", Some("u2"), "tests added"),
+            // Prompt edited: rewound to a1 and asked something else
+            entry("user", "u2x", Some("a1"), "now add docs"),
+            entry("assistant", "a2x", Some("u2x"), "docs added"),
+        ];
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+        let store = running_claude_session(session_id, &path);
+        let uuids = |conversation: &crate::Conversation| -> Vec<String> {
+            conversation
+                .messages
+                .iter()
+                .filter_map(|message| message.uuid.clone())
+                .collect()
+        };
+
+        let current = crate::conversation_on_branch(&store, session_id, None).unwrap();
+        assert_eq!(uuids(&current), vec!["u1", "a1", "u2x", "a2x"]);
+        assert_eq!(current.branches.len(), 1);
+        assert_eq!(current.branches[0].selected, 1);
+
+        let old = crate::conversation_on_branch(&store, session_id, Some("a2")).unwrap();
+        assert_eq!(uuids(&old), vec!["u1", "a1", "u2", "a2"]);
+        assert_eq!(old.branches[0].selected, 0);
+        assert_eq!(
+            crate::message_content(&store, session_id, "a2", None).unwrap(),
+            "tests added"
+        );
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_message_content_picks_tool_result_by_id() {
+        let session_id = "0b1c2d3e-4a5b-4c6d-8e9f-0a1b2c3d4e5f";
+        let path =
+            std::env::temp_dir().join(format!("c9watch-tool-results-{}.jsonl", std::process::id()));
+        let results = serde_json::json!({
+            "type": "user",
+            "uuid": "u2",
+            "parentUuid": "a1",
+            "timestamp": "2026-01-08T15:23:05.000Z",
+            "message": {
+                "role": "user",
+                "content": [
+                    { "type": "tool_result", "tool_use_id": "toolu_1", "content": "a.rs" },
+                    { "type": "tool_result", "tool_use_id": "toolu_2", "content": "b.rs" }
+                ]
+            }
+        });
+        let lines = [
+            entry("user", "u1", None, "list both dirs"),
+            entry("assistant", "a1", Some("u1"), "listing"),
+            results.to_string(),
+        ];
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+        let store = running_claude_session(session_id, &path);
+
+        let content =
+            |tool_use_id| crate::message_content(&store, session_id, "u2", tool_use_id).unwrap();
+        assert_eq!(content(Some("toolu_1")), "a.rs");
+        assert_eq!(content(Some("toolu_2")), "b.rs");
+        assert_eq!(content(None), "a.rs");
+        assert!(crate::message_content(&store, session_id, "u2", Some("toolu_3")).is_err());
+
+        fs::remove_file(&path).ok();
+    }
+}
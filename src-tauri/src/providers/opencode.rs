@@ -0,0 +1,407 @@
+use super::{AgentProvider, SessionSnapshot};
+use crate::session::parser::{truncate_string, MAX_PREVIEW_CHARS};
+use crate::session::{
+    AgentProcess, ConversationMessage, DetectedSession, MessageType, SessionDetectorError,
+    SessionStatus, ToolCall, ToolResultInfo,
+};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// OpenCode: `opencode` processes storing each session, message and message
+/// part as its own JSON file under ~/.local/share/opencode/storage/
+pub struct OpenCodeProvider {
+    storage_dir: PathBuf,
+    /// Parsed session info files, re-read only when their mtime changes
+    infos: Mutex<HashMap<PathBuf, (Option<SystemTime>, SessionInfo)>>,
+}
+
+/// storage/session/<projectID>/<sessionID>.json
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionInfo {
+    id: String,
+    directory: PathBuf,
+    /// Set on sessions spawned by a subagent task
+    parent_id: Option<String>,
+    #[serde(default)]
+    title: String,
+    time: Times,
+}
+
+/// Epoch milliseconds
+#[derive(Debug, Clone, Deserialize)]
+struct Times {
+    created: u64,
+    #[serde(default)]
+    updated: Option<u64>,
+    #[serde(default)]
+    completed: Option<u64>,
+}
+
+/// storage/message/<sessionID>/<messageID>.json
+#[derive(Debug, Deserialize)]
+struct MessageInfo {
+    id: String,
+    role: String,
+    time: Times,
+    /// On assistant messages
+    #[serde(rename = "modelID")]
+    model_id: Option<String>,
+}
+
+/// storage/part/<messageID>/<partID>.json
+#[derive(Debug, Deserialize)]
+struct Part {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+    /// Tool name, on tool parts
+    tool: Option<String>,
+    /// Tool call status, input and output, on tool parts
+    #[serde(default)]
+    state: Value,
+    /// Injected by OpenCode rather than typed by the user
+    #[serde(default)]
+    synthetic: bool,
+}
+
+impl OpenCodeProvider {
+    pub fn new() -> Result<Self, SessionDetectorError> {
+        let data_dir = match std::env::var_os("XDG_DATA_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => dirs::home_dir()
+                .ok_or(SessionDetectorError::HomeDirectoryNotFound)?
+                .join(".local")
+                .join("share"),
+        };
+
+        Ok(Self {
+            storage_dir: data_dir.join("opencode").join("storage"),
+            infos: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Top-level sessions across all projects
+    fn sessions(&self) -> Vec<(PathBuf, SessionInfo)> {
+        let pattern = format!("{}/session/*/*.json", self.storage_dir.display());
+        let paths: Vec<PathBuf> = glob::glob(&pattern)
+            .map(|paths| paths.flatten().collect())
+            .unwrap_or_default();
+
+        let existing: HashSet<&PathBuf> = paths.iter().collect();
+        let mut infos = self.infos.lock().unwrap_or_else(|e| e.into_inner());
+        infos.retain(|path, _| existing.contains(path));
+
+        let mut sessions = Vec::new();
+        for path in paths {
+            let mtime = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            let info = match infos.get(&path) {
+                Some((cached_mtime, info)) if *cached_mtime == mtime => Some(info.clone()),
+                _ => read_json::<SessionInfo>(&path),
+            };
+            if let Some(info) = info {
+                infos.insert(path.clone(), (mtime, info.clone()));
+                if info.parent_id.is_none() {
+                    sessions.push((path, info));
+                }
+            }
+        }
+        sessions
+    }
+
+    /// A session's messages, oldest first
+    fn messages(&self, session_id: &str) -> Vec<MessageInfo> {
+        let dir = self.storage_dir.join("message").join(session_id);
+        let mut messages: Vec<MessageInfo> = json_files(&dir)
+            .iter()
+            .filter_map(|path| read_json(path))
+            .collect();
+        messages.sort_by(|a, b| (a.time.created, &a.id).cmp(&(b.time.created, &b.id)));
+        messages
+    }
+
+    /// A message's parts, in order (part ids sort by creation)
+    fn parts(&self, message_id: &str) -> Vec<Part> {
+        let dir = self.storage_dir.join("part").join(message_id);
+        let mut parts: Vec<Part> = json_files(&dir)
+            .iter()
+            .filter_map(|path| read_json(path))
+            .collect();
+        parts.sort_by(|a, b| a.id.cmp(&b.id));
+        parts
+    }
+}
+
+impl AgentProvider for OpenCodeProvider {
+    fn id(&self) -> &'static str {
+        "opencode"
+    }
+
+    fn matches_process(&self, process: &AgentProcess) -> bool {
+        if process.name == "opencode" {
+            return true;
+        }
+        // Installed from npm it may run as `node .../bin/opencode`
+        (process.name.starts_with("bun") || process.name.starts_with("node"))
+            && process
+                .cmd
+                .iter()
+                .skip(1)
+                .any(|arg| Path::new(arg).file_name().and_then(|n| n.to_str()) == Some("opencode"))
+    }
+
+    fn locate_sessions(
+        &self,
+        processes: &[&AgentProcess],
+    ) -> Result<Vec<DetectedSession>, SessionDetectorError> {
+        let mut candidates = self.sessions();
+        // Most recently updated first
+        candidates.sort_by_key(|(_, info)| {
+            std::cmp::Reverse(info.time.updated.unwrap_or(info.time.created))
+        });
+
+        let mut sorted_processes: Vec<&AgentProcess> = processes.to_vec();
+        sorted_processes.sort_by(|a, b| b.start_time.cmp(&a.start_time));
+
+        let mut used: HashSet<String> = HashSet::new();
+        let mut sessions = Vec::new();
+        for proc in sorted_processes {
+            let Some(proc_cwd) = &proc.cwd else {
+                continue;
+            };
+
+            let matching = candidates.iter().find(|(_, info)| {
+                let updated_secs = info.time.updated.unwrap_or(info.time.created) / 1000;
+                !used.contains(&info.id)
+                    && &info.directory == proc_cwd
+                    && updated_secs + 5 >= proc.start_time
+            });
+
+            if let Some((path, info)) = matching {
+                used.insert(info.id.clone());
+                sessions.push(DetectedSession {
+                    agent: self.id().to_string(),
+                    pid: proc.pid,
+                    cwd: proc_cwd.clone(),
+                    project_path: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+                    transcript_path: path.clone(),
+                    session_id: Some(info.id.clone()),
+                    project_name: proc_cwd
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("unknown")
+                        .to_string(),
+                    start_time: proc.start_time,
+                });
+            }
+        }
+
+        Ok(sessions)
+    }
+
+    fn read_session(&self, detected: &DetectedSession) -> SessionSnapshot {
+        let session_id = detected.session_id.as_deref().unwrap_or_default();
+        let info = read_json::<SessionInfo>(&detected.transcript_path);
+        let messages = self.messages(session_id);
+
+        let first_prompt = messages
+            .iter()
+            .find(|m| m.role == "user")
+            .and_then(|m| first_text(&self.parts(&m.id)))
+            .map(|text| truncate_string(&text, MAX_PREVIEW_CHARS))
+            .unwrap_or_else(|| "(Active session)".to_string());
+
+        let last_parts = messages
+            .last()
+            .map(|m| self.parts(&m.id))
+            .unwrap_or_default();
+        let latest_message = last_parts
+            .iter()
+            .rev()
+            .find_map(|part| match part.kind.as_str() {
+                "text" if !part.text.trim().is_empty() => {
+                    Some(truncate_string(&part.text, MAX_PREVIEW_CHARS))
+                }
+                "tool" => Some(format!(
+                    "Executing {}...",
+                    part.tool.as_deref().unwrap_or("tool")
+                )),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        let updated = info
+            .as_ref()
+            .and_then(|i| i.time.updated)
+            .and_then(|ms| DateTime::<Utc>::from_timestamp_millis(ms as i64))
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default();
+
+        SessionSnapshot {
+            first_prompt,
+            summary: info.map(|i| i.title).filter(|t| !t.is_empty()),
+            message_count: messages.len() as u32,
+            modified: updated,
+            git_branch: None,
+            status: determine_status(&messages),
+            latest_message,
+            pending_tool_name: None,
+            pending_tool_input: None,
+            context_percent: None,
+            permission_mode: None,
+            model: messages.iter().rev().find_map(|m| m.model_id.clone()),
+            mcp_servers: Vec::new(),
+        }
+    }
+
+    fn conversation(&self, detected: &DetectedSession) -> Result<Vec<ConversationMessage>, String> {
+        let session_id = detected
+            .session_id
+            .as_deref()
+            .ok_or_else(|| "Session has no id".to_string())?;
+
+        let mut conversation = Vec::new();
+        for message in self.messages(session_id) {
+            let timestamp = DateTime::<Utc>::from_timestamp_millis(message.time.created as i64)
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default();
+            let text_kind = if message.role == "user" {
+                MessageType::User
+            } else {
+                MessageType::Assistant
+            };
+
+            for part in self.parts(&message.id) {
+                match part.kind.as_str() {
+                    "text" if !part.synthetic && !part.text.trim().is_empty() => {
+                        conversation.push(ConversationMessage::new(
+                            timestamp.clone(),
+                            text_kind.clone(),
+                            part.text,
+                        ));
+                    }
+                    "reasoning" if !part.text.trim().is_empty() => {
+                        conversation.push(ConversationMessage::new(
+                            timestamp.clone(),
+                            MessageType::Thinking,
+                            part.text,
+                        ));
+                    }
+                    "tool" => {
+                        let tool = part.tool.as_deref().unwrap_or("tool");
+                        conversation.push(ConversationMessage {
+                            tool_call: Some(ToolCall {
+                                id: part.id.clone(),
+                                name: tool.to_string(),
+                                input: part.state["input"].clone(),
+                            }),
+                            ..ConversationMessage::new(
+                                timestamp.clone(),
+                                MessageType::ToolUse,
+                                format!("{}: {}", tool, part.state["input"]),
+                            )
+                        });
+                        let output = part.state["output"].as_str();
+                        let result = output.or_else(|| part.state["error"].as_str());
+                        if let Some(result) = result {
+                            conversation.push(ConversationMessage {
+                                tool_result: Some(ToolResultInfo {
+                                    tool_use_id: part.id.clone(),
+                                    tool_name: Some(tool.to_string()),
+                                    is_error: output.is_none(),
+                                }),
+                                ..ConversationMessage::new(
+                                    timestamp.clone(),
+                                    MessageType::ToolResult,
+                                    result.to_string(),
+                                )
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(conversation)
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn json_files(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn first_text(parts: &[Part]) -> Option<String> {
+    parts
+        .iter()
+        .find(|p| p.kind == "text" && !p.synthetic && !p.text.trim().is_empty())
+        .map(|p| p.text.clone())
+}
+
+/// The last message tells it all: an unanswered prompt or an assistant
+/// message still being written means OpenCode is working
+fn determine_status(messages: &[MessageInfo]) -> SessionStatus {
+    match messages.last() {
+        None => SessionStatus::Connecting,
+        Some(message) if message.role == "user" => SessionStatus::Working,
+        Some(message) if message.time.completed.is_none() => SessionStatus::Working,
+        Some(_) => SessionStatus::WaitingForInput,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, completed: Option<u64>) -> MessageInfo {
+        serde_json::from_value(serde_json::json!({
+            "id": "msg_1",
+            "sessionID": "ses_1",
+            "role": role,
+            "time": { "created": 1_770_000_000_000u64, "completed": completed }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_status_from_last_message() {
+        assert_eq!(determine_status(&[]), SessionStatus::Connecting);
+        assert_eq!(
+            determine_status(&[message("user", None)]),
+            SessionStatus::Working
+        );
+        assert_eq!(
+            determine_status(&[message("user", None), message("assistant", None)]),
+            SessionStatus::Working
+        );
+        assert_eq!(
+            determine_status(&[
+                message("user", None),
+                message("assistant", Some(1_770_000_005_000))
+            ]),
+            SessionStatus::WaitingForInput
+        );
+    }
+}
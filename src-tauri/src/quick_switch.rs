@@ -0,0 +1,159 @@
+use crate::polling::Session;
+use serde::Serialize;
+
+/// Results returned for one query
+pub const MAX_RESULTS: usize = 20;
+
+/// Which part of the session the query matched best
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchField {
+    Title,
+    Name,
+    ProjectPath,
+    FirstPrompt,
+}
+
+/// A ranked quick-switcher result
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMatch {
+    pub session: Session,
+    pub score: u32,
+    pub field: MatchField,
+}
+
+/// Rank `sessions` against `query`, best first. Every whitespace-separated
+/// word has to match somewhere; an empty query matches nothing.
+pub fn query(sessions: Vec<Session>, query: &str) -> Vec<SessionMatch> {
+    let words: Vec<Vec<char>> = query
+        .split_whitespace()
+        .map(|word| word.to_lowercase().chars().collect())
+        .collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches: Vec<SessionMatch> = sessions
+        .into_iter()
+        .filter_map(|session| {
+            let (score, field) = score_session(&session, &words)?;
+            Some(SessionMatch {
+                session,
+                score,
+                field,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| b.score.cmp(&a.score));
+    matches.truncate(MAX_RESULTS);
+    matches
+}
+
+fn score_session(session: &Session, words: &[Vec<char>]) -> Option<(u32, MatchField)> {
+    // Weights in percent: short, user-chosen labels beat long free text
+    let mut fields = vec![
+        (MatchField::Name, session.session_name.as_str(), 100),
+        (MatchField::ProjectPath, session.project_path.as_str(), 60),
+        (MatchField::FirstPrompt, session.first_prompt.as_str(), 50),
+    ];
+    if let Some(title) = session.custom_title.as_deref() {
+        fields.insert(0, (MatchField::Title, title, 100));
+    }
+    let fields: Vec<(MatchField, Vec<char>, u32)> = fields
+        .into_iter()
+        .map(|(field, text, weight)| (field, text.to_lowercase().chars().collect(), weight))
+        .collect();
+
+    let mut total = 0;
+    let mut best: Option<(u32, MatchField)> = None;
+    for word in words {
+        let (score, field) = fields
+            .iter()
+            .filter_map(|(field, text, weight)| {
+                fuzzy_score(word, text).map(|score| (score * weight / 100, *field))
+            })
+            .max_by_key(|(score, _)| *score)?;
+        total += score;
+        if !matches!(best, Some((best_score, _)) if best_score >= score) {
+            best = Some((score, field));
+        }
+    }
+    best.map(|(_, field)| (total, field))
+}
+
+/// Score `needle` as a subsequence of `haystack` (both lowercased), or None
+/// if it isn't one. Runs of consecutive characters and matches at the start
+/// of a word score highest; characters are matched greedily, left to right.
+fn fuzzy_score(needle: &[char], haystack: &[char]) -> Option<u32> {
+    const MATCH: u32 = 10;
+    const CONSECUTIVE: u32 = 15;
+    const WORD_START: u32 = 20;
+    const MAX_GAP_PENALTY: u32 = 10;
+
+    let mut score = 0u32;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for &c in needle {
+        let found = position + haystack[position..].iter().position(|&h| h == c)?;
+        score += MATCH;
+        match previous {
+            Some(prev) if found == prev + 1 => score += CONSECUTIVE,
+            Some(prev) => {
+                score = score.saturating_sub(((found - prev - 1) as u32).min(MAX_GAP_PENALTY))
+            }
+            None => {}
+        }
+        let at_word_start = found == 0 || !haystack[found - 1].is_alphanumeric();
+        if at_word_start {
+            score += WORD_START;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polling::tests::test_session;
+    use crate::session::SessionStatus;
+
+    fn session(id: &str, name: &str, path: &str, prompt: &str) -> Session {
+        let mut session = test_session(SessionStatus::Working, 0);
+        session.id = id.to_string();
+        session.session_name = name.to_string();
+        session.project_path = path.to_string();
+        session.first_prompt = prompt.to_string();
+        session
+    }
+
+    #[test]
+    fn test_query_ranks_name_and_word_starts_first() {
+        let sessions = vec![
+            session("a", "api", "/work/api", "write the data migration script"),
+            session("b", "c9watch", "/work/c9watch", "fix the tray icon"),
+            session("c", "docs", "/work/c-nine-watch-docs", "typo"),
+        ];
+
+        let results = query(sessions.clone(), "c9");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].session.id, "b");
+        assert_eq!(results[0].field, MatchField::Name);
+
+        let ids: Vec<String> = query(sessions.clone(), "cw")
+            .into_iter()
+            .map(|m| m.session.id)
+            .collect();
+        assert_eq!(ids, vec!["b", "c"]);
+
+        let results = query(sessions.clone(), "migration api");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].session.id, "a");
+
+        assert!(query(sessions.clone(), "zz").is_empty());
+        assert!(query(sessions, "  ").is_empty());
+    }
+}
@@ -0,0 +1,105 @@
+use crate::deep_link::SCHEME;
+use crate::polling::Session;
+use crate::session::SessionStatus;
+use serde::Serialize;
+use std::path::Path;
+
+/// One row of `GET /api/quicklist`, shaped for launcher extensions (Raycast,
+/// Alfred) so they can list sessions without speaking the WS protocol
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickListItem {
+    pub id: String,
+    pub pid: u32,
+    /// Custom title if set, otherwise the session name
+    pub title: String,
+    /// "project · branch · status"
+    pub subtitle: String,
+    pub status: SessionStatus,
+    /// Emoji for the status, usable as an icon as-is
+    pub icon: &'static str,
+    /// Opens the session in c9watch
+    pub url: String,
+    /// Brings the session's terminal or IDE to the front
+    pub focus_url: String,
+}
+
+/// Sessions most in need of attention first: permission prompts (longest
+/// waiting first), then finished ones (longest idle first), then the rest
+pub fn build(sessions: &[Session]) -> Vec<QuickListItem> {
+    let mut sorted: Vec<&Session> = sessions.iter().collect();
+    sorted.sort_by_key(|session| match session.status {
+        SessionStatus::NeedsPermission => (0, u64::MAX - session.needs_permission_seconds),
+        SessionStatus::WaitingForInput => (1, u64::MAX - session.idle_seconds),
+        SessionStatus::Working => (2, 0),
+        SessionStatus::Connecting => (3, 0),
+    });
+    sorted.into_iter().map(item).collect()
+}
+
+fn item(session: &Session) -> QuickListItem {
+    let (icon, status) = match session.status {
+        SessionStatus::NeedsPermission => (
+            "🔐",
+            match &session.pending_tool_name {
+                Some(tool) => format!("Needs permission for {}", tool),
+                None => "Needs permission".to_string(),
+            },
+        ),
+        SessionStatus::WaitingForInput => ("✅", "Waiting for input".to_string()),
+        SessionStatus::Working => ("⚙️", "Working".to_string()),
+        SessionStatus::Connecting => ("⏳", "Connecting".to_string()),
+    };
+
+    let project = Path::new(&session.project_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| session.project_path.clone());
+    let subtitle = [Some(project), session.git_branch.clone(), Some(status)]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" · ");
+
+    QuickListItem {
+        id: session.id.clone(),
+        pid: session.pid,
+        title: session
+            .custom_title
+            .clone()
+            .unwrap_or_else(|| session.session_name.clone()),
+        subtitle,
+        status: session.status.clone(),
+        icon,
+        url: format!("{}://session/{}", SCHEME, session.id),
+        focus_url: format!("{}://focus/{}", SCHEME, session.id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polling::tests::test_session;
+
+    #[test]
+    fn test_quicklist_order_and_shape() {
+        let mut working = test_session(SessionStatus::Working, 0);
+        working.id = "working".to_string();
+        let mut asking = test_session(SessionStatus::NeedsPermission, 0);
+        asking.id = "asking".to_string();
+        asking.pending_tool_name = Some("Bash".to_string());
+        asking.project_path = "/code/c9watch".to_string();
+        asking.git_branch = Some("main".to_string());
+        let mut idle = test_session(SessionStatus::WaitingForInput, 0);
+        idle.id = "idle".to_string();
+
+        let items = build(&[working, idle, asking]);
+        let ids: Vec<&str> = items.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, ["asking", "idle", "working"]);
+        assert_eq!(
+            items[0].subtitle,
+            "c9watch · main · Needs permission for Bash"
+        );
+        assert_eq!(items[0].focus_url, "c9watch://focus/asking");
+    }
+}
@@ -0,0 +1,238 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Global limiter shared by all web server routes
+static RATE_LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+
+pub fn rate_limiter() -> &'static RateLimiter {
+    RATE_LIMITER.get_or_init(RateLimiter::default)
+}
+
+/// Requests allowed per peer in each `WINDOW` (a page load fetches a few
+/// dozen static assets)
+const MAX_REQUESTS_PER_WINDOW: u32 = 300;
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Backoff after the first failed token attempt; doubles with each further failure
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(15 * 60);
+
+/// Peers with no activity for this long are forgotten
+const IDLE_EXPIRY: Duration = Duration::from_secs(60 * 60);
+
+/// Rejected attempts kept for diagnostics
+const MAX_LOG_ENTRIES: usize = 200;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RejectReason {
+    /// Peer address is outside the allowed ranges
+    NotAllowed,
+    /// Too many requests in the current window
+    RateLimited,
+    /// Still backing off after earlier failures
+    BackedOff,
+    InvalidToken,
+    InvalidPairingCode,
+}
+
+/// A connection or request the server turned away
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedAttempt {
+    pub ip: String,
+    pub path: String,
+    pub reason: RejectReason,
+    /// RFC 3339
+    pub at: String,
+}
+
+struct PeerState {
+    window_start: Instant,
+    requests: u32,
+    failures: u32,
+    blocked_until: Option<Instant>,
+    last_seen: Instant,
+}
+
+impl PeerState {
+    fn new(now: Instant) -> Self {
+        Self {
+            window_start: now,
+            requests: 0,
+            failures: 0,
+            blocked_until: None,
+            last_seen: now,
+        }
+    }
+}
+
+#[derive(Default)]
+struct LimiterState {
+    peers: HashMap<IpAddr, PeerState>,
+    log: VecDeque<RejectedAttempt>,
+}
+
+impl LimiterState {
+    fn log(&mut self, ip: IpAddr, path: &str, reason: RejectReason) {
+        if self.log.len() >= MAX_LOG_ENTRIES {
+            self.log.pop_front();
+        }
+        self.log.push_back(RejectedAttempt {
+            ip: ip.to_canonical().to_string(),
+            path: path.to_string(),
+            reason,
+            at: Utc::now().to_rfc3339(),
+        });
+    }
+}
+
+/// Per-IP request rate limiting plus exponential backoff after failed
+/// token or pairing-code attempts
+#[derive(Default)]
+pub struct RateLimiter {
+    state: Mutex<LimiterState>,
+}
+
+impl RateLimiter {
+    /// Count a request from `ip`. On rejection returns how long to wait.
+    pub fn check(&self, ip: IpAddr, path: &str) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        state
+            .peers
+            .retain(|_, peer| now.duration_since(peer.last_seen) < IDLE_EXPIRY);
+
+        let peer = state
+            .peers
+            .entry(ip.to_canonical())
+            .or_insert_with(|| PeerState::new(now));
+        peer.last_seen = now;
+
+        if let Some(until) = peer.blocked_until.filter(|until| *until > now) {
+            let wait = until - now;
+            state.log(ip, path, RejectReason::BackedOff);
+            return Err(wait);
+        }
+
+        if now.duration_since(peer.window_start) >= WINDOW {
+            peer.window_start = now;
+            peer.requests = 0;
+        }
+        peer.requests += 1;
+        if peer.requests > MAX_REQUESTS_PER_WINDOW {
+            let wait = WINDOW - now.duration_since(peer.window_start);
+            state.log(ip, path, RejectReason::RateLimited);
+            return Err(wait);
+        }
+
+        Ok(())
+    }
+
+    /// Record a failed credential and start (or extend) the peer's backoff.
+    /// Loopback peers are only logged: they're on this machine already, and
+    /// a stale token in a local client shouldn't lock the user out.
+    pub fn record_failure(&self, ip: IpAddr, path: &str, reason: RejectReason) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if ip.to_canonical().is_loopback() {
+            state.log(ip, path, reason);
+            return;
+        }
+        let now = Instant::now();
+        let peer = state
+            .peers
+            .entry(ip.to_canonical())
+            .or_insert_with(|| PeerState::new(now));
+        peer.failures += 1;
+        peer.blocked_until = Some(now + backoff(peer.failures));
+        state.log(ip, path, reason);
+    }
+
+    /// A successful login clears the peer's failure count
+    pub fn record_success(&self, ip: IpAddr) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(peer) = state.peers.get_mut(&ip.to_canonical()) {
+            peer.failures = 0;
+            peer.blocked_until = None;
+        }
+    }
+
+    /// Log a rejection that isn't subject to backoff (e.g. address not allowed)
+    pub fn record_rejection(&self, ip: IpAddr, path: &str, reason: RejectReason) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.log(ip, path, reason);
+    }
+
+    /// Recent rejected attempts, newest first
+    pub fn rejected_attempts(&self) -> Vec<RejectedAttempt> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.log.iter().rev().cloned().collect()
+    }
+}
+
+/// 1s, 2s, 4s, ... capped at `MAX_BACKOFF`
+fn backoff(failures: u32) -> Duration {
+    let exponent = failures.saturating_sub(1).min(20);
+    (BASE_BACKOFF * 2u32.pow(exponent)).min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(4), Duration::from_secs(8));
+        assert_eq!(backoff(100), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_failures_block_until_success() {
+        let limiter = RateLimiter::default();
+        let peer = ip("192.168.1.20");
+        assert!(limiter.check(peer, "/ws").is_ok());
+
+        limiter.record_failure(peer, "/ws", RejectReason::InvalidToken);
+        assert!(limiter.check(peer, "/ws").is_err());
+        // Other peers are unaffected, and v4-mapped addresses share state
+        assert!(limiter.check(ip("192.168.1.21"), "/ws").is_ok());
+        assert!(limiter.check(ip("::ffff:192.168.1.20"), "/ws").is_err());
+
+        limiter.record_success(peer);
+        assert!(limiter.check(peer, "/ws").is_ok());
+
+        let log = limiter.rejected_attempts();
+        assert_eq!(log[0].reason, RejectReason::BackedOff);
+        assert_eq!(log.last().unwrap().reason, RejectReason::InvalidToken);
+    }
+
+    #[test]
+    fn test_loopback_failures_are_not_backed_off() {
+        let limiter = RateLimiter::default();
+        for peer in [ip("127.0.0.1"), ip("::1"), ip("::ffff:127.0.0.1")] {
+            limiter.record_failure(peer, "/ws", RejectReason::InvalidToken);
+            assert!(limiter.check(peer, "/ws").is_ok());
+        }
+        assert_eq!(limiter.rejected_attempts().len(), 3);
+    }
+
+    #[test]
+    fn test_rate_limit_per_window() {
+        let limiter = RateLimiter::default();
+        let peer = ip("10.0.0.2");
+        for _ in 0..MAX_REQUESTS_PER_WINDOW {
+            assert!(limiter.check(peer, "/info").is_ok());
+        }
+        assert!(limiter.check(peer, "/info").is_err());
+    }
+}
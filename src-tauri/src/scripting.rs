@@ -0,0 +1,110 @@
+use crate::store::SessionStore;
+use cocoa::base::{id, nil};
+use cocoa::foundation::NSString;
+use objc::declare::ClassDecl;
+use objc::runtime::{Object, Sel, NO, YES};
+use objc::{class, msg_send, sel, sel_impl};
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::sync::{Arc, OnceLock};
+use tracing::warn;
+
+/// Sessions the script commands read, set once at startup
+static STORE: OnceLock<Arc<SessionStore>> = OnceLock::new();
+
+/// errOSAGeneralError, reported to the script along with our message
+const SCRIPT_ERROR: i64 = -2700;
+
+type Perform = extern "C" fn(&Object, Sel) -> id;
+
+/// Define the NSScriptCommand subclasses named in c9watch.sdef. Cocoa
+/// scripting looks them up by name when a script (or a Shortcuts "Run
+/// AppleScript" action) sends one of its commands.
+pub fn register(store: Arc<SessionStore>) {
+    if STORE.set(store).is_err() {
+        return;
+    }
+    declare_command("C9ListSessionsCommand", list_sessions);
+    declare_command("C9FocusSessionCommand", focus_session);
+    declare_command("C9ToggleDoNotDisturbCommand", toggle_do_not_disturb);
+}
+
+fn declare_command(name: &str, perform: Perform) {
+    let Some(mut decl) = ClassDecl::new(name, class!(NSScriptCommand)) else {
+        warn!("Script command class {} is already defined", name);
+        return;
+    };
+    unsafe {
+        decl.add_method(sel!(performDefaultImplementation), perform);
+    }
+    decl.register();
+}
+
+/// `list sessions`: the latest sessions as JSON text
+extern "C" fn list_sessions(_this: &Object, _cmd: Sel) -> id {
+    let json = STORE
+        .get()
+        .map(|store| crate::automation::sessions_json(store))
+        .unwrap_or_else(|| "[]".to_string());
+    ns_string(&json)
+}
+
+/// `focus session "<id>"`
+extern "C" fn focus_session(this: &Object, _cmd: Sel) -> id {
+    let session_id = unsafe {
+        let parameter: id = msg_send![this, directParameter];
+        rust_string(parameter)
+    };
+    let result = match (STORE.get(), session_id) {
+        (Some(store), Some(session_id)) => crate::automation::focus_session(store, &session_id),
+        (_, None) => Err(crate::error::C9Error::ParseError(
+            "Expected a session ID".to_string(),
+        )),
+        (None, _) => Err(crate::error::C9Error::Other(
+            "c9watch is still starting".to_string(),
+        )),
+    };
+    if let Err(e) = result {
+        fail(this, &e.to_string());
+    }
+    nil
+}
+
+/// `toggle do not disturb`: returns whether it's now on
+extern "C" fn toggle_do_not_disturb(this: &Object, _cmd: Sel) -> id {
+    match crate::automation::set_do_not_disturb(None) {
+        Ok(enabled) => unsafe {
+            msg_send![class!(NSNumber), numberWithBool: if enabled { YES } else { NO }]
+        },
+        Err(e) => {
+            fail(this, &e.to_string());
+            nil
+        }
+    }
+}
+
+/// Make the command raise an error in the calling script
+fn fail(command: &Object, message: &str) {
+    unsafe {
+        let _: () = msg_send![command, setScriptErrorNumber: SCRIPT_ERROR];
+        let _: () = msg_send![command, setScriptErrorString: ns_string(message)];
+    }
+}
+
+fn ns_string(text: &str) -> id {
+    unsafe {
+        let string = NSString::alloc(nil).init_str(text);
+        msg_send![string, autorelease]
+    }
+}
+
+unsafe fn rust_string(string: id) -> Option<String> {
+    if string == nil {
+        return None;
+    }
+    let utf8: *const c_char = msg_send![string, UTF8String];
+    if utf8.is_null() {
+        return None;
+    }
+    Some(CStr::from_ptr(utf8).to_string_lossy().to_string())
+}
@@ -0,0 +1,263 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Hits returned when the caller doesn't pick a limit
+pub const DEFAULT_LIMIT: usize = 20;
+
+/// Most hits one search may return
+pub const MAX_LIMIT: usize = 100;
+
+/// Characters of context kept on each side of the first match
+const SNIPPET_CONTEXT: usize = 60;
+
+/// A run of snippet text, highlighted if it matched the query
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SnippetSpan {
+    pub text: String,
+    pub matched: bool,
+}
+
+/// The first message of a session that matched
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub session_id: String,
+    pub project_path: String,
+    /// RFC 3339 time of the matching message
+    pub timestamp: String,
+    /// Text around the match, split into highlighted and plain spans
+    pub snippet: Vec<SnippetSpan>,
+}
+
+/// Search the user and assistant text of every Claude transcript, most
+/// recently active sessions first. A message matches when it contains every
+/// word of `query` (case-insensitive); each session is reported once.
+pub fn search_conversations(query: &str, limit: usize) -> Vec<SearchHit> {
+    let words: Vec<Vec<char>> = query.split_whitespace().map(lowercase_chars).collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+    let limit = limit.clamp(1, MAX_LIMIT);
+
+    let mut hits = Vec::new();
+    for path in transcripts_newest_first() {
+        if let Some(hit) = search_file(&path, &words) {
+            hits.push(hit);
+            if hits.len() >= limit {
+                break;
+            }
+        }
+    }
+    hits
+}
+
+/// Every transcript under ~/.claude/projects, most recently modified first
+fn transcripts_newest_first() -> Vec<PathBuf> {
+    let Some(projects_dir) = dirs::home_dir().map(|home| home.join(".claude").join("projects"))
+    else {
+        return Vec::new();
+    };
+    let Ok(project_dirs) = fs::read_dir(projects_dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<(SystemTime, PathBuf)> = project_dirs
+        .flatten()
+        .filter_map(|dir| fs::read_dir(dir.path()).ok())
+        .flat_map(|files| files.flatten())
+        .map(|file| file.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("jsonl"))
+        .map(|path| {
+            let modified = fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            (modified, path)
+        })
+        .collect();
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+fn search_file(path: &Path, words: &[Vec<char>]) -> Option<SearchHit> {
+    let file = File::open(path).ok()?;
+    let session_id = path.file_stem()?.to_string_lossy().to_string();
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        // Cheap pre-filter before full JSON parsing; misses only words
+        // the JSON escapes, which are rare in search queries
+        let lowered = line.to_lowercase();
+        if !words
+            .iter()
+            .all(|word| lowered.contains(&word.iter().collect::<String>()))
+        {
+            continue;
+        }
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if !matches!(entry["type"].as_str(), Some("user" | "assistant")) {
+            continue;
+        }
+
+        for text in message_texts(&entry["message"]["content"]) {
+            if let Some(snippet) = snippet(text, words) {
+                return Some(SearchHit {
+                    session_id,
+                    project_path: entry["cwd"].as_str().unwrap_or_default().to_string(),
+                    timestamp: entry["timestamp"].as_str().unwrap_or_default().to_string(),
+                    snippet,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Plain text of a message: the string itself or its text blocks
+fn message_texts(content: &Value) -> Vec<&str> {
+    match content {
+        Value::String(text) => vec![text.as_str()],
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter(|block| block["type"] == "text")
+            .filter_map(|block| block["text"].as_str())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Lowercase char by char so offsets line up with the original text
+fn lowercase_chars(text: &str) -> Vec<char> {
+    text.chars()
+        .map(|c| c.to_lowercase().next().unwrap_or(c))
+        .collect()
+}
+
+/// Highlighted excerpt of `text` around its first match, if every word occurs
+fn snippet(text: &str, words: &[Vec<char>]) -> Option<Vec<SnippetSpan>> {
+    let original: Vec<char> = text.chars().collect();
+    let lowered = lowercase_chars(text);
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for word in words {
+        let mut found = false;
+        let mut start = 0;
+        while start + word.len() <= lowered.len() {
+            if lowered[start..start + word.len()] == word[..] {
+                ranges.push((start, start + word.len()));
+                found = true;
+                start += word.len();
+            } else {
+                start += 1;
+            }
+        }
+        if !found {
+            return None;
+        }
+    }
+    ranges.sort();
+
+    let window_start = ranges[0].0.saturating_sub(SNIPPET_CONTEXT);
+    let window_end = (ranges[0].1 + SNIPPET_CONTEXT).min(original.len());
+
+    let mut spans: Vec<SnippetSpan> = Vec::new();
+    let mut push = |from: usize, to: usize, matched: bool| {
+        if from >= to {
+            return;
+        }
+        let text: String = original[from..to]
+            .iter()
+            .map(|&c| if c.is_whitespace() { ' ' } else { c })
+            .collect();
+        match spans.last_mut() {
+            Some(last) if last.matched == matched => last.text.push_str(&text),
+            _ => spans.push(SnippetSpan { text, matched }),
+        }
+    };
+
+    let mut position = window_start;
+    for (start, end) in ranges {
+        let (start, end) = (start.max(position), end.min(window_end));
+        if start >= window_end {
+            break;
+        }
+        if end <= position {
+            continue;
+        }
+        push(position, start, false);
+        push(start, end, true);
+        position = end;
+    }
+    push(position, window_end, false);
+
+    if window_start > 0 {
+        match spans.first_mut() {
+            Some(first) if !first.matched => first.text.insert(0, '…'),
+            _ => spans.insert(
+                0,
+                SnippetSpan {
+                    text: "…".to_string(),
+                    matched: false,
+                },
+            ),
+        }
+    }
+    if window_end < original.len() {
+        match spans.last_mut() {
+            Some(last) if !last.matched => last.text.push('…'),
+            _ => spans.push(SnippetSpan {
+                text: "…".to_string(),
+                matched: false,
+            }),
+        }
+    }
+    Some(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(query: &str) -> Vec<Vec<char>> {
+        query.split_whitespace().map(lowercase_chars).collect()
+    }
+
+    fn render(spans: &[SnippetSpan]) -> String {
+        spans
+            .iter()
+            .map(|span| {
+                if span.matched {
+                    format!("[{}]", span.text)
+                } else {
+                    span.text.clone()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_snippet_highlights_every_word() {
+        let spans = snippet(
+            "Please write the\nMigration script for users",
+            &words("migration SCRIPT"),
+        )
+        .unwrap();
+        assert_eq!(
+            render(&spans),
+            "Please write the [Migration] [script] for users"
+        );
+
+        assert!(snippet("migration only", &words("migration script")).is_none());
+
+        let long = format!("{}needle{}", "a".repeat(100), "b".repeat(100));
+        let spans = snippet(&long, &words("needle")).unwrap();
+        assert_eq!(
+            render(&spans),
+            format!("…{}[needle]{}…", "a".repeat(60), "b".repeat(60))
+        );
+    }
+}
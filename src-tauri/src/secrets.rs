@@ -0,0 +1,43 @@
+use keyring::Entry;
+
+/// Service name all c9watch secrets are filed under in the platform store:
+/// macOS Keychain, Windows Credential Manager, or Secret Service on Linux.
+///
+/// Every function below errors when the store is unavailable (e.g. no Secret
+/// Service daemon on a headless Linux box) so callers can fall back.
+const SERVICE: &str = "c9watch";
+
+/// Key of the desktop's own WebSocket auth token
+pub const AUTH_TOKEN_KEY: &str = "auth-token";
+
+/// Key of the credential issued to a paired device
+pub fn device_key(device_id: &str) -> String {
+    format!("device:{}", device_id)
+}
+
+fn entry(key: &str) -> Result<Entry, String> {
+    Entry::new(SERVICE, key).map_err(|e| format!("Failed to open credential store: {}", e))
+}
+
+/// Read a secret. `Ok(None)` means the store works but has no such entry.
+pub fn get(key: &str) -> Result<Option<String>, String> {
+    match entry(key)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read secret {}: {}", key, e)),
+    }
+}
+
+pub fn set(key: &str, secret: &str) -> Result<(), String> {
+    entry(key)?
+        .set_password(secret)
+        .map_err(|e| format!("Failed to store secret {}: {}", key, e))
+}
+
+/// Delete a secret; deleting one that doesn't exist is not an error
+pub fn delete(key: &str) -> Result<(), String> {
+    match entry(key)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to delete secret {}: {}", key, e)),
+    }
+}
@@ -0,0 +1,239 @@
+use super::parser::{SessionEntry, SessionEntryBase};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// A place where the conversation was rewound and taken another way
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchPoint {
+    /// Entry the alternatives all follow
+    pub parent_uuid: String,
+    /// First entry of each alternative, oldest first
+    pub alternatives: Vec<String>,
+    /// Index into `alternatives` of the one on the chosen branch
+    pub selected: usize,
+}
+
+/// One path through a transcript's tree
+#[derive(Debug, Default)]
+pub struct Branch {
+    /// Branch points along the path, in conversation order
+    pub points: Vec<BranchPoint>,
+    /// Entries on the alternatives not taken
+    pub hidden: HashSet<String>,
+}
+
+impl Branch {
+    /// Drop the entries that aren't on this branch
+    pub fn retain(&self, entries: &mut Vec<SessionEntry>) {
+        entries.retain(|entry| match entry.base() {
+            Some(base) => !self.hidden.contains(&base.uuid),
+            None => true,
+        });
+    }
+}
+
+/// Pick a path through the transcript's tree.
+///
+/// Rewinding a session (editing an earlier prompt, retrying a response)
+/// doesn't rewrite the file: the new attempt is appended with the same
+/// `parentUuid` as the old one. At each such branch point the alternative
+/// leading to `through` (any entry uuid) is taken, otherwise the one written
+/// most recently, which is where the session actually is now.
+pub fn resolve(entries: &[SessionEntry], through: Option<&str>) -> Branch {
+    let nodes: Vec<(&SessionEntryBase, &SessionEntry)> = entries
+        .iter()
+        .filter_map(|entry| Some((entry.base()?, entry)))
+        .filter(|(base, _)| base.is_sidechain != Some(true))
+        .collect();
+    let index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, (base, _))| (base.uuid.as_str(), i))
+        .collect();
+    let parent_of = |i: usize| {
+        nodes[i]
+            .0
+            .parent_uuid
+            .as_deref()
+            .and_then(|parent| index.get(parent).copied())
+    };
+
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..nodes.len() {
+        if let Some(parent) = parent_of(i) {
+            children.entry(parent).or_default().push(i);
+        }
+    }
+
+    // Latest position in the file of anything under each entry; children
+    // are written after their parents, so one backwards pass is enough
+    let mut latest: Vec<usize> = (0..nodes.len()).collect();
+    for i in (0..nodes.len()).rev() {
+        if let Some(parent) = parent_of(i) {
+            latest[parent] = latest[parent].max(latest[i]);
+        }
+    }
+
+    let mut wanted: HashSet<usize> = HashSet::new();
+    let mut cursor = through.and_then(|uuid| index.get(uuid).copied());
+    while let Some(i) = cursor {
+        if !wanted.insert(i) {
+            break;
+        }
+        cursor = parent_of(i);
+    }
+
+    let mut forks: Vec<(usize, Vec<usize>)> = children
+        .iter()
+        .map(|(&parent, kids)| {
+            let alternatives: Vec<usize> = kids
+                .iter()
+                .copied()
+                .filter(|&kid| starts_alternative(nodes[kid].1, nodes[parent].1))
+                .collect();
+            (parent, alternatives)
+        })
+        .filter(|(_, alternatives)| alternatives.len() > 1)
+        .collect();
+    forks.sort_by_key(|(parent, _)| *parent);
+
+    let mut branch = Branch::default();
+    let mut hidden: HashSet<usize> = HashSet::new();
+    for (parent, alternatives) in forks {
+        // Inside an alternative that was already left out
+        if hidden.contains(&parent) {
+            continue;
+        }
+        let selected = alternatives
+            .iter()
+            .position(|kid| wanted.contains(kid))
+            .unwrap_or_else(|| {
+                (0..alternatives.len())
+                    .max_by_key(|&a| latest[alternatives[a]])
+                    .unwrap_or(0)
+            });
+
+        for (a, &kid) in alternatives.iter().enumerate() {
+            if a == selected {
+                continue;
+            }
+            let mut stack = vec![kid];
+            while let Some(i) = stack.pop() {
+                if hidden.insert(i) {
+                    stack.extend(children.get(&i).into_iter().flatten().copied());
+                }
+            }
+        }
+        branch.points.push(BranchPoint {
+            parent_uuid: nodes[parent].0.uuid.clone(),
+            alternatives: alternatives
+                .iter()
+                .map(|&kid| nodes[kid].0.uuid.clone())
+                .collect(),
+            selected,
+        });
+    }
+    branch.hidden = hidden
+        .into_iter()
+        .map(|i| nodes[i].0.uuid.clone())
+        .collect();
+    branch
+}
+
+/// Whether `child` begins a separate attempt after `parent`, rather than
+/// continuing it. Streamed responses write one entry per content block and
+/// parallel tool calls get their results as siblings; neither is a rewind.
+fn starts_alternative(child: &SessionEntry, parent: &SessionEntry) -> bool {
+    match (child, parent) {
+        (SessionEntry::User { message, .. }, _) => !message.is_tool_result,
+        (
+            SessionEntry::Assistant { message, .. },
+            SessionEntry::Assistant {
+                message: previous, ..
+            },
+        ) => message.id != previous.id,
+        (SessionEntry::Assistant { .. }, _) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(uuid: &str, parent: Option<&str>, text: &str) -> SessionEntry {
+        serde_json::from_value(serde_json::json!({
+            "type": "user",
+            "uuid": uuid,
+            "parentUuid": parent,
+            "timestamp": "2026-01-08T15:23:03.096Z",
+            "message": { "role": "user", "content": text }
+        }))
+        .unwrap()
+    }
+
+    fn assistant(uuid: &str, parent: &str, id: &str) -> SessionEntry {
+        serde_json::from_value(serde_json::json!({
+            "type": "assistant",
+            "uuid": uuid,
+            "parentUuid": parent,
+            "timestamp": "2026-01-08T15:23:03.096Z",
+            "message": {
+                "model": "claude-sonnet-4-5",
+                "id": id,
+                "role": "assistant",
+                "content": [{ "type": "text", "text": uuid }]
+            }
+        }))
+        .unwrap()
+    }
+
+    fn uuids(entries: &[SessionEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .filter_map(|e| e.base().map(|b| b.uuid.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_prefers_latest_branch() {
+        let entries = vec![
+            user("u1", None, "write the script"),
+            assistant("a1", "u1", "msg_1"),
+            // Streamed second block of the same response: not a fork
+            assistant("a1b", "a1", "msg_1"),
+            user("u2", Some("a1b"), "now add tests"),
+            assistant("a2", "u2", "msg_2"),
+            // Prompt edited: rewound to a1b and asked something else
+            user("u2x", Some("a1b"), "now add docs"),
+            assistant("a2x", "u2x", "msg_3"),
+        ];
+
+        let branch = resolve(&entries, None);
+        assert_eq!(
+            branch.points,
+            vec![BranchPoint {
+                parent_uuid: "a1b".to_string(),
+                alternatives: vec!["u2".to_string(), "u2x".to_string()],
+                selected: 1,
+            }]
+        );
+        let mut active = entries.clone();
+        branch.retain(&mut active);
+        assert_eq!(uuids(&active), vec!["u1", "a1", "a1b", "u2x", "a2x"]);
+
+        // Exploring the abandoned alternative
+        let branch = resolve(&entries, Some("a2"));
+        assert_eq!(branch.points[0].selected, 0);
+        let mut old = entries.clone();
+        branch.retain(&mut old);
+        assert_eq!(uuids(&old), vec!["u1", "a1", "a1b", "u2", "a2"]);
+
+        // A linear transcript has nothing to choose
+        let linear = entries[..5].to_vec();
+        let branch = resolve(&linear, None);
+        assert!(branch.points.is_empty());
+        assert!(branch.hidden.is_empty());
+    }
+}
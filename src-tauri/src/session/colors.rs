@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Labels the user can give a session to pick it out at a glance. A fixed
+/// palette rather than free-form colors, so every client can render them
+/// legibly in both themes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Teal,
+    Blue,
+    Purple,
+    Pink,
+}
+
+/// Color labels the user has given sessions
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct SessionColors {
+    pub colors: HashMap<String, SessionColor>,
+}
+
+impl SessionColors {
+    pub fn load() -> Self {
+        let path = Self::get_path();
+        if let Ok(content) = fs::read_to_string(path) {
+            serde_json::from_str(&content).unwrap_or_default()
+        } else {
+            Self::default()
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::get_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| e.to_string())
+    }
+
+    fn get_path() -> PathBuf {
+        let home = dirs::home_dir().expect("Failed to get home directory");
+        home.join(".claude").join("session-monitor-colors.json")
+    }
+
+    pub fn get(&self, session_id: &str) -> Option<SessionColor> {
+        self.colors.get(session_id).copied()
+    }
+
+    /// Set a session's color; None removes it
+    pub fn set(&mut self, session_id: String, color: Option<SessionColor>) {
+        match color {
+            Some(color) => {
+                self.colors.insert(session_id, color);
+            }
+            None => {
+                self.colors.remove(&session_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_clear_color() {
+        let mut colors = SessionColors::default();
+        colors.set("abc".to_string(), Some(SessionColor::Teal));
+        assert_eq!(colors.get("abc"), Some(SessionColor::Teal));
+        assert_eq!(
+            serde_json::to_string(&colors.colors).unwrap(),
+            r#"{"abc":"teal"}"#
+        );
+
+        colors.set("abc".to_string(), None);
+        assert!(colors.get("abc").is_none());
+    }
+}
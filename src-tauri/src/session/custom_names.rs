@@ -74,7 +74,12 @@ impl CustomTitles {
         self.titles.get(session_id)
     }
 
+    /// Set a session's custom title; a blank title clears it
     pub fn set(&mut self, session_id: String, title: String) {
-        self.titles.insert(session_id, title);
+        if title.is_empty() {
+            self.titles.remove(&session_id);
+        } else {
+            self.titles.insert(session_id, title);
+        }
     }
 }
@@ -0,0 +1,259 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Global parse diagnostics, updated every time a session file is parsed
+static DIAGNOSTICS: OnceLock<ParseDiagnostics> = OnceLock::new();
+
+pub fn diagnostics() -> &'static ParseDiagnostics {
+    DIAGNOSTICS.get_or_init(ParseDiagnostics::default)
+}
+
+/// Maximum length of the offending line kept for display
+const SAMPLE_LINE_CHARS: usize = 300;
+
+/// Top-level JSONL fields c9watch knows about, whether it uses them or not.
+/// Anything else on a user/assistant/system entry is reported as unknown so
+/// schema changes in Claude Code show up here first.
+const KNOWN_ENTRY_FIELDS: &[&str] = &[
+    "type",
+    "uuid",
+    "timestamp",
+    "sessionId",
+    "cwd",
+    "version",
+    "gitBranch",
+    "parentUuid",
+    "logicalParentUuid",
+    "isSidechain",
+    "slug",
+    "message",
+    "userType",
+    "requestId",
+    "isMeta",
+    "isCompactSummary",
+    "isApiErrorMessage",
+    "isVisibleInTranscriptOnly",
+    "toolUseResult",
+    "thinkingMetadata",
+    "todos",
+    "agentId",
+    "subtype",
+    "content",
+    "level",
+    "compactMetadata",
+    "toolUseID",
+    "permissionMode",
+];
+
+/// Newest Claude Code release (major.minor) whose JSONL output the fields
+/// above were checked against
+pub const SCHEMA_CLAUDE_VERSION: (u32, u32) = (2, 0);
+
+/// Outcome of the most recent parse of a single JSONL file
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileParseStats {
+    pub path: String,
+    /// Lines that parsed with the strict schema
+    pub parsed: u32,
+    /// Lines that only parsed after lenient repair
+    pub recovered: u32,
+    /// Lines that could not be parsed at all and were dropped
+    pub failures: u32,
+    /// Entry `type` values that fell through to `SessionEntry::Unknown`
+    pub unknown_types: BTreeMap<String, u32>,
+    /// Unrecognised top-level fields on user/assistant/system entries
+    pub unknown_fields: BTreeMap<String, u32>,
+    pub last_error: Option<String>,
+    /// Truncated copy of the last line that failed
+    pub last_error_line: Option<String>,
+    /// When this file was last parsed (RFC 3339)
+    pub updated_at: String,
+}
+
+impl FileParseStats {
+    /// Fold in the results of parsing more lines from the same file
+    pub fn merge(&mut self, other: FileParseStats) {
+        self.parsed += other.parsed;
+        self.recovered += other.recovered;
+        self.failures += other.failures;
+        for (key, count) in other.unknown_types {
+            *self.unknown_types.entry(key).or_insert(0) += count;
+        }
+        for (key, count) in other.unknown_fields {
+            *self.unknown_fields.entry(key).or_insert(0) += count;
+        }
+        if other.last_error.is_some() {
+            self.last_error = other.last_error;
+            self.last_error_line = other.last_error_line;
+        }
+    }
+
+    pub(crate) fn record_failure(&mut self, error: String, line: &str) {
+        self.failures += 1;
+        self.last_error = Some(error);
+        self.last_error_line = Some(line.chars().take(SAMPLE_LINE_CHARS).collect());
+    }
+
+    /// Tally unknown entry types and top-level fields for a parsed line
+    pub(crate) fn inspect(&mut self, value: &serde_json::Value) {
+        let Some(object) = value.as_object() else {
+            return;
+        };
+        let entry_type = object.get("type").and_then(|t| t.as_str()).unwrap_or("");
+
+        match entry_type {
+            "user" | "assistant" | "system" => {
+                for key in object.keys() {
+                    if !KNOWN_ENTRY_FIELDS.contains(&key.as_str()) {
+                        *self.unknown_fields.entry(key.clone()).or_insert(0) += 1;
+                    }
+                }
+            }
+            "summary" | "file-history-snapshot" | "progress" => {}
+            other => {
+                *self.unknown_types.entry(other.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+}
+
+/// Per-file parse statistics, keyed by JSONL path
+#[derive(Debug, Default)]
+pub struct ParseDiagnostics {
+    /// Running totals from the tail reader, for files being watched
+    files: Mutex<HashMap<PathBuf, FileParseStats>>,
+    /// One-off reads (opening a conversation, scanning a file's tail). Kept
+    /// apart so they don't overwrite the tail reader's totals, and only
+    /// shown for files it isn't tracking.
+    reads: Mutex<HashMap<PathBuf, FileParseStats>>,
+}
+
+impl ParseDiagnostics {
+    /// Replace the tail reader's stats for a file
+    pub fn record(&self, path: &Path, stats: FileParseStats) {
+        Self::insert(&self.files, path, stats);
+    }
+
+    /// Replace the stats of the latest one-off read of a file
+    pub fn record_read(&self, path: &Path, stats: FileParseStats) {
+        Self::insert(&self.reads, path, stats);
+    }
+
+    fn insert(
+        map: &Mutex<HashMap<PathBuf, FileParseStats>>,
+        path: &Path,
+        mut stats: FileParseStats,
+    ) {
+        stats.path = path.to_string_lossy().to_string();
+        stats.updated_at = Utc::now().to_rfc3339();
+        map.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(path.to_path_buf(), stats);
+    }
+
+    /// Drop stats for files no longer being watched
+    pub fn retain(&self, paths: &HashSet<PathBuf>) {
+        for map in [&self.files, &self.reads] {
+            map.lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .retain(|path, _| paths.contains(path));
+        }
+    }
+
+    /// All tracked files, worst first
+    pub fn snapshot(&self) -> Vec<FileParseStats> {
+        let files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        let reads = self.reads.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stats: Vec<FileParseStats> = files
+            .values()
+            .chain(
+                reads
+                    .iter()
+                    .filter(|(path, _)| !files.contains_key(*path))
+                    .map(|(_, stats)| stats),
+            )
+            .cloned()
+            .collect();
+        stats.sort_by(|a, b| {
+            (b.failures, b.recovered)
+                .cmp(&(a.failures, a.recovered))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inspect_tallies_unknown_types_and_fields() {
+        let mut stats = FileParseStats::default();
+        stats.inspect(&serde_json::json!({
+            "type": "user",
+            "uuid": "u1",
+            "timestamp": "2026-01-08T15:23:03.096Z",
+            "message": {},
+            "brandNewField": true
+        }));
+        stats.inspect(&serde_json::json!({"type": "queue-operation"}));
+        stats.inspect(&serde_json::json!({"type": "progress"}));
+
+        assert_eq!(stats.unknown_fields.get("brandNewField"), Some(&1));
+        assert_eq!(stats.unknown_fields.len(), 1);
+        assert_eq!(stats.unknown_types.get("queue-operation"), Some(&1));
+        assert_eq!(stats.unknown_types.len(), 1);
+    }
+
+    #[test]
+    fn test_snapshot_orders_by_failures() {
+        let diagnostics = ParseDiagnostics::default();
+        diagnostics.record(Path::new("/a.jsonl"), FileParseStats::default());
+        diagnostics.record(
+            Path::new("/b.jsonl"),
+            FileParseStats {
+                failures: 2,
+                ..Default::default()
+            },
+        );
+
+        let snapshot = diagnostics.snapshot();
+        assert_eq!(snapshot[0].path, "/b.jsonl");
+        assert_eq!(snapshot[1].path, "/a.jsonl");
+    }
+
+    #[test]
+    fn test_reads_dont_replace_tail_stats() {
+        let diagnostics = ParseDiagnostics::default();
+        diagnostics.record(
+            Path::new("/a.jsonl"),
+            FileParseStats {
+                parsed: 40,
+                ..Default::default()
+            },
+        );
+        diagnostics.record_read(
+            Path::new("/a.jsonl"),
+            FileParseStats {
+                parsed: 900,
+                ..Default::default()
+            },
+        );
+        diagnostics.record_read(Path::new("/b.jsonl"), FileParseStats::default());
+
+        let snapshot = diagnostics.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        let a = snapshot.iter().find(|s| s.path == "/a.jsonl").unwrap();
+        assert_eq!(a.parsed, 40);
+
+        diagnostics.retain(&HashSet::from([PathBuf::from("/b.jsonl")]));
+        let snapshot = diagnostics.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].path, "/b.jsonl");
+    }
+}
@@ -0,0 +1,95 @@
+use crate::error::C9Error;
+use std::path::{Path, PathBuf};
+
+/// Longer than any agent's IDs (UUIDs, `aider-…`, `ses_…`)
+const MAX_LEN: usize = 128;
+
+/// Reject session IDs that could be anything other than an ID: every agent's
+/// IDs are ASCII letters, digits, `-` and `_`, so path separators, `..` and
+/// NULs never get as far as a filename.
+pub fn validate_session_id(session_id: &str) -> Result<(), C9Error> {
+    let valid = !session_id.is_empty()
+        && session_id.len() <= MAX_LEN
+        && session_id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+    if valid {
+        Ok(())
+    } else {
+        Err(C9Error::ParseError(format!(
+            "Invalid session ID: {:?}",
+            session_id
+        )))
+    }
+}
+
+/// Claude (and Codex/Gemini) session IDs: 8-4-4-4-12 hex digits
+pub fn is_uuid(session_id: &str) -> bool {
+    let groups: Vec<&str> = session_id.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// `path` with symlinks resolved, provided it's still inside `dir`
+pub fn contained_in(path: &Path, dir: &Path) -> Result<PathBuf, C9Error> {
+    let dir = dir.canonicalize()?;
+    let path = path.canonicalize()?;
+    if path.starts_with(&dir) {
+        Ok(path)
+    } else {
+        Err(C9Error::PermissionDenied(format!(
+            "{} is outside {}",
+            path.display(),
+            dir.display()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_session_id() {
+        for id in [
+            "0a5b3c1d-2e4f-4a6b-8c9d-0e1f2a3b4c5d",
+            "aider-3f2a9c01b7d4e8f6a5",
+            "ses_4kP9xQz2",
+        ] {
+            assert!(validate_session_id(id).is_ok(), "{}", id);
+        }
+        for id in [
+            "",
+            "..",
+            "../../../etc/passwd",
+            "..\\..\\windows\\win.ini",
+            "/etc/passwd",
+            "abc\0def",
+            "abc.jsonl",
+            &"a".repeat(MAX_LEN + 1),
+        ] {
+            assert!(validate_session_id(id).is_err(), "{:?}", id);
+        }
+
+        assert!(is_uuid("0a5b3c1d-2e4f-4a6b-8c9d-0e1f2a3b4c5d"));
+        assert!(!is_uuid("aider-3f2a9c01b7d4e8f6a5"));
+        assert!(!is_uuid("0a5b3c1d-2e4f-4a6b-8c9d-0e1f2a3b4c5"));
+    }
+
+    #[test]
+    fn test_contained_in() {
+        let dir = std::env::temp_dir().join(format!("c9watch-id-{}", std::process::id()));
+        let inner = dir.join("project");
+        std::fs::create_dir_all(&inner).unwrap();
+        let file = inner.join("session.jsonl");
+        std::fs::write(&file, "").unwrap();
+
+        assert!(contained_in(&file, &dir).is_ok());
+        assert!(contained_in(&inner.join("..").join("..").join(".."), &dir).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,120 @@
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+/// Sessions and projects the user has chosen to hide from the monitor.
+///
+/// Ignored sessions are dropped before enrichment, so they never show up in
+/// the session list and never trigger notifications.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnoreList {
+    /// Exact session IDs to hide
+    #[serde(default)]
+    pub session_ids: HashSet<String>,
+    /// Glob patterns matched against the session's project path,
+    /// e.g. "/Users/me/automation/*" or "**/scratch-*"
+    #[serde(default)]
+    pub project_patterns: Vec<String>,
+}
+
+impl IgnoreList {
+    pub fn load() -> Self {
+        let path = Self::get_path();
+        if let Ok(content) = fs::read_to_string(path) {
+            serde_json::from_str(&content).unwrap_or_default()
+        } else {
+            Self::default()
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::get_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| e.to_string())
+    }
+
+    fn get_path() -> PathBuf {
+        let home = dirs::home_dir().expect("Failed to get home directory");
+        home.join(".claude").join("session-monitor-ignored.json")
+    }
+
+    /// Check whether a session should be hidden, either by ID or by project path
+    pub fn is_ignored(&self, session_id: &str, project_path: &str) -> bool {
+        if self.session_ids.contains(session_id) {
+            return true;
+        }
+
+        self.project_patterns.iter().any(|pattern| {
+            Pattern::new(pattern)
+                .map(|p| p.matches(project_path))
+                .unwrap_or(false)
+        })
+    }
+
+    pub fn ignore_session(&mut self, session_id: String) {
+        self.session_ids.insert(session_id);
+    }
+
+    pub fn unignore_session(&mut self, session_id: &str) {
+        self.session_ids.remove(session_id);
+    }
+
+    /// Validate every project pattern, returning the first invalid one as an error
+    pub fn validate(&self) -> Result<(), String> {
+        for pattern in &self.project_patterns {
+            Pattern::new(pattern)
+                .map_err(|e| format!("Invalid ignore pattern '{}': {}", pattern, e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignore_by_session_id() {
+        let mut list = IgnoreList::default();
+        list.ignore_session("abc".to_string());
+
+        assert!(list.is_ignored("abc", "/Users/test/project"));
+        assert!(!list.is_ignored("def", "/Users/test/project"));
+
+        list.unignore_session("abc");
+        assert!(!list.is_ignored("abc", "/Users/test/project"));
+    }
+
+    #[test]
+    fn test_ignore_by_project_pattern() {
+        let list = IgnoreList {
+            session_ids: HashSet::new(),
+            project_patterns: vec![
+                "/Users/test/automation/*".to_string(),
+                "**/scratch-*".to_string(),
+            ],
+        };
+
+        assert!(list.is_ignored("a", "/Users/test/automation/nightly"));
+        assert!(list.is_ignored("b", "/tmp/work/scratch-42"));
+        assert!(!list.is_ignored("c", "/Users/test/c9watch"));
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_reported() {
+        let list = IgnoreList {
+            session_ids: HashSet::new(),
+            project_patterns: vec!["[unclosed".to_string()],
+        };
+
+        assert!(list.validate().is_err());
+        // Invalid patterns never match instead of panicking
+        assert!(!list.is_ignored("a", "[unclosed"));
+    }
+}
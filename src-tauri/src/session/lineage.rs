@@ -0,0 +1,188 @@
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Resume markers are written before the new session's first prompt
+const HEAD_LINES: usize = 50;
+
+/// Longest chain of resumes followed back
+const MAX_DEPTH: usize = 20;
+
+/// Lineage per transcript. A session's head never changes once written, so
+/// each file is only read once.
+static LINEAGES: OnceLock<Mutex<HashMap<PathBuf, Vec<String>>>> = OnceLock::new();
+
+/// Earlier sessions this one was resumed from, oldest first.
+///
+/// `/resume` starts a new transcript for the same work. Claude Code either
+/// copies the old conversation in (entries keep the old `sessionId`) or
+/// writes `summary` entries whose `leafUuid` is the last message of the old
+/// transcript; either is followed back to the session it came from.
+pub fn lineage(transcript: &Path) -> Vec<String> {
+    let lineages = LINEAGES.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(lineage) = lineages
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(transcript)
+    {
+        return lineage.clone();
+    }
+
+    let lineage = trace(transcript);
+    lineages
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(transcript.to_path_buf(), lineage.clone());
+    lineage
+}
+
+/// Forget lineages of transcripts no longer being watched
+pub fn retain(paths: &HashSet<PathBuf>) {
+    if let Some(lineages) = LINEAGES.get() {
+        lineages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|path, _| paths.contains(path));
+    }
+}
+
+fn trace(transcript: &Path) -> Vec<String> {
+    let Some(dir) = transcript.parent() else {
+        return Vec::new();
+    };
+    let mut chain: Vec<String> = Vec::new();
+    let mut current = transcript.to_path_buf();
+    while chain.len() < MAX_DEPTH {
+        let Some(parent) = resumed_from(&current) else {
+            break;
+        };
+        // The ID comes from the transcript, so check it before it names a file
+        if super::id::validate_session_id(&parent).is_err()
+            || chain.contains(&parent)
+            || Some(parent.as_str()) == session_id(transcript)
+        {
+            break;
+        }
+        current = dir.join(format!("{}.jsonl", parent));
+        chain.push(parent);
+    }
+    chain.reverse();
+    chain
+}
+
+fn session_id(transcript: &Path) -> Option<&str> {
+    transcript.file_stem().and_then(|stem| stem.to_str())
+}
+
+/// The session a transcript was resumed from, if it was
+fn resumed_from(transcript: &Path) -> Option<String> {
+    let own_id = session_id(transcript)?;
+    let file = File::open(transcript).ok()?;
+
+    let mut leaf_uuids = Vec::new();
+    for line in BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .take(HEAD_LINES)
+    {
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if entry["type"] == "summary" {
+            if let Some(leaf) = entry["leafUuid"].as_str() {
+                leaf_uuids.push(leaf.to_string());
+            }
+            continue;
+        }
+        if let Some(id) = entry["sessionId"].as_str() {
+            if id != own_id {
+                return Some(id.to_string());
+            }
+        }
+    }
+
+    // Summaries can also describe this session itself; only a leaf that
+    // lives in another transcript marks a resume
+    let dir = transcript.parent()?;
+    let own = fs::read_to_string(transcript).unwrap_or_default();
+    let markers: Vec<String> = leaf_uuids
+        .iter()
+        .map(|leaf| format!("\"uuid\":\"{}\"", leaf))
+        .filter(|marker| !own.contains(marker.as_str()))
+        .collect();
+    if markers.is_empty() {
+        return None;
+    }
+
+    // Later resumes may have copied the same entry in; the oldest file is
+    // where it was first written
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("jsonl"))
+        .filter(|path| path != transcript)
+        .filter(|path| {
+            let content = fs::read_to_string(path).unwrap_or_default();
+            markers
+                .iter()
+                .any(|marker| content.contains(marker.as_str()))
+        })
+        .min_by_key(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .and_then(|path| session_id(&path).map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lineage_follows_resumes_back() {
+        let dir = std::env::temp_dir().join(format!("c9watch-lineage-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let write = |id: &str, lines: &[&str]| {
+            let path = dir.join(format!("{}.jsonl", id));
+            fs::write(&path, lines.join("\n")).unwrap();
+            path
+        };
+
+        write(
+            "s1",
+            &[r#"{"type":"user","uuid":"u1","sessionId":"s1","message":{"content":"start"}}"#],
+        );
+        // Resumed by summary pointing at s1's last message
+        write(
+            "s2",
+            &[
+                r#"{"type":"summary","summary":"Start","leafUuid":"u1"}"#,
+                r#"{"type":"user","uuid":"u2","sessionId":"s2","message":{"content":"go on"}}"#,
+            ],
+        );
+        // Resumed by copying s2's conversation in
+        let s3 = write(
+            "s3",
+            &[
+                r#"{"type":"user","uuid":"u2","sessionId":"s2","message":{"content":"go on"}}"#,
+                r#"{"type":"user","uuid":"u3","sessionId":"s3","message":{"content":"more"}}"#,
+            ],
+        );
+        // A summary of its own conversation isn't a resume
+        let s4 = write(
+            "s4",
+            &[
+                r#"{"type":"summary","summary":"Own","leafUuid":"u4"}"#,
+                r#"{"type":"user","uuid":"u4","sessionId":"s4","message":{"content":"new"}}"#,
+            ],
+        );
+
+        let s3_lineage = trace(&s3);
+        let s4_lineage = trace(&s4);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(s3_lineage, vec!["s1", "s2"]);
+        assert!(s4_lineage.is_empty());
+    }
+}
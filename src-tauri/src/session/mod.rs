@@ -1,16 +1,43 @@
+pub mod branches;
+pub mod colors;
 pub mod custom_names;
 pub mod detector;
+pub mod diagnostics;
+pub mod id;
+pub mod ignore_list;
+pub mod lineage;
+pub mod notes;
 pub mod parser;
 pub mod permissions;
 pub mod status;
+pub mod tail;
+pub mod title;
+pub mod todos;
 
+pub use branches::BranchPoint;
+pub use colors::{SessionColor, SessionColors};
 pub use custom_names::{CustomNames, CustomTitles};
-pub use detector::{DetectedSession, SessionDetector};
+pub use detector::{
+    AgentProcess, DetectedSession, ProcessUsage, SessionDetector, SessionDetectorError,
+};
+pub use diagnostics::{diagnostics, FileParseStats};
+pub use id::validate_session_id;
+pub use ignore_list::IgnoreList;
+pub use lineage::lineage;
+pub use notes::SessionNotes;
 pub use parser::{
-    extract_messages, parse_all_entries, parse_last_n_entries, parse_sessions_index,
-    MessageContent, MessageType, SessionEntry, SessionIndexEntry, SessionsIndex,
+    edit_diff, extract_messages, parse_all_entries, parse_last_n_entries, parse_sessions_index,
+    preview_tool_results, ConversationFilter, ConversationMessage, EditDiff, EditHunk,
+    MessageContent, MessageType, SessionEntry, SessionIndexEntry, SessionsIndex, ToolCall,
+    ToolResultInfo,
 };
-pub use permissions::PermissionChecker;
+pub use permissions::{PermissionCheck, PermissionChecker, PermissionRule, PermissionRules};
 pub use status::{
-    determine_status, determine_status_with_context, get_pending_tool_name, SessionStatus,
+    determine_status, determine_status_in_mode, determine_status_with_context,
+    get_pending_tool_input, get_pending_tool_input_in_mode, get_pending_tool_name,
+    get_pending_tool_name_in_mode, get_permission_checker, get_permission_mode,
+    reload_permission_checker, summarize_tool_input, PermissionMode, SessionStatus,
 };
+pub use tail::tail_reader;
+pub use title::generate_title;
+pub use todos::{load_todos, TodoItem, TodoStatus};
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Free-form notes the user has attached to sessions, e.g. "waiting on PR review"
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct SessionNotes {
+    pub notes: HashMap<String, String>,
+}
+
+impl SessionNotes {
+    pub fn load() -> Self {
+        let path = Self::get_path();
+        if let Ok(content) = fs::read_to_string(path) {
+            serde_json::from_str(&content).unwrap_or_default()
+        } else {
+            Self::default()
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::get_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| e.to_string())
+    }
+
+    fn get_path() -> PathBuf {
+        let home = dirs::home_dir().expect("Failed to get home directory");
+        home.join(".claude").join("session-monitor-notes.json")
+    }
+
+    pub fn get(&self, session_id: &str) -> Option<&String> {
+        self.notes.get(session_id)
+    }
+
+    /// Set a session's note; blank text removes it
+    pub fn set(&mut self, session_id: String, text: String) {
+        let text = text.trim();
+        if text.is_empty() {
+            self.notes.remove(&session_id);
+        } else {
+            self.notes.insert(session_id, text.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blank_note_clears() {
+        let mut notes = SessionNotes::default();
+        notes.set("abc".to_string(), "  waiting on PR review\n".to_string());
+        assert_eq!(notes.get("abc").unwrap(), "waiting on PR review");
+
+        notes.set("abc".to_string(), "   ".to_string());
+        assert!(notes.get("abc").is_none());
+    }
+}
@@ -1,7 +1,10 @@
+use super::diagnostics::{diagnostics, FileParseStats};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Represents the sessions-index.json file structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +45,15 @@ pub enum SessionEntry {
         base: SessionEntryBase,
         message: AssistantMessage,
     },
+    /// Slash command output, compaction boundaries, API errors and other
+    /// notices Claude Code writes outside the conversation
+    System {
+        #[serde(flatten)]
+        base: SessionEntryBase,
+        subtype: Option<String>,
+        content: Option<String>,
+        level: Option<String>,
+    },
     #[serde(rename = "file-history-snapshot")]
     FileHistorySnapshot {
         #[serde(rename = "messageId")]
@@ -59,6 +71,18 @@ pub enum SessionEntry {
     Unknown,
 }
 
+impl SessionEntry {
+    /// Common fields, for entries that are part of the conversation
+    pub fn base(&self) -> Option<&SessionEntryBase> {
+        match self {
+            SessionEntry::User { base, .. }
+            | SessionEntry::Assistant { base, .. }
+            | SessionEntry::System { base, .. } => Some(base),
+            _ => None,
+        }
+    }
+}
+
 /// Common fields shared across session entries
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -72,6 +96,10 @@ pub struct SessionEntryBase {
     pub parent_uuid: Option<String>,
     pub is_sidechain: Option<bool>,
     pub slug: Option<String>,
+    /// Permission mode in effect when a prompt was sent ("default", "plan",
+    /// "acceptEdits", "bypassPermissions"); only on user entries
+    #[serde(default)]
+    pub permission_mode: Option<String>,
 }
 
 /// User message structure
@@ -85,6 +113,17 @@ pub struct UserMessage {
     pub content: String,
     /// Whether this user entry is a tool result rather than an actual user prompt
     pub is_tool_result: bool,
+    /// Each tool_result block, in order; an entry answering parallel calls
+    /// carries several
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tool_results: Vec<ToolResultBlock>,
+}
+
+/// One tool_result block of a user entry: the call it answers and its text
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolResultBlock {
+    pub result: ToolResultInfo,
+    pub content: String,
 }
 
 impl<'de> Deserialize<'de> for UserMessage {
@@ -102,35 +141,71 @@ impl<'de> Deserialize<'de> for UserMessage {
             .to_string();
 
         let content_value = value.get("content");
+        let mut tool_results = Vec::new();
 
         let (content, is_tool_result) = match content_value {
             Some(Value::String(s)) => (s.clone(), false),
             Some(Value::Array(arr)) => {
                 let mut parts = Vec::new();
+                let mut has_tool_result = false;
+                let mut has_attachment = false;
                 for item in arr {
                     match item.get("type").and_then(|t| t.as_str()) {
                         Some("tool_result") => {
+                            has_tool_result = true;
+                            let mut block_parts = Vec::new();
                             if let Some(content) = item.get("content") {
                                 match content {
-                                    Value::String(s) => parts.push(s.clone()),
+                                    Value::String(s) => block_parts.push(s.clone()),
                                     Value::Array(inner) => {
                                         for block in inner {
                                             if let Some(text) =
                                                 block.get("text").and_then(|t| t.as_str())
                                             {
-                                                parts.push(text.to_string());
+                                                block_parts.push(text.to_string());
+                                            } else if let Some(placeholder) =
+                                                media_placeholder(block)
+                                            {
+                                                block_parts.push(placeholder);
                                             }
                                         }
                                     }
                                     _ => {}
                                 }
                             }
+                            let block_text = block_parts.join("\n");
+                            if let Some(id) = item.get("tool_use_id").and_then(|id| id.as_str()) {
+                                tool_results.push(ToolResultBlock {
+                                    result: ToolResultInfo {
+                                        tool_use_id: id.to_string(),
+                                        tool_name: None,
+                                        is_error: item
+                                            .get("is_error")
+                                            .and_then(|e| e.as_bool())
+                                            .unwrap_or(false),
+                                    },
+                                    content: if block_text.is_empty() {
+                                        "[tool result]".to_string()
+                                    } else {
+                                        block_text.clone()
+                                    },
+                                });
+                            }
+                            if !block_parts.is_empty() {
+                                parts.push(block_text);
+                            }
                         }
                         Some("text") => {
                             if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
                                 parts.push(text.to_string());
                             }
                         }
+                        Some("image") | Some("document") => {
+                            has_attachment = true;
+                            if let Some(placeholder) = media_placeholder(item) {
+                                parts.push(placeholder);
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -139,7 +214,10 @@ impl<'de> Deserialize<'de> for UserMessage {
                 } else {
                     parts.join("\n")
                 };
-                (text, true)
+                // A prompt with a pasted screenshot or attached file is still a
+                // user prompt, even though its content is an array of blocks
+                let is_tool_result = has_tool_result || !has_attachment;
+                (text, is_tool_result)
             }
             _ => (String::new(), false),
         };
@@ -148,10 +226,54 @@ impl<'de> Deserialize<'de> for UserMessage {
             role,
             content,
             is_tool_result,
+            tool_results,
         })
     }
 }
 
+/// What the text of a user entry actually represents.
+///
+/// Claude Code records slash commands and their local output as user entries
+/// wrapped in XML-style tags rather than as a separate entry type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UserContentKind {
+    /// A prompt typed by the user (or a tool result)
+    Prompt,
+    /// `<command-name>/foo</command-name><command-args>...</command-args>`
+    SlashCommand { name: String, args: String },
+    /// `<local-command-stdout>...</local-command-stdout>` from a built-in command
+    LocalCommandOutput(String),
+}
+
+impl UserMessage {
+    pub fn kind(&self) -> UserContentKind {
+        if let Some(name) = tag_contents(&self.content, "command-name") {
+            return UserContentKind::SlashCommand {
+                name: name.trim().to_string(),
+                args: tag_contents(&self.content, "command-args")
+                    .unwrap_or("")
+                    .trim()
+                    .to_string(),
+            };
+        }
+        if let Some(output) = tag_contents(&self.content, "local-command-stdout")
+            .or_else(|| tag_contents(&self.content, "local-command-stderr"))
+        {
+            return UserContentKind::LocalCommandOutput(output.trim().to_string());
+        }
+        UserContentKind::Prompt
+    }
+}
+
+/// Text between `<tag>` and `</tag>`, if present
+fn tag_contents<'a>(text: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = text.find(&open)? + open.len();
+    let end = text[start..].find(&close)? + start;
+    Some(&text[start..end])
+}
+
 /// Assistant message structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssistantMessage {
@@ -185,12 +307,90 @@ pub enum MessageContent {
         content: String,
         is_error: Option<bool>,
     },
+    /// Tool run by the API itself (e.g. web_search), not by Claude Code
+    ServerToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    /// Results of a server-side web search; `content` is a list of results or an error object
+    WebSearchToolResult {
+        tool_use_id: String,
+        content: serde_json::Value,
+    },
+    /// `source` is missing on blocks whose data was stripped from the transcript
+    Image {
+        #[serde(default)]
+        source: Option<MediaSource>,
+    },
+    Document {
+        #[serde(default)]
+        source: Option<MediaSource>,
+        title: Option<String>,
+    },
     #[serde(other)]
     Unknown,
 }
 
-/// Token usage information
+/// Source of an image or document block.
+///
+/// Inline base64 data is not kept; only what's needed to describe the attachment.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaSource {
+    /// "base64", "url", "file", or "text"
+    #[serde(rename = "type")]
+    pub source_type: String,
+    pub media_type: Option<String>,
+    pub url: Option<String>,
+    pub file_id: Option<String>,
+}
+
+/// Description of an optional source, "unknown" when there's none
+fn describe_source(source: &Option<MediaSource>) -> String {
+    source
+        .as_ref()
+        .map(MediaSource::describe)
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+impl MediaSource {
+    /// Short human-readable description, e.g. "image/png" or the URL
+    pub fn describe(&self) -> String {
+        self.url
+            .clone()
+            .or_else(|| self.media_type.clone())
+            .or_else(|| self.file_id.clone())
+            .unwrap_or_else(|| self.source_type.clone())
+    }
+}
+
+/// Placeholder text for an image or document content block
+pub fn attachment_placeholder(content: &MessageContent) -> Option<String> {
+    match content {
+        MessageContent::Image { source } => Some(format!("[Image: {}]", describe_source(source))),
+        MessageContent::Document { source, title } => Some(match (title, source) {
+            (Some(title), None) => format!("[Document: {}]", title),
+            (Some(title), Some(source)) => format!("[Document: {} ({})]", title, source.describe()),
+            (None, source) => format!("[Document: {}]", describe_source(source)),
+        }),
+        _ => None,
+    }
+}
+
+/// Placeholder text for a raw JSON image/document block
+fn media_placeholder(block: &serde_json::Value) -> Option<String> {
+    match block.get("type").and_then(|t| t.as_str()) {
+        Some("image") | Some("document") => {}
+        _ => return None,
+    }
+    let content: MessageContent = serde_json::from_value(block.clone()).ok()?;
+    attachment_placeholder(&content)
+}
+
+/// Token usage information, read in the API's snake_case and sent to the
+/// frontend in camelCase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase"))]
 pub struct Usage {
     pub input_tokens: Option<u32>,
     pub output_tokens: Option<u32>,
@@ -198,6 +398,20 @@ pub struct Usage {
     pub cache_read_input_tokens: Option<u32>,
 }
 
+/// Longest first prompt / latest message providers keep for a session. The
+/// user's (shorter) preview lengths from settings are applied in polling.
+pub const MAX_PREVIEW_CHARS: usize = 2000;
+
+/// Truncate a string to `max_chars` user-perceived characters, adding "..."
+/// when anything was cut. Counts grapheme clusters, so emoji sequences and
+/// accented letters are never split.
+pub fn truncate_string(s: &str, max_chars: usize) -> String {
+    match s.grapheme_indices(true).nth(max_chars) {
+        None => s.to_string(),
+        Some((cut, _)) => format!("{}...", s[..cut].trim_end()),
+    }
+}
+
 /// Parse a sessions-index.json file
 pub fn parse_sessions_index<P: AsRef<Path>>(path: P) -> Result<SessionsIndex, String> {
     let file = File::open(path.as_ref())
@@ -208,63 +422,173 @@ pub fn parse_sessions_index<P: AsRef<Path>>(path: P) -> Result<SessionsIndex, St
         .map_err(|e| format!("Failed to parse sessions-index.json: {}", e))
 }
 
+/// Size of the first block read from the end of a file; later blocks double
+/// so files with very long lines are still read in a handful of passes
+const TAIL_BLOCK_SIZE: u64 = 64 * 1024;
+
 /// Read the last N lines from a JSONL file efficiently
 ///
-/// This function uses a reverse-reading strategy to avoid loading
-/// the entire file into memory for large files.
+/// This function reads backwards from the end of the file in blocks, walking
+/// newline boundaries until N complete lines are found, so single lines of
+/// hundreds of KB (large tool results) are never truncated or miscounted.
 pub fn read_last_n_lines<P: AsRef<Path>>(path: P, n: usize) -> Result<Vec<String>, String> {
-    let file =
+    let mut file =
         File::open(path.as_ref()).map_err(|e| format!("Failed to open JSONL file: {}", e))?;
 
     let metadata = file
         .metadata()
         .map_err(|e| format!("Failed to read file metadata: {}", e))?;
 
-    let file_size = metadata.len();
+    last_lines_before(&mut file, metadata.len(), n)
+        .map_err(|e| format!("Failed to read JSONL file: {}", e))
+}
 
-    // If file is empty, return empty vec
-    if file_size == 0 {
+/// The last `n` non-empty lines among the first `end` bytes of `file`.
+///
+/// Blocks are read backwards from `end`, doubling in size, and newlines are
+/// only counted in each new block, so even a tail of multi-MB lines is read
+/// in a few passes and copied once.
+pub(crate) fn last_lines_before(
+    file: &mut File,
+    end: u64,
+    n: usize,
+) -> std::io::Result<Vec<String>> {
+    if end == 0 || n == 0 {
         return Ok(vec![]);
     }
 
-    // For small files, just read everything
-    if file_size < 10_000 {
-        let reader = BufReader::new(file);
-        let lines: Vec<String> = reader
-            .lines()
-            .filter_map(|line| line.ok())
-            .filter(|line| !line.trim().is_empty())
-            .collect();
+    let mut start = end;
+    let mut block_size = TAIL_BLOCK_SIZE;
+    // Blocks read so far, the one nearest `end` first
+    let mut blocks: Vec<Vec<u8>> = Vec::new();
+    let mut newlines = 0usize;
 
-        let start = if lines.len() > n { lines.len() - n } else { 0 };
-        return Ok(lines[start..].to_vec());
-    }
+    loop {
+        let read_from = start.saturating_sub(block_size);
+        let mut block = vec![0u8; (start - read_from) as usize];
+        file.seek(SeekFrom::Start(read_from))?;
+        file.read_exact(&mut block)?;
 
-    // For larger files, read from the end
-    // Estimate: average line is ~1KB, so read last n*1KB + buffer
-    let chunk_size = (n * 1024 * 2).min(file_size as usize);
-    let mut file = file;
+        newlines += block.iter().filter(|&&b| b == b'\n').count();
+        blocks.push(block);
+        start = read_from;
 
-    file.seek(SeekFrom::End(-(chunk_size as i64)))
-        .map_err(|e| format!("Failed to seek in file: {}", e))?;
+        // Lines are only known to be complete once the newline before them
+        // has been seen (or we've reached the start of the file)
+        let at_file_start = start == 0;
+        if at_file_start || newlines > n {
+            let tail: Vec<u8> = blocks.iter().rev().flatten().copied().collect();
+            let lines = split_complete_lines(&tail, at_file_start);
+            if at_file_start || lines.len() >= n {
+                let skip = lines.len().saturating_sub(n);
+                return Ok(lines.into_iter().skip(skip).collect());
+            }
+        }
 
-    let reader = BufReader::new(file);
-    let lines: Vec<String> = reader
-        .lines()
-        .filter_map(|line| line.ok())
-        .filter(|line| !line.trim().is_empty())
-        .collect();
+        block_size = block_size.saturating_mul(2);
+    }
+}
 
-    let start = if lines.len() > n { lines.len() - n } else { 0 };
-    Ok(lines[start..].to_vec())
+/// Split a buffer read from the tail of a file into non-empty lines.
+///
+/// Unless the buffer starts at the beginning of the file, everything before
+/// the first newline is the end of a line we haven't fully read and is dropped.
+fn split_complete_lines(buf: &[u8], at_file_start: bool) -> Vec<String> {
+    let mut segments = buf.split(|&b| b == b'\n');
+    if !at_file_start {
+        segments.next();
+    }
+    segments
+        .map(|segment| String::from_utf8_lossy(segment).into_owned())
+        .filter(|line| !line.trim().is_empty())
+        .collect()
 }
 
 /// Parse JSONL lines into SessionEntry structs
 pub fn parse_jsonl_entries(lines: Vec<String>) -> Vec<SessionEntry> {
-    lines
-        .iter()
-        .filter_map(|line| serde_json::from_str::<SessionEntry>(line).ok())
-        .collect()
+    parse_jsonl_entries_with_stats(&lines).0
+}
+
+/// Parse JSONL lines, falling back to a lenient repair pass for lines that
+/// don't match the strict schema, and report what happened along the way
+pub fn parse_jsonl_entries_with_stats(lines: &[String]) -> (Vec<SessionEntry>, FileParseStats) {
+    let mut stats = FileParseStats::default();
+    let mut entries = Vec::with_capacity(lines.len());
+
+    for line in lines {
+        let value = match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(value) => value,
+            Err(e) => {
+                stats.record_failure(format!("Invalid JSON: {}", e), line);
+                continue;
+            }
+        };
+        stats.inspect(&value);
+
+        match SessionEntry::deserialize(&value) {
+            Ok(entry) => {
+                stats.parsed += 1;
+                entries.push(entry);
+            }
+            Err(strict_error) => match parse_entry_lenient(value) {
+                Some(entry) => {
+                    stats.recovered += 1;
+                    entries.push(entry);
+                }
+                None => stats.record_failure(strict_error.to_string(), line),
+            },
+        }
+    }
+
+    (entries, stats)
+}
+
+/// Try to salvage an entry whose shape drifted from what we expect.
+///
+/// Fills in missing identifiers, wraps plain-string assistant content into a
+/// text block, and downgrades content blocks that no longer match their
+/// variant to `Unknown` so the rest of the message survives.
+fn parse_entry_lenient(mut value: serde_json::Value) -> Option<SessionEntry> {
+    use serde_json::Value;
+
+    let object = value.as_object_mut()?;
+    for key in ["uuid", "timestamp"] {
+        if !matches!(object.get(key), Some(Value::String(_))) {
+            object.insert(key.to_string(), Value::String(String::new()));
+        }
+    }
+
+    if object.get("type").and_then(|t| t.as_str()) == Some("assistant") {
+        let message = object.get_mut("message")?.as_object_mut()?;
+        for key in ["model", "id", "role"] {
+            if !matches!(message.get(key), Some(Value::String(_))) {
+                message.insert(key.to_string(), Value::String(String::new()));
+            }
+        }
+        let content = match message.remove("content") {
+            Some(Value::String(text)) => vec![serde_json::json!({"type": "text", "text": text})],
+            Some(Value::Array(blocks)) => blocks
+                .into_iter()
+                .map(|block| {
+                    if MessageContent::deserialize(&block).is_ok() {
+                        block
+                    } else {
+                        serde_json::json!({"type": "unparseable"})
+                    }
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        message.insert("content".to_string(), Value::Array(content));
+        if message
+            .get("usage")
+            .is_some_and(|usage| Usage::deserialize(usage).is_err())
+        {
+            message.remove("usage");
+        }
+    }
+
+    SessionEntry::deserialize(&value).ok()
 }
 
 /// Parse the last N entries from a session JSONL file
@@ -272,8 +596,10 @@ pub fn parse_last_n_entries<P: AsRef<Path>>(
     path: P,
     n: usize,
 ) -> Result<Vec<SessionEntry>, String> {
-    let lines = read_last_n_lines(path, n)?;
-    Ok(parse_jsonl_entries(lines))
+    let lines = read_last_n_lines(path.as_ref(), n)?;
+    let (entries, stats) = parse_jsonl_entries_with_stats(&lines);
+    diagnostics().record_read(path.as_ref(), stats);
+    Ok(entries)
 }
 
 /// Parse all entries from a session JSONL file
@@ -288,60 +614,128 @@ pub fn parse_all_entries<P: AsRef<Path>>(path: P) -> Result<Vec<SessionEntry>, S
         .filter(|line| !line.trim().is_empty())
         .collect();
 
-    Ok(parse_jsonl_entries(lines))
+    let (entries, stats) = parse_jsonl_entries_with_stats(&lines);
+    diagnostics().record_read(path.as_ref(), stats);
+    Ok(entries)
 }
 
 /// Get all user and assistant messages from session entries
-pub fn extract_messages(entries: &[SessionEntry]) -> Vec<(String, MessageType, String)> {
-    let mut messages = Vec::new();
+pub fn extract_messages(entries: &[SessionEntry]) -> Vec<ConversationMessage> {
+    let mut messages: Vec<ConversationMessage> = Vec::new();
+    // API message id -> index of the conversation message carrying its usage
+    let mut usage_holders: HashMap<&str, usize> = HashMap::new();
+    // Tool call id -> tool name, to label the results
+    let mut tool_names: HashMap<&str, &str> = HashMap::new();
 
     for entry in entries {
+        let first_new = messages.len();
         match entry {
             SessionEntry::User { base, message } => {
-                if message.is_tool_result {
+                if message.is_tool_result && message.tool_results.is_empty() {
                     // Tool result entries should be shown as ToolResult, not User
-                    messages.push((
+                    messages.push(ConversationMessage::new(
                         base.timestamp.clone(),
                         MessageType::ToolResult,
                         message.content.clone(),
                     ));
+                } else if message.is_tool_result {
+                    // One message per result, so parallel calls each get theirs
+                    for block in &message.tool_results {
+                        messages.push(ConversationMessage {
+                            tool_result: Some(block.result.clone().named(&tool_names)),
+                            ..ConversationMessage::new(
+                                base.timestamp.clone(),
+                                MessageType::ToolResult,
+                                block.content.clone(),
+                            )
+                        });
+                    }
                 } else {
-                    messages.push((
-                        base.timestamp.clone(),
-                        MessageType::User,
-                        message.content.clone(),
-                    ));
+                    match message.kind() {
+                        UserContentKind::Prompt => messages.push(ConversationMessage::new(
+                            base.timestamp.clone(),
+                            MessageType::User,
+                            message.content.clone(),
+                        )),
+                        UserContentKind::SlashCommand { name, args } => {
+                            let command = if args.is_empty() {
+                                name
+                            } else {
+                                format!("{} {}", name, args)
+                            };
+                            messages.push(ConversationMessage::new(
+                                base.timestamp.clone(),
+                                MessageType::System,
+                                command,
+                            ));
+                        }
+                        UserContentKind::LocalCommandOutput(output) => {
+                            if !output.is_empty() {
+                                messages.push(ConversationMessage::new(
+                                    base.timestamp.clone(),
+                                    MessageType::System,
+                                    output,
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+            SessionEntry::System { base, content, .. } => {
+                if let Some(content) = content {
+                    let text = tag_contents(content, "local-command-stdout")
+                        .unwrap_or(content)
+                        .trim();
+                    if !text.is_empty() {
+                        messages.push(ConversationMessage::new(
+                            base.timestamp.clone(),
+                            MessageType::System,
+                            text.to_string(),
+                        ));
+                    }
                 }
             }
             SessionEntry::Assistant { base, message } => {
                 for content in &message.content {
                     match content {
                         MessageContent::Text { text } => {
-                            messages.push((
+                            messages.push(ConversationMessage::new(
                                 base.timestamp.clone(),
                                 MessageType::Assistant,
                                 text.clone(),
                             ));
                         }
                         MessageContent::Thinking { thinking, .. } => {
-                            messages.push((
+                            messages.push(ConversationMessage::new(
                                 base.timestamp.clone(),
                                 MessageType::Thinking,
                                 thinking.clone(),
                             ));
                         }
                         MessageContent::ToolUse { id, name, input } => {
-                            let tool_desc = format!(
-                                "[{}] {} - {}",
-                                name,
-                                id,
-                                serde_json::to_string_pretty(input).unwrap_or_default()
-                            );
-                            messages.push((
-                                base.timestamp.clone(),
-                                MessageType::ToolUse,
-                                tool_desc,
-                            ));
+                            tool_names.insert(id, name);
+                            // File edits carry a structured diff instead of the raw
+                            // input, which would repeat the whole file or both strings
+                            let edit = edit_diff(name, input);
+                            let (detail, tool_call) = match &edit {
+                                Some(edit) => (
+                                    edit.file_path.clone(),
+                                    ToolCall::new(id, name, &serde_json::Value::Null),
+                                ),
+                                None => (
+                                    serde_json::to_string_pretty(input).unwrap_or_default(),
+                                    ToolCall::new(id, name, input),
+                                ),
+                            };
+                            messages.push(ConversationMessage {
+                                edit,
+                                tool_call: Some(tool_call),
+                                ..ConversationMessage::new(
+                                    base.timestamp.clone(),
+                                    MessageType::ToolUse,
+                                    format!("[{}] {} - {}", name, id, detail),
+                                )
+                            });
                         }
                         MessageContent::ToolResult {
                             tool_use_id,
@@ -355,11 +749,67 @@ pub fn extract_messages(entries: &[SessionEntry]) -> Vec<(String, MessageType, S
                             };
                             let tool_desc =
                                 format!("[{}] {}: {}", result_type, tool_use_id, content);
-                            messages.push((
-                                base.timestamp.clone(),
-                                MessageType::ToolResult,
-                                tool_desc,
-                            ));
+                            let result = ToolResultInfo {
+                                tool_use_id: tool_use_id.clone(),
+                                tool_name: None,
+                                is_error: is_error.unwrap_or(false),
+                            };
+                            messages.push(ConversationMessage {
+                                tool_result: Some(result.named(&tool_names)),
+                                ..ConversationMessage::new(
+                                    base.timestamp.clone(),
+                                    MessageType::ToolResult,
+                                    tool_desc,
+                                )
+                            });
+                        }
+                        MessageContent::ServerToolUse { id, name, input } => {
+                            tool_names.insert(id, name);
+                            let tool_desc = match input.get("query").and_then(|q| q.as_str()) {
+                                Some(query) => format!("[{}] {} - {}", name, id, query),
+                                None => format!(
+                                    "[{}] {} - {}",
+                                    name,
+                                    id,
+                                    serde_json::to_string_pretty(input).unwrap_or_default()
+                                ),
+                            };
+                            messages.push(ConversationMessage {
+                                tool_call: Some(ToolCall::new(id, name, input)),
+                                ..ConversationMessage::new(
+                                    base.timestamp.clone(),
+                                    MessageType::ToolUse,
+                                    tool_desc,
+                                )
+                            });
+                        }
+                        MessageContent::WebSearchToolResult {
+                            tool_use_id,
+                            content,
+                        } => {
+                            let result = ToolResultInfo {
+                                tool_use_id: tool_use_id.clone(),
+                                tool_name: None,
+                                // Failed searches come back as an error object
+                                is_error: !content.is_array(),
+                            };
+                            messages.push(ConversationMessage {
+                                tool_result: Some(result.named(&tool_names)),
+                                ..ConversationMessage::new(
+                                    base.timestamp.clone(),
+                                    MessageType::ToolResult,
+                                    format_web_search_result(tool_use_id, content),
+                                )
+                            });
+                        }
+                        MessageContent::Image { .. } | MessageContent::Document { .. } => {
+                            if let Some(placeholder) = attachment_placeholder(content) {
+                                messages.push(ConversationMessage::new(
+                                    base.timestamp.clone(),
+                                    MessageType::Assistant,
+                                    placeholder,
+                                ));
+                            }
                         }
                         MessageContent::Unknown => {}
                     }
@@ -367,11 +817,252 @@ pub fn extract_messages(entries: &[SessionEntry]) -> Vec<(String, MessageType, S
             }
             _ => {}
         }
+
+        if let Some(base) = entry.base() {
+            for message in &mut messages[first_new..] {
+                message.uuid = Some(base.uuid.clone());
+                message.parent_uuid = base.parent_uuid.clone();
+            }
+        }
+
+        // Streamed responses repeat the same usage on every content block's
+        // entry; keep it on the response's last message only
+        if let SessionEntry::Assistant { message, .. } = entry {
+            if let (Some(usage), Some(last)) = (&message.usage, (first_new..messages.len()).last())
+            {
+                if let Some(previous) = usage_holders.insert(&message.id, last) {
+                    messages[previous].usage = None;
+                }
+                messages[last].usage = Some(usage.clone());
+            }
+        }
     }
 
     messages
 }
 
+/// Render a web_search_tool_result block as one "title (url)" line per result
+fn format_web_search_result(tool_use_id: &str, content: &serde_json::Value) -> String {
+    let Some(results) = content.as_array() else {
+        let error_code = content
+            .get("error_code")
+            .and_then(|c| c.as_str())
+            .unwrap_or("unknown error");
+        return format!(
+            "[Error] {}: web search failed ({})",
+            tool_use_id, error_code
+        );
+    };
+
+    let mut lines = vec![format!(
+        "[Result] {}: {} search results",
+        tool_use_id,
+        results.len()
+    )];
+    for result in results {
+        let title = result.get("title").and_then(|t| t.as_str()).unwrap_or("");
+        let url = result.get("url").and_then(|u| u.as_str()).unwrap_or("");
+        lines.push(format!("- {} ({})", title, url));
+    }
+    lines.join("\n")
+}
+
+/// Before/after text of one replacement in a file edit
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EditHunk {
+    /// Empty when the file or cell is written from scratch
+    pub old_string: String,
+    pub new_string: String,
+}
+
+/// A file edit tool call, ready for diff rendering
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EditDiff {
+    pub file_path: String,
+    /// In the order the tool applies them
+    pub hunks: Vec<EditHunk>,
+}
+
+/// Structured diff for Edit, MultiEdit, Write and NotebookEdit calls
+pub fn edit_diff(tool_name: &str, input: &serde_json::Value) -> Option<EditDiff> {
+    let text = |value: &serde_json::Value, key: &str| value[key].as_str().map(str::to_string);
+    let hunk = |value: &serde_json::Value, old_key: &str, new_key: &str| {
+        Some(EditHunk {
+            old_string: text(value, old_key).unwrap_or_default(),
+            new_string: text(value, new_key)?,
+        })
+    };
+
+    let (file_path, hunks) = match tool_name {
+        "Edit" => (
+            text(input, "file_path")?,
+            vec![hunk(input, "old_string", "new_string")?],
+        ),
+        "MultiEdit" => (
+            text(input, "file_path")?,
+            input["edits"]
+                .as_array()?
+                .iter()
+                .filter_map(|edit| hunk(edit, "old_string", "new_string"))
+                .collect(),
+        ),
+        "Write" => (text(input, "file_path")?, vec![hunk(input, "", "content")?]),
+        "NotebookEdit" => (
+            text(input, "notebook_path")?,
+            vec![hunk(input, "", "new_source")?],
+        ),
+        _ => return None,
+    };
+    Some(EditDiff { file_path, hunks })
+}
+
+/// One message of a conversation, as shown in the viewer
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationMessage {
+    pub timestamp: String,
+    pub message_type: MessageType,
+    pub content: String,
+    /// Set on file edit tool calls; `content` then only names the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edit: Option<EditDiff>,
+    /// Transcript entry the message came from; None for other agents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// Entry this one follows in the conversation tree; None at the root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_uuid: Option<String>,
+    /// Length in characters of the full content, set when `content` was cut
+    /// short; fetch the rest with `get_message_content`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full_length: Option<usize>,
+    /// Tokens used by the API response, on the last message it produced
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+    /// Set on tool calls, for rendering them as cards rather than `content`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call: Option<ToolCall>,
+    /// Set on tool results: the call they answer and whether it failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_result: Option<ToolResultInfo>,
+}
+
+/// A tool call with its arguments as the model sent them
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    /// Null for file edits, whose input is in the message's `edit`
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    pub input: serde_json::Value,
+}
+
+impl ToolCall {
+    fn new(id: &str, name: &str, input: &serde_json::Value) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            input: input.clone(),
+        }
+    }
+}
+
+/// Which tool call a result answers, and how it went
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolResultInfo {
+    pub tool_use_id: String,
+    /// Name of the tool, when its call is in the same transcript
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    pub is_error: bool,
+}
+
+impl ToolResultInfo {
+    /// Fill in the tool name from the calls seen so far
+    fn named(mut self, tool_names: &HashMap<&str, &str>) -> Self {
+        if let Some(name) = tool_names.get(self.tool_use_id.as_str()) {
+            self.tool_name = Some(name.to_string());
+        }
+        self
+    }
+}
+
+impl ConversationMessage {
+    pub fn new(timestamp: String, message_type: MessageType, content: String) -> Self {
+        Self {
+            timestamp,
+            message_type,
+            content,
+            edit: None,
+            uuid: None,
+            parent_uuid: None,
+            full_length: None,
+            usage: None,
+            tool_call: None,
+            tool_result: None,
+        }
+    }
+
+    /// Cut the content to `max_chars` graphemes (as `truncate_string`
+    /// counts them), remembering how many chars it had
+    pub fn truncate(&mut self, max_chars: usize) {
+        if self.content.graphemes(true).nth(max_chars).is_some() {
+            let length = self.content.chars().count();
+            self.content = truncate_string(&self.content, max_chars);
+            self.full_length.get_or_insert(length);
+        }
+    }
+}
+
+/// Tool results longer than this are sent as a preview in conversation
+/// payloads, so one huge bash output doesn't bloat the whole load
+pub const TOOL_RESULT_PREVIEW_CHARS: usize = 4000;
+
+/// Shorten long tool results to `TOOL_RESULT_PREVIEW_CHARS`
+pub fn preview_tool_results(messages: &mut [ConversationMessage]) {
+    for message in messages {
+        if message.message_type == MessageType::ToolResult {
+            message.truncate(TOOL_RESULT_PREVIEW_CHARS);
+        }
+    }
+}
+
+/// Trimming options for a conversation payload, so small screens aren't
+/// dominated by thinking blocks or 50KB bash outputs
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConversationFilter {
+    pub exclude_thinking: bool,
+    pub exclude_tool_results: bool,
+    /// Longest message content to keep, in characters
+    pub max_message_length: Option<usize>,
+    /// Any entry uuid on the branch to show; the current branch if unset
+    pub branch: Option<String>,
+}
+
+impl ConversationFilter {
+    pub fn apply(&self, messages: Vec<ConversationMessage>) -> Vec<ConversationMessage> {
+        messages
+            .into_iter()
+            .filter(|message| match message.message_type {
+                MessageType::Thinking => !self.exclude_thinking,
+                MessageType::ToolResult => !self.exclude_tool_results,
+                _ => true,
+            })
+            .map(|mut message| {
+                if let Some(max) = self.max_message_length {
+                    message.truncate(max);
+                }
+                message
+            })
+            .collect()
+    }
+}
+
 /// Message type enumeration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MessageType {
@@ -380,6 +1071,8 @@ pub enum MessageType {
     Thinking,
     ToolUse,
     ToolResult,
+    /// Slash commands, their output, and other Claude Code notices
+    System,
 }
 
 #[cfg(test)]
@@ -550,6 +1243,332 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_user_message_with_image() {
+        // A pasted screenshot makes the prompt an array of blocks
+        let json = r#"{
+            "type": "user",
+            "uuid": "test-uuid",
+            "timestamp": "2026-01-08T15:23:03.096Z",
+            "sessionId": "test-session",
+            "message": {
+                "role": "user",
+                "content": [
+                    {"type": "text", "text": "what is wrong here?"},
+                    {
+                        "type": "image",
+                        "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0KGgo="}
+                    }
+                ]
+            }
+        }"#;
+
+        let entry: SessionEntry = serde_json::from_str(json).unwrap();
+        if let SessionEntry::User { message, .. } = entry {
+            assert!(!message.is_tool_result);
+            assert!(message.content.contains("what is wrong here?"));
+            assert!(message.content.contains("[Image: image/png]"));
+            assert!(!message.content.contains("iVBORw0KGgo="));
+        } else {
+            panic!("Expected User entry");
+        }
+    }
+
+    #[test]
+    fn test_parse_document_content_block() {
+        let json = r#"{
+            "type": "document",
+            "title": "spec.pdf",
+            "source": {"type": "base64", "media_type": "application/pdf", "data": "JVBERi0="}
+        }"#;
+
+        let content: MessageContent = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            attachment_placeholder(&content).as_deref(),
+            Some("[Document: spec.pdf (application/pdf)]")
+        );
+    }
+
+    #[test]
+    fn test_media_blocks_without_source() {
+        let image: MessageContent = serde_json::from_str(r#"{"type": "image"}"#).unwrap();
+        assert_eq!(
+            attachment_placeholder(&image).as_deref(),
+            Some("[Image: unknown]")
+        );
+        let document: MessageContent =
+            serde_json::from_str(r#"{"type": "document", "title": "spec.pdf"}"#).unwrap();
+        assert_eq!(
+            attachment_placeholder(&document).as_deref(),
+            Some("[Document: spec.pdf]")
+        );
+    }
+
+    #[test]
+    fn test_extract_web_search_messages() {
+        let json = r#"{
+            "type": "assistant",
+            "uuid": "test-uuid",
+            "timestamp": "2026-01-08T15:23:03.096Z",
+            "message": {
+                "model": "claude-sonnet-4-5",
+                "id": "msg_1",
+                "role": "assistant",
+                "content": [
+                    {
+                        "type": "server_tool_use",
+                        "id": "srvtoolu_1",
+                        "name": "web_search",
+                        "input": {"query": "tauri 2 tray icon"}
+                    },
+                    {
+                        "type": "web_search_tool_result",
+                        "tool_use_id": "srvtoolu_1",
+                        "content": [
+                            {"type": "web_search_result", "title": "System Tray", "url": "https://v2.tauri.app/learn/system-tray/", "encrypted_content": "abc"}
+                        ]
+                    }
+                ]
+            }
+        }"#;
+
+        let entry: SessionEntry = serde_json::from_str(json).unwrap();
+        let messages = extract_messages(&[entry]);
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].message_type, MessageType::ToolUse);
+        assert!(messages[0].content.contains("tauri 2 tray icon"));
+        assert_eq!(messages[1].message_type, MessageType::ToolResult);
+        assert!(messages[1]
+            .content
+            .contains("System Tray (https://v2.tauri.app/learn/system-tray/)"));
+    }
+
+    #[test]
+    fn test_tool_calls_and_results_are_structured() {
+        let call = r#"{
+            "type": "assistant",
+            "uuid": "a1",
+            "timestamp": "2026-01-08T15:23:03.096Z",
+            "message": {
+                "model": "claude-sonnet-4-5",
+                "id": "msg_1",
+                "role": "assistant",
+                "content": [
+                    {"type": "tool_use", "id": "toolu_1", "name": "Bash", "input": {"command": "cargo test"}}
+                ]
+            }
+        }"#;
+        let result = r#"{
+            "type": "user",
+            "uuid": "u1",
+            "parentUuid": "a1",
+            "timestamp": "2026-01-08T15:23:05.000Z",
+            "message": {
+                "role": "user",
+                "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "1 test failed", "is_error": true}
+                ]
+            }
+        }"#;
+
+        let entries: Vec<SessionEntry> = [call, result]
+            .iter()
+            .map(|json| serde_json::from_str(json).unwrap())
+            .collect();
+        let messages = extract_messages(&entries);
+
+        let tool_call = messages[0].tool_call.as_ref().unwrap();
+        assert_eq!(tool_call.name, "Bash");
+        assert_eq!(tool_call.input["command"], "cargo test");
+        assert_eq!(
+            messages[1].tool_result,
+            Some(ToolResultInfo {
+                tool_use_id: "toolu_1".to_string(),
+                tool_name: Some("Bash".to_string()),
+                is_error: true,
+            })
+        );
+        assert_eq!(messages[1].content, "1 test failed");
+    }
+
+    #[test]
+    fn test_parallel_tool_results_are_separate_messages() {
+        let json = r#"{
+            "type": "user",
+            "uuid": "u1",
+            "timestamp": "2026-01-08T15:23:05.000Z",
+            "message": {
+                "role": "user",
+                "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "a.rs"},
+                    {"type": "tool_result", "tool_use_id": "toolu_2", "content": "no such file", "is_error": true}
+                ]
+            }
+        }"#;
+
+        let entry: SessionEntry = serde_json::from_str(json).unwrap();
+        let messages = extract_messages(&[entry]);
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, "a.rs");
+        assert!(!messages[0].tool_result.as_ref().unwrap().is_error);
+        assert_eq!(messages[1].content, "no such file");
+        let second = messages[1].tool_result.as_ref().unwrap();
+        assert_eq!(second.tool_use_id, "toolu_2");
+        assert!(second.is_error);
+    }
+
+    #[test]
+    fn test_edit_tool_use_carries_diff() {
+        let json = r#"{
+            "type": "assistant",
+            "uuid": "a1",
+            "timestamp": "2026-01-08T15:23:03.096Z",
+            "message": {
+                "model": "claude-sonnet-4-5",
+                "id": "msg_1",
+                "role": "assistant",
+                "content": [
+                    {
+                        "type": "tool_use",
+                        "id": "toolu_1",
+                        "name": "MultiEdit",
+                        "input": {
+                            "file_path": "/p/src/lib.rs",
+                            "edits": [
+                                {"old_string": "fn a()", "new_string": "fn b()"},
+                                {"old_string": "a()", "new_string": "b()", "replace_all": true}
+                            ]
+                        }
+                    },
+                    {
+                        "type": "tool_use",
+                        "id": "toolu_2",
+                        "name": "Bash",
+                        "input": {"command": "cargo test"}
+                    }
+                ]
+            }
+        }"#;
+
+        let entry: SessionEntry = serde_json::from_str(json).unwrap();
+        let messages = extract_messages(&[entry]);
+
+        assert_eq!(messages[0].content, "[MultiEdit] toolu_1 - /p/src/lib.rs");
+        let edit = messages[0].edit.as_ref().unwrap();
+        assert_eq!(edit.file_path, "/p/src/lib.rs");
+        assert_eq!(edit.hunks.len(), 2);
+        assert_eq!(edit.hunks[1].new_string, "b()");
+        // The diff is the only copy of the edit's input
+        assert!(messages[0].tool_call.as_ref().unwrap().input.is_null());
+        assert!(messages[1].edit.is_none());
+        assert_eq!(
+            messages[1].tool_call.as_ref().unwrap().input["command"],
+            "cargo test"
+        );
+        assert!(messages[1].content.contains("cargo test"));
+
+        let write = edit_diff(
+            "Write",
+            &serde_json::json!({"file_path": "/p/new.rs", "content": "fn main() {}"}),
+        )
+        .unwrap();
+        assert_eq!(write.hunks[0].old_string, "");
+        assert_eq!(write.hunks[0].new_string, "fn main() {}");
+    }
+
+    #[test]
+    fn test_parse_slash_command_and_system_entries() {
+        let command = r#"{
+            "type": "user",
+            "uuid": "u1",
+            "timestamp": "2026-01-08T15:23:03.096Z",
+            "message": {
+                "role": "user",
+                "content": "<command-name>/model</command-name>\n<command-message>model</command-message>\n<command-args>opus</command-args>"
+            }
+        }"#;
+        let system = r#"{
+            "type": "system",
+            "subtype": "local_command",
+            "uuid": "s1",
+            "parentUuid": "u1",
+            "timestamp": "2026-01-08T15:23:04.096Z",
+            "content": "<local-command-stdout>Set model to opus</local-command-stdout>",
+            "level": "info"
+        }"#;
+
+        let command: SessionEntry = serde_json::from_str(command).unwrap();
+        if let SessionEntry::User { message, .. } = &command {
+            assert_eq!(
+                message.kind(),
+                UserContentKind::SlashCommand {
+                    name: "/model".to_string(),
+                    args: "opus".to_string()
+                }
+            );
+        } else {
+            panic!("Expected User entry");
+        }
+
+        let system: SessionEntry = serde_json::from_str(system).unwrap();
+        assert!(matches!(system, SessionEntry::System { .. }));
+
+        let messages = extract_messages(&[command, system]);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].message_type, MessageType::System);
+        assert_eq!(messages[0].content, "/model opus");
+        assert_eq!(messages[1].message_type, MessageType::System);
+        assert_eq!(messages[1].content, "Set model to opus");
+        assert_eq!(messages[1].uuid.as_deref(), Some("s1"));
+        assert_eq!(messages[1].parent_uuid.as_deref(), Some("u1"));
+        assert_eq!(messages[0].parent_uuid, None);
+    }
+
+    #[test]
+    fn test_lenient_parse_recovers_drifted_entries() {
+        let lines = vec![
+            // Assistant content as a plain string instead of blocks, no uuid
+            r#"{"type":"assistant","timestamp":"2026-01-08T15:23:03.096Z","message":{"model":"claude-sonnet-4-5","id":"msg_1","role":"assistant","content":"hello"}}"#.to_string(),
+            // A tool_use block missing its input no longer breaks the message
+            r#"{"type":"assistant","uuid":"a2","timestamp":"2026-01-08T15:23:04.096Z","message":{"model":"claude-sonnet-4-5","id":"msg_2","role":"assistant","content":[{"type":"text","text":"ok"},{"type":"tool_use","id":"t1"}]}}"#.to_string(),
+            "not json".to_string(),
+        ];
+
+        let (entries, stats) = parse_jsonl_entries_with_stats(&lines);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(stats.parsed, 0);
+        assert_eq!(stats.recovered, 2);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.last_error_line.as_deref(), Some("not json"));
+
+        let messages = extract_messages(&entries);
+        assert_eq!(messages[0].content, "hello");
+        assert_eq!(messages[1].content, "ok");
+    }
+
+    #[test]
+    fn test_read_last_n_lines_with_huge_lines() {
+        let path = std::env::temp_dir().join(format!(
+            "c9watch-parser-huge-lines-{}.jsonl",
+            std::process::id()
+        ));
+        // Lines far larger than the initial block, ending without a newline
+        let lines: Vec<String> = (0..5)
+            .map(|i| format!("{{\"n\":{},\"pad\":\"{}\"}}", i, "x".repeat(300 * 1024)))
+            .collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let last_three = read_last_n_lines(&path, 3).unwrap();
+        let all = read_last_n_lines(&path, 10).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(last_three, lines[2..].to_vec());
+        assert_eq!(all, lines);
+    }
+
     #[test]
     fn test_parse_progress_entry() {
         // Progress entries should parse as Unknown (not cause errors)
@@ -565,4 +1584,121 @@ mod tests {
         assert!(entry.is_ok(), "Progress entries should parse as Unknown");
         assert!(matches!(entry.unwrap(), SessionEntry::Unknown));
     }
+
+    #[test]
+    fn test_conversation_filter() {
+        let messages = vec![
+            ConversationMessage::new(String::new(), MessageType::User, "run it".to_string()),
+            ConversationMessage::new(String::new(), MessageType::Thinking, "hmm".to_string()),
+            ConversationMessage::new(String::new(), MessageType::ToolResult, "x".repeat(100)),
+        ];
+
+        assert_eq!(
+            ConversationFilter::default().apply(messages.clone()).len(),
+            3
+        );
+
+        let filter = ConversationFilter {
+            exclude_thinking: true,
+            max_message_length: Some(10),
+            ..Default::default()
+        };
+        let filtered = filter.apply(messages.clone());
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered[0].content, "run it");
+        assert_eq!(filtered[1].content, format!("{}...", "x".repeat(10)));
+
+        let filter: ConversationFilter =
+            serde_json::from_str(r#"{ "excludeToolResults": true }"#).unwrap();
+        let types: Vec<MessageType> = filter
+            .apply(messages)
+            .into_iter()
+            .map(|m| m.message_type)
+            .collect();
+        assert_eq!(types, vec![MessageType::User, MessageType::Thinking]);
+    }
+
+    #[test]
+    fn test_tool_result_preview_keeps_full_length() {
+        let long = "x".repeat(TOOL_RESULT_PREVIEW_CHARS + 10);
+        let mut messages = vec![
+            ConversationMessage::new(String::new(), MessageType::ToolResult, long.clone()),
+            ConversationMessage::new(String::new(), MessageType::Assistant, long),
+            ConversationMessage::new(String::new(), MessageType::ToolResult, "ok".to_string()),
+        ];
+        preview_tool_results(&mut messages);
+
+        assert_eq!(
+            messages[0].content.chars().count(),
+            TOOL_RESULT_PREVIEW_CHARS + 3
+        );
+        assert_eq!(
+            messages[0].full_length,
+            Some(TOOL_RESULT_PREVIEW_CHARS + 10)
+        );
+        assert_eq!(messages[1].full_length, None);
+        assert_eq!(messages[2].content, "ok");
+        assert_eq!(messages[2].full_length, None);
+
+        // Cutting further keeps the original length
+        messages[0].truncate(10);
+        assert_eq!(
+            messages[0].full_length,
+            Some(TOOL_RESULT_PREVIEW_CHARS + 10)
+        );
+    }
+
+    #[test]
+    fn test_truncate_only_marks_content_it_cut() {
+        // 8 chars but 4 graphemes: fits in 5, so nothing to load in full
+        let mut message = ConversationMessage::new(
+            String::new(),
+            MessageType::Assistant,
+            "e\u{301}e\u{301}e\u{301}e\u{301}".to_string(),
+        );
+        message.truncate(5);
+        assert_eq!(message.content, "e\u{301}e\u{301}e\u{301}e\u{301}");
+        assert_eq!(message.full_length, None);
+
+        message.truncate(2);
+        assert_eq!(message.content, "e\u{301}e\u{301}...");
+        assert_eq!(message.full_length, Some(8));
+    }
+
+    #[test]
+    fn test_truncate_string_keeps_graphemes_whole() {
+        assert_eq!(truncate_string("short", 10), "short");
+        assert_eq!(truncate_string("fix the bug now", 8), "fix the...");
+        // Family emoji (ZWJ sequence) and a combining accent count as one each
+        assert_eq!(
+            truncate_string("👨‍👩‍👧 cafe\u{301} time", 6),
+            "👨‍👩‍👧 cafe\u{301}..."
+        );
+    }
+
+    #[test]
+    fn test_usage_on_last_message_of_each_response() {
+        let line = |uuid: &str, id: &str, text: &str, output: u32| {
+            format!(
+                r#"{{"type":"assistant","uuid":"{}","timestamp":"2026-01-08T15:23:03.096Z","message":{{"model":"claude-sonnet-4-5","id":"{}","role":"assistant","content":[{{"type":"text","text":"{}"}}],"usage":{{"input_tokens":10,"output_tokens":{}}}}}}}"#,
+                uuid, id, text, output
+            )
+        };
+        let entries: Vec<SessionEntry> = [
+            line("a1", "msg_1", "first block", 5),
+            line("a2", "msg_1", "second block", 40),
+            line("a3", "msg_2", "next turn", 7),
+        ]
+        .iter()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+
+        let messages = extract_messages(&entries);
+        assert!(messages[0].usage.is_none());
+        assert_eq!(messages[1].usage.as_ref().unwrap().output_tokens, Some(40));
+        assert_eq!(messages[2].usage.as_ref().unwrap().output_tokens, Some(7));
+
+        let json = serde_json::to_value(&messages[2]).unwrap();
+        assert_eq!(json["usage"]["outputTokens"], 7);
+    }
 }
@@ -1,6 +1,7 @@
-use serde::Deserialize;
+use crate::error::C9Error;
+use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Claude Code settings structure (partial - only what we need)
 #[derive(Debug, Deserialize)]
@@ -11,12 +12,41 @@ pub struct ClaudeSettings {
 #[derive(Debug, Deserialize)]
 pub struct Permissions {
     pub allow: Option<Vec<String>>,
+    pub deny: Option<Vec<String>>,
 }
 
 /// Cached permissions for quick lookup
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct PermissionChecker {
     allowed_patterns: Vec<AllowPattern>,
+    /// Only listed for the user; denied tools never reach a prompt
+    denied_patterns: Vec<AllowPattern>,
+    /// settings.json the rules came from
+    source: Option<PathBuf>,
+}
+
+/// Parsed rules as returned by `get_permission_rules`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionRules {
+    /// settings.json the rules came from; None if there isn't one
+    pub source: Option<String>,
+    pub allow: Vec<PermissionRule>,
+    pub deny: Vec<PermissionRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionRule {
+    /// As written in settings.json, e.g. "Bash(git add:*)"
+    pub rule: String,
+    /// "bash", "tool", "mcp" or "skill"
+    pub kind: &'static str,
+}
+
+/// Claude Code's user settings file, ~/.claude/settings.json
+pub fn settings_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".claude").join("settings.json"))
 }
 
 #[derive(Debug, Clone)]
@@ -35,13 +65,10 @@ enum AllowPattern {
 impl PermissionChecker {
     /// Load permissions from settings file
     pub fn from_settings_file() -> Self {
-        let home_dir = match dirs::home_dir() {
-            Some(dir) => dir,
-            None => return Self::default(),
-        };
-
-        let settings_path = home_dir.join(".claude").join("settings.json");
-        Self::from_file(&settings_path)
+        match settings_path() {
+            Some(settings_path) => Self::from_file(&settings_path),
+            None => Self::default(),
+        }
     }
 
     /// Load permissions from a specific file
@@ -56,18 +83,42 @@ impl PermissionChecker {
             Err(_) => return Self::default(),
         };
 
-        let allowed = settings
+        let (allowed, denied) = settings
             .permissions
-            .and_then(|p| p.allow)
+            .map(|p| (p.allow.unwrap_or_default(), p.deny.unwrap_or_default()))
             .unwrap_or_default();
-
-        let patterns = allowed
-            .iter()
-            .filter_map(|s| Self::parse_pattern(s))
-            .collect();
+        let parse = |rules: Vec<String>| {
+            rules
+                .iter()
+                .filter_map(|s| Self::parse_pattern(s))
+                .collect()
+        };
 
         Self {
-            allowed_patterns: patterns,
+            allowed_patterns: parse(allowed),
+            denied_patterns: parse(denied),
+            source: Some(path.to_path_buf()),
+        }
+    }
+
+    /// The loaded allow and deny rules, for showing to the user
+    pub fn rules(&self) -> PermissionRules {
+        let list = |patterns: &[AllowPattern]| {
+            patterns
+                .iter()
+                .map(|pattern| PermissionRule {
+                    rule: pattern.rule(),
+                    kind: pattern.kind(),
+                })
+                .collect()
+        };
+        PermissionRules {
+            source: self
+                .source
+                .as_ref()
+                .map(|path| path.to_string_lossy().to_string()),
+            allow: list(&self.allowed_patterns),
+            deny: list(&self.denied_patterns),
         }
     }
 
@@ -124,11 +175,52 @@ impl PermissionChecker {
     /// # Returns
     /// true if the tool is auto-approved, false if it needs user permission
     pub fn is_auto_approved(&self, tool_name: &str, tool_input: &serde_json::Value) -> bool {
+        match self.decide(tool_name, tool_input) {
+            Decision::ReadOnly => true,
+            Decision::Rules(matched) => !matched.is_empty(),
+            Decision::NotCovered => false,
+        }
+    }
+
+    /// Dry-run a tool call against the loaded rules, reporting which ones
+    /// matched and why it would or wouldn't need approval
+    pub fn explain(&self, tool_name: &str, tool_input: &serde_json::Value) -> PermissionCheck {
+        match self.decide(tool_name, tool_input) {
+            Decision::ReadOnly => PermissionCheck {
+                auto_approved: true,
+                matched_rules: Vec::new(),
+                reason: format!("{} is read-only and never asks for permission", tool_name),
+            },
+            Decision::Rules(matched) if matched.is_empty() => PermissionCheck {
+                auto_approved: false,
+                matched_rules: Vec::new(),
+                reason: format!(
+                    "No allow rule in settings.json matches this {} call",
+                    tool_name
+                ),
+            },
+            Decision::Rules(matched) => {
+                let matched_rules: Vec<String> = matched.iter().map(|p| p.rule()).collect();
+                PermissionCheck {
+                    auto_approved: true,
+                    reason: format!("Allowed by {}", matched_rules.join(", ")),
+                    matched_rules,
+                }
+            }
+            Decision::NotCovered => PermissionCheck {
+                auto_approved: false,
+                matched_rules: Vec::new(),
+                reason: format!("c9watch assumes {} always asks for permission", tool_name),
+            },
+        }
+    }
+
+    fn decide(&self, tool_name: &str, tool_input: &serde_json::Value) -> Decision<'_> {
         // These tools are always auto-approved (read-only operations)
         match tool_name {
             "Read" | "Glob" | "Grep" | "WebFetch" | "WebSearch" | "Task" | "TaskList"
             | "TaskGet" | "TaskCreate" | "TaskUpdate" | "AskUserQuestion" => {
-                return true;
+                return Decision::ReadOnly;
             }
             _ => {}
         }
@@ -140,80 +232,192 @@ impl PermissionChecker {
                 .and_then(|c| c.as_str())
                 .unwrap_or("");
 
-            return self.is_bash_allowed(command);
+            return Decision::Rules(self.bash_matches(command));
         }
 
-        // For Write/Edit, check if explicitly allowed
+        // For Write/Edit, check if explicitly allowed; these typically need
+        // permission otherwise
         if tool_name == "Write" || tool_name == "Edit" || tool_name == "NotebookEdit" {
-            // These typically need permission unless explicitly allowed
-            return self.is_tool_allowed(tool_name);
+            return Decision::Rules(self.matching(
+                |pattern| matches!(pattern, AllowPattern::Tool { name } if name == tool_name),
+            ));
         }
 
         // For MCP tools, check pattern
         if tool_name.starts_with("mcp__") {
-            return self.is_mcp_allowed(tool_name);
+            return Decision::Rules(self.matching(
+                |pattern| matches!(pattern, AllowPattern::Mcp { name } if name == tool_name),
+            ));
         }
 
         // Default: assume needs permission
-        false
+        Decision::NotCovered
     }
 
-    /// Check if a bash command matches any allowed pattern
-    fn is_bash_allowed(&self, command: &str) -> bool {
+    /// Allowed Bash patterns matching a command
+    fn bash_matches(&self, command: &str) -> Vec<&AllowPattern> {
         let command_trimmed = command.trim();
 
-        for pattern in &self.allowed_patterns {
-            if let AllowPattern::Bash { prefix, wildcard } = pattern {
+        self.matching(|pattern| match pattern {
+            // Prefix match with wildcard, exact match without
+            AllowPattern::Bash { prefix, wildcard } => {
                 if *wildcard {
-                    // Prefix match with wildcard
-                    if command_trimmed.starts_with(prefix) {
-                        return true;
-                    }
+                    command_trimmed.starts_with(prefix.as_str())
                 } else {
-                    // Exact match
-                    if command_trimmed == prefix {
-                        return true;
-                    }
+                    command_trimmed == prefix
                 }
             }
-        }
+            _ => false,
+        })
+    }
 
-        false
+    fn matching(&self, matches: impl Fn(&AllowPattern) -> bool) -> Vec<&AllowPattern> {
+        self.allowed_patterns
+            .iter()
+            .filter(|pattern| matches(pattern))
+            .collect()
     }
+}
 
-    /// Check if a tool is explicitly allowed
-    fn is_tool_allowed(&self, tool_name: &str) -> bool {
-        for pattern in &self.allowed_patterns {
-            if let AllowPattern::Tool { name } = pattern {
-                if name == tool_name {
-                    return true;
-                }
-            }
+/// How a tool call was decided
+enum Decision<'a> {
+    /// Read-only tools never ask
+    ReadOnly,
+    /// Decided by allow rules; approved if any matched
+    Rules(Vec<&'a AllowPattern>),
+    /// Tools c9watch always assumes ask for permission
+    NotCovered,
+}
+
+impl AllowPattern {
+    /// The rule as written in settings.json
+    fn rule(&self) -> String {
+        match self {
+            AllowPattern::Bash {
+                prefix,
+                wildcard: true,
+            } => format!("Bash({}:*)", prefix),
+            AllowPattern::Bash { prefix, .. } => format!("Bash({})", prefix),
+            AllowPattern::Tool { name } | AllowPattern::Mcp { name } => name.clone(),
+            AllowPattern::Skill { name } => format!("Skill({})", name),
         }
-        false
     }
 
-    /// Check if an MCP tool is allowed
-    fn is_mcp_allowed(&self, tool_name: &str) -> bool {
-        for pattern in &self.allowed_patterns {
-            if let AllowPattern::Mcp { name } = pattern {
-                if name == tool_name {
-                    return true;
-                }
-            }
+    fn kind(&self) -> &'static str {
+        match self {
+            AllowPattern::Bash { .. } => "bash",
+            AllowPattern::Tool { .. } => "tool",
+            AllowPattern::Mcp { .. } => "mcp",
+            AllowPattern::Skill { .. } => "skill",
         }
-        false
     }
 }
 
-impl Default for PermissionChecker {
-    fn default() -> Self {
-        Self {
-            allowed_patterns: Vec::new(),
+/// Reject allow rules that would wave through more than one recurring
+/// command: anything unparseable, blanket Bash access, and Bash prefixes
+/// containing shell operators (`git status && rm -rf ~` starts with
+/// `git status`).
+pub fn validate_allow_rule(rule: &str) -> Result<(), String> {
+    let rule = rule.trim();
+    match PermissionChecker::parse_pattern(rule) {
+        None => Err(format!(
+            "Not a permission rule c9watch understands: {}",
+            rule
+        )),
+        Some(AllowPattern::Tool { name }) if name.is_empty() || name == "Bash" => {
+            Err("Allowing every Bash command has to be done by hand in settings.json".to_string())
         }
+        Some(AllowPattern::Bash { prefix, .. }) => {
+            let prefix = prefix.trim();
+            if prefix.is_empty() || prefix == "*" {
+                return Err(
+                    "Allowing every Bash command has to be done by hand in settings.json"
+                        .to_string(),
+                );
+            }
+            if prefix.ends_with("...") {
+                return Err("The command was cut short; write the rule out in full".to_string());
+            }
+            const OPERATORS: &[&str] =
+                &["&&", "||", ";", "|", "&", "`", "$(", ">", "<", "\n", "\r"];
+            match OPERATORS.iter().find(|op| prefix.contains(**op)) {
+                Some(op) => Err(format!(
+                    "Rules containing shell operators ({}) are too easy to slip other commands past",
+                    op
+                )),
+                None => Ok(()),
+            }
+        }
+        Some(_) => Ok(()),
     }
 }
 
+/// Append an allow rule to `path` (Claude Code's settings.json), keeping
+/// everything else in the file, including its key order. Returns false if the
+/// rule was already there.
+pub fn add_allow_rule(path: &Path, rule: &str) -> Result<bool, C9Error> {
+    validate_allow_rule(rule).map_err(C9Error::ParseError)?;
+    let rule = rule.trim();
+
+    let mut settings: serde_json::Value = match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| {
+            C9Error::ParseError(format!("{} isn't valid JSON: {}", path.display(), e))
+        })?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_json::json!({}),
+        Err(e) => return Err(e.into()),
+    };
+
+    let invalid = || C9Error::ParseError(format!("Unexpected layout in {}", path.display()));
+    let allow = settings
+        .as_object_mut()
+        .ok_or_else(invalid)?
+        .entry("permissions")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or_else(invalid)?
+        .entry("allow")
+        .or_insert_with(|| serde_json::json!([]))
+        .as_array_mut()
+        .ok_or_else(invalid)?;
+    if allow.iter().any(|existing| existing.as_str() == Some(rule)) {
+        return Ok(false);
+    }
+    allow.push(serde_json::Value::String(rule.to_string()));
+
+    // Write then rename, so Claude Code never reads a half-written file. A
+    // symlinked settings.json (dotfile repos) is replaced at its target so
+    // the link survives, and the file keeps its permissions.
+    let content =
+        serde_json::to_string_pretty(&settings).map_err(|e| C9Error::Other(e.to_string()))?;
+    let target = match fs::canonicalize(path) {
+        Ok(target) => target,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => path.to_path_buf(),
+        Err(e) => return Err(e.into()),
+    };
+    let tmp = target.with_extension("json.c9watch-tmp");
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&tmp, content + "\n")?;
+    if let Ok(metadata) = fs::metadata(&target) {
+        fs::set_permissions(&tmp, metadata.permissions())?;
+    }
+    fs::rename(&tmp, &target)?;
+    Ok(true)
+}
+
+/// Result of a permission dry-run (`check_permission`)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionCheck {
+    /// Whether c9watch treats the call as running without a prompt
+    pub auto_approved: bool,
+    /// Allow rules that match the call, as written in settings.json
+    pub matched_rules: Vec<String>,
+    /// Why, in one sentence
+    pub reason: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -264,6 +468,7 @@ mod tests {
                     wildcard: false,
                 },
             ],
+            ..Default::default()
         };
 
         // Should match git add with wildcard
@@ -282,6 +487,108 @@ mod tests {
         assert!(!checker.is_auto_approved("Bash", &serde_json::json!({"command": "rm -rf /"})));
     }
 
+    #[test]
+    fn test_explain_reports_matched_rules() {
+        let checker = PermissionChecker {
+            allowed_patterns: ["Bash(git:*)", "Bash(git status)", "Edit"]
+                .iter()
+                .filter_map(|rule| PermissionChecker::parse_pattern(rule))
+                .collect(),
+            ..Default::default()
+        };
+
+        let check = checker.explain("Bash", &serde_json::json!({"command": "git status"}));
+        assert!(check.auto_approved);
+        assert_eq!(check.matched_rules, ["Bash(git:*)", "Bash(git status)"]);
+
+        let check = checker.explain("Bash", &serde_json::json!({"command": "rm -rf build"}));
+        assert!(!check.auto_approved);
+        assert!(check.matched_rules.is_empty());
+
+        assert_eq!(
+            checker
+                .explain("Edit", &serde_json::json!({"file_path": "a.rs"}))
+                .matched_rules,
+            ["Edit"]
+        );
+        assert!(
+            checker
+                .explain("Read", &serde_json::json!({}))
+                .auto_approved
+        );
+        assert!(
+            !checker
+                .explain("Write", &serde_json::json!({}))
+                .auto_approved
+        );
+    }
+
+    #[test]
+    fn test_add_allow_rule() {
+        assert!(validate_allow_rule("Bash(cargo test:*)").is_ok());
+        assert!(validate_allow_rule("Bash").is_err());
+        assert!(validate_allow_rule("Bash(:*)").is_err());
+        assert!(validate_allow_rule("Bash(*)").is_err());
+        assert!(validate_allow_rule("Bash(make && rm -rf ~:*)").is_err());
+        assert!(validate_allow_rule("Bash(sleep 1 & rm -rf ~:*)").is_err());
+        assert!(validate_allow_rule("Bash(ls\rrm -rf ~:*)").is_err());
+
+        let path =
+            std::env::temp_dir().join(format!("c9watch-permissions-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{"model": "opus", "permissions": {"deny": ["WebFetch"]}, "env": {}}"#,
+        )
+        .unwrap();
+
+        assert!(add_allow_rule(&path, "Bash(cargo test:*)").unwrap());
+        assert!(!add_allow_rule(&path, "Bash(cargo test:*)").unwrap());
+
+        let checker = PermissionChecker::from_file(&path);
+        let rules = checker.rules();
+        assert_eq!(rules.allow.len(), 1);
+        assert_eq!(rules.allow[0].rule, "Bash(cargo test:*)");
+        assert_eq!(rules.deny[0].rule, "WebFetch");
+        assert!(
+            checker.is_auto_approved("Bash", &serde_json::json!({"command": "cargo test -p x"}))
+        );
+        // Unrelated settings survive, in the order the user wrote them
+        let content = fs::read_to_string(&path).unwrap();
+        let position = |key: &str| content.find(&format!("\"{}\"", key)).unwrap();
+        assert!(position("model") < position("permissions"));
+        assert!(position("permissions") < position("env"));
+        assert!(position("deny") < position("allow"));
+
+        fs::write(&path, "{ not json").unwrap();
+        assert!(add_allow_rule(&path, "Bash(ls:*)").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ not json");
+        fs::remove_file(&path).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_add_allow_rule_through_symlink() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = std::env::temp_dir().join(format!("c9watch-symlink-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("dotfiles-settings.json");
+        let link = dir.join("settings.json");
+        fs::write(&target, "{}").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+        symlink(&target, &link).unwrap();
+
+        assert!(add_allow_rule(&link, "Bash(cargo test:*)").unwrap());
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert!(fs::read_to_string(&target).unwrap().contains("cargo test"));
+        let mode = fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        fs::remove_dir_all(&dir).ok();
+    }
+
     #[test]
     fn test_load_from_real_settings() {
         // This test uses the real settings file if available
@@ -1,14 +1,30 @@
-use super::parser::{AssistantMessage, MessageContent, SessionEntry};
+use super::parser::{AssistantMessage, MessageContent, SessionEntry, UserContentKind};
 use super::permissions::PermissionChecker;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::sync::OnceLock;
+use std::sync::{Arc, Mutex, OnceLock};
 
-/// Global permission checker (loaded once from settings)
-static PERMISSION_CHECKER: OnceLock<PermissionChecker> = OnceLock::new();
+/// Global permission checker (loaded from settings on first use, and again
+/// whenever c9watch edits them)
+static PERMISSION_CHECKER: OnceLock<Mutex<Arc<PermissionChecker>>> = OnceLock::new();
 
-fn get_permission_checker() -> &'static PermissionChecker {
-    PERMISSION_CHECKER.get_or_init(PermissionChecker::from_settings_file)
+fn permission_checker() -> &'static Mutex<Arc<PermissionChecker>> {
+    PERMISSION_CHECKER.get_or_init(|| Mutex::new(Arc::new(PermissionChecker::from_settings_file())))
+}
+
+pub fn get_permission_checker() -> Arc<PermissionChecker> {
+    let checker = permission_checker()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    Arc::clone(&checker)
+}
+
+/// Re-read ~/.claude/settings.json after changing it
+pub fn reload_permission_checker() {
+    let reloaded = Arc::new(PermissionChecker::from_settings_file());
+    *permission_checker()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = reloaded;
 }
 
 /// Represents the current status of a Claude Code session
@@ -28,6 +44,57 @@ pub enum SessionStatus {
     Connecting,
 }
 
+/// Claude Code's permission mode (cycled with Shift+Tab, or set with
+/// `--permission-mode` / `--dangerously-skip-permissions`)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionMode {
+    #[default]
+    Default,
+    /// Read-only planning; leaving plan mode asks for approval
+    Plan,
+    /// File edits are approved automatically
+    AcceptEdits,
+    /// Nothing asks for approval
+    BypassPermissions,
+}
+
+impl PermissionMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::Default),
+            "plan" => Some(Self::Plan),
+            "acceptEdits" => Some(Self::AcceptEdits),
+            "bypassPermissions" => Some(Self::BypassPermissions),
+            _ => None,
+        }
+    }
+
+    /// Whether this mode approves a tool without asking, regardless of the
+    /// allow rules in settings
+    fn approves(&self, tool_name: &str) -> bool {
+        match self {
+            Self::BypassPermissions => true,
+            Self::AcceptEdits => {
+                matches!(tool_name, "Edit" | "MultiEdit" | "Write" | "NotebookEdit")
+            }
+            Self::Default | Self::Plan => false,
+        }
+    }
+}
+
+/// The permission mode of the most recent prompt in `entries`, if any
+/// entry records one
+pub fn get_permission_mode(entries: &[SessionEntry]) -> Option<PermissionMode> {
+    entries.iter().rev().find_map(|entry| match entry {
+        SessionEntry::User { base, .. } => base
+            .permission_mode
+            .as_deref()
+            .and_then(PermissionMode::from_name),
+        _ => None,
+    })
+}
+
 /// Analyzes session entries to determine the current status
 ///
 /// # Arguments
@@ -36,6 +103,13 @@ pub enum SessionStatus {
 /// # Returns
 /// The determined session status
 pub fn determine_status(entries: &[SessionEntry]) -> SessionStatus {
+    determine_status_in_mode(entries, get_permission_mode(entries).unwrap_or_default())
+}
+
+/// Like `determine_status`, for a permission mode known from elsewhere (the
+/// entry recording it may be older than `entries`). Tools the mode approves
+/// never count as needing permission.
+pub fn determine_status_in_mode(entries: &[SessionEntry], mode: PermissionMode) -> SessionStatus {
     // If no entries, session is likely starting up
     if entries.is_empty() {
         return SessionStatus::Connecting;
@@ -45,12 +119,13 @@ pub fn determine_status(entries: &[SessionEntry]) -> SessionStatus {
     // file-history-snapshot, summary, and other non-status-bearing entries.
     // Claude Code writes "progress" entries during tool execution (e.g., bash_progress)
     // which must not override the actual session status.
-    let last_meaningful = entries.iter().rev().find(|entry| {
-        matches!(
-            entry,
-            SessionEntry::User { .. } | SessionEntry::Assistant { .. }
-        )
-    });
+    // A built-in command's output, written as a system entry, also ends a turn.
+    let is_meaningful = |entry: &&SessionEntry| match entry {
+        SessionEntry::User { .. } | SessionEntry::Assistant { .. } => true,
+        SessionEntry::System { subtype, .. } => subtype.as_deref() == Some("local_command"),
+        _ => false,
+    };
+    let last_meaningful = entries.iter().rev().find(is_meaningful);
 
     let last_entry = match last_meaningful {
         Some(entry) => entry,
@@ -61,22 +136,24 @@ pub fn determine_status(entries: &[SessionEntry]) -> SessionStatus {
     // Progress entries (e.g., bash_progress) indicate active tool execution.
     let last_meaningful_idx = entries
         .iter()
-        .rposition(|entry| {
-            matches!(
-                entry,
-                SessionEntry::User { .. } | SessionEntry::Assistant { .. }
-            )
-        })
+        .rposition(|entry| is_meaningful(&entry))
         .unwrap_or(0);
     let has_trailing_progress = entries[last_meaningful_idx + 1..]
         .iter()
         .any(|entry| matches!(entry, SessionEntry::Unknown));
 
     match last_entry {
+        // Built-in slash commands (/model, /cost, ...) run locally and don't
+        // wait on Claude
+        SessionEntry::System { .. } => SessionStatus::WaitingForInput,
         SessionEntry::User { base, message } => {
             // Check if this is a tool_result or an actual user prompt.
             // Tool results mean Claude is still processing.
-            if message.is_tool_result {
+            if let UserContentKind::LocalCommandOutput(_) = message.kind() {
+                // Same as the System arm: local command output arrives as a
+                // user entry, so the session is idle
+                SessionStatus::WaitingForInput
+            } else if message.is_tool_result {
                 // This is a tool result - Claude should be generating its next response.
                 // But if it's old, the session might be idle (process died, etc.).
                 // 30s threshold (increased from 15s) accommodates API latency and longer operations.
@@ -95,7 +172,7 @@ pub fn determine_status(entries: &[SessionEntry]) -> SessionStatus {
         }
         SessionEntry::Assistant { base, message } => {
             // Analyze the assistant message content
-            let raw_status = analyze_assistant_message(message);
+            let raw_status = analyze_assistant_message(message, mode);
 
             match raw_status {
                 SessionStatus::Working => {
@@ -136,7 +213,7 @@ pub fn determine_status(entries: &[SessionEntry]) -> SessionStatus {
             }
         }
         _ => {
-            // Should not reach here since we filtered for User/Assistant above
+            // Should not reach here since we filtered for meaningful entries above
             SessionStatus::WaitingForInput
         }
     }
@@ -155,7 +232,7 @@ fn is_entry_recent(timestamp: &str, seconds: i64) -> bool {
 }
 
 /// Analyzes an assistant message to determine status
-fn analyze_assistant_message(message: &AssistantMessage) -> SessionStatus {
+fn analyze_assistant_message(message: &AssistantMessage, mode: PermissionMode) -> SessionStatus {
     // Check if the message contains any tool uses
     let has_tool_use = message
         .content
@@ -181,7 +258,7 @@ fn analyze_assistant_message(message: &AssistantMessage) -> SessionStatus {
         } else {
             // Tool use present but not all completed
             // Check if pending tools are auto-approved
-            if are_pending_tools_auto_approved(&message.content) {
+            if are_pending_tools_auto_approved(&message.content, mode) {
                 // All pending tools will be auto-approved, so status is Working
                 SessionStatus::Working
             } else {
@@ -204,7 +281,7 @@ fn analyze_assistant_message(message: &AssistantMessage) -> SessionStatus {
 }
 
 /// Checks if all pending (incomplete) tool uses are auto-approved
-fn are_pending_tools_auto_approved(content: &[MessageContent]) -> bool {
+fn are_pending_tools_auto_approved(content: &[MessageContent], mode: PermissionMode) -> bool {
     let checker = get_permission_checker();
 
     // Get IDs of tools that have results
@@ -228,7 +305,7 @@ fn are_pending_tools_auto_approved(content: &[MessageContent]) -> bool {
             }
 
             // This tool is pending - check if it's auto-approved
-            if !checker.is_auto_approved(name, input) {
+            if !mode.approves(name) && !checker.is_auto_approved(name, input) {
                 // Found a tool that needs permission
                 return false;
             }
@@ -255,6 +332,38 @@ fn are_pending_tools_auto_approved(content: &[MessageContent]) -> bool {
 /// - No pending tools found
 /// - All pending tools are auto-approved
 pub fn get_pending_tool_name(entries: &[SessionEntry]) -> Option<String> {
+    get_pending_tool_name_in_mode(entries, get_permission_mode(entries).unwrap_or_default())
+}
+
+/// `get_pending_tool_name` for a permission mode known from elsewhere
+pub fn get_pending_tool_name_in_mode(
+    entries: &[SessionEntry],
+    mode: PermissionMode,
+) -> Option<String> {
+    pending_tool_in_mode(entries, mode).map(|(name, _)| name.clone())
+}
+
+/// A short description of what the pending tool wants to do (the command,
+/// the file, the arguments), for the tool `get_pending_tool_name` returns
+pub fn get_pending_tool_input(entries: &[SessionEntry]) -> Option<String> {
+    get_pending_tool_input_in_mode(entries, get_permission_mode(entries).unwrap_or_default())
+}
+
+/// `get_pending_tool_input` for a permission mode known from elsewhere
+pub fn get_pending_tool_input_in_mode(
+    entries: &[SessionEntry],
+    mode: PermissionMode,
+) -> Option<String> {
+    pending_tool_in_mode(entries, mode)
+        .map(|(name, input)| summarize_tool_input(name, input))
+        .filter(|summary| !summary.is_empty())
+}
+
+/// Name and input of the first pending tool use that needs permission
+fn pending_tool_in_mode(
+    entries: &[SessionEntry],
+    mode: PermissionMode,
+) -> Option<(&String, &serde_json::Value)> {
     // Find the last assistant message entry
     let last_assistant = entries.iter().rev().find_map(|entry| {
         if let SessionEntry::Assistant { message, .. } = entry {
@@ -288,9 +397,9 @@ pub fn get_pending_tool_name(entries: &[SessionEntry]) -> Option<String> {
             }
 
             // This tool is pending - check if it needs permission
-            if !checker.is_auto_approved(name, input) {
+            if !mode.approves(name) && !checker.is_auto_approved(name, input) {
                 // Found a tool that needs permission
-                return Some(name.clone());
+                return Some((name, input));
             }
         }
     }
@@ -299,6 +408,31 @@ pub fn get_pending_tool_name(entries: &[SessionEntry]) -> Option<String> {
     None
 }
 
+/// Longest tool input summary, in characters
+const TOOL_INPUT_SUMMARY_CHARS: usize = 200;
+
+/// The part of a tool's input a person approving it cares about
+pub fn summarize_tool_input(tool_name: &str, input: &serde_json::Value) -> String {
+    let field = |key: &str| input.get(key).and_then(|v| v.as_str());
+    let summary = match tool_name {
+        "Bash" => field("command").map(str::to_string),
+        "Edit" | "MultiEdit" | "Write" | "Read" => field("file_path").map(str::to_string),
+        "NotebookEdit" => field("notebook_path").map(str::to_string),
+        "WebFetch" => field("url").map(str::to_string),
+        "WebSearch" => field("query").map(str::to_string),
+        "Glob" | "Grep" => field("pattern").map(str::to_string),
+        "Task" => field("description").map(str::to_string),
+        _ => None,
+    };
+    // MCP and unknown tools: show the arguments themselves
+    let summary = summary.unwrap_or_else(|| match input {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::Object(map) if map.is_empty() => String::new(),
+        other => other.to_string(),
+    });
+    super::parser::truncate_string(&summary, TOOL_INPUT_SUMMARY_CHARS)
+}
+
 /// Checks if there are any pending (incomplete) tool uses
 fn has_pending_tool_uses(content: &[MessageContent]) -> bool {
     !check_all_tools_completed(content)
@@ -402,6 +536,7 @@ mod tests {
             parent_uuid: None,
             is_sidechain: None,
             slug: None,
+            permission_mode: None,
         }
     }
 
@@ -417,6 +552,7 @@ mod tests {
             parent_uuid: None,
             is_sidechain: None,
             slug: None,
+            permission_mode: None,
         }
     }
 
@@ -434,6 +570,7 @@ mod tests {
                 role: "user".to_string(),
                 content: "Hello".to_string(),
                 is_tool_result: false,
+                tool_results: Vec::new(),
             },
         }];
         assert_eq!(determine_status(&entries), SessionStatus::Working);
@@ -722,6 +859,7 @@ mod tests {
                     role: "user".to_string(),
                     content: "Hello".to_string(),
                     is_tool_result: false,
+                    tool_results: Vec::new(),
                 },
             },
             SessionEntry::Unknown,
@@ -779,6 +917,35 @@ mod tests {
         assert_eq!(determine_status(&entries), SessionStatus::Working);
     }
 
+    #[test]
+    fn test_trailing_local_command_output_is_idle() {
+        // /model writes the command as a user entry, then its output as a
+        // system entry; the session is back at the prompt
+        let entries = vec![
+            SessionEntry::User {
+                base: create_base(),
+                message: UserMessage {
+                    role: "user".to_string(),
+                    content:
+                        "<command-name>/model</command-name>\n<command-args>opus</command-args>"
+                            .to_string(),
+                    is_tool_result: false,
+                    tool_results: Vec::new(),
+                },
+            },
+            SessionEntry::System {
+                base: create_base(),
+                subtype: Some("local_command".to_string()),
+                content: Some(
+                    "<local-command-stdout>Set model to opus</local-command-stdout>".to_string(),
+                ),
+                level: Some("info".to_string()),
+            },
+            SessionEntry::Unknown,
+        ];
+        assert_eq!(determine_status(&entries), SessionStatus::WaitingForInput);
+    }
+
     #[test]
     fn test_old_user_prompt_is_idle() {
         // A user prompt from long ago with no response should be idle
@@ -788,6 +955,7 @@ mod tests {
                 role: "user".to_string(),
                 content: "Hello".to_string(),
                 is_tool_result: false,
+                tool_results: Vec::new(),
             },
         }];
         assert_eq!(determine_status(&entries), SessionStatus::WaitingForInput);
@@ -813,6 +981,10 @@ mod tests {
             },
         }];
         assert_eq!(get_pending_tool_name(&entries), Some("Bash".to_string()));
+        assert_eq!(
+            get_pending_tool_input(&entries),
+            Some("rm -rf /some/path".to_string())
+        );
     }
 
     #[test]
@@ -925,6 +1097,7 @@ mod tests {
                 role: "user".to_string(),
                 content: "Hello".to_string(),
                 is_tool_result: false,
+                tool_results: Vec::new(),
             },
         }];
         assert_eq!(get_pending_tool_name(&entries), None);
@@ -970,4 +1143,80 @@ mod tests {
         }];
         assert_eq!(get_pending_tool_name(&entries), Some("Bash".to_string()));
     }
+
+    #[test]
+    fn test_permission_mode_approves_pending_tools() {
+        let mut prompt_base = create_base();
+        prompt_base.permission_mode = Some("acceptEdits".to_string());
+        let pending = |tool: &str| SessionEntry::Assistant {
+            base: create_base(),
+            message: AssistantMessage {
+                model: "claude-opus-4-5-20251101".to_string(),
+                id: "msg_test".to_string(),
+                role: "assistant".to_string(),
+                content: vec![MessageContent::ToolUse {
+                    id: "toolu_123".to_string(),
+                    name: tool.to_string(),
+                    input: serde_json::json!({"file_path": "/test/file.txt"}),
+                }],
+                stop_reason: Some("tool_use".to_string()),
+                stop_sequence: None,
+                usage: None,
+            },
+        };
+        let prompt = SessionEntry::User {
+            base: prompt_base,
+            message: UserMessage {
+                role: "user".to_string(),
+                content: "Fix it".to_string(),
+                is_tool_result: false,
+                tool_results: Vec::new(),
+            },
+        };
+
+        let edit = vec![prompt.clone(), pending("Write")];
+        assert_eq!(
+            get_permission_mode(&edit),
+            Some(PermissionMode::AcceptEdits)
+        );
+        assert_eq!(determine_status(&edit), SessionStatus::Working);
+        assert_eq!(get_pending_tool_name(&edit), None);
+
+        let exit_plan = vec![prompt, pending("ExitPlanMode")];
+        assert_eq!(determine_status(&exit_plan), SessionStatus::NeedsPermission);
+        assert_eq!(
+            determine_status_in_mode(&exit_plan, PermissionMode::BypassPermissions),
+            SessionStatus::Working
+        );
+        assert_eq!(
+            determine_status_in_mode(&exit_plan[1..], PermissionMode::Default),
+            SessionStatus::NeedsPermission
+        );
+    }
+
+    #[test]
+    fn test_summarize_tool_input() {
+        assert_eq!(
+            summarize_tool_input("Bash", &serde_json::json!({"command": "npm install"})),
+            "npm install"
+        );
+        assert_eq!(
+            summarize_tool_input(
+                "Edit",
+                &serde_json::json!({"file_path": "/src/main.rs", "old_string": "a"})
+            ),
+            "/src/main.rs"
+        );
+        assert_eq!(
+            summarize_tool_input(
+                "mcp__github__create_issue",
+                &serde_json::json!({"title": "Bug"})
+            ),
+            r#"{"title":"Bug"}"#
+        );
+        assert_eq!(
+            summarize_tool_input("ExitPlanMode", &serde_json::json!({})),
+            ""
+        );
+    }
 }
@@ -0,0 +1,360 @@
+use super::diagnostics::{diagnostics, FileParseStats};
+use super::parser::{last_lines_before, parse_jsonl_entries_with_stats, SessionEntry};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Global tail reader shared by the poller and ad-hoc session refreshes
+static TAIL_READER: OnceLock<TailReader> = OnceLock::new();
+
+pub fn tail_reader() -> &'static TailReader {
+    TAIL_READER.get_or_init(TailReader::default)
+}
+
+/// Block size used when scanning backwards for the last newline
+const SCAN_BLOCK_SIZE: u64 = 8 * 1024;
+
+/// Leading bytes remembered per file; if they change, the file was rewritten
+const HEAD_BYTES: usize = 256;
+
+/// Which file a state was built from, to tell an append from a replacement
+/// (compaction, or a save through a temp file) that happens not to shrink
+struct FileIdentity {
+    #[cfg(unix)]
+    inode: u64,
+    /// Up to HEAD_BYTES from the start of the file, compared as a prefix
+    head: Vec<u8>,
+}
+
+impl FileIdentity {
+    fn read(file: &mut File, metadata: &std::fs::Metadata) -> std::io::Result<Self> {
+        let mut head = Vec::with_capacity(HEAD_BYTES);
+        file.seek(SeekFrom::Start(0))?;
+        file.by_ref()
+            .take(HEAD_BYTES as u64)
+            .read_to_end(&mut head)?;
+        #[cfg(not(unix))]
+        let _ = metadata;
+        Ok(Self {
+            #[cfg(unix)]
+            inode: std::os::unix::fs::MetadataExt::ino(metadata),
+            head,
+        })
+    }
+
+    /// Whether `current` is this file, possibly with more appended
+    fn same_file(&self, current: &FileIdentity) -> bool {
+        #[cfg(unix)]
+        if self.inode != current.inode {
+            return false;
+        }
+        current.head.starts_with(&self.head)
+    }
+}
+
+/// What we know about one JSONL file between polls
+struct TailState {
+    /// Byte offset just past the last complete line we've parsed
+    offset: u64,
+    identity: FileIdentity,
+    /// Most recent entries, oldest first, at most `window` long
+    entries: VecDeque<SessionEntry>,
+    window: usize,
+    /// Parse results accumulated since the file was first seeded
+    stats: FileParseStats,
+}
+
+/// Keeps a rolling window of recent entries per session file and only parses
+/// lines appended since the previous poll.
+#[derive(Default)]
+pub struct TailReader {
+    files: Mutex<HashMap<PathBuf, TailState>>,
+}
+
+impl TailReader {
+    /// The last `n` entries of a session file, reading only what was appended
+    /// since the last call for this path.
+    pub fn recent_entries(&self, path: &Path, n: usize) -> Result<Vec<SessionEntry>, String> {
+        // Everything below reads through this one handle, so it all sees the
+        // same file even if it is replaced meanwhile
+        let mut file = File::open(path).map_err(|e| format!("Failed to open JSONL file: {}", e))?;
+        let metadata = file
+            .metadata()
+            .map_err(|e| format!("Failed to read file metadata: {}", e))?;
+        let len = metadata.len();
+        let identity = FileIdentity::read(&mut file, &metadata)
+            .map_err(|e| format!("Failed to read JSONL file: {}", e))?;
+
+        let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+
+        // (Re)seed on first sight, when the file shrank or was replaced
+        // (rewritten), or when a larger window is requested than we've been
+        // keeping
+        let needs_seed = match files.get(path) {
+            Some(state) => {
+                len < state.offset || !state.identity.same_file(&identity) || n > state.window
+            }
+            None => true,
+        };
+        if needs_seed {
+            let state = seed(&mut file, identity, n, len)?;
+            files.insert(path.to_path_buf(), state);
+        } else if let Some(state) = files.get_mut(path) {
+            if len > state.offset {
+                read_appended(&mut file, state)?;
+            }
+        }
+
+        let state = files
+            .get(path)
+            .ok_or_else(|| "Tail state missing after read".to_string())?;
+        diagnostics().record(path, state.stats.clone());
+
+        let skip = state.entries.len().saturating_sub(n);
+        Ok(state.entries.iter().skip(skip).cloned().collect())
+    }
+
+    /// Drop state for files no longer being watched
+    pub fn retain(&self, paths: &HashSet<PathBuf>) {
+        let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        files.retain(|path, _| paths.contains(path));
+    }
+}
+
+/// Build initial state from the file's tail. Only lines that end before the
+/// offset recorded here are parsed, so lines appended while seeding are left
+/// for the next read instead of being parsed twice.
+fn seed(
+    file: &mut File,
+    identity: FileIdentity,
+    window: usize,
+    len: u64,
+) -> Result<TailState, String> {
+    let offset = end_of_last_complete_line(file, len)
+        .map_err(|e| format!("Failed to scan JSONL file: {}", e))?;
+    let lines = last_lines_before(file, offset, window)
+        .map_err(|e| format!("Failed to read JSONL file: {}", e))?;
+    let (entries, stats) = parse_jsonl_entries_with_stats(&lines);
+
+    Ok(TailState {
+        offset,
+        identity,
+        entries: entries.into(),
+        window,
+        stats,
+    })
+}
+
+/// Parse complete lines appended after `state.offset`
+fn read_appended(file: &mut File, state: &mut TailState) -> Result<(), String> {
+    file.seek(SeekFrom::Start(state.offset))
+        .map_err(|e| format!("Failed to seek in file: {}", e))?;
+
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)
+        .map_err(|e| format!("Failed to read JSONL file: {}", e))?;
+
+    // Only consume up to the last newline; the remainder is an incomplete line
+    let Some(last_newline) = buf.iter().rposition(|&b| b == b'\n') else {
+        return Ok(());
+    };
+    let complete = &buf[..=last_newline];
+    state.offset += complete.len() as u64;
+
+    let (entries, stats) = parse_jsonl_entries_with_stats(&non_empty_lines(complete));
+
+    state.stats.merge(stats);
+    state.entries.extend(entries);
+    while state.entries.len() > state.window {
+        state.entries.pop_front();
+    }
+
+    Ok(())
+}
+
+fn non_empty_lines(bytes: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Offset just past the last '\n' in the first `len` bytes (0 if there is none)
+fn end_of_last_complete_line(file: &mut File, len: u64) -> std::io::Result<u64> {
+    let mut end = len;
+    let mut buf = vec![0u8; SCAN_BLOCK_SIZE as usize];
+
+    while end > 0 {
+        let start = end.saturating_sub(SCAN_BLOCK_SIZE);
+        let size = (end - start) as usize;
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut buf[..size])?;
+
+        if let Some(pos) = buf[..size].iter().rposition(|&b| b == b'\n') {
+            return Ok(start + pos as u64 + 1);
+        }
+        end = start;
+    }
+
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// A JSONL file in the temp dir, removed on drop
+    struct TempJsonl {
+        path: PathBuf,
+        file: File,
+    }
+
+    impl TempJsonl {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "c9watch-tail-{}-{}.jsonl",
+                name,
+                std::process::id()
+            ));
+            let file = File::create(&path).unwrap();
+            Self { path, file }
+        }
+    }
+
+    impl Drop for TempJsonl {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    fn user_line(uuid: &str, text: &str) -> String {
+        format!(
+            r#"{{"type":"user","uuid":"{}","timestamp":"2026-01-08T15:23:03.096Z","message":{{"role":"user","content":"{}"}}}}"#,
+            uuid, text
+        )
+    }
+
+    fn user_texts(entries: &[SessionEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .filter_map(|entry| match entry {
+                SessionEntry::User { message, .. } => Some(message.content.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_reads_only_appended_lines_and_keeps_window() {
+        let mut tmp = TempJsonl::new("append");
+        for i in 0..3 {
+            writeln!(
+                tmp.file,
+                "{}",
+                user_line(&format!("u{}", i), &format!("m{}", i))
+            )
+            .unwrap();
+        }
+
+        let reader = TailReader::default();
+        let entries = reader.recent_entries(&tmp.path, 2).unwrap();
+        assert_eq!(user_texts(&entries), vec!["m1", "m2"]);
+
+        // Append one complete line and half of another
+        writeln!(tmp.file, "{}", user_line("u3", "m3")).unwrap();
+        let partial = user_line("u4", "m4");
+        write!(tmp.file, "{}", &partial[..20]).unwrap();
+        tmp.file.flush().unwrap();
+
+        let entries = reader.recent_entries(&tmp.path, 2).unwrap();
+        assert_eq!(user_texts(&entries), vec!["m2", "m3"]);
+
+        // Finish the partial line
+        writeln!(tmp.file, "{}", &partial[20..]).unwrap();
+        tmp.file.flush().unwrap();
+
+        let entries = reader.recent_entries(&tmp.path, 2).unwrap();
+        assert_eq!(user_texts(&entries), vec!["m3", "m4"]);
+    }
+
+    #[test]
+    fn test_reseeds_when_file_is_rewritten() {
+        let mut tmp = TempJsonl::new("rewrite");
+        writeln!(tmp.file, "{}", user_line("u0", "old message")).unwrap();
+
+        let reader = TailReader::default();
+        reader.recent_entries(&tmp.path, 5).unwrap();
+
+        std::fs::write(&tmp.path, format!("{}\n", user_line("u1", "new"))).unwrap();
+        let entries = reader.recent_entries(&tmp.path, 5).unwrap();
+        assert_eq!(user_texts(&entries), vec!["new"]);
+    }
+
+    #[test]
+    fn test_reseeds_when_file_is_replaced_with_a_longer_one() {
+        let mut tmp = TempJsonl::new("replace");
+        writeln!(tmp.file, "{}", user_line("u0", "before compaction")).unwrap();
+
+        let reader = TailReader::default();
+        reader.recent_entries(&tmp.path, 5).unwrap();
+
+        // Not shorter, so only the changed head gives the rewrite away
+        let replacement = format!(
+            "{}\n{}\n",
+            user_line("u1", "summary"),
+            user_line("u2", "after compaction")
+        );
+        let staged = tmp.path.with_extension("staged");
+        std::fs::write(&staged, replacement).unwrap();
+        std::fs::rename(&staged, &tmp.path).unwrap();
+
+        let entries = reader.recent_entries(&tmp.path, 5).unwrap();
+        assert_eq!(user_texts(&entries), vec!["summary", "after compaction"]);
+    }
+
+    #[test]
+    fn test_seed_reads_window_across_blocks() {
+        let mut tmp = TempJsonl::new("blocks");
+        let filler = "x".repeat(SCAN_BLOCK_SIZE as usize / 3);
+        for i in 0..6 {
+            writeln!(
+                tmp.file,
+                "{}",
+                user_line(&format!("u{}", i), &format!("{}{}", i, filler))
+            )
+            .unwrap();
+        }
+        tmp.file.flush().unwrap();
+
+        let entries = TailReader::default().recent_entries(&tmp.path, 4).unwrap();
+        let firsts: Vec<String> = user_texts(&entries)
+            .iter()
+            .map(|text| text[..1].to_string())
+            .collect();
+        assert_eq!(firsts, vec!["2", "3", "4", "5"]);
+    }
+
+    #[test]
+    fn test_seed_reads_lines_far_larger_than_a_block() {
+        let mut tmp = TempJsonl::new("huge");
+        // A multi-MB tool result, many times the first block read
+        let huge = "y".repeat(3 * 1024 * 1024);
+        writeln!(tmp.file, "{}", user_line("u0", "before")).unwrap();
+        writeln!(tmp.file, "{}", user_line("u1", &huge)).unwrap();
+        writeln!(tmp.file, "{}", user_line("u2", "after")).unwrap();
+        tmp.file.flush().unwrap();
+
+        let entries = TailReader::default().recent_entries(&tmp.path, 2).unwrap();
+        let texts = user_texts(&entries);
+        assert_eq!(texts.len(), 2);
+        assert_eq!(texts[0].len(), huge.len());
+        assert_eq!(texts[1], "after");
+
+        let entries = TailReader::default().recent_entries(&tmp.path, 3).unwrap();
+        assert_eq!(user_texts(&entries)[0], "before");
+    }
+}
@@ -0,0 +1,184 @@
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Longest generated title, in characters
+const MAX_TITLE_CHARS: usize = 60;
+
+/// Prompts shorter than this ("continue", "hi") don't say what a session is about
+const MIN_TOPIC_WORDS: usize = 3;
+
+/// A summary for a session that has none in sessions-index.json.
+///
+/// Uses, in order: a summary Claude Code wrote into the transcript, the first
+/// prompt that says something, the latest prompt, and the session's slug.
+pub fn generate_title(transcript: &Path) -> Option<String> {
+    let file = File::open(transcript).ok()?;
+    title_from_lines(BufReader::new(file).lines().map_while(Result::ok))
+}
+
+fn title_from_lines(lines: impl Iterator<Item = String>) -> Option<String> {
+    let mut summary: Option<String> = None;
+    let mut first_topic: Option<String> = None;
+    let mut latest_prompt: Option<String> = None;
+    let mut slug: Option<String> = None;
+
+    for line in lines {
+        // Skip parsing the assistant and tool lines that make up most of a file
+        if !line.contains("\"summary\"") && !line.contains("\"user\"") {
+            continue;
+        }
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        match entry["type"].as_str() {
+            Some("summary") => {
+                if let Some(text) = entry["summary"].as_str() {
+                    summary = Some(text.to_string());
+                }
+            }
+            Some("user") if entry["isMeta"] != true => {
+                if slug.is_none() {
+                    slug = entry["slug"].as_str().map(str::to_string);
+                }
+                let Some(prompt) = prompt_text(&entry["message"]["content"]) else {
+                    continue;
+                };
+                let title = shorten(&prompt);
+                if first_topic.is_none() && title.split_whitespace().count() >= MIN_TOPIC_WORDS {
+                    first_topic = Some(title.clone());
+                }
+                latest_prompt = Some(title);
+            }
+            _ => {}
+        }
+    }
+
+    summary
+        .or(first_topic)
+        .or(latest_prompt)
+        .or_else(|| slug.map(|slug| capitalize(&slug.replace('-', " "))))
+}
+
+/// Text the user typed, or None for tool results, slash commands and notices
+fn prompt_text(content: &Value) -> Option<String> {
+    let text = match content {
+        Value::String(text) => text.as_str(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .find(|block| block["type"] == "text")
+            .and_then(|block| block["text"].as_str())?,
+        _ => return None,
+    };
+    let text = text.trim();
+    if text.is_empty() || text.starts_with('<') || text.starts_with("Caveat:") {
+        return None;
+    }
+    Some(text.to_string())
+}
+
+/// First sentence of the first line, capitalized and cut at a word boundary
+fn shorten(prompt: &str) -> String {
+    let line = prompt
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("");
+    let sentence = line
+        .find(". ")
+        .or_else(|| line.find("? "))
+        .or_else(|| line.find("! "))
+        .map_or(line, |end| &line[..end]);
+    let sentence = sentence
+        .trim()
+        .trim_end_matches(['.', '!', '?', ':', ','])
+        .trim();
+    let sentence = ["please ", "Please "]
+        .iter()
+        .find_map(|prefix| sentence.strip_prefix(prefix))
+        .unwrap_or(sentence);
+
+    if sentence.chars().count() <= MAX_TITLE_CHARS {
+        return capitalize(sentence);
+    }
+    let mut title = String::new();
+    for word in sentence.split_whitespace() {
+        if title.chars().count() + word.chars().count() + 1 > MAX_TITLE_CHARS - 1 {
+            break;
+        }
+        if !title.is_empty() {
+            title.push(' ');
+        }
+        title.push_str(word);
+    }
+    if title.is_empty() {
+        title = sentence.chars().take(MAX_TITLE_CHARS - 1).collect();
+    }
+    capitalize(&format!("{}…", title))
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(content: &str) -> String {
+        serde_json::json!({
+            "type": "user",
+            "slug": "witty-dancing-pony",
+            "message": { "role": "user", "content": content }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_title_prefers_summary_then_topic() {
+        let summary = r#"{"type":"summary","summary":"Fix flaky login test","leafUuid":"u9"}"#;
+        let lines = vec![
+            user("hi"),
+            user("<command-name>/model</command-name>"),
+            user("please write the data migration script for the users table. It should be idempotent."),
+            user("continue"),
+        ];
+
+        assert_eq!(
+            title_from_lines(lines.clone().into_iter()).as_deref(),
+            Some("Write the data migration script for the users table")
+        );
+
+        let mut with_summary = lines;
+        with_summary.push(summary.to_string());
+        assert_eq!(
+            title_from_lines(with_summary.into_iter()).as_deref(),
+            Some("Fix flaky login test")
+        );
+
+        // Nothing informative: the latest prompt, then the slug
+        assert_eq!(
+            title_from_lines(vec![user("hi"), user("go on")].into_iter()).as_deref(),
+            Some("Go on")
+        );
+        assert_eq!(
+            title_from_lines(
+                vec![user("<local-command-stdout>x</local-command-stdout>")].into_iter()
+            )
+            .as_deref(),
+            Some("Witty dancing pony")
+        );
+    }
+
+    #[test]
+    fn test_shorten_cuts_at_word_boundary() {
+        let title = shorten(&"refactor ".repeat(20));
+        assert!(title.chars().count() <= MAX_TITLE_CHARS);
+        assert!(title.starts_with("Refactor refactor"));
+        assert!(title.ends_with("refactor…"));
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// State of a TodoWrite item
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoStatus {
+    Pending,
+    InProgress,
+    Completed,
+}
+
+/// One entry of a session's TODO list, as written by the TodoWrite tool
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoItem {
+    pub content: String,
+    pub status: TodoStatus,
+    /// Present-tense label shown while the item is in progress
+    #[serde(default)]
+    pub active_form: Option<String>,
+}
+
+fn todos_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".claude").join("todos"))
+}
+
+/// The main agent's TODO list for a session, empty if it never used TodoWrite.
+///
+/// Claude Code keeps one file per agent in ~/.claude/todos/, named
+/// `<sessionId>-agent-<agentId>.json`; the main agent's id is the session id.
+pub fn load_todos(session_id: &str) -> Vec<TodoItem> {
+    match todos_dir() {
+        Some(dir) => load_todos_from(&dir, session_id),
+        None => Vec::new(),
+    }
+}
+
+fn load_todos_from(dir: &Path, session_id: &str) -> Vec<TodoItem> {
+    let path = dir.join(format!("{}-agent-{}.json", session_id, session_id));
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_todos_from_main_agent_file() {
+        let dir = std::env::temp_dir().join(format!("c9watch-todos-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("s1-agent-s1.json"),
+            r#"[
+                {"content": "Write tests", "status": "completed", "activeForm": "Writing tests"},
+                {"content": "Fix bug", "status": "in_progress", "activeForm": "Fixing bug"},
+                {"content": "Ship", "status": "pending"}
+            ]"#,
+        )
+        .unwrap();
+        fs::write(dir.join("s1-agent-sub.json"), "[]").unwrap();
+
+        let todos = load_todos_from(&dir, "s1");
+        assert_eq!(todos.len(), 3);
+        assert_eq!(todos[1].status, TodoStatus::InProgress);
+        assert_eq!(todos[1].active_form.as_deref(), Some("Fixing bug"));
+        assert_eq!(todos[2].active_form, None);
+        assert!(load_todos_from(&dir, "missing").is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
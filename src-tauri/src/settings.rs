@@ -0,0 +1,206 @@
+use crate::api_status::DEFAULT_STATUS_PAGE_URL;
+use crate::session::parser::MAX_PREVIEW_CHARS;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// User-configurable app settings, stored next to the custom names/titles files.
+///
+/// Every field has a serde default so older settings files keep loading
+/// after new options are added.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppSettings {
+    /// Send a usage summary notification once a week (Monday)
+    #[serde(default)]
+    pub weekly_digest: bool,
+
+    /// Token budget for the current local day (alerts at 80% and 100%)
+    pub daily_token_budget: Option<u64>,
+    /// Token budget for the current week, starting Monday
+    pub weekly_token_budget: Option<u64>,
+    /// Estimated cost budget in USD for the current local day
+    pub daily_cost_budget: Option<f64>,
+    /// Estimated cost budget in USD for the current week, starting Monday
+    pub weekly_cost_budget: Option<f64>,
+
+    /// Let phones and other machines reach the web server. When off, it
+    /// only listens on localhost.
+    #[serde(default = "default_true")]
+    pub mobile_access: bool,
+    /// Specific interface address to listen on instead of all interfaces
+    #[serde(default)]
+    pub bind_address: Option<String>,
+    /// CIDR ranges allowed to connect (e.g. "192.168.1.0/24"); empty allows
+    /// any peer. Localhost is always allowed.
+    #[serde(default)]
+    pub allowed_cidrs: Vec<String>,
+    /// Interface whose address goes in the QR code/URLs (e.g. "en0",
+    /// "tailscale0"); None uses the default route
+    #[serde(default)]
+    pub advertised_interface: Option<String>,
+
+    /// Run a local OTLP receiver (localhost:4318) for Claude Code telemetry.
+    /// Takes effect on the next launch.
+    #[serde(default)]
+    pub otlp_receiver: bool,
+
+    /// Statuspage summary.json polled for API incidents; empty turns the
+    /// check off
+    #[serde(default = "default_status_page_url")]
+    pub status_page_url: String,
+
+    /// Poll less often on battery while no window or WS client is open
+    #[serde(default = "default_true")]
+    pub adaptive_polling: bool,
+
+    /// Skip the native notification when the session's terminal window is
+    /// already in front
+    #[serde(default = "default_true")]
+    pub suppress_when_focused: bool,
+
+    /// Hold back native notifications for every session. Meant to be flipped
+    /// from Shortcuts, AppleScript or a c9watch://dnd link.
+    #[serde(default)]
+    pub do_not_disturb: bool,
+
+    /// List sessions that have no messages yet, as Connecting, instead of
+    /// hiding them until the first prompt
+    #[serde(default)]
+    pub show_empty_sessions: bool,
+
+    /// Set the terminal tab's title (iTerm2, kitty, WezTerm) when a session
+    /// is renamed in c9watch
+    #[serde(default)]
+    pub sync_terminal_title: bool,
+
+    /// Alert when a session's process keeps a core busy for this many
+    /// minutes, usually a wedged CLI; None turns the alert off
+    #[serde(default)]
+    pub cpu_alert_minutes: Option<u32>,
+
+    /// Drop usage history older than this many days; None keeps it forever
+    #[serde(default = "default_history_retention_days")]
+    pub history_retention_days: Option<u32>,
+    /// Drop the oldest days of usage history once the file grows past this
+    /// many megabytes; None lets it grow
+    #[serde(default = "default_history_max_mb")]
+    pub history_max_mb: Option<u64>,
+
+    /// Mask likely secrets (cloud keys, API tokens, KEY=value assignments)
+    /// in everything sent to WS/mobile clients and share links
+    #[serde(default = "default_true")]
+    pub redact_secrets: bool,
+    /// Extra regexes to redact on top of the built-in ones. A `secret`
+    /// named group masks just that part of the match.
+    #[serde(default)]
+    pub redaction_patterns: Vec<String>,
+
+    /// Characters of the first prompt shown for each session (and of the
+    /// pending tool input in permission notifications)
+    #[serde(default = "default_prompt_preview_chars")]
+    pub prompt_preview_chars: usize,
+    /// Characters of the latest message shown for each session
+    #[serde(default = "default_message_preview_chars")]
+    pub message_preview_chars: usize,
+    /// Characters of the first prompt used as a notification's title
+    #[serde(default = "default_notification_title_chars")]
+    pub notification_title_chars: usize,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_status_page_url() -> String {
+    DEFAULT_STATUS_PAGE_URL.to_string()
+}
+
+fn default_prompt_preview_chars() -> usize {
+    100
+}
+
+fn default_message_preview_chars() -> usize {
+    200
+}
+
+fn default_notification_title_chars() -> usize {
+    60
+}
+
+fn default_history_retention_days() -> Option<u32> {
+    Some(90)
+}
+
+fn default_history_max_mb() -> Option<u64> {
+    Some(500)
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            weekly_digest: false,
+            daily_token_budget: None,
+            weekly_token_budget: None,
+            daily_cost_budget: None,
+            weekly_cost_budget: None,
+            mobile_access: true,
+            bind_address: None,
+            allowed_cidrs: Vec::new(),
+            advertised_interface: None,
+            otlp_receiver: false,
+            status_page_url: default_status_page_url(),
+            adaptive_polling: true,
+            suppress_when_focused: true,
+            do_not_disturb: false,
+            show_empty_sessions: false,
+            sync_terminal_title: false,
+            cpu_alert_minutes: None,
+            history_retention_days: default_history_retention_days(),
+            history_max_mb: default_history_max_mb(),
+            redact_secrets: true,
+            redaction_patterns: Vec::new(),
+            prompt_preview_chars: default_prompt_preview_chars(),
+            message_preview_chars: default_message_preview_chars(),
+            notification_title_chars: default_notification_title_chars(),
+        }
+    }
+}
+
+impl AppSettings {
+    pub fn load() -> Self {
+        let path = Self::get_path();
+        if let Ok(content) = fs::read_to_string(path) {
+            serde_json::from_str(&content).unwrap_or_default()
+        } else {
+            Self::default()
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::get_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| e.to_string())
+    }
+
+    /// Preview lengths, capped at what providers keep
+    pub fn prompt_preview_chars(&self) -> usize {
+        self.prompt_preview_chars.min(MAX_PREVIEW_CHARS)
+    }
+
+    pub fn message_preview_chars(&self) -> usize {
+        self.message_preview_chars.min(MAX_PREVIEW_CHARS)
+    }
+
+    pub fn notification_title_chars(&self) -> usize {
+        self.notification_title_chars.min(MAX_PREVIEW_CHARS)
+    }
+
+    fn get_path() -> PathBuf {
+        let home = dirs::home_dir().expect("Failed to get home directory");
+        home.join(".claude").join("session-monitor-settings.json")
+    }
+}
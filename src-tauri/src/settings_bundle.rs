@@ -0,0 +1,152 @@
+use crate::error::C9Error;
+use crate::session::{
+    CustomNames, CustomTitles, IgnoreList, SessionColor, SessionColors, SessionNotes,
+};
+use crate::settings::AppSettings;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Bump when the bundle layout changes, and add a step to `migrate`
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// Everything a user has configured, in one file for moving to a new machine.
+///
+/// Session history and paired devices are left out: the first is data, not
+/// setup, and the second holds credentials tied to this machine's token.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsBundle {
+    pub version: u64,
+    /// RFC 3339
+    #[serde(default)]
+    pub exported_at: String,
+    #[serde(default)]
+    pub settings: AppSettings,
+    #[serde(default)]
+    pub custom_names: HashMap<String, String>,
+    #[serde(default)]
+    pub custom_titles: HashMap<String, String>,
+    #[serde(default)]
+    pub ignore_list: IgnoreList,
+    #[serde(default)]
+    pub notes: HashMap<String, String>,
+    #[serde(default)]
+    pub colors: HashMap<String, SessionColor>,
+}
+
+/// Bundle the current setup as pretty JSON
+pub fn export() -> Result<String, String> {
+    let bundle = SettingsBundle {
+        version: SCHEMA_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        settings: AppSettings::load(),
+        custom_names: CustomNames::load().names,
+        custom_titles: CustomTitles::load().titles,
+        ignore_list: IgnoreList::load(),
+        notes: SessionNotes::load().notes,
+        colors: SessionColors::load().colors,
+    };
+    serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())
+}
+
+/// Replace the current setup with a bundle from `export`.
+///
+/// Everything is validated before anything is written, so a bad bundle
+/// leaves the existing setup alone.
+pub fn import(json: &str) -> Result<(), C9Error> {
+    let mut bundle = parse(json).map_err(C9Error::ParseError)?;
+    bundle.ignore_list.validate().map_err(C9Error::ParseError)?;
+    crate::redact::validate_patterns(&bundle.settings.redaction_patterns)
+        .map_err(C9Error::ParseError)?;
+
+    // These name this machine's interfaces, which the new one won't have
+    bundle.settings.bind_address = None;
+    bundle.settings.advertised_interface = None;
+
+    bundle.settings.save()?;
+    CustomNames {
+        names: bundle.custom_names,
+    }
+    .save()?;
+    CustomTitles {
+        titles: bundle.custom_titles,
+    }
+    .save()?;
+    SessionNotes {
+        notes: bundle.notes,
+    }
+    .save()?;
+    SessionColors {
+        colors: bundle.colors,
+    }
+    .save()?;
+    Ok(bundle.ignore_list.save()?)
+}
+
+fn parse(json: &str) -> Result<SettingsBundle, String> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| format!("Not a settings bundle: {}", e))?;
+    let value = migrate(value)?;
+    serde_json::from_value(value).map_err(|e| format!("Invalid settings bundle: {}", e))
+}
+
+/// Bring an older bundle up to `SCHEMA_VERSION`, one version at a time
+fn migrate(mut value: Value) -> Result<Value, String> {
+    if !value.is_object() {
+        return Err("Not a settings bundle".to_string());
+    }
+
+    loop {
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+        if version > SCHEMA_VERSION {
+            return Err(format!(
+                "Settings bundle version {} is newer than this c9watch supports ({}); update c9watch first",
+                version, SCHEMA_VERSION
+            ));
+        }
+        if version == SCHEMA_VERSION {
+            return Ok(value);
+        }
+
+        value = match version {
+            // Before bundles existed: a bare session-monitor-settings.json
+            // copied from the old machine
+            0 => json!({ "version": 1, "settings": value }),
+            _ => unreachable!("every version below SCHEMA_VERSION has a migration"),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_round_trip_and_migration() {
+        let bundle = SettingsBundle {
+            version: SCHEMA_VERSION,
+            exported_at: String::new(),
+            settings: AppSettings::default(),
+            custom_names: HashMap::from([("s1".to_string(), "release".to_string())]),
+            custom_titles: HashMap::new(),
+            ignore_list: IgnoreList::default(),
+            notes: HashMap::new(),
+            colors: HashMap::new(),
+        };
+        let json = serde_json::to_string(&bundle).unwrap();
+        let parsed = parse(&json).unwrap();
+        assert_eq!(parsed.custom_names["s1"], "release");
+
+        // An old bare settings file
+        let parsed = parse(r#"{ "weeklyDigest": true, "otlpReceiver": true }"#).unwrap();
+        assert_eq!(parsed.version, SCHEMA_VERSION);
+        assert!(parsed.settings.weekly_digest);
+        assert!(parsed.settings.otlp_receiver);
+        assert!(parsed.custom_names.is_empty());
+
+        assert!(parse(r#"{ "version": 99 }"#).is_err());
+        assert!(parse("[]").is_err());
+    }
+}
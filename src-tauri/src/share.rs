@@ -0,0 +1,103 @@
+use crate::Conversation;
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Lifetime of a share link when the caller doesn't pick one
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Longest a share link may stay valid
+pub const MAX_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A transcript snapshot reachable at `/share/<token>`
+struct Share {
+    html: String,
+    expires: Instant,
+}
+
+/// Link handed back to the UI
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareLink {
+    pub url: String,
+    /// RFC 3339
+    pub expires_at: String,
+}
+
+static SHARES: OnceLock<Mutex<HashMap<String, Share>>> = OnceLock::new();
+
+fn shares() -> &'static Mutex<HashMap<String, Share>> {
+    SHARES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Snapshot `conversation` as a read-only page behind a fresh random token.
+/// The link works once, until it expires.
+pub fn create(conversation: &Conversation, base_url: &str, ttl: Duration) -> ShareLink {
+    let ttl = ttl.min(MAX_TTL);
+    let token = crate::auth::generate_token();
+
+    let mut shares = shares().lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    shares.retain(|_, share| share.expires > now);
+    shares.insert(
+        token.clone(),
+        Share {
+            html: crate::transcript::render_html(conversation),
+            expires: now + ttl,
+        },
+    );
+
+    let expires_at = chrono::Duration::from_std(ttl)
+        .map(|ttl| Utc::now() + ttl)
+        .unwrap_or_else(|_| Utc::now());
+    ShareLink {
+        url: format!("{}/share/{}", base_url.trim_end_matches('/'), token),
+        expires_at: expires_at.to_rfc3339(),
+    }
+}
+
+/// The page behind `token`, if it exists and hasn't expired. Viewing it
+/// uses the token up.
+pub fn take_page(token: &str) -> Option<String> {
+    let mut shares = shares().lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    shares.retain(|_, share| share.expires > now);
+    shares.remove(token).map(|share| share.html)
+}
+
+/// Whether `token` is still valid, without using it up
+pub fn exists(token: &str) -> bool {
+    let shares = shares().lock().unwrap_or_else(|e| e.into_inner());
+    shares
+        .get(token)
+        .is_some_and(|share| share.expires > Instant::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_links_expire_and_are_single_use() {
+        let conversation = Conversation {
+            session_id: "s1".to_string(),
+            messages: Vec::new(),
+            branches: Vec::new(),
+        };
+
+        let link = create(&conversation, "http://10.0.0.2:9210/", DEFAULT_TTL);
+        let token = link.url.rsplit('/').next().unwrap();
+        assert!(link.url.starts_with("http://10.0.0.2:9210/share/"));
+        assert!(exists(token));
+        assert!(take_page(token).unwrap().contains("Session s1"));
+        // Single use
+        assert!(!exists(token));
+        assert!(take_page(token).is_none());
+        assert!(take_page("not-a-token").is_none());
+
+        let expired = create(&conversation, "http://10.0.0.2:9210", Duration::ZERO);
+        assert!(take_page(expired.url.rsplit('/').next().unwrap()).is_none());
+    }
+}
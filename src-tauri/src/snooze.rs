@@ -0,0 +1,96 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+
+/// Longest a session can be snoozed for
+pub const MAX_SNOOZE_MINUTES: u32 = 24 * 60;
+
+/// Snoozes the user has set, checked by the polling loop before notifying
+static SNOOZES: OnceLock<Snoozes> = OnceLock::new();
+
+pub fn snoozes() -> &'static Snoozes {
+    SNOOZES.get_or_init(Snoozes::default)
+}
+
+/// Sessions whose notifications are held back until a set time. Unlike
+/// ignoring, the session stays visible and starts notifying again by itself.
+#[derive(Debug, Default)]
+pub struct Snoozes {
+    until: Mutex<HashMap<String, DateTime<Utc>>>,
+}
+
+impl Snoozes {
+    /// Silence a session for `minutes` (0 ends a snooze). Returns when it ends.
+    pub fn snooze(&self, session_id: &str, minutes: u32) -> Option<DateTime<Utc>> {
+        self.snooze_at(session_id, minutes, Utc::now())
+    }
+
+    fn snooze_at(
+        &self,
+        session_id: &str,
+        minutes: u32,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let mut until = self.until.lock().unwrap_or_else(|e| e.into_inner());
+        if minutes == 0 {
+            until.remove(session_id);
+            return None;
+        }
+        let end = now + Duration::minutes(minutes.min(MAX_SNOOZE_MINUTES) as i64);
+        until.insert(session_id.to_string(), end);
+        Some(end)
+    }
+
+    /// When the session's snooze ends, if it's snoozed
+    pub fn snoozed_until(&self, session_id: &str) -> Option<DateTime<Utc>> {
+        self.snoozed_until_at(session_id, Utc::now())
+    }
+
+    fn snoozed_until_at(&self, session_id: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut until = self.until.lock().unwrap_or_else(|e| e.into_inner());
+        match until.get(session_id) {
+            Some(end) if *end > now => Some(*end),
+            Some(_) => {
+                until.remove(session_id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Drop snoozes for sessions that are no longer running
+    pub fn retain(&self, session_ids: &HashSet<String>) {
+        let mut until = self.until.lock().unwrap_or_else(|e| e.into_inner());
+        until.retain(|id, _| session_ids.contains(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snooze_ends_by_itself() {
+        let snoozes = Snoozes::default();
+        let t0 = Utc::now();
+
+        let end = snoozes.snooze_at("s1", 30, t0).unwrap();
+        assert_eq!(end, t0 + Duration::minutes(30));
+        assert_eq!(
+            snoozes.snoozed_until_at("s1", t0 + Duration::minutes(29)),
+            Some(end)
+        );
+        assert_eq!(
+            snoozes.snoozed_until_at("s1", t0 + Duration::minutes(30)),
+            None
+        );
+        assert_eq!(snoozes.snoozed_until_at("s2", t0), None);
+
+        snoozes.snooze_at("s1", 30, t0);
+        assert_eq!(snoozes.snooze_at("s1", 0, t0), None);
+        assert_eq!(snoozes.snoozed_until_at("s1", t0), None);
+
+        let capped = snoozes.snooze_at("s1", u32::MAX, t0).unwrap();
+        assert_eq!(capped, t0 + Duration::hours(24));
+    }
+}
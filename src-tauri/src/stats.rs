@@ -0,0 +1,704 @@
+use crate::error::C9Error;
+use crate::providers::claude::find_transcript;
+use crate::session::{summarize_tool_input, Usage};
+use crate::usage::TokenCounts;
+use chrono::DateTime;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use tracing::warn;
+
+/// Failed calls of the same tool with the same input needed before they are
+/// reported as a retry loop
+const MIN_REPEATED_FAILURES: u32 = 2;
+
+/// Call counts and durations for one tool in one session
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolStats {
+    pub name: String,
+    pub calls: u32,
+    /// Calls whose result was an error
+    pub failures: u32,
+    /// Calls still waiting for a result
+    pub pending: u32,
+    /// Time from the tool call to its result, over completed calls
+    pub total_ms: u64,
+    pub max_ms: u64,
+}
+
+/// The same call failing again and again
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RepeatedFailure {
+    pub tool_name: String,
+    /// Summary of the input (command, file, arguments)
+    pub input: String,
+    pub failures: u32,
+    /// RFC 3339 time of the latest failure
+    pub last_failed_at: String,
+}
+
+/// Transcript messages by kind, counting content blocks the way the
+/// conversation view splits them
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageCounts {
+    pub user: u32,
+    pub assistant: u32,
+    pub thinking: u32,
+    pub tool_use: u32,
+    pub tool_result: u32,
+    pub system: u32,
+}
+
+/// Message, token and tool analytics for a session
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStats {
+    pub session_id: String,
+    pub messages: MessageCounts,
+    pub tokens: TokenCounts,
+    /// From the first to the latest transcript entry
+    pub duration_seconds: u64,
+    /// API errors Claude Code recorded (overloaded, rate limited, ...)
+    pub api_errors: u32,
+    pub tool_calls: u32,
+    pub tool_failures: u32,
+    /// Sorted by call count, most used first
+    pub tools: Vec<ToolStats>,
+    /// Most failures first
+    pub repeated_failures: Vec<RepeatedFailure>,
+}
+
+/// A file the agent modified, with how often
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChange {
+    pub path: String,
+    /// Successful Edit, MultiEdit and NotebookEdit calls
+    pub edits: u32,
+    /// Successful Write calls (file created or overwritten)
+    pub writes: u32,
+    /// RFC 3339 time of the latest change
+    pub last_changed_at: String,
+}
+
+/// One MCP server a session has called
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerUsage {
+    pub name: String,
+    pub calls: u32,
+    /// Calls whose result was an error
+    pub errors: u32,
+    /// Tools used on this server, sorted
+    pub tools: Vec<String>,
+    /// RFC 3339 time of the latest error, if any
+    pub last_error_at: Option<String>,
+}
+
+/// Split an MCP tool name, `mcp__<server>__<tool>`, into (server, tool)
+pub fn parse_mcp_tool(name: &str) -> Option<(&str, &str)> {
+    name.strip_prefix("mcp__")?
+        .split_once("__")
+        .filter(|(server, tool)| !server.is_empty() && !tool.is_empty())
+}
+
+/// MCP usage of one transcript, updated from whatever was appended since the
+/// last call so the whole file is only read once
+#[derive(Default)]
+pub struct McpScan {
+    offset: u64,
+    /// tool_use id -> server, for calls without a result yet
+    pending: HashMap<String, String>,
+    servers: BTreeMap<String, McpServerUsage>,
+}
+
+impl McpScan {
+    /// Read newly appended lines and return every server used so far,
+    /// by name
+    pub fn update(&mut self, path: &Path) -> Vec<McpServerUsage> {
+        if let Err(e) = self.read_appended(path) {
+            warn!("Failed to scan {} for MCP calls: {}", path.display(), e);
+        }
+        self.servers.values().cloned().collect()
+    }
+
+    fn read_appended(&mut self, path: &Path) -> std::io::Result<()> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            // Rewritten: start over
+            *self = Self::default();
+        }
+        if len == self.offset {
+            return Ok(());
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        file.take(len - self.offset).read_to_end(&mut buf)?;
+        // Leave a partially written last line for the next call
+        let Some(end) = buf.iter().rposition(|&b| b == b'\n') else {
+            return Ok(());
+        };
+        self.offset += end as u64 + 1;
+
+        for line in String::from_utf8_lossy(&buf[..end]).lines() {
+            tool_blocks_in_line(line, &mut |timestamp, block| self.record(timestamp, block));
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, timestamp: &str, block: &Value) {
+        match block["type"].as_str() {
+            Some("tool_use") => {
+                let Some((server, tool)) = block["name"].as_str().and_then(parse_mcp_tool) else {
+                    return;
+                };
+                let usage =
+                    self.servers
+                        .entry(server.to_string())
+                        .or_insert_with(|| McpServerUsage {
+                            name: server.to_string(),
+                            ..Default::default()
+                        });
+                usage.calls += 1;
+                if let Err(pos) = usage.tools.binary_search_by(|t| t.as_str().cmp(tool)) {
+                    usage.tools.insert(pos, tool.to_string());
+                }
+                if let Some(id) = block["id"].as_str() {
+                    self.pending.insert(id.to_string(), server.to_string());
+                }
+            }
+            Some("tool_result") => {
+                let Some(server) = block["tool_use_id"]
+                    .as_str()
+                    .and_then(|id| self.pending.remove(id))
+                else {
+                    return;
+                };
+                if block["is_error"].as_bool() == Some(true) {
+                    if let Some(usage) = self.servers.get_mut(&server) {
+                        usage.errors += 1;
+                        usage.last_error_at = Some(timestamp.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Tool call statistics for a Claude session, from its full transcript
+pub fn session_stats(session_id: &str) -> Result<SessionStats, C9Error> {
+    let path = find_transcript(session_id)?;
+    let mut stats = stats_from_file(&path)?;
+    stats.session_id = session_id.to_string();
+    Ok(stats)
+}
+
+/// Files a Claude session modified through its edit tools, most recently
+/// changed first. Calls that failed or are still pending are not counted.
+pub fn session_changes(session_id: &str) -> Result<Vec<FileChange>, C9Error> {
+    Ok(changes_from_file(&find_transcript(session_id)?)?)
+}
+
+/// A tool_use block waiting to be paired with its tool_result
+struct PendingCall {
+    name: String,
+    input: String,
+    started_ms: Option<i64>,
+}
+
+/// Call `f` with every tool_use and tool_result content block of a
+/// transcript, in file order, along with its entry's timestamp
+fn for_each_tool_block(path: &Path, mut f: impl FnMut(&str, &Value)) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open session file: {}", e))?;
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        tool_blocks_in_line(&line, &mut f);
+    }
+    Ok(())
+}
+
+/// Call `f` with the tool_use and tool_result blocks of one JSONL line
+fn tool_blocks_in_line(line: &str, f: &mut impl FnMut(&str, &Value)) {
+    // Cheap pre-filter before full JSON parsing
+    if !line.contains("\"tool_use") && !line.contains("\"tool_result") {
+        return;
+    }
+    let Ok(entry) = serde_json::from_str::<Value>(line) else {
+        return;
+    };
+    let timestamp = entry["timestamp"].as_str().unwrap_or_default();
+    let Some(blocks) = entry["message"]["content"].as_array() else {
+        return;
+    };
+    for block in blocks {
+        f(timestamp, block);
+    }
+}
+
+/// Message counts, deduplicated token usage, time span and API errors
+#[derive(Default)]
+struct EntryTally {
+    messages: MessageCounts,
+    tokens: TokenCounts,
+    api_errors: u32,
+    first_ms: Option<i64>,
+    last_ms: Option<i64>,
+    // Claude Code writes one line per content block, each repeating the
+    // message's usage, so only count each message ID once
+    seen_message_ids: HashSet<String>,
+}
+
+impl EntryTally {
+    fn add(&mut self, entry: &Value) {
+        if let Some(ms) = entry["timestamp"]
+            .as_str()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.timestamp_millis())
+        {
+            self.first_ms = Some(self.first_ms.map_or(ms, |first| first.min(ms)));
+            self.last_ms = Some(self.last_ms.map_or(ms, |last| last.max(ms)));
+        }
+        if entry["isApiErrorMessage"].as_bool() == Some(true) {
+            self.api_errors += 1;
+        }
+
+        let content = &entry["message"]["content"];
+        match entry["type"].as_str() {
+            Some("user") => {
+                if content.is_string() {
+                    self.messages.user += 1;
+                }
+                let blocks = content.as_array().map(Vec::as_slice).unwrap_or_default();
+                if blocks.iter().any(|b| b["type"] == "text") {
+                    self.messages.user += 1;
+                }
+                self.messages.tool_result +=
+                    blocks.iter().filter(|b| b["type"] == "tool_result").count() as u32;
+            }
+            Some("assistant") => {
+                for block in content.as_array().map(Vec::as_slice).unwrap_or_default() {
+                    match block["type"].as_str() {
+                        Some("text") => self.messages.assistant += 1,
+                        Some("thinking") => self.messages.thinking += 1,
+                        Some("tool_use") => self.messages.tool_use += 1,
+                        _ => {}
+                    }
+                }
+                let message = &entry["message"];
+                let first_sighting = match message["id"].as_str() {
+                    Some(id) => self.seen_message_ids.insert(id.to_string()),
+                    None => true,
+                };
+                if first_sighting {
+                    if let Ok(usage) = serde_json::from_value::<Usage>(message["usage"].clone()) {
+                        self.tokens.add(&TokenCounts::from_usage(&usage));
+                    }
+                }
+            }
+            Some("system") => self.messages.system += 1,
+            _ => {}
+        }
+    }
+
+    fn duration_seconds(&self) -> u64 {
+        match (self.first_ms, self.last_ms) {
+            (Some(first), Some(last)) => (last - first).max(0) as u64 / 1000,
+            _ => 0,
+        }
+    }
+}
+
+fn stats_from_file(path: &Path) -> Result<SessionStats, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open session file: {}", e))?;
+    let mut tally = EntryTally::default();
+    let mut pending: HashMap<String, PendingCall> = HashMap::new();
+    let mut tools: HashMap<String, ToolStats> = HashMap::new();
+    let mut failures: HashMap<(String, String), RepeatedFailure> = HashMap::new();
+
+    let mut on_tool_block = |timestamp: &str, block: &Value| {
+        let time_ms = DateTime::parse_from_rfc3339(timestamp)
+            .ok()
+            .map(|t| t.timestamp_millis());
+
+        match block["type"].as_str() {
+            Some("tool_use") => {
+                let (Some(id), Some(name)) = (block["id"].as_str(), block["name"].as_str()) else {
+                    return;
+                };
+                tools
+                    .entry(name.to_string())
+                    .or_insert_with(|| ToolStats {
+                        name: name.to_string(),
+                        ..Default::default()
+                    })
+                    .calls += 1;
+                pending.insert(
+                    id.to_string(),
+                    PendingCall {
+                        name: name.to_string(),
+                        input: summarize_tool_input(name, &block["input"]),
+                        started_ms: time_ms,
+                    },
+                );
+            }
+            Some("tool_result") => {
+                let Some(call) = block["tool_use_id"]
+                    .as_str()
+                    .and_then(|id| pending.remove(id))
+                else {
+                    return;
+                };
+                let Some(tool) = tools.get_mut(&call.name) else {
+                    return;
+                };
+                if let (Some(start), Some(end)) = (call.started_ms, time_ms) {
+                    let elapsed = end.saturating_sub(start).max(0) as u64;
+                    tool.total_ms += elapsed;
+                    tool.max_ms = tool.max_ms.max(elapsed);
+                }
+                if block["is_error"].as_bool() == Some(true) {
+                    tool.failures += 1;
+                    let failure = failures
+                        .entry((call.name.clone(), call.input.clone()))
+                        .or_insert_with(|| RepeatedFailure {
+                            tool_name: call.name,
+                            input: call.input,
+                            failures: 0,
+                            last_failed_at: String::new(),
+                        });
+                    failure.failures += 1;
+                    failure.last_failed_at = timestamp.to_string();
+                }
+            }
+            _ => {}
+        }
+    };
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        tally.add(&entry);
+
+        let timestamp = entry["timestamp"].as_str().unwrap_or_default();
+        for block in entry["message"]["content"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+        {
+            if matches!(block["type"].as_str(), Some("tool_use" | "tool_result")) {
+                on_tool_block(timestamp, block);
+            }
+        }
+    }
+
+    for call in pending.values() {
+        if let Some(tool) = tools.get_mut(&call.name) {
+            tool.pending += 1;
+        }
+    }
+
+    let mut tools: Vec<ToolStats> = tools.into_values().collect();
+    tools.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.name.cmp(&b.name)));
+
+    let mut repeated_failures: Vec<RepeatedFailure> = failures
+        .into_values()
+        .filter(|f| f.failures >= MIN_REPEATED_FAILURES)
+        .collect();
+    repeated_failures.sort_by(|a, b| {
+        b.failures
+            .cmp(&a.failures)
+            .then_with(|| b.last_failed_at.cmp(&a.last_failed_at))
+    });
+
+    Ok(SessionStats {
+        session_id: String::new(),
+        duration_seconds: tally.duration_seconds(),
+        messages: tally.messages,
+        tokens: tally.tokens,
+        api_errors: tally.api_errors,
+        tool_calls: tools.iter().map(|t| t.calls).sum(),
+        tool_failures: tools.iter().map(|t| t.failures).sum(),
+        tools,
+        repeated_failures,
+    })
+}
+
+fn changes_from_file(path: &Path) -> Result<Vec<FileChange>, String> {
+    // tool_use id -> (file path, whether it's a Write)
+    let mut pending: HashMap<String, (String, bool)> = HashMap::new();
+    let mut files: HashMap<String, FileChange> = HashMap::new();
+
+    for_each_tool_block(path, |timestamp, block| match block["type"].as_str() {
+        Some("tool_use") => {
+            let path_key = match block["name"].as_str() {
+                Some("Edit" | "MultiEdit" | "Write") => "file_path",
+                Some("NotebookEdit") => "notebook_path",
+                _ => return,
+            };
+            let (Some(id), Some(file)) = (block["id"].as_str(), block["input"][path_key].as_str())
+            else {
+                return;
+            };
+            let is_write = block["name"] == "Write";
+            pending.insert(id.to_string(), (file.to_string(), is_write));
+        }
+        Some("tool_result") => {
+            let Some((file, is_write)) = block["tool_use_id"]
+                .as_str()
+                .and_then(|id| pending.remove(id))
+            else {
+                return;
+            };
+            if block["is_error"].as_bool() == Some(true) {
+                return;
+            }
+            let change = files.entry(file.clone()).or_insert_with(|| FileChange {
+                path: file,
+                edits: 0,
+                writes: 0,
+                last_changed_at: String::new(),
+            });
+            if is_write {
+                change.writes += 1;
+            } else {
+                change.edits += 1;
+            }
+            change.last_changed_at = timestamp.to_string();
+        }
+        _ => {}
+    })?;
+
+    let mut changes: Vec<FileChange> = files.into_values().collect();
+    changes.sort_by(|a, b| {
+        b.last_changed_at
+            .cmp(&a.last_changed_at)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool_use(id: &str, command: &str, at: &str) -> String {
+        tool_call(id, "Bash", json!({ "command": command }), at)
+    }
+
+    fn tool_call(id: &str, name: &str, input: Value, at: &str) -> String {
+        json!({
+            "type": "assistant",
+            "timestamp": at,
+            "message": { "content": [
+                { "type": "tool_use", "id": id, "name": name, "input": input }
+            ]}
+        })
+        .to_string()
+    }
+
+    fn write_transcript(name: &str, lines: &[String]) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("c9watch-{}-{}.jsonl", name, std::process::id()));
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        path
+    }
+
+    fn tool_result(id: &str, is_error: bool, at: &str) -> String {
+        json!({
+            "type": "user",
+            "timestamp": at,
+            "message": { "content": [
+                { "type": "tool_result", "tool_use_id": id, "content": "", "is_error": is_error }
+            ]}
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_stats_pair_calls_with_results() {
+        let lines = [
+            tool_use("t1", "cargo test", "2026-02-10T10:00:00Z"),
+            tool_result("t1", true, "2026-02-10T10:00:04Z"),
+            tool_use("t2", "cargo test", "2026-02-10T10:01:00Z"),
+            tool_result("t2", true, "2026-02-10T10:01:02Z"),
+            tool_use("t3", "ls", "2026-02-10T10:02:00Z"),
+            tool_result("t3", false, "2026-02-10T10:02:00.500Z"),
+            tool_use("t4", "cargo build", "2026-02-10T10:03:00Z"),
+        ];
+        let path = write_transcript("stats", &lines);
+
+        let stats = stats_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(stats.tool_calls, 4);
+        assert_eq!(stats.tool_failures, 2);
+        let bash = &stats.tools[0];
+        assert_eq!((bash.calls, bash.failures, bash.pending), (4, 2, 1));
+        assert_eq!((bash.total_ms, bash.max_ms), (6_500, 4_000));
+        assert_eq!(stats.repeated_failures.len(), 1);
+        assert_eq!(stats.repeated_failures[0].input, "cargo test");
+        assert_eq!(stats.repeated_failures[0].failures, 2);
+    }
+
+    #[test]
+    fn test_stats_count_messages_and_tokens() {
+        let assistant = |block: Value, at: &str| {
+            json!({
+                "type": "assistant",
+                "timestamp": at,
+                "message": {
+                    "id": "msg_1",
+                    "content": [block],
+                    "usage": { "input_tokens": 100, "output_tokens": 20 }
+                }
+            })
+            .to_string()
+        };
+        let lines = [
+            json!({
+                "type": "user",
+                "timestamp": "2026-02-10T10:00:00Z",
+                "message": { "content": "Fix the build" }
+            })
+            .to_string(),
+            // One API message split over three lines, usage repeated on each
+            assistant(
+                json!({ "type": "thinking", "thinking": "..." }),
+                "2026-02-10T10:00:05Z",
+            ),
+            assistant(
+                json!({ "type": "text", "text": "On it" }),
+                "2026-02-10T10:00:06Z",
+            ),
+            assistant(
+                json!({ "type": "tool_use", "id": "t1", "name": "Bash", "input": {} }),
+                "2026-02-10T10:00:07Z",
+            ),
+            tool_result("t1", false, "2026-02-10T10:01:30Z"),
+            json!({
+                "type": "system",
+                "timestamp": "2026-02-10T10:01:31Z",
+                "isApiErrorMessage": true
+            })
+            .to_string(),
+        ];
+        let path = write_transcript("messages", &lines);
+
+        let stats = stats_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            stats.messages,
+            MessageCounts {
+                user: 1,
+                assistant: 1,
+                thinking: 1,
+                tool_use: 1,
+                tool_result: 1,
+                system: 1,
+            }
+        );
+        assert_eq!(stats.tokens.input_tokens, 100);
+        assert_eq!(stats.tokens.output_tokens, 20);
+        assert_eq!(stats.duration_seconds, 91);
+        assert_eq!(stats.api_errors, 1);
+        assert_eq!(stats.tool_calls, 1);
+    }
+
+    #[test]
+    fn test_changes_count_successful_edits_per_file() {
+        let edit = |file: &str| json!({ "file_path": file, "old_string": "a", "new_string": "b" });
+        let lines = [
+            tool_call(
+                "e1",
+                "Write",
+                json!({ "file_path": "/p/new.rs", "content": "" }),
+                "2026-02-10T10:00:00Z",
+            ),
+            tool_result("e1", false, "2026-02-10T10:00:01Z"),
+            tool_call("e2", "Edit", edit("/p/lib.rs"), "2026-02-10T10:01:00Z"),
+            tool_result("e2", false, "2026-02-10T10:01:01Z"),
+            tool_call("e3", "Edit", edit("/p/new.rs"), "2026-02-10T10:02:00Z"),
+            tool_result("e3", false, "2026-02-10T10:02:01Z"),
+            tool_call("e4", "Edit", edit("/p/lib.rs"), "2026-02-10T10:03:00Z"),
+            tool_result("e4", true, "2026-02-10T10:03:01Z"),
+            tool_call(
+                "e5",
+                "Read",
+                json!({ "file_path": "/p/main.rs" }),
+                "2026-02-10T10:04:00Z",
+            ),
+            tool_result("e5", false, "2026-02-10T10:04:01Z"),
+            tool_call("e6", "Edit", edit("/p/main.rs"), "2026-02-10T10:05:00Z"),
+        ];
+        let path = write_transcript("changes", &lines);
+
+        let changes = changes_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].path, "/p/new.rs");
+        assert_eq!((changes[0].edits, changes[0].writes), (1, 1));
+        assert_eq!(changes[0].last_changed_at, "2026-02-10T10:02:01Z");
+        assert_eq!(changes[1].path, "/p/lib.rs");
+        assert_eq!((changes[1].edits, changes[1].writes), (1, 0));
+    }
+
+    #[test]
+    fn test_mcp_scan_reads_appended_lines() {
+        assert_eq!(
+            parse_mcp_tool("mcp__github__create_issue"),
+            Some(("github", "create_issue"))
+        );
+        assert_eq!(parse_mcp_tool("Bash"), None);
+
+        let query = || json!({ "sql": "select 1" });
+        let mut lines = vec![
+            tool_call(
+                "m1",
+                "mcp__postgres__query",
+                query(),
+                "2026-02-10T10:00:00Z",
+            ),
+            tool_result("m1", true, "2026-02-10T10:00:01Z"),
+            tool_use("b1", "ls", "2026-02-10T10:00:02Z"),
+        ];
+        let path = write_transcript("mcp", &lines);
+        let mut scan = McpScan::default();
+        let servers = scan.update(&path);
+        assert_eq!(servers.len(), 1);
+        assert_eq!((servers[0].calls, servers[0].errors), (1, 1));
+
+        // The rest of the unfinished b1 line and a second call arrive later
+        lines.push(tool_call(
+            "m2",
+            "mcp__postgres__list_tables",
+            json!({}),
+            "2026-02-10T10:01:00Z",
+        ));
+        lines.push(tool_result("m2", false, "2026-02-10T10:01:01Z"));
+        lines.push(String::new());
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let servers = scan.update(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let postgres = &servers[0];
+        assert_eq!((postgres.calls, postgres.errors), (2, 1));
+        assert_eq!(postgres.tools, vec!["list_tables", "query"]);
+        assert_eq!(
+            postgres.last_error_at.as_deref(),
+            Some("2026-02-10T10:00:01Z")
+        );
+    }
+}
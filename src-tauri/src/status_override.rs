@@ -0,0 +1,131 @@
+use crate::session::SessionStatus;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+
+/// Lifetime of an override when the caller doesn't pick one
+pub const DEFAULT_TTL_SECS: u64 = 60 * 60;
+
+/// Overrides the user has set, applied by the polling loop
+static OVERRIDES: OnceLock<StatusOverrides> = OnceLock::new();
+
+pub fn overrides() -> &'static StatusOverrides {
+    OVERRIDES.get_or_init(StatusOverrides::default)
+}
+
+#[derive(Debug)]
+struct StatusOverride {
+    status: SessionStatus,
+    expires: DateTime<Utc>,
+    /// Status the heuristic reported when the override was first applied;
+    /// None until the next poll sees the session
+    detected: Option<SessionStatus>,
+}
+
+/// Manual status corrections for sessions the heuristic gets wrong (e.g. an
+/// abandoned session still showing Working). An override lasts until its
+/// TTL runs out or the detected status genuinely changes, whichever is first.
+#[derive(Debug, Default)]
+pub struct StatusOverrides {
+    overrides: Mutex<HashMap<String, StatusOverride>>,
+}
+
+impl StatusOverrides {
+    /// Show `status` for the session for `ttl` seconds (0 drops an override)
+    pub fn set(&self, session_id: &str, status: SessionStatus, ttl: u64) {
+        self.set_at(session_id, status, ttl, Utc::now());
+    }
+
+    fn set_at(&self, session_id: &str, status: SessionStatus, ttl: u64, now: DateTime<Utc>) {
+        let mut overrides = self.overrides.lock().unwrap_or_else(|e| e.into_inner());
+        if ttl == 0 {
+            overrides.remove(session_id);
+            return;
+        }
+        let ttl = Duration::seconds(ttl.min(i64::MAX as u64) as i64);
+        overrides.insert(
+            session_id.to_string(),
+            StatusOverride {
+                status,
+                expires: now + ttl,
+                detected: None,
+            },
+        );
+    }
+
+    /// Status to report for a session given what was detected, and whether
+    /// it's an override
+    pub fn apply(&self, session_id: &str, detected: SessionStatus) -> (SessionStatus, bool) {
+        self.apply_at(session_id, detected, Utc::now())
+    }
+
+    fn apply_at(
+        &self,
+        session_id: &str,
+        detected: SessionStatus,
+        now: DateTime<Utc>,
+    ) -> (SessionStatus, bool) {
+        let mut overrides = self.overrides.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(entry) = overrides.get_mut(session_id) else {
+            return (detected, false);
+        };
+
+        let baseline = entry.detected.get_or_insert_with(|| detected.clone());
+        if now >= entry.expires || *baseline != detected {
+            overrides.remove(session_id);
+            return (detected, false);
+        }
+        (entry.status.clone(), true)
+    }
+
+    /// Drop overrides for sessions that are no longer running
+    pub fn retain(&self, session_ids: &HashSet<String>) {
+        let mut overrides = self.overrides.lock().unwrap_or_else(|e| e.into_inner());
+        overrides.retain(|id, _| session_ids.contains(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_override_decays_on_ttl_or_transition() {
+        let overrides = StatusOverrides::default();
+        let t0 = Utc::now();
+
+        overrides.set_at("s1", SessionStatus::WaitingForInput, 60, t0);
+        assert_eq!(
+            overrides.apply_at("s1", SessionStatus::Working, t0),
+            (SessionStatus::WaitingForInput, true)
+        );
+        assert_eq!(
+            overrides.apply_at("s1", SessionStatus::Working, t0 + Duration::seconds(30)),
+            (SessionStatus::WaitingForInput, true)
+        );
+        // Expired
+        assert_eq!(
+            overrides.apply_at("s1", SessionStatus::Working, t0 + Duration::seconds(60)),
+            (SessionStatus::Working, false)
+        );
+
+        // The heuristic moving on ends the override, even within the TTL
+        overrides.set_at("s2", SessionStatus::WaitingForInput, 600, t0);
+        overrides.apply_at("s2", SessionStatus::Working, t0);
+        assert_eq!(
+            overrides.apply_at("s2", SessionStatus::NeedsPermission, t0),
+            (SessionStatus::NeedsPermission, false)
+        );
+        assert_eq!(
+            overrides.apply_at("s2", SessionStatus::Working, t0),
+            (SessionStatus::Working, false)
+        );
+
+        overrides.set_at("s3", SessionStatus::WaitingForInput, 600, t0);
+        overrides.set_at("s3", SessionStatus::WaitingForInput, 0, t0);
+        assert_eq!(
+            overrides.apply_at("s3", SessionStatus::Working, t0),
+            (SessionStatus::Working, false)
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use crate::polling::Session;
+use crate::providers::Transcripts;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Notify};
+
+/// Longest `refresh` waits for the polling loop before settling for the
+/// sessions it already has
+const REFRESH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One poll cycle's result
+#[derive(Clone)]
+pub struct Snapshot {
+    pub sessions: Arc<Vec<Session>>,
+    /// When the cycle that produced it started; None before the first poll
+    pub polled_at: Option<Instant>,
+}
+
+/// The latest session list, published by the polling loop and read by
+/// commands and the web server instead of detecting sessions again
+pub struct SessionStore {
+    latest: watch::Sender<Snapshot>,
+    refresh: Notify,
+    /// The polling loop's providers, set once it starts
+    transcripts: OnceLock<Transcripts>,
+}
+
+impl SessionStore {
+    pub fn new() -> Self {
+        let (latest, _) = watch::channel(Snapshot {
+            sessions: Arc::new(Vec::new()),
+            polled_at: None,
+        });
+        Self {
+            latest,
+            refresh: Notify::new(),
+            transcripts: OnceLock::new(),
+        }
+    }
+
+    /// Sessions from the latest poll (empty until the first one finishes)
+    pub fn sessions(&self) -> Arc<Vec<Session>> {
+        self.latest.borrow().sessions.clone()
+    }
+
+    /// Let commands read running sessions through the provider that
+    /// detected them
+    pub fn set_transcripts(&self, transcripts: Transcripts) {
+        let _ = self.transcripts.set(transcripts);
+    }
+
+    /// None until the polling loop has started
+    pub fn transcripts(&self) -> Option<&Transcripts> {
+        self.transcripts.get()
+    }
+
+    /// Receiver that sees every published snapshot
+    pub fn subscribe(&self) -> watch::Receiver<Snapshot> {
+        self.latest.subscribe()
+    }
+
+    /// Replace the sessions with a finished cycle's result
+    pub fn publish(&self, sessions: Vec<Session>, polled_at: Instant) {
+        self.latest.send_replace(Snapshot {
+            sessions: Arc::new(sessions),
+            polled_at: Some(polled_at),
+        });
+    }
+
+    /// Sessions from the latest poll, waiting for the first one if it hasn't
+    /// finished yet (right after launch)
+    pub async fn current(&self) -> Arc<Vec<Session>> {
+        self.wait_for(|polled_at| polled_at.is_some()).await
+    }
+
+    /// Ask the polling loop to run now instead of waiting out its interval
+    pub fn request_refresh(&self) {
+        self.refresh.notify_one();
+    }
+
+    /// Resolves when someone calls `request_refresh`
+    pub async fn refresh_requested(&self) {
+        self.refresh.notified().await;
+    }
+
+    /// Poll now and return sessions detected after this call
+    pub async fn refresh(&self) -> Arc<Vec<Session>> {
+        let requested = Instant::now();
+        self.request_refresh();
+        self.wait_for(|polled_at| polled_at.is_some_and(|at| at >= requested))
+            .await
+    }
+
+    /// The first snapshot whose poll time passes `ready`, or the latest one
+    /// if none does within REFRESH_TIMEOUT
+    async fn wait_for(&self, ready: impl Fn(Option<Instant>) -> bool) -> Arc<Vec<Session>> {
+        let mut rx = self.subscribe();
+        let fresh = rx.wait_for(|snapshot| ready(snapshot.polled_at));
+        match tokio::time::timeout(REFRESH_TIMEOUT, fresh).await {
+            Ok(Ok(snapshot)) => snapshot.sessions.clone(),
+            _ => self.sessions(),
+        }
+    }
+}
+
+impl Default for SessionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polling::tests::test_session;
+    use crate::session::SessionStatus;
+
+    #[test]
+    fn test_refresh_waits_for_a_newer_poll() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let store = Arc::new(SessionStore::new());
+            store.publish(
+                vec![test_session(SessionStatus::Working, 0)],
+                Instant::now(),
+            );
+
+            // Stand-in for the polling loop
+            let poller = store.clone();
+            tokio::spawn(async move {
+                poller.refresh_requested().await;
+                let polled_at = Instant::now();
+                poller.publish(Vec::new(), polled_at);
+            });
+
+            assert_eq!(store.sessions().len(), 1);
+            assert!(store.refresh().await.is_empty());
+        });
+    }
+}
@@ -0,0 +1,442 @@
+//! Local OTLP/HTTP receiver for Claude Code's OpenTelemetry export.
+//!
+//! With telemetry turned on Claude Code reports exact token counts, cost, API
+//! errors and tool timings, which are otherwise estimated from the JSONL
+//! transcripts. Point it at c9watch with:
+//!
+//! ```text
+//! CLAUDE_CODE_ENABLE_TELEMETRY=1
+//! OTEL_METRICS_EXPORTER=otlp
+//! OTEL_LOGS_EXPORTER=otlp
+//! OTEL_EXPORTER_OTLP_PROTOCOL=http/json
+//! OTEL_EXPORTER_OTLP_ENDPOINT=http://localhost:4318
+//! ```
+//!
+//! Only the JSON encoding of OTLP is accepted.
+
+use crate::usage::TokenCounts;
+use axum::{
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::post,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+/// The standard OTLP/HTTP port, so OTEL_EXPORTER_OTLP_ENDPOINT needs no
+/// unusual value
+pub const OTLP_PORT: u16 = 4318;
+
+/// API errors kept per session
+const MAX_API_ERRORS: usize = 20;
+
+/// Sessions that have sent nothing for this long are dropped
+const SESSION_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// Metric data point temporality (OTLP AggregationTemporality)
+const TEMPORALITY_DELTA: u64 = 1;
+
+static TELEMETRY: OnceLock<TelemetryStore> = OnceLock::new();
+
+pub fn telemetry() -> &'static TelemetryStore {
+    TELEMETRY.get_or_init(TelemetryStore::new)
+}
+
+/// Call counts and timings for one tool
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolLatency {
+    pub calls: u32,
+    pub failures: u32,
+    pub total_ms: u64,
+    pub max_ms: u64,
+}
+
+/// A failed API request reported by Claude Code
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiError {
+    /// RFC 3339
+    pub timestamp: String,
+    pub message: String,
+    pub status_code: Option<String>,
+    pub model: Option<String>,
+}
+
+/// What telemetry has reported for one session
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTelemetry {
+    #[serde(flatten)]
+    pub tokens: TokenCounts,
+    pub cost_usd: f64,
+    pub api_requests: u32,
+    /// Total time spent waiting on the API
+    pub api_duration_ms: u64,
+    /// Most recent last
+    pub api_errors: Vec<ApiError>,
+    /// Keyed by tool name
+    pub tools: BTreeMap<String, ToolLatency>,
+}
+
+#[derive(Default)]
+struct SessionEntry {
+    /// Running value of each metric series (metric name + point attributes).
+    /// Cumulative points replace the value; delta points add to it.
+    series: HashMap<String, (String, f64)>,
+    report: SessionTelemetry,
+    last_seen: Option<Instant>,
+}
+
+impl SessionEntry {
+    fn refresh_totals(&mut self) {
+        let mut tokens = TokenCounts::default();
+        let mut cost = 0.0;
+        for (kind, value) in self.series.values() {
+            let value_u64 = value.max(0.0) as u64;
+            match kind.as_str() {
+                "input" => tokens.input_tokens += value_u64,
+                "output" => tokens.output_tokens += value_u64,
+                "cacheRead" => tokens.cache_read_input_tokens += value_u64,
+                "cacheCreation" => tokens.cache_creation_input_tokens += value_u64,
+                "cost" => cost += value,
+                _ => {}
+            }
+        }
+        self.report.tokens = tokens;
+        self.report.cost_usd = cost;
+    }
+}
+
+/// Per-session telemetry collected by the receiver, keyed by session id
+pub struct TelemetryStore {
+    sessions: Mutex<HashMap<String, SessionEntry>>,
+}
+
+impl TelemetryStore {
+    fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Telemetry for a session, if Claude Code has reported any
+    pub fn session(&self, session_id: &str) -> Option<SessionTelemetry> {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.get(session_id).map(|entry| entry.report.clone())
+    }
+
+    /// Apply an OTLP ExportMetricsServiceRequest (JSON encoding)
+    pub fn ingest_metrics(&self, request: &Value) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        for resource in array(&request["resourceMetrics"]) {
+            let resource_attrs = attributes(&resource["resource"]["attributes"]);
+            for scope in array(&resource["scopeMetrics"]) {
+                for metric in array(&scope["metrics"]) {
+                    let name = metric["name"].as_str().unwrap_or_default();
+                    if name != "claude_code.token.usage" && name != "claude_code.cost.usage" {
+                        continue;
+                    }
+                    let sum = &metric["sum"];
+                    let delta = sum["aggregationTemporality"].as_u64() == Some(TEMPORALITY_DELTA);
+
+                    for point in array(&sum["dataPoints"]) {
+                        let mut attrs = resource_attrs.clone();
+                        attrs.extend(attributes(&point["attributes"]));
+                        let Some(session_id) = attrs.get("session.id").cloned() else {
+                            continue;
+                        };
+                        let Some(value) =
+                            number(&point["asDouble"]).or_else(|| number(&point["asInt"]))
+                        else {
+                            continue;
+                        };
+                        let kind = if name == "claude_code.cost.usage" {
+                            "cost".to_string()
+                        } else {
+                            attrs.get("type").cloned().unwrap_or_default()
+                        };
+
+                        let series_key = format!(
+                            "{}|{}|{}",
+                            name,
+                            kind,
+                            attrs.get("model").map(String::as_str).unwrap_or_default()
+                        );
+                        let entry = sessions.entry(session_id).or_default();
+                        let slot = entry.series.entry(series_key).or_insert((kind, 0.0));
+                        if delta {
+                            slot.1 += value;
+                        } else {
+                            slot.1 = value;
+                        }
+                        entry.refresh_totals();
+                        entry.last_seen = Some(Instant::now());
+                    }
+                }
+            }
+        }
+        prune(&mut sessions);
+    }
+
+    /// Apply an OTLP ExportLogsServiceRequest (JSON encoding). Claude Code
+    /// sends its events (api_request, api_error, tool_result, ...) as logs.
+    pub fn ingest_logs(&self, request: &Value) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        for resource in array(&request["resourceLogs"]) {
+            let resource_attrs = attributes(&resource["resource"]["attributes"]);
+            for scope in array(&resource["scopeLogs"]) {
+                for record in array(&scope["logRecords"]) {
+                    let mut attrs = resource_attrs.clone();
+                    attrs.extend(attributes(&record["attributes"]));
+                    let Some(session_id) = attrs.get("session.id").cloned() else {
+                        continue;
+                    };
+                    let event = attrs
+                        .get("event.name")
+                        .cloned()
+                        .or_else(|| record["body"]["stringValue"].as_str().map(str::to_string))
+                        .unwrap_or_default();
+
+                    let entry = sessions.entry(session_id).or_default();
+                    entry.last_seen = Some(Instant::now());
+                    apply_event(
+                        &mut entry.report,
+                        event.trim_start_matches("claude_code."),
+                        &attrs,
+                        &record["timeUnixNano"],
+                    );
+                }
+            }
+        }
+        prune(&mut sessions);
+    }
+}
+
+fn apply_event(
+    report: &mut SessionTelemetry,
+    event: &str,
+    attrs: &HashMap<String, String>,
+    time_unix_nano: &Value,
+) {
+    let duration_ms = attrs
+        .get("duration_ms")
+        .and_then(|d| d.parse::<f64>().ok())
+        .map(|d| d.max(0.0) as u64)
+        .unwrap_or(0);
+
+    match event {
+        "api_request" => {
+            report.api_requests += 1;
+            report.api_duration_ms += duration_ms;
+        }
+        "api_error" => {
+            report.api_errors.push(ApiError {
+                timestamp: number(time_unix_nano)
+                    .map(|nanos| DateTime::from_timestamp_nanos(nanos as i64))
+                    .unwrap_or_else(Utc::now)
+                    .to_rfc3339(),
+                message: attrs.get("error").cloned().unwrap_or_default(),
+                status_code: attrs.get("status_code").cloned(),
+                model: attrs.get("model").cloned(),
+            });
+            let excess = report.api_errors.len().saturating_sub(MAX_API_ERRORS);
+            report.api_errors.drain(..excess);
+        }
+        "tool_result" => {
+            let tool = attrs
+                .get("tool_name")
+                .cloned()
+                .unwrap_or_else(|| "unknown".to_string());
+            let latency = report.tools.entry(tool).or_default();
+            latency.calls += 1;
+            if attrs.get("success").map(String::as_str) == Some("false") {
+                latency.failures += 1;
+            }
+            latency.total_ms += duration_ms;
+            latency.max_ms = latency.max_ms.max(duration_ms);
+        }
+        _ => {}
+    }
+}
+
+fn prune(sessions: &mut HashMap<String, SessionEntry>) {
+    sessions.retain(|_, entry| {
+        entry
+            .last_seen
+            .map(|t| t.elapsed() < SESSION_TTL)
+            .unwrap_or(false)
+    });
+}
+
+fn array(value: &Value) -> &[Value] {
+    value.as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// OTLP JSON encodes 64-bit integers as strings
+fn number(value: &Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+/// Flatten an OTLP KeyValue list into strings
+fn attributes(list: &Value) -> HashMap<String, String> {
+    let mut attrs = HashMap::new();
+    for kv in array(list) {
+        let Some(key) = kv["key"].as_str() else {
+            continue;
+        };
+        let value = &kv["value"];
+        let text = if let Some(s) = value["stringValue"].as_str() {
+            s.to_string()
+        } else if let Some(b) = value["boolValue"].as_bool() {
+            b.to_string()
+        } else if let Some(n) = number(&value["intValue"]).or_else(|| number(&value["doubleValue"]))
+        {
+            n.to_string()
+        } else {
+            continue;
+        };
+        attrs.insert(key.to_string(), text);
+    }
+    attrs
+}
+
+// ── Receiver ────────────────────────────────────────────────────────
+
+/// Serve /v1/metrics and /v1/logs on localhost until the app exits
+pub fn start_receiver() {
+    tauri::async_runtime::spawn(async move {
+        let app = Router::new()
+            .route("/v1/metrics", post(metrics))
+            .route("/v1/logs", post(logs))
+            // Accepted and dropped so a traces exporter doesn't log errors
+            .route("/v1/traces", post(|| async { Json(serde_json::json!({})) }));
+
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, OTLP_PORT));
+        let listener = match tokio::net::TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to bind OTLP receiver on {}: {}", addr, e);
+                return;
+            }
+        };
+        info!("OTLP receiver listening on {}", addr);
+        if let Err(e) = axum::serve(listener, app).await {
+            error!("OTLP receiver error: {}", e);
+        }
+    });
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.starts_with("application/json"))
+        .unwrap_or(false)
+}
+
+const PROTOBUF_UNSUPPORTED: &str =
+    "Only OTLP/JSON is supported; set OTEL_EXPORTER_OTLP_PROTOCOL=http/json";
+
+async fn metrics(headers: HeaderMap, body: axum::body::Bytes) -> axum::response::Response {
+    if !is_json(&headers) {
+        return (StatusCode::UNSUPPORTED_MEDIA_TYPE, PROTOBUF_UNSUPPORTED).into_response();
+    }
+    match serde_json::from_slice::<Value>(&body) {
+        Ok(request) => {
+            telemetry().ingest_metrics(&request);
+            Json(serde_json::json!({})).into_response()
+        }
+        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    }
+}
+
+async fn logs(headers: HeaderMap, body: axum::body::Bytes) -> axum::response::Response {
+    if !is_json(&headers) {
+        return (StatusCode::UNSUPPORTED_MEDIA_TYPE, PROTOBUF_UNSUPPORTED).into_response();
+    }
+    match serde_json::from_slice::<Value>(&body) {
+        Ok(request) => {
+            telemetry().ingest_logs(&request);
+            Json(serde_json::json!({})).into_response()
+        }
+        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn attr(key: &str, value: &str) -> Value {
+        json!({ "key": key, "value": { "stringValue": value } })
+    }
+
+    #[test]
+    fn test_ingest_metrics_and_events() {
+        let store = TelemetryStore::new();
+        let tokens = |value: &str| {
+            json!({ "resourceMetrics": [{
+                "resource": { "attributes": [attr("service.name", "claude-code")] },
+                "scopeMetrics": [{ "metrics": [{
+                    "name": "claude_code.token.usage",
+                    "sum": {
+                        "aggregationTemporality": 1,
+                        "dataPoints": [{
+                            "asDouble": value.parse::<f64>().unwrap(),
+                            "attributes": [
+                                attr("session.id", "s1"),
+                                attr("type", "input"),
+                                attr("model", "claude-sonnet-4-5")
+                            ]
+                        }]
+                    }
+                }]}]
+            }]})
+        };
+        store.ingest_metrics(&tokens("120"));
+        store.ingest_metrics(&tokens("30"));
+
+        store.ingest_logs(&json!({ "resourceLogs": [{
+            "scopeLogs": [{ "logRecords": [
+                {
+                    "body": { "stringValue": "claude_code.tool_result" },
+                    "attributes": [
+                        attr("session.id", "s1"),
+                        attr("event.name", "tool_result"),
+                        attr("tool_name", "Bash"),
+                        attr("success", "false"),
+                        attr("duration_ms", "250")
+                    ]
+                },
+                {
+                    "timeUnixNano": "1770000000000000000",
+                    "attributes": [
+                        attr("session.id", "s1"),
+                        attr("event.name", "api_error"),
+                        attr("error", "overloaded"),
+                        { "key": "status_code", "value": { "intValue": "529" } }
+                    ]
+                }
+            ]}]
+        }]}));
+
+        let report = store.session("s1").unwrap();
+        assert_eq!(report.tokens.input_tokens, 150);
+        let bash = &report.tools["Bash"];
+        assert_eq!((bash.calls, bash.failures, bash.max_ms), (1, 1, 250));
+        assert_eq!(report.api_errors.len(), 1);
+        assert_eq!(report.api_errors[0].status_code.as_deref(), Some("529"));
+        assert!(store.session("other").is_none());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::session::SessionStatus;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+
+/// Global tracker shared by the polling loop and on-demand session lookups
+static TRACKER: OnceLock<SessionTracker> = OnceLock::new();
+
+pub fn tracker() -> &'static SessionTracker {
+    TRACKER.get_or_init(SessionTracker::default)
+}
+
+/// Gaps between observations longer than this are not attributed to any status.
+/// They usually mean the machine was asleep, not that the session was working.
+const MAX_SAMPLE_GAP_SECS: i64 = 300;
+
+/// Maximum number of transitions kept per session (oldest are dropped first)
+const MAX_TRANSITIONS_PER_SESSION: usize = 500;
+
+/// A single status change, used to render a session's activity timeline
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusTransition {
+    /// When the new status was first observed (RFC 3339)
+    pub timestamp: String,
+    /// Previous status (None for the first observation of a session)
+    pub from: Option<SessionStatus>,
+    pub to: SessionStatus,
+}
+
+/// Accumulated time (in seconds) a session has spent in each status
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StatusDurations {
+    pub working: u64,
+    pub idle: u64,
+    pub needs_permission: u64,
+    pub connecting: u64,
+}
+
+#[derive(Debug, Clone)]
+struct SessionTiming {
+    status: SessionStatus,
+    last_seen: DateTime<Utc>,
+    /// Milliseconds spent per status, kept in ms so short poll intervals don't round away
+    working_ms: i64,
+    idle_ms: i64,
+    needs_permission_ms: i64,
+    connecting_ms: i64,
+    transitions: Vec<StatusTransition>,
+}
+
+impl SessionTiming {
+    fn durations(&self) -> StatusDurations {
+        StatusDurations {
+            working: (self.working_ms / 1000) as u64,
+            idle: (self.idle_ms / 1000) as u64,
+            needs_permission: (self.needs_permission_ms / 1000) as u64,
+            connecting: (self.connecting_ms / 1000) as u64,
+        }
+    }
+}
+
+/// Tracks how long each session spends in each status across poll cycles
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    sessions: Mutex<HashMap<String, SessionTiming>>,
+}
+
+impl SessionTracker {
+    /// Record the current status of a session and return its accumulated durations
+    pub fn observe(&self, session_id: &str, status: &SessionStatus) -> StatusDurations {
+        self.observe_at(session_id, status, Utc::now())
+    }
+
+    fn observe_at(
+        &self,
+        session_id: &str,
+        status: &SessionStatus,
+        now: DateTime<Utc>,
+    ) -> StatusDurations {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+
+        let timing = sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| SessionTiming {
+                status: status.clone(),
+                last_seen: now,
+                working_ms: 0,
+                idle_ms: 0,
+                needs_permission_ms: 0,
+                connecting_ms: 0,
+                transitions: vec![StatusTransition {
+                    timestamp: now.to_rfc3339(),
+                    from: None,
+                    to: status.clone(),
+                }],
+            });
+
+        // Attribute the time since the last observation to the previous status
+        let elapsed_ms = now
+            .signed_duration_since(timing.last_seen)
+            .num_milliseconds();
+        if elapsed_ms > 0 && elapsed_ms <= MAX_SAMPLE_GAP_SECS * 1000 {
+            match timing.status {
+                SessionStatus::Working => timing.working_ms += elapsed_ms,
+                SessionStatus::WaitingForInput => timing.idle_ms += elapsed_ms,
+                SessionStatus::NeedsPermission => timing.needs_permission_ms += elapsed_ms,
+                SessionStatus::Connecting => timing.connecting_ms += elapsed_ms,
+            }
+        }
+
+        if timing.status != *status {
+            timing.transitions.push(StatusTransition {
+                timestamp: now.to_rfc3339(),
+                from: Some(timing.status.clone()),
+                to: status.clone(),
+            });
+            if timing.transitions.len() > MAX_TRANSITIONS_PER_SESSION {
+                let excess = timing.transitions.len() - MAX_TRANSITIONS_PER_SESSION;
+                timing.transitions.drain(..excess);
+            }
+        }
+
+        timing.status = status.clone();
+        timing.last_seen = now;
+        timing.durations()
+    }
+
+    /// Get the recorded status transitions for a session, oldest first
+    pub fn timeline(&self, session_id: &str) -> Option<Vec<StatusTransition>> {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.get(session_id).map(|t| t.transitions.clone())
+    }
+
+    /// Drop tracking data for sessions that are no longer running
+    pub fn retain(&self, session_ids: &HashSet<String>) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.retain(|id, _| session_ids.contains(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_time_is_attributed_to_previous_status() {
+        let tracker = SessionTracker::default();
+        let t0 = Utc::now();
+
+        tracker.observe_at("s1", &SessionStatus::Working, t0);
+        tracker.observe_at(
+            "s1",
+            &SessionStatus::WaitingForInput,
+            t0 + Duration::seconds(10),
+        );
+        let durations =
+            tracker.observe_at("s1", &SessionStatus::Working, t0 + Duration::seconds(25));
+
+        assert_eq!(durations.working, 10);
+        assert_eq!(durations.idle, 15);
+        assert_eq!(durations.needs_permission, 0);
+    }
+
+    #[test]
+    fn test_long_gaps_are_not_attributed() {
+        let tracker = SessionTracker::default();
+        let t0 = Utc::now();
+
+        tracker.observe_at("s1", &SessionStatus::Working, t0);
+        let durations = tracker.observe_at("s1", &SessionStatus::Working, t0 + Duration::hours(2));
+
+        assert_eq!(durations.working, 0);
+    }
+
+    #[test]
+    fn test_timeline_records_only_changes() {
+        let tracker = SessionTracker::default();
+        let t0 = Utc::now();
+
+        tracker.observe_at("s1", &SessionStatus::Working, t0);
+        tracker.observe_at("s1", &SessionStatus::Working, t0 + Duration::seconds(3));
+        tracker.observe_at(
+            "s1",
+            &SessionStatus::NeedsPermission,
+            t0 + Duration::seconds(6),
+        );
+        tracker.observe_at("s1", &SessionStatus::Working, t0 + Duration::seconds(9));
+
+        let timeline = tracker.timeline("s1").unwrap();
+        assert_eq!(timeline.len(), 3);
+        assert_eq!(timeline[0].from, None);
+        assert_eq!(timeline[1].from, Some(SessionStatus::Working));
+        assert_eq!(timeline[1].to, SessionStatus::NeedsPermission);
+        assert!(tracker.timeline("unknown").is_none());
+    }
+
+    #[test]
+    fn test_retain_drops_stale_sessions() {
+        let tracker = SessionTracker::default();
+        let t0 = Utc::now();
+
+        tracker.observe_at("s1", &SessionStatus::Working, t0);
+        tracker.observe_at("s2", &SessionStatus::Working, t0);
+        tracker.retain(&HashSet::from(["s2".to_string()]));
+
+        let sessions = tracker.sessions.lock().unwrap();
+        assert!(!sessions.contains_key("s1"));
+        assert!(sessions.contains_key("s2"));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::session::MessageType;
+use crate::Conversation;
+use serde::Deserialize;
+
+/// Output format for a rendered transcript
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    Markdown,
+    Text,
+}
+
+/// Render a conversation for pasting into an issue or chat
+pub fn render(conversation: &Conversation, format: TranscriptFormat) -> String {
+    let sections: Vec<String> = conversation
+        .messages
+        .iter()
+        .map(|message| {
+            let content = message.content.trim_end();
+            match format {
+                TranscriptFormat::Markdown => render_markdown(&message.message_type, content),
+                TranscriptFormat::Text => {
+                    format!("{}:\n{}", label(&message.message_type), content)
+                }
+            }
+        })
+        .collect();
+    let mut rendered = sections.join("\n\n");
+    rendered.push('\n');
+    rendered
+}
+
+/// Render a conversation as a standalone, read-only HTML page
+pub fn render_html(conversation: &Conversation) -> String {
+    let mut messages = String::new();
+    for message in &conversation.messages {
+        let class = match message.message_type {
+            MessageType::User => "user",
+            MessageType::Assistant => "assistant",
+            MessageType::Thinking => "thinking",
+            MessageType::ToolUse | MessageType::ToolResult => "tool",
+            MessageType::System => "system",
+        };
+        messages.push_str(&format!(
+            "<section class=\"{}\"><h2>{} <time>{}</time></h2><pre>{}</pre></section>\n",
+            class,
+            label(&message.message_type),
+            escape_html(&message.timestamp),
+            escape_html(message.content.trim_end())
+        ));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<title>c9watch transcript</title>
+<style>
+body {{ font-family: -apple-system, BlinkMacSystemFont, sans-serif; max-width: 860px; margin: 2rem auto; padding: 0 1rem; color: #1d1d1f; }}
+section {{ border-left: 3px solid #d2d2d7; padding: 0.25rem 0.75rem; margin: 1rem 0; }}
+section.user {{ border-color: #0071e3; }}
+section.assistant {{ border-color: #34c759; }}
+section.thinking, section.system {{ color: #6e6e73; }}
+section.tool pre {{ background: #f5f5f7; padding: 0.5rem; }}
+h2 {{ font-size: 0.8rem; text-transform: uppercase; letter-spacing: 0.04em; margin: 0 0 0.25rem; }}
+time {{ font-weight: normal; color: #86868b; text-transform: none; }}
+pre {{ white-space: pre-wrap; word-break: break-word; margin: 0; font-family: ui-monospace, Menlo, monospace; font-size: 0.85rem; }}
+</style>
+</head>
+<body>
+<h1>Session {}</h1>
+{}</body>
+</html>
+"#,
+        escape_html(&conversation.session_id),
+        messages
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn label(message_type: &MessageType) -> &'static str {
+    match message_type {
+        MessageType::User => "User",
+        MessageType::Assistant => "Assistant",
+        MessageType::Thinking => "Thinking",
+        MessageType::ToolUse => "Tool use",
+        MessageType::ToolResult => "Tool result",
+        MessageType::System => "System",
+    }
+}
+
+fn render_markdown(message_type: &MessageType, content: &str) -> String {
+    match message_type {
+        MessageType::User | MessageType::Assistant => {
+            format!("### {}\n\n{}", label(message_type), content)
+        }
+        MessageType::Thinking | MessageType::System => {
+            let quoted: Vec<String> = content
+                .lines()
+                .map(|line| format!("> {}", line).trim_end().to_string())
+                .collect();
+            format!("> *{}*\n>\n{}", label(message_type), quoted.join("\n"))
+        }
+        MessageType::ToolUse | MessageType::ToolResult => {
+            let fence = fence_for(content);
+            format!(
+                "**{}**\n\n{fence}\n{}\n{fence}",
+                label(message_type),
+                content,
+                fence = fence
+            )
+        }
+    }
+}
+
+/// A backtick fence longer than any run of backticks in `content`, so tool
+/// output that contains code blocks doesn't close ours early
+fn fence_for(content: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in content.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    "`".repeat((longest + 1).max(3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConversationMessage;
+
+    #[test]
+    fn test_render_markdown_and_text() {
+        let conversation = Conversation {
+            session_id: "s1".to_string(),
+            messages: vec![
+                ConversationMessage::new("t1".into(), MessageType::User, "Fix the build".into()),
+                ConversationMessage::new(
+                    "t2".into(),
+                    MessageType::ToolResult,
+                    "```rust\nfn main() {}\n```".into(),
+                ),
+                ConversationMessage::new("t3".into(), MessageType::Thinking, "a\n\nb".into()),
+            ],
+            branches: Vec::new(),
+        };
+
+        assert_eq!(
+            render(&conversation, TranscriptFormat::Markdown),
+            "### User\n\nFix the build\n\n\
+             **Tool result**\n\n````\n```rust\nfn main() {}\n```\n````\n\n\
+             > *Thinking*\n>\n> a\n>\n> b\n"
+        );
+        assert_eq!(
+            render(&conversation, TranscriptFormat::Text),
+            "User:\nFix the build\n\n\
+             Tool result:\n```rust\nfn main() {}\n```\n\n\
+             Thinking:\na\n\nb\n"
+        );
+    }
+
+    #[test]
+    fn test_render_html_escapes_content() {
+        let conversation = Conversation {
+            session_id: "s1".to_string(),
+            messages: vec![ConversationMessage::new(
+                "t1".into(),
+                MessageType::Assistant,
+                "<script>alert('x')</script>".into(),
+            )],
+            branches: Vec::new(),
+        };
+
+        let html = render_html(&conversation);
+        assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+}
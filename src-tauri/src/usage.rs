@@ -0,0 +1,483 @@
+use crate::history::history;
+use crate::session::parser::Usage;
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Time range for a usage report, in local days
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportRange {
+    /// Today only
+    Day,
+    /// The last 7 days, including today
+    Week,
+}
+
+impl ReportRange {
+    /// Inclusive local-date bounds for this range
+    pub fn bounds(&self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            ReportRange::Day => (today, today),
+            ReportRange::Week => (today - Duration::days(6), today),
+        }
+    }
+}
+
+/// Token counts summed over one or more assistant messages
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenCounts {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+impl TokenCounts {
+    pub fn from_usage(usage: &Usage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens.unwrap_or(0) as u64,
+            output_tokens: usage.output_tokens.unwrap_or(0) as u64,
+            cache_creation_input_tokens: usage.cache_creation_input_tokens.unwrap_or(0) as u64,
+            cache_read_input_tokens: usage.cache_read_input_tokens.unwrap_or(0) as u64,
+        }
+    }
+
+    pub fn add(&mut self, other: &TokenCounts) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_input_tokens += other.cache_creation_input_tokens;
+        self.cache_read_input_tokens += other.cache_read_input_tokens;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+    }
+}
+
+/// Usage for one project on one local day
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDayUsage {
+    pub date: String,
+    pub project_path: String,
+    pub sessions_run: u32,
+    pub working_seconds: u64,
+    #[serde(flatten)]
+    pub tokens: TokenCounts,
+    pub cost_usd: f64,
+}
+
+/// Aggregate usage report over a date range
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    pub start_date: String,
+    pub end_date: String,
+    pub sessions_run: u32,
+    pub working_seconds: u64,
+    #[serde(flatten)]
+    pub tokens: TokenCounts,
+    pub cost_usd: f64,
+    /// Per-project, per-day breakdown sorted by date then project
+    pub days: Vec<ProjectDayUsage>,
+    /// Per-model breakdown sorted by estimated cost, highest first
+    pub models: Vec<ModelUsage>,
+}
+
+/// Usage attributed to a single model ID over the report range
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelUsage {
+    pub model: String,
+    /// Number of distinct assistant messages from this model
+    pub message_count: u32,
+    #[serde(flatten)]
+    pub tokens: TokenCounts,
+    pub cost_usd: f64,
+}
+
+/// Token usage from a single assistant message
+#[derive(Debug, Clone)]
+pub struct UsageSample {
+    pub date: NaiveDate,
+    pub project_path: String,
+    pub model: String,
+    pub tokens: TokenCounts,
+}
+
+/// Per-million-token list prices (input, output) in USD for a model name.
+///
+/// Matched by family substring so dated model IDs resolve without a table update.
+/// Unknown models are priced at zero rather than guessed.
+pub fn model_pricing(model: &str) -> (f64, f64) {
+    let model = model.to_lowercase();
+    if model.contains("opus-4-5") || model.contains("opus-4-6") {
+        (5.0, 25.0)
+    } else if model.contains("opus") {
+        (15.0, 75.0)
+    } else if model.contains("sonnet") {
+        (3.0, 15.0)
+    } else if model.contains("haiku-4") {
+        (1.0, 5.0)
+    } else if model.contains("haiku") {
+        (0.8, 4.0)
+    } else {
+        (0.0, 0.0)
+    }
+}
+
+/// Estimate the cost of a set of tokens for a model.
+///
+/// Cache writes are billed at 1.25x the input price, cache reads at 0.1x.
+pub fn estimate_cost(model: &str, tokens: &TokenCounts) -> f64 {
+    let (input, output) = model_pricing(model);
+    let per_token = |price: f64| price / 1_000_000.0;
+
+    tokens.input_tokens as f64 * per_token(input)
+        + tokens.output_tokens as f64 * per_token(output)
+        + tokens.cache_creation_input_tokens as f64 * per_token(input * 1.25)
+        + tokens.cache_read_input_tokens as f64 * per_token(input * 0.1)
+}
+
+/// Format a token count compactly, e.g. 1234 -> "1.2K", 4_200_000 -> "4.2M"
+pub fn format_token_count(tokens: u64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1_000 {
+        format!("{:.1}K", tokens as f64 / 1_000.0)
+    } else {
+        tokens.to_string()
+    }
+}
+
+/// Scan all session transcripts for assistant token usage on local dates in `[start, end]`
+pub fn scan_usage(start: NaiveDate, end: NaiveDate) -> Vec<UsageSample> {
+    let projects_dir = match dirs::home_dir() {
+        Some(home) => home.join(".claude").join("projects"),
+        None => return Vec::new(),
+    };
+
+    // Files not touched since the start of the range can't contain samples in it
+    let range_start = start
+        .and_hms_opt(0, 0, 0)
+        .and_then(|dt| dt.and_local_timezone(Local).earliest())
+        .map(std::time::SystemTime::from);
+
+    let mut samples = Vec::new();
+    let Ok(project_dirs) = fs::read_dir(&projects_dir) else {
+        return samples;
+    };
+
+    for project_dir in project_dirs.flatten() {
+        let project_dir = project_dir.path();
+        if !project_dir.is_dir() {
+            continue;
+        }
+        let Ok(files) = fs::read_dir(&project_dir) else {
+            continue;
+        };
+
+        for file in files.flatten() {
+            let path = file.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+                continue;
+            }
+
+            let recently_modified =
+                match (range_start, fs::metadata(&path).and_then(|m| m.modified())) {
+                    (Some(range_start), Ok(modified)) => modified >= range_start,
+                    _ => true,
+                };
+            if recently_modified {
+                scan_file_usage(&path, &project_dir, start, end, &mut samples);
+            }
+        }
+    }
+
+    samples
+}
+
+/// Collect usage samples from a single JSONL transcript
+fn scan_file_usage(
+    path: &Path,
+    project_dir: &Path,
+    start: NaiveDate,
+    end: NaiveDate,
+    samples: &mut Vec<UsageSample>,
+) {
+    let Ok(file) = File::open(path) else {
+        return;
+    };
+
+    let fallback_project = project_dir.to_string_lossy().to_string();
+    // Claude Code writes one line per content block, each repeating the
+    // message's usage, so only count each message ID once.
+    let mut seen_message_ids: HashSet<String> = HashSet::new();
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        // Cheap pre-filter before full JSON parsing
+        if !line.contains("\"usage\"") {
+            continue;
+        }
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        if value.get("type").and_then(|t| t.as_str()) != Some("assistant") {
+            continue;
+        }
+
+        let Some(date) = value
+            .get("timestamp")
+            .and_then(|t| t.as_str())
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Local).date_naive())
+        else {
+            continue;
+        };
+        if date < start || date > end {
+            continue;
+        }
+
+        let Some(message) = value.get("message") else {
+            continue;
+        };
+        if let Some(id) = message.get("id").and_then(|i| i.as_str()) {
+            if !seen_message_ids.insert(id.to_string()) {
+                continue;
+            }
+        }
+        let Some(usage) = message
+            .get("usage")
+            .and_then(|u| serde_json::from_value::<Usage>(u.clone()).ok())
+        else {
+            continue;
+        };
+
+        samples.push(UsageSample {
+            date,
+            project_path: value
+                .get("cwd")
+                .and_then(|c| c.as_str())
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| fallback_project.clone()),
+            model: message
+                .get("model")
+                .and_then(|m| m.as_str())
+                .unwrap_or("unknown")
+                .to_string(),
+            tokens: TokenCounts::from_usage(&usage),
+        });
+    }
+}
+
+/// Generate a usage report for a range ending today
+pub fn generate_report(range: ReportRange) -> UsageReport {
+    let (start, end) = range.bounds(Local::now().date_naive());
+    generate_report_between(start, end)
+}
+
+/// Generate a usage report for local dates in `[start, end]`
+pub fn generate_report_between(start: NaiveDate, end: NaiveDate) -> UsageReport {
+    let records = history().records_between(start, end);
+    let samples = scan_usage(start, end);
+    build_report(start, end, &records, &samples)
+}
+
+fn build_report(
+    start: NaiveDate,
+    end: NaiveDate,
+    records: &[(String, String, crate::history::DailySessionRecord)],
+    samples: &[UsageSample],
+) -> UsageReport {
+    let mut days: BTreeMap<(String, String), ProjectDayUsage> = BTreeMap::new();
+    let mut sessions_per_day: HashMap<(String, String), HashSet<String>> = HashMap::new();
+    let mut all_sessions: HashSet<String> = HashSet::new();
+    let mut models: HashMap<String, ModelUsage> = HashMap::new();
+
+    for (date, session_id, record) in records {
+        let key = (date.clone(), record.project_path.clone());
+        let entry = days.entry(key.clone()).or_insert_with(|| ProjectDayUsage {
+            date: date.clone(),
+            project_path: record.project_path.clone(),
+            ..Default::default()
+        });
+        entry.working_seconds += record.working_seconds;
+        sessions_per_day
+            .entry(key)
+            .or_default()
+            .insert(session_id.clone());
+        all_sessions.insert(session_id.clone());
+    }
+
+    for sample in samples {
+        let date = sample.date.format("%Y-%m-%d").to_string();
+        let entry = days
+            .entry((date.clone(), sample.project_path.clone()))
+            .or_insert_with(|| ProjectDayUsage {
+                date,
+                project_path: sample.project_path.clone(),
+                ..Default::default()
+            });
+        let cost = estimate_cost(&sample.model, &sample.tokens);
+        entry.tokens.add(&sample.tokens);
+        entry.cost_usd += cost;
+
+        let model = models
+            .entry(sample.model.clone())
+            .or_insert_with(|| ModelUsage {
+                model: sample.model.clone(),
+                ..Default::default()
+            });
+        model.message_count += 1;
+        model.tokens.add(&sample.tokens);
+        model.cost_usd += cost;
+    }
+
+    let mut report = UsageReport {
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        sessions_run: all_sessions.len() as u32,
+        ..Default::default()
+    };
+
+    for (key, mut day) in days {
+        day.sessions_run = sessions_per_day.get(&key).map_or(0, |s| s.len() as u32);
+        report.working_seconds += day.working_seconds;
+        report.tokens.add(&day.tokens);
+        report.cost_usd += day.cost_usd;
+        report.days.push(day);
+    }
+
+    report.models = models.into_values().collect();
+    report.models.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| b.tokens.total().cmp(&a.tokens.total()))
+            .then_with(|| a.model.cmp(&b.model))
+    });
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::DailySessionRecord;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_week_bounds_include_today() {
+        let (start, end) = ReportRange::Week.bounds(date("2026-02-10"));
+        assert_eq!(start, date("2026-02-04"));
+        assert_eq!(end, date("2026-02-10"));
+    }
+
+    #[test]
+    fn test_model_pricing_by_family() {
+        assert_eq!(model_pricing("claude-opus-4-5-20251101"), (5.0, 25.0));
+        assert_eq!(model_pricing("claude-opus-4-1-20250805"), (15.0, 75.0));
+        assert_eq!(model_pricing("claude-sonnet-4-5-20250929"), (3.0, 15.0));
+        assert_eq!(model_pricing("claude-haiku-4-5-20251001"), (1.0, 5.0));
+        assert_eq!(model_pricing("<synthetic>"), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_format_token_count() {
+        assert_eq!(format_token_count(950), "950");
+        assert_eq!(format_token_count(1_234), "1.2K");
+        assert_eq!(format_token_count(4_200_000), "4.2M");
+    }
+
+    #[test]
+    fn test_estimate_cost_includes_cache_multipliers() {
+        let tokens = TokenCounts {
+            input_tokens: 1_000_000,
+            output_tokens: 1_000_000,
+            cache_creation_input_tokens: 1_000_000,
+            cache_read_input_tokens: 1_000_000,
+        };
+        // 3 + 15 + 3.75 + 0.3
+        let cost = estimate_cost("claude-sonnet-4-5", &tokens);
+        assert!((cost - 22.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_build_report_merges_history_and_tokens() {
+        let records = vec![
+            (
+                "2026-02-10".to_string(),
+                "s1".to_string(),
+                DailySessionRecord {
+                    project_path: "/p/a".to_string(),
+                    working_seconds: 600,
+                    ..Default::default()
+                },
+            ),
+            (
+                "2026-02-10".to_string(),
+                "s2".to_string(),
+                DailySessionRecord {
+                    project_path: "/p/a".to_string(),
+                    working_seconds: 300,
+                    ..Default::default()
+                },
+            ),
+        ];
+        let samples = vec![UsageSample {
+            date: date("2026-02-10"),
+            project_path: "/p/a".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            tokens: TokenCounts {
+                output_tokens: 1_000_000,
+                ..Default::default()
+            },
+        }];
+
+        let report = build_report(date("2026-02-10"), date("2026-02-10"), &records, &samples);
+
+        assert_eq!(report.sessions_run, 2);
+        assert_eq!(report.working_seconds, 900);
+        assert_eq!(report.tokens.output_tokens, 1_000_000);
+        assert_eq!(report.days.len(), 1);
+        assert_eq!(report.days[0].sessions_run, 2);
+        assert!((report.cost_usd - 15.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_build_report_breaks_down_by_model() {
+        let sample = |model: &str, output_tokens: u64| UsageSample {
+            date: date("2026-02-10"),
+            project_path: "/p/a".to_string(),
+            model: model.to_string(),
+            tokens: TokenCounts {
+                output_tokens,
+                ..Default::default()
+            },
+        };
+        let samples = vec![
+            sample("claude-haiku-4-5", 2_000_000),
+            sample("claude-opus-4-6", 1_000_000),
+            sample("claude-haiku-4-5", 1_000_000),
+        ];
+
+        let report = build_report(date("2026-02-10"), date("2026-02-10"), &[], &samples);
+
+        assert_eq!(report.models.len(), 2);
+        assert_eq!(report.models[0].model, "claude-opus-4-6");
+        assert!((report.models[0].cost_usd - 25.0).abs() < 1e-9);
+        assert_eq!(report.models[1].model, "claude-haiku-4-5");
+        assert_eq!(report.models[1].message_count, 2);
+        assert_eq!(report.models[1].tokens.output_tokens, 3_000_000);
+    }
+}
@@ -1,17 +1,26 @@
+use crate::error::C9Error;
+use crate::network::AccessPolicy;
+use crate::rate_limit::{rate_limiter, RejectReason};
+use crate::store::SessionStore;
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        Query, State,
+        ConnectInfo, Path, Query, Request, State,
     },
-    http::{header, StatusCode},
+    http::{header, HeaderMap, StatusCode},
+    middleware::{self, Next},
     response::IntoResponse,
-    routing::get,
+    routing::{get, post},
     Json, Router,
 };
 use rust_embed::Embed;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, watch};
+use tracing::{error, info, warn};
 
 /// Embed the SvelteKit build output into the binary
 #[derive(Embed)]
@@ -21,11 +30,38 @@ struct Assets;
 /// WebSocket server port
 pub const WS_PORT: u16 = 9210;
 
+/// Version of the WS message protocol. Clients that never send `hello` are
+/// treated as version 0 and only receive the original message types.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Optional protocol features this server supports, advertised in `hello`
+pub const CAPABILITIES: &[&str] = &[
+    "alerts",
+    "subscriptions",
+    "conversationUpdates",
+    "sessionTimeline",
+    "sessionStats",
+    "conversationSearch",
+    "messageContent",
+    "usageReports",
+    "parseDiagnostics",
+    "apiStatus",
+    "recentEvents",
+    "msgpack",
+];
+
 /// Shared state for the WebSocket server
 pub struct WsState {
     pub auth_token: String,
+    /// Bind address and allowed peer ranges
+    pub access: AccessPolicy,
     pub sessions_tx: broadcast::Sender<String>,
     pub notifications_tx: broadcast::Sender<String>,
+    pub alerts_tx: broadcast::Sender<String>,
+    /// Sessions from the latest poll
+    pub store: Arc<SessionStore>,
+    /// Flips to true when the server is shutting down (e.g. for a restart)
+    pub shutdown: watch::Receiver<bool>,
 }
 
 // ── Protocol types ──────────────────────────────────────────────────
@@ -35,16 +71,36 @@ pub struct WsState {
 #[serde(tag = "type")]
 enum ClientMsg {
     #[serde(rename = "getSessions")]
-    GetSessions,
+    GetSessions {
+        /// Poll again instead of answering with the latest poll
+        #[serde(default)]
+        refresh: bool,
+    },
 
     #[serde(rename = "getConversation")]
     GetConversation {
         #[serde(rename = "sessionId")]
         session_id: String,
+        #[serde(default)]
+        filter: Option<crate::session::ConversationFilter>,
+    },
+
+    #[serde(rename = "getMessageContent")]
+    GetMessageContent {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+        uuid: String,
+        #[serde(rename = "toolUseId", default)]
+        tool_use_id: Option<String>,
     },
 
     #[serde(rename = "stopSession")]
-    StopSession { pid: u32 },
+    StopSession {
+        pid: u32,
+        /// SIGKILL the process if it ignores SIGTERM
+        #[serde(default)]
+        force: bool,
+    },
 
     #[serde(rename = "openSession")]
     OpenSession {
@@ -53,6 +109,18 @@ enum ClientMsg {
         project_path: String,
     },
 
+    #[serde(rename = "respondToPermission")]
+    RespondToPermission {
+        pid: u32,
+        approve: bool,
+        /// The tool the prompt being answered is about
+        #[serde(rename = "toolName", default)]
+        tool_name: Option<String>,
+    },
+
+    #[serde(rename = "quitSessionGracefully")]
+    QuitSessionGracefully { pid: u32 },
+
     #[serde(rename = "renameSession")]
     RenameSession {
         #[serde(rename = "sessionId")]
@@ -60,12 +128,152 @@ enum ClientMsg {
         #[serde(rename = "newName")]
         new_name: String,
     },
+
+    #[serde(rename = "overrideStatus")]
+    OverrideStatus {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+        status: crate::session::SessionStatus,
+        ttl: Option<u64>,
+    },
+
+    #[serde(rename = "snoozeSession")]
+    SnoozeSession {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+        minutes: u32,
+    },
+
+    #[serde(rename = "setNote")]
+    SetNote {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+        text: String,
+    },
+
+    #[serde(rename = "setSessionColor")]
+    SetSessionColor {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+        #[serde(default)]
+        color: Option<crate::session::SessionColor>,
+    },
+
+    #[serde(rename = "ignoreSession")]
+    IgnoreSession {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+    },
+
+    #[serde(rename = "getSessionTimeline")]
+    GetSessionTimeline {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+    },
+
+    #[serde(rename = "getSessionStats")]
+    GetSessionStats {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+    },
+
+    #[serde(rename = "getSessionChanges")]
+    GetSessionChanges {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+    },
+
+    #[serde(rename = "searchConversations")]
+    SearchConversations {
+        query: String,
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+
+    #[serde(rename = "getTodos")]
+    GetTodos {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+    },
+
+    #[serde(rename = "getUsageReport")]
+    GetUsageReport { range: crate::usage::ReportRange },
+
+    #[serde(rename = "getParseDiagnostics")]
+    GetParseDiagnostics,
+
+    #[serde(rename = "getApiStatus")]
+    GetApiStatus,
+
+    #[serde(rename = "getRecentEvents")]
+    GetRecentEvents {
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+
+    /// Protocol handshake (handled per connection, not in handle_message)
+    #[serde(rename = "hello")]
+    Hello {
+        #[serde(rename = "protocolVersion")]
+        protocol_version: u32,
+        #[serde(default)]
+        capabilities: Vec<String>,
+    },
+
+    /// Opt in to a push stream (handled per connection, not in handle_message)
+    #[serde(rename = "subscribe")]
+    Subscribe {
+        topic: SubscriptionTopic,
+        #[serde(rename = "sessionId", default)]
+        session_id: Option<String>,
+    },
+
+    #[serde(rename = "unsubscribe")]
+    Unsubscribe {
+        topic: SubscriptionTopic,
+        #[serde(rename = "sessionId", default)]
+        session_id: Option<String>,
+    },
+}
+
+impl ClientMsg {
+    /// The session a message is about, checked before anything uses it
+    fn session_id(&self) -> Option<&str> {
+        match self {
+            ClientMsg::GetConversation { session_id, .. }
+            | ClientMsg::GetMessageContent { session_id, .. }
+            | ClientMsg::RenameSession { session_id, .. }
+            | ClientMsg::OverrideStatus { session_id, .. }
+            | ClientMsg::SnoozeSession { session_id, .. }
+            | ClientMsg::SetNote { session_id, .. }
+            | ClientMsg::SetSessionColor { session_id, .. }
+            | ClientMsg::IgnoreSession { session_id }
+            | ClientMsg::GetSessionTimeline { session_id }
+            | ClientMsg::GetSessionStats { session_id }
+            | ClientMsg::GetSessionChanges { session_id }
+            | ClientMsg::GetTodos { session_id } => Some(session_id),
+            ClientMsg::Subscribe { session_id, .. } | ClientMsg::Unsubscribe { session_id, .. } => {
+                session_id.as_deref()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Push streams a client can opt in or out of
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum SubscriptionTopic {
+    /// The full session list (`sessionsUpdated`), on by default
+    Sessions,
+    /// Updates to one session's conversation (`conversationUpdated`)
+    Conversation,
 }
 
 /// Server → Client messages
 #[derive(Debug, Serialize)]
 #[serde(tag = "type")]
-enum ServerMsg {
+pub(crate) enum ServerMsg {
     #[serde(rename = "sessions")]
     Sessions { data: serde_json::Value },
 
@@ -75,54 +283,550 @@ enum ServerMsg {
     #[serde(rename = "sessionsUpdated")]
     SessionsUpdated { data: serde_json::Value },
 
+    /// `code` is a `C9Error` code, e.g. "notFound"
     #[serde(rename = "error")]
-    Error { message: String },
+    Error { code: &'static str, message: String },
 
     #[serde(rename = "ok")]
     Ok,
 
     #[serde(rename = "notification")]
     Notification { data: serde_json::Value },
+
+    #[serde(rename = "alert")]
+    Alert { data: serde_json::Value },
+
+    #[serde(rename = "sessionTimeline")]
+    SessionTimeline { data: serde_json::Value },
+
+    #[serde(rename = "sessionStats")]
+    SessionStats { data: serde_json::Value },
+
+    #[serde(rename = "sessionChanges")]
+    SessionChanges { data: serde_json::Value },
+
+    #[serde(rename = "messageContent")]
+    MessageContent { data: serde_json::Value },
+
+    #[serde(rename = "searchResults")]
+    SearchResults { data: serde_json::Value },
+
+    #[serde(rename = "stopResult")]
+    StopResult { data: serde_json::Value },
+
+    #[serde(rename = "todos")]
+    Todos { data: serde_json::Value },
+
+    #[serde(rename = "usageReport")]
+    UsageReport { data: serde_json::Value },
+
+    #[serde(rename = "parseDiagnostics")]
+    ParseDiagnostics { data: serde_json::Value },
+
+    #[serde(rename = "apiStatus")]
+    ApiStatus { data: serde_json::Value },
+
+    #[serde(rename = "recentEvents")]
+    RecentEvents { data: serde_json::Value },
+
+    #[serde(rename = "hello")]
+    Hello {
+        #[serde(rename = "protocolVersion")]
+        protocol_version: u32,
+        #[serde(rename = "serverVersion")]
+        server_version: String,
+        capabilities: Vec<String>,
+    },
+
+    #[serde(rename = "conversationUpdated")]
+    ConversationUpdated {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+        data: serde_json::Value,
+    },
+}
+
+impl From<C9Error> for ServerMsg {
+    fn from(error: C9Error) -> Self {
+        ServerMsg::Error {
+            code: error.code(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Wire encoding for server → client messages on one connection.
+///
+/// JSON text frames by default; clients that include "msgpack" in their
+/// hello capabilities get MessagePack binary frames for everything after
+/// the hello reply (much smaller for large conversations).
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Encoding {
+    Json,
+    MessagePack,
+}
+
+impl Encoding {
+    fn encode<T: Serialize>(&self, msg: &T) -> Message {
+        match self {
+            Encoding::Json => Message::Text(serde_json::to_string(msg).unwrap_or_default()),
+            // Named fields so the payload has the same shape as the JSON one
+            Encoding::MessagePack => {
+                Message::Binary(rmp_serde::to_vec_named(msg).unwrap_or_default())
+            }
+        }
+    }
+}
+
+/// What a single WS connection has asked to be pushed
+struct Subscriptions {
+    sessions: bool,
+    /// Subscribed session IDs -> fingerprint of the transcript when its
+    /// conversation was last pushed (None until the first push)
+    conversations: HashMap<String, Option<String>>,
+}
+
+impl Default for Subscriptions {
+    fn default() -> Self {
+        // Clients that never send `subscribe` keep the original behaviour
+        Self {
+            sessions: true,
+            conversations: HashMap::new(),
+        }
+    }
+}
+
+impl Subscriptions {
+    fn update(
+        &mut self,
+        topic: SubscriptionTopic,
+        session_id: Option<String>,
+        subscribed: bool,
+    ) -> ServerMsg {
+        match topic {
+            SubscriptionTopic::Sessions => self.sessions = subscribed,
+            SubscriptionTopic::Conversation => {
+                let Some(session_id) = session_id else {
+                    return C9Error::ParseError(
+                        "sessionId is required for conversation subscriptions".to_string(),
+                    )
+                    .into();
+                };
+                if subscribed {
+                    self.conversations.insert(session_id, None);
+                } else {
+                    self.conversations.remove(&session_id);
+                }
+            }
+        }
+        ServerMsg::Ok
+    }
+
+    /// Subscribed sessions whose conversation changed in this session list
+    fn changed_conversations(
+        &mut self,
+        store: &SessionStore,
+        sessions: &serde_json::Value,
+    ) -> Vec<String> {
+        let mut changed = Vec::new();
+        for session in sessions.as_array().into_iter().flatten() {
+            let Some(id) = session.get("id").and_then(|id| id.as_str()) else {
+                continue;
+            };
+            let Some(last_seen) = self.conversations.get_mut(id) else {
+                continue;
+            };
+            let fingerprint = transcript_fingerprint(store, id).unwrap_or_else(|| {
+                format!(
+                    "{}|{}|{}",
+                    session.get("modified").unwrap_or(&serde_json::Value::Null),
+                    session
+                        .get("messageCount")
+                        .unwrap_or(&serde_json::Value::Null),
+                    session
+                        .get("latestMessage")
+                        .unwrap_or(&serde_json::Value::Null),
+                )
+            });
+            if last_seen.as_deref() != Some(fingerprint.as_str()) {
+                *last_seen = Some(fingerprint);
+                changed.push(id.to_string());
+            }
+        }
+        changed
+    }
+}
+
+/// Size and mtime of a running session's transcript. These change with every
+/// entry written, unlike `modified`/`messageCount` (from the sessions index,
+/// fixed during a run) or `latestMessage` (the same across repeated tool calls).
+fn transcript_fingerprint(store: &SessionStore, session_id: &str) -> Option<String> {
+    let path = store.transcripts()?.transcript_path(session_id)?;
+    let metadata = std::fs::metadata(path).ok()?;
+    Some(format!("{}|{:?}", metadata.len(), metadata.modified().ok()))
 }
 
 // ── Server entrypoint ───────────────────────────────────────────────
 
-/// Start the axum WebSocket server (call from tauri::async_runtime::spawn)
-pub async fn start_server(state: Arc<WsState>) {
+/// A running server task, stopped by signalling its shutdown channel
+pub struct ServerHandle {
+    shutdown_tx: watch::Sender<bool>,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+impl ServerHandle {
+    /// Stop accepting connections, close open WebSocket clients, and wait for
+    /// the server task to finish
+    pub async fn shutdown(self) {
+        let _ = self.shutdown_tx.send(true);
+        let _ = self.task.await;
+    }
+}
+
+/// Bind the listener and spawn the axum server. Binding happens up front so a
+/// bad address is reported to the caller rather than only logged.
+pub async fn start_server(
+    state: Arc<WsState>,
+    shutdown_tx: watch::Sender<bool>,
+) -> Result<ServerHandle, String> {
     let app = Router::new()
         .route("/ws", get(ws_handler))
         .route("/health", get(health))
         .route("/info", get(info))
+        .route("/pair", post(pair))
+        .route("/share/:token", get(shared_transcript))
+        .route("/widget", get(widget))
+        .route("/api/quicklist", get(quicklist))
         .fallback(get(serve_static_fallback))
-        .with_state(state);
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            restrict_peers,
+        ))
+        .with_state(state.clone());
 
-    // [::] accepts both IPv4 and IPv6 (localhost can resolve to ::1)
-    let addr = format!("[::]:{}", WS_PORT);
-    eprintln!("[ws-server] Listening on {}", addr);
+    let addr = state.access.bind_addr;
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Failed to bind {}: {}", addr, e))?;
+    info!("Listening on {}", addr);
 
-    match tokio::net::TcpListener::bind(&addr).await {
-        Ok(listener) => {
-            if let Err(e) = axum::serve(listener, app).await {
-                eprintln!("[ws-server] Error: {}", e);
-            }
+    let mut shutdown = state.shutdown.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        let service = app.into_make_service_with_connect_info::<SocketAddr>();
+        let result = axum::serve(listener, service)
+            .with_graceful_shutdown(async move {
+                let _ = shutdown.changed().await;
+            })
+            .await;
+        match result {
+            Ok(()) => info!("Stopped listening on {}", addr),
+            Err(e) => error!("Error: {}", e),
         }
-        Err(e) => {
-            eprintln!("[ws-server] Failed to bind {}: {}", addr, e);
+    });
+
+    Ok(ServerHandle { shutdown_tx, task })
+}
+
+/// Owns the running server so it can be restarted with new bind settings
+/// without quitting the app
+pub struct ServerRuntime {
+    auth_token: String,
+    sessions_tx: broadcast::Sender<String>,
+    notifications_tx: broadcast::Sender<String>,
+    alerts_tx: broadcast::Sender<String>,
+    store: Arc<SessionStore>,
+    access: std::sync::Mutex<AccessPolicy>,
+    handle: tokio::sync::Mutex<Option<ServerHandle>>,
+    /// Never signalled: restarts don't close local socket clients
+    local_shutdown: watch::Sender<bool>,
+}
+
+impl ServerRuntime {
+    pub fn new(
+        auth_token: String,
+        access: AccessPolicy,
+        store: Arc<SessionStore>,
+        sessions_tx: broadcast::Sender<String>,
+        notifications_tx: broadcast::Sender<String>,
+        alerts_tx: broadcast::Sender<String>,
+    ) -> Self {
+        Self {
+            auth_token,
+            sessions_tx,
+            notifications_tx,
+            alerts_tx,
+            store,
+            access: std::sync::Mutex::new(access),
+            handle: tokio::sync::Mutex::new(None),
+            local_shutdown: watch::channel(false).0,
         }
     }
+
+    /// State for connections on the local control socket. They share the
+    /// session and notification channels but outlive server restarts.
+    pub fn local_state(&self) -> Arc<WsState> {
+        Arc::new(WsState {
+            auth_token: self.auth_token.clone(),
+            access: self.access(),
+            sessions_tx: self.sessions_tx.clone(),
+            notifications_tx: self.notifications_tx.clone(),
+            alerts_tx: self.alerts_tx.clone(),
+            store: self.store.clone(),
+            shutdown: self.local_shutdown.subscribe(),
+        })
+    }
+
+    /// The access policy of the running (or last started) server
+    pub fn access(&self) -> AccessPolicy {
+        self.access
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub async fn is_running(&self) -> bool {
+        self.handle.lock().await.is_some()
+    }
+
+    /// Start the server with `access`, shutting down any running one first.
+    /// If the new address can't be bound, the previous server is brought
+    /// back with its old policy before the error is returned.
+    pub async fn restart(&self, access: AccessPolicy) -> Result<(), String> {
+        let mut handle = self.handle.lock().await;
+        // Stopped first: restarting on the same port is the common case, and
+        // the old listener would hold it
+        let previous = match handle.take() {
+            Some(running) => {
+                running.shutdown().await;
+                Some(self.access())
+            }
+            None => None,
+        };
+
+        let (state, shutdown_tx) = self.state(access.clone());
+        match start_server(state, shutdown_tx).await {
+            Ok(started) => {
+                *handle = Some(started);
+                *self.access.lock().unwrap_or_else(|e| e.into_inner()) = access;
+                Ok(())
+            }
+            Err(e) => {
+                if let Some(previous) = previous {
+                    let (state, shutdown_tx) = self.state(previous);
+                    match start_server(state, shutdown_tx).await {
+                        Ok(restored) => *handle = Some(restored),
+                        Err(restore_error) => {
+                            error!("Could not restore the previous server: {}", restore_error)
+                        }
+                    }
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Connection state for a new server instance with `access`
+    fn state(&self, access: AccessPolicy) -> (Arc<WsState>, watch::Sender<bool>) {
+        let (shutdown_tx, shutdown) = watch::channel(false);
+        let state = Arc::new(WsState {
+            auth_token: self.auth_token.clone(),
+            access,
+            sessions_tx: self.sessions_tx.clone(),
+            notifications_tx: self.notifications_tx.clone(),
+            alerts_tx: self.alerts_tx.clone(),
+            store: self.store.clone(),
+            shutdown,
+        });
+        (state, shutdown_tx)
+    }
+}
+
+/// Reject peers outside the allowed CIDR ranges, over the request rate, or
+/// backing off after failed logins, before any route runs
+async fn restrict_peers(
+    State(state): State<Arc<WsState>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> axum::response::Response {
+    let path = request.uri().path().to_string();
+    if !state.access.allows(peer.ip()) {
+        warn!("Rejected connection from {}", peer.ip());
+        rate_limiter().record_rejection(peer.ip(), &path, RejectReason::NotAllowed);
+        return (StatusCode::FORBIDDEN, "Address not allowed").into_response();
+    }
+    if let Err(retry_after) = rate_limiter().check(peer.ip(), &path) {
+        let retry_after = retry_after.as_secs().max(1).to_string();
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after)],
+            "Too many requests",
+        )
+            .into_response();
+    }
+    next.run(request).await
 }
 
 // ── HTTP endpoints ──────────────────────────────────────────────────
 
-async fn health() -> &'static str {
-    "ok"
+/// 200 while polling is keeping up, 503 once it has stalled
+async fn health() -> axum::response::Response {
+    let report = crate::health::health().report();
+    let code = if report.status == "degraded" {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    (
+        code,
+        Json(serde_json::json!({
+            "status": report.status,
+            "lastPollSecondsAgo": report.last_poll_seconds_ago,
+        })),
+    )
+        .into_response()
 }
 
 async fn info() -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "name": "c9watch",
-        "version": env!("CARGO_PKG_VERSION"),
-    }))
+    let mut info = serde_json::json!({ "name": "c9watch" });
+    if let (Some(info), Ok(serde_json::Value::Object(report))) = (
+        info.as_object_mut(),
+        serde_json::to_value(crate::health::health().report()),
+    ) {
+        info.extend(report);
+    }
+    Json(info)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PairRequest {
+    code: String,
+    #[serde(default)]
+    device_name: String,
+}
+
+/// Exchange a one-time pairing code for a device credential
+async fn pair(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Json(request): Json<PairRequest>,
+) -> axum::response::Response {
+    match crate::pairing::pairing().redeem(request.code.trim(), &request.device_name) {
+        Ok(credential) => {
+            info!("Paired device {}", credential.device_id);
+            rate_limiter().record_success(peer.ip());
+            Json(credential).into_response()
+        }
+        Err(e) => {
+            rate_limiter().record_failure(peer.ip(), "/pair", RejectReason::InvalidPairingCode);
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(serde_json::json!({ "error": e })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Token check for the plain HTTP endpoints: the `/ws` token (header or
+/// `?token=`), recorded with the rate limiter like a WS login. Returns the
+/// 401 to send when it fails.
+fn reject_bad_token(
+    headers: &HeaderMap,
+    params: &WsQuery,
+    peer: SocketAddr,
+    state: &WsState,
+    route: &str,
+) -> Option<axum::response::Response> {
+    match extract_token(headers, params) {
+        Some(token) if is_authorized(state, &token) => {
+            rate_limiter().record_success(peer.ip());
+            None
+        }
+        _ => {
+            rate_limiter().record_failure(peer.ip(), route, RejectReason::InvalidToken);
+            Some((StatusCode::UNAUTHORIZED, "Invalid or missing token").into_response())
+        }
+    }
+}
+
+/// Status counts and the most urgent session for home-screen widgets. Takes
+/// the same token as `/ws`; send `If-None-Match` to get a bodiless 304 when
+/// nothing changed.
+async fn widget(
+    headers: HeaderMap,
+    Query(params): Query<WsQuery>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    State(state): State<Arc<WsState>>,
+) -> axum::response::Response {
+    if let Some(rejected) = reject_bad_token(&headers, &params, peer, &state, "/widget") {
+        return rejected;
+    }
+
+    let (summary, etag) = crate::widget::current();
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        // Widgets should revalidate every time; a 304 is cheap
+        (header::CACHE_CONTROL, "no-cache".to_string()),
+    ];
+    let unchanged = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|tags| tags.split(',').any(|tag| tag.trim() == etag));
+    if unchanged {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+    (StatusCode::OK, cache_headers, Json(summary)).into_response()
+}
+
+/// Sessions as a flat, pre-sorted list for launcher extensions (Raycast,
+/// Alfred). Takes the same token as `/ws`.
+async fn quicklist(
+    headers: HeaderMap,
+    Query(params): Query<WsQuery>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    State(state): State<Arc<WsState>>,
+) -> axum::response::Response {
+    if let Some(rejected) = reject_bad_token(&headers, &params, peer, &state, "/api/quicklist") {
+        return rejected;
+    }
+    let sessions = state.store.current().await;
+    Json(crate::quicklist::build(&sessions)).into_response()
+}
+
+/// Read-only transcript published with `share_conversation`. The token in
+/// the path is the only credential, so misses count as failed logins. A GET
+/// uses the link up; a HEAD (e.g. a link checker) doesn't.
+async fn shared_transcript(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    method: axum::http::Method,
+    Path(token): Path<String>,
+) -> axum::response::Response {
+    let page = if method == axum::http::Method::HEAD {
+        crate::share::exists(&token).then(String::new)
+    } else {
+        crate::share::take_page(&token)
+    };
+    match page {
+        Some(html) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+                (header::CACHE_CONTROL, "no-store"),
+                (header::REFERRER_POLICY, "no-referrer"),
+                (
+                    header::CONTENT_SECURITY_POLICY,
+                    "default-src 'none'; style-src 'unsafe-inline'",
+                ),
+            ],
+            html,
+        )
+            .into_response(),
+        None => {
+            rate_limiter().record_failure(peer.ip(), "/share", RejectReason::InvalidToken);
+            (StatusCode::NOT_FOUND, "This link has expired").into_response()
+        }
+    }
 }
 
 // ── Static file serving (mobile client) ─────────────────────────────
@@ -166,123 +870,585 @@ struct WsQuery {
     token: Option<String>,
 }
 
+/// Subprotocol the server selects for authenticated browser clients
+const WS_SUBPROTOCOL: &str = "c9watch";
+
+/// Prefix of the subprotocol entry that carries the auth token.
+/// Browsers can't set headers on WebSocket requests, so web clients send
+/// `Sec-WebSocket-Protocol: c9watch, c9watch-token.<token>` instead.
+const TOKEN_SUBPROTOCOL_PREFIX: &str = "c9watch-token.";
+
+/// Find the auth token in the request, in order of preference:
+/// `Authorization: Bearer`, `Sec-WebSocket-Protocol`, then the legacy
+/// `?token=` query parameter (which ends up in logs and proxy histories).
+fn extract_token(headers: &HeaderMap, query: &WsQuery) -> Option<String> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+
+    let subprotocol = headers
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .find_map(|protocol| protocol.strip_prefix(TOKEN_SUBPROTOCOL_PREFIX))
+                .map(|token| token.to_string())
+        });
+
+    bearer.or(subprotocol).or_else(|| query.token.clone())
+}
+
+/// The desktop's own token, or a credential issued to a paired device
+fn is_authorized(state: &WsState, token: &str) -> bool {
+    token == state.auth_token || crate::pairing::pairing().authenticate(token)
+}
+
 async fn ws_handler(
     ws: WebSocketUpgrade,
+    headers: HeaderMap,
     Query(params): Query<WsQuery>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     State(state): State<Arc<WsState>>,
 ) -> axum::response::Response {
-    match &params.token {
-        Some(token) if token == &state.auth_token => ws
-            .on_upgrade(move |socket| handle_socket(socket, state))
-            .into_response(),
-        _ => (
-            axum::http::StatusCode::UNAUTHORIZED,
-            "Invalid or missing token",
-        )
-            .into_response(),
+    match extract_token(&headers, &params) {
+        Some(token) if is_authorized(&state, &token) => {
+            rate_limiter().record_success(peer.ip());
+            ws
+                // Echo our subprotocol back; browsers reject the upgrade otherwise
+                .protocols([WS_SUBPROTOCOL])
+                .on_upgrade(move |socket| serve_connection(socket, state))
+                .into_response()
+        }
+        _ => {
+            rate_limiter().record_failure(peer.ip(), "/ws", RejectReason::InvalidToken);
+            (
+                axum::http::StatusCode::UNAUTHORIZED,
+                "Invalid or missing token",
+            )
+                .into_response()
+        }
+    }
+}
+
+// ── Connections ─────────────────────────────────────────────────────
+
+/// A client message as it arrived, before parsing
+pub(crate) enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// What a client connection runs over: a WebSocket, or the local control
+/// socket (see `local_socket`)
+pub(crate) trait Transport: Send {
+    /// Whether MessagePack binary frames can be carried
+    const BINARY: bool;
+
+    /// Next message from the client, or None once it disconnected
+    fn next_frame(&mut self) -> impl Future<Output = Option<Frame>> + Send;
+
+    /// Send a message, returning false if the client is gone
+    fn send_msg(
+        &mut self,
+        msg: &ServerMsg,
+        encoding: Encoding,
+    ) -> impl Future<Output = bool> + Send;
+
+    /// Tell the client the server is going away
+    fn close(&mut self) -> impl Future<Output = ()> + Send;
+}
+
+impl Transport for WebSocket {
+    const BINARY: bool = true;
+
+    async fn next_frame(&mut self) -> Option<Frame> {
+        loop {
+            match self.recv().await {
+                Some(Ok(Message::Text(text))) => return Some(Frame::Text(text)),
+                Some(Ok(Message::Binary(bytes))) => return Some(Frame::Binary(bytes)),
+                Some(Ok(Message::Ping(data))) => {
+                    if self.send(Message::Pong(data)).await.is_err() {
+                        return None;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => return None,
+                _ => continue,
+            }
+        }
+    }
+
+    /// WS clients may be on another machine, so secrets are masked here
+    async fn send_msg(&mut self, msg: &ServerMsg, encoding: Encoding) -> bool {
+        let frame = match crate::redact::current() {
+            Some(redactor) => {
+                let mut value = serde_json::to_value(msg).unwrap_or_default();
+                redactor.redact_value(&mut value);
+                encoding.encode(&value)
+            }
+            None => encoding.encode(msg),
+        };
+        self.send(frame).await.is_ok()
+    }
+
+    async fn close(&mut self) {
+        let _ = self.send(Message::Close(None)).await;
     }
 }
 
-async fn handle_socket(mut socket: WebSocket, state: Arc<WsState>) {
-    eprintln!("[ws-server] Client connected");
+/// Capabilities a transport can offer; MessagePack needs binary frames
+fn capabilities_for<T: Transport>() -> impl Iterator<Item = &'static str> {
+    CAPABILITIES
+        .iter()
+        .copied()
+        .filter(|c| T::BINARY || *c != "msgpack")
+}
+
+/// Run one client connection until it disconnects or the server stops
+pub(crate) async fn serve_connection<T: Transport>(mut transport: T, state: Arc<WsState>) {
+    info!("Client connected");
+    let _client = crate::health::health().client_connected();
     let mut sessions_rx = state.sessions_tx.subscribe();
     let mut notifications_rx = state.notifications_tx.subscribe();
+    let mut alerts_rx = state.alerts_tx.subscribe();
+    let mut subscriptions = Subscriptions::default();
+    // Capabilities both sides support, empty until the client says hello
+    let mut negotiated: HashSet<String> = HashSet::new();
+    let mut encoding = Encoding::Json;
+    let mut shutdown = state.shutdown.clone();
 
-    loop {
+    'connection: loop {
         tokio::select! {
-            // Incoming client message
-            msg = socket.recv() => {
-                match msg {
-                    Some(Ok(Message::Text(text))) => {
-                        let text_str: &str = &text;
-                        let response = match serde_json::from_str::<ClientMsg>(text_str) {
-                            Ok(client_msg) => handle_message(client_msg).await,
-                            Err(e) => ServerMsg::Error {
-                                message: format!("Invalid message: {}", e),
-                            },
-                        };
-                        let json = serde_json::to_string(&response).unwrap_or_default();
-                        if socket.send(Message::Text(json)).await.is_err() {
-                            break;
-                        }
+            // Server is stopping (e.g. restart): close so the client reconnects
+            _ = shutdown.changed() => {
+                transport.close().await;
+                break;
+            }
+            // Incoming client message (JSON text, or MessagePack binary)
+            frame = transport.next_frame() => {
+                let parsed = match frame {
+                    Some(Frame::Text(text)) => {
+                        serde_json::from_str::<ClientMsg>(&text).map_err(|e| e.to_string())
+                    }
+                    Some(Frame::Binary(bytes)) => {
+                        rmp_serde::from_slice::<ClientMsg>(&bytes).map_err(|e| e.to_string())
                     }
-                    Some(Ok(Message::Ping(data))) => {
-                        if socket.send(Message::Pong(data)).await.is_err() {
-                            break;
+                    None => break,
+                };
+                let parsed = parsed.and_then(|msg| match msg.session_id() {
+                    Some(session_id) => crate::session::validate_session_id(session_id)
+                        .map(|()| msg)
+                        .map_err(|e| e.to_string()),
+                    None => Ok(msg),
+                });
+
+                // The hello reply itself still goes out in the old encoding
+                let mut switch_encoding = None;
+                let response = match parsed {
+                    Ok(ClientMsg::Hello { protocol_version, capabilities }) => {
+                        info!(
+                            "Client hello: protocol v{}, {} capabilities",
+                            protocol_version,
+                            capabilities.len()
+                        );
+                        negotiated = capabilities
+                            .into_iter()
+                            .filter(|c| capabilities_for::<T>().any(|ours| ours == c))
+                            .collect();
+                        if negotiated.contains("msgpack") {
+                            switch_encoding = Some(Encoding::MessagePack);
+                        }
+                        ServerMsg::Hello {
+                            protocol_version: PROTOCOL_VERSION,
+                            server_version: env!("CARGO_PKG_VERSION").to_string(),
+                            capabilities: capabilities_for::<T>().map(str::to_string).collect(),
                         }
                     }
-                    Some(Ok(Message::Close(_))) | None => break,
-                    _ => {}
+                    Ok(ClientMsg::Subscribe { topic, session_id }) => {
+                        subscriptions.update(topic, session_id, true)
+                    }
+                    Ok(ClientMsg::Unsubscribe { topic, session_id }) => {
+                        subscriptions.update(topic, session_id, false)
+                    }
+                    Ok(client_msg) => handle_message(client_msg, &state).await,
+                    Err(e) => C9Error::ParseError(format!("Invalid message: {}", e)).into(),
+                };
+                if !transport.send_msg(&response, encoding).await {
+                    break;
+                }
+                if let Some(new_encoding) = switch_encoding {
+                    encoding = new_encoding;
                 }
             }
-            // Push session updates from polling loop
+            // Push session updates from polling loop, plus conversation
+            // updates for any sessions this client subscribed to
             Ok(sessions_json) = sessions_rx.recv() => {
-                let msg = ServerMsg::SessionsUpdated {
-                    data: serde_json::from_str(&sessions_json).unwrap_or_default(),
-                };
-                let json = serde_json::to_string(&msg).unwrap_or_default();
-                if socket.send(Message::Text(json)).await.is_err() {
-                    break;
+                let data: serde_json::Value =
+                    serde_json::from_str(&sessions_json).unwrap_or_default();
+
+                // Reading a transcript is a full parse, so keep it off the
+                // connection's task
+                let changed = subscriptions.changed_conversations(&state.store, &data);
+                let store = Arc::clone(&state.store);
+                let mut outgoing = tauri::async_runtime::spawn_blocking(move || {
+                    changed
+                        .into_iter()
+                        .filter_map(|session_id| {
+                            let conv = crate::conversation_payload(&store, &session_id, None).ok()?;
+                            Some(ServerMsg::ConversationUpdated {
+                                session_id,
+                                data: serde_json::to_value(&conv).unwrap_or_default(),
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await
+                .unwrap_or_default();
+                if subscriptions.sessions {
+                    outgoing.insert(0, ServerMsg::SessionsUpdated { data });
+                }
+
+                for msg in outgoing {
+                    if !transport.send_msg(&msg, encoding).await {
+                        break 'connection;
+                    }
                 }
             }
-            // Push notifications to WS clients
+            // Push notifications to clients
             Ok(notif_json) = notifications_rx.recv() => {
                 let msg = ServerMsg::Notification {
                     data: serde_json::from_str(&notif_json).unwrap_or_default(),
                 };
-                let json = serde_json::to_string(&msg).unwrap_or_default();
-                if socket.send(Message::Text(json)).await.is_err() {
+                if !transport.send_msg(&msg, encoding).await {
+                    break;
+                }
+            }
+            // Push alerts (budgets, etc.) to clients that understand them
+            Ok(alert_json) = alerts_rx.recv() => {
+                if !negotiated.contains("alerts") {
+                    continue;
+                }
+                let msg = ServerMsg::Alert {
+                    data: serde_json::from_str(&alert_json).unwrap_or_default(),
+                };
+                if !transport.send_msg(&msg, encoding).await {
                     break;
                 }
             }
         }
     }
 
-    eprintln!("[ws-server] Client disconnected");
+    info!("Client disconnected");
 }
 
 // ── Message dispatch ────────────────────────────────────────────────
 
-async fn handle_message(msg: ClientMsg) -> ServerMsg {
+async fn handle_message(msg: ClientMsg, state: &WsState) -> ServerMsg {
     match msg {
-        ClientMsg::GetSessions => match crate::polling::detect_and_enrich_sessions() {
-            Ok(sessions) => ServerMsg::Sessions {
-                data: serde_json::to_value(&sessions).unwrap_or_default(),
-            },
-            Err(e) => ServerMsg::Error { message: e },
-        },
+        ClientMsg::GetSessions { refresh } => {
+            let sessions = if refresh {
+                state.store.refresh().await
+            } else {
+                state.store.current().await
+            };
+            ServerMsg::Sessions {
+                data: serde_json::to_value(&*sessions).unwrap_or_default(),
+            }
+        }
 
-        ClientMsg::GetConversation { session_id } => {
-            match crate::get_conversation_data(&session_id) {
+        ClientMsg::GetConversation { session_id, filter } => {
+            match crate::conversation_payload(&state.store, &session_id, filter) {
                 Ok(conv) => ServerMsg::Conversation {
                     data: serde_json::to_value(&conv).unwrap_or_default(),
                 },
-                Err(e) => ServerMsg::Error { message: e },
+                Err(e) => C9Error::from(e).into(),
             }
         }
 
-        ClientMsg::StopSession { pid } => match crate::actions::stop_session(pid) {
-            Ok(()) => ServerMsg::Ok,
-            Err(e) => ServerMsg::Error { message: e },
-        },
+        ClientMsg::GetMessageContent {
+            session_id,
+            uuid,
+            tool_use_id,
+        } => {
+            match crate::message_content(&state.store, &session_id, &uuid, tool_use_id.as_deref()) {
+                Ok(content) => ServerMsg::MessageContent {
+                    data: serde_json::Value::String(content),
+                },
+                Err(e) => C9Error::from(e).into(),
+            }
+        }
+
+        ClientMsg::StopSession { pid, force } => {
+            let stopped = tauri::async_runtime::spawn_blocking(move || {
+                crate::actions::stop_session(pid, force)
+            })
+            .await
+            .map_err(|e| C9Error::Other(e.to_string()))
+            .and_then(|result| result);
+            match stopped {
+                Ok(result) => {
+                    state.store.request_refresh();
+                    ServerMsg::StopResult {
+                        data: serde_json::to_value(result).unwrap_or_default(),
+                    }
+                }
+                Err(e) => C9Error::from(e).into(),
+            }
+        }
 
         ClientMsg::OpenSession { pid, project_path } => {
             match crate::actions::open_session(pid, project_path) {
                 Ok(()) => ServerMsg::Ok,
-                Err(e) => ServerMsg::Error { message: e },
+                Err(e) => C9Error::from(e).into(),
+            }
+        }
+
+        ClientMsg::RespondToPermission {
+            pid,
+            approve,
+            tool_name,
+        } => {
+            let store = Arc::clone(&state.store);
+            let responded = tauri::async_runtime::spawn_blocking(move || {
+                crate::actions::respond_to_permission(&store, pid, approve, tool_name.as_deref())
+            })
+            .await
+            .map_err(|e| C9Error::Other(e.to_string()))
+            .and_then(|result| result);
+            match responded {
+                Ok(()) => ServerMsg::Ok,
+                Err(e) => C9Error::from(e).into(),
+            }
+        }
+
+        ClientMsg::QuitSessionGracefully { pid } => {
+            let store = Arc::clone(&state.store);
+            let quit = tauri::async_runtime::spawn_blocking(move || {
+                crate::actions::quit_session_gracefully(&store, pid)
+            })
+            .await
+            .map_err(|e| C9Error::Other(e.to_string()))
+            .and_then(|result| result);
+            match quit {
+                Ok(()) => ServerMsg::Ok,
+                Err(e) => C9Error::from(e).into(),
             }
         }
 
         ClientMsg::RenameSession {
             session_id,
             new_name,
-        } => {
-            let mut custom_titles = crate::session::CustomTitles::load();
-            custom_titles.set(session_id, new_name);
-            match custom_titles.save() {
+        } => match crate::metadata::rename(&state.store, session_id, new_name) {
+            Ok(()) => ServerMsg::Ok,
+            Err(e) => e.into(),
+        },
+
+        ClientMsg::OverrideStatus {
+            session_id,
+            status,
+            ttl,
+        } => match crate::metadata::override_status(&state.store, &session_id, status, ttl) {
+            Ok(()) => ServerMsg::Ok,
+            Err(e) => e.into(),
+        },
+
+        ClientMsg::SnoozeSession {
+            session_id,
+            minutes,
+        } => match crate::metadata::snooze(&state.store, &session_id, minutes) {
+            Ok(_) => ServerMsg::Ok,
+            Err(e) => e.into(),
+        },
+
+        ClientMsg::SetNote { session_id, text } => {
+            match crate::metadata::set_note(&state.store, session_id, text) {
                 Ok(()) => ServerMsg::Ok,
-                Err(e) => ServerMsg::Error { message: e },
+                Err(e) => e.into(),
             }
         }
+
+        ClientMsg::SetSessionColor { session_id, color } => {
+            match crate::metadata::set_color(&state.store, session_id, color) {
+                Ok(()) => ServerMsg::Ok,
+                Err(e) => e.into(),
+            }
+        }
+
+        ClientMsg::IgnoreSession { session_id } => {
+            match crate::metadata::ignore(&state.store, session_id) {
+                Ok(()) => ServerMsg::Ok,
+                Err(e) => e.into(),
+            }
+        }
+
+        ClientMsg::GetSessionTimeline { session_id } => {
+            match crate::tracking::tracker().timeline(&session_id) {
+                Some(timeline) => ServerMsg::SessionTimeline {
+                    data: serde_json::to_value(&timeline).unwrap_or_default(),
+                },
+                None => {
+                    C9Error::NotFound(format!("Session {} is not being tracked", session_id)).into()
+                }
+            }
+        }
+
+        ClientMsg::GetSessionStats { session_id } => {
+            match crate::stats::session_stats(&session_id) {
+                Ok(stats) => ServerMsg::SessionStats {
+                    data: serde_json::to_value(&stats).unwrap_or_default(),
+                },
+                Err(e) => C9Error::from(e).into(),
+            }
+        }
+
+        ClientMsg::GetSessionChanges { session_id } => {
+            match crate::stats::session_changes(&session_id) {
+                Ok(changes) => ServerMsg::SessionChanges {
+                    data: serde_json::to_value(&changes).unwrap_or_default(),
+                },
+                Err(e) => C9Error::from(e).into(),
+            }
+        }
+
+        ClientMsg::SearchConversations { query, limit } => {
+            let hits = crate::search::search_conversations(
+                &query,
+                limit.unwrap_or(crate::search::DEFAULT_LIMIT),
+            );
+            ServerMsg::SearchResults {
+                data: serde_json::to_value(&hits).unwrap_or_default(),
+            }
+        }
+
+        ClientMsg::GetTodos { session_id } => ServerMsg::Todos {
+            data: serde_json::to_value(crate::session::load_todos(&session_id)).unwrap_or_default(),
+        },
+
+        ClientMsg::GetUsageReport { range } => ServerMsg::UsageReport {
+            data: serde_json::to_value(crate::usage::generate_report(range)).unwrap_or_default(),
+        },
+        ClientMsg::GetParseDiagnostics => ServerMsg::ParseDiagnostics {
+            data: serde_json::to_value(crate::session::diagnostics().snapshot())
+                .unwrap_or_default(),
+        },
+        ClientMsg::GetApiStatus => ServerMsg::ApiStatus {
+            data: serde_json::to_value(crate::api_status::current()).unwrap_or_default(),
+        },
+        ClientMsg::GetRecentEvents { limit } => ServerMsg::RecentEvents {
+            data: serde_json::to_value(
+                crate::events::events().recent(limit.unwrap_or(crate::events::DEFAULT_LIMIT)),
+            )
+            .unwrap_or_default(),
+        },
+        // Handshake and subscriptions are per-connection state, handled in serve_connection
+        ClientMsg::Hello { .. } | ClientMsg::Subscribe { .. } | ClientMsg::Unsubscribe { .. } => {
+            ServerMsg::Ok
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn query(token: Option<&str>) -> WsQuery {
+        WsQuery {
+            token: token.map(|t| t.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_extract_token_prefers_headers_over_query() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::SEC_WEBSOCKET_PROTOCOL,
+            HeaderValue::from_static("c9watch, c9watch-token.abc123"),
+        );
+        assert_eq!(
+            extract_token(&headers, &query(Some("legacy"))).as_deref(),
+            Some("abc123")
+        );
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer xyz789"),
+        );
+        assert_eq!(
+            extract_token(&headers, &query(Some("legacy"))).as_deref(),
+            Some("xyz789")
+        );
+    }
+
+    #[test]
+    fn test_extract_token_falls_back_to_query() {
+        let headers = HeaderMap::new();
+        assert_eq!(
+            extract_token(&headers, &query(Some("legacy"))).as_deref(),
+            Some("legacy")
+        );
+        assert_eq!(extract_token(&headers, &query(None)), None);
+    }
+
+    #[test]
+    fn test_client_msg_session_id() {
+        let msg: ClientMsg = serde_json::from_str(
+            r#"{"type": "getConversation", "sessionId": "../../../etc/passwd"}"#,
+        )
+        .unwrap();
+        assert_eq!(msg.session_id(), Some("../../../etc/passwd"));
+        assert!(crate::session::validate_session_id(msg.session_id().unwrap()).is_err());
+
+        let msg: ClientMsg =
+            serde_json::from_str(r#"{"type": "subscribe", "topic": "sessions"}"#).unwrap();
+        assert_eq!(msg.session_id(), None);
+    }
+
+    #[test]
+    fn test_restart_onto_taken_port_keeps_old_server() {
+        let free_port = || {
+            std::net::TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap()
+                .port()
+        };
+        let localhost = |port| {
+            let settings = crate::settings::AppSettings {
+                mobile_access: false,
+                ..Default::default()
+            };
+            AccessPolicy::from_settings(&settings, port)
+        };
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let server = ServerRuntime::new(
+                "token".to_string(),
+                localhost(free_port()),
+                Arc::new(SessionStore::new()),
+                broadcast::channel(1).0,
+                broadcast::channel(1).0,
+                broadcast::channel(1).0,
+            );
+            let original = localhost(free_port());
+            server.restart(original.clone()).await.unwrap();
+
+            let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let taken_port = taken.local_addr().unwrap().port();
+            assert!(server.restart(localhost(taken_port)).await.is_err());
+
+            assert!(server.is_running().await);
+            assert_eq!(server.access().bind_addr, original.bind_addr);
+            assert!(tokio::net::TcpStream::connect(original.bind_addr)
+                .await
+                .is_ok());
+        });
     }
 }
@@ -0,0 +1,145 @@
+use crate::polling::Session;
+use crate::session::SessionStatus;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::sync::{Mutex, OnceLock};
+
+/// Sessions per status
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusCounts {
+    pub total: usize,
+    pub working: usize,
+    pub needs_permission: usize,
+    pub waiting_for_input: usize,
+    pub connecting: usize,
+}
+
+/// The one session a glance should point at
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UrgentSession {
+    pub id: String,
+    pub name: String,
+    pub project_path: String,
+    pub status: SessionStatus,
+    /// Tool waiting for approval, when status is NeedsPermission
+    pub pending_tool_name: Option<String>,
+}
+
+/// Payload of `GET /widget`, kept small for home-screen widgets and menu-bar
+/// tools. Deliberately free of ticking counters so the ETag only changes
+/// when something a widget would show changes.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WidgetSummary {
+    pub counts: StatusCounts,
+    pub most_urgent: Option<UrgentSession>,
+}
+
+/// Latest summary and its ETag, refreshed by the polling loop
+static WIDGET: OnceLock<Mutex<(WidgetSummary, String)>> = OnceLock::new();
+
+fn widget() -> &'static Mutex<(WidgetSummary, String)> {
+    WIDGET.get_or_init(|| {
+        let summary = WidgetSummary::default();
+        let etag = etag(&summary);
+        Mutex::new((summary, etag))
+    })
+}
+
+/// Record the sessions from a poll
+pub fn update(sessions: &[Session]) {
+    let summary = summarize(sessions);
+    let mut current = widget().lock().unwrap_or_else(|e| e.into_inner());
+    if current.0 != summary {
+        let etag = etag(&summary);
+        *current = (summary, etag);
+    }
+}
+
+/// Current summary and its ETag (quoted, ready for the header)
+pub fn current() -> (WidgetSummary, String) {
+    widget().lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn summarize(sessions: &[Session]) -> WidgetSummary {
+    let mut counts = StatusCounts {
+        total: sessions.len(),
+        ..Default::default()
+    };
+    for session in sessions {
+        match session.status {
+            SessionStatus::Working => counts.working += 1,
+            SessionStatus::NeedsPermission => counts.needs_permission += 1,
+            SessionStatus::WaitingForInput => counts.waiting_for_input += 1,
+            SessionStatus::Connecting => counts.connecting += 1,
+        }
+    }
+
+    // Longest-blocked permission prompt first, then whoever has been idle
+    // longest, then any working session
+    let most_urgent = sessions
+        .iter()
+        .filter(|s| s.status == SessionStatus::NeedsPermission)
+        .max_by_key(|s| s.needs_permission_seconds)
+        .or_else(|| {
+            sessions
+                .iter()
+                .filter(|s| s.status == SessionStatus::WaitingForInput)
+                .max_by_key(|s| s.idle_seconds)
+        })
+        .or_else(|| sessions.iter().find(|s| s.status == SessionStatus::Working))
+        .map(|session| UrgentSession {
+            id: session.id.clone(),
+            name: session.session_name.clone(),
+            project_path: session.project_path.clone(),
+            status: session.status.clone(),
+            pending_tool_name: session.pending_tool_name.clone(),
+        });
+
+    WidgetSummary {
+        counts,
+        most_urgent,
+    }
+}
+
+fn etag(summary: &WidgetSummary) -> String {
+    let json = serde_json::to_vec(summary).unwrap_or_default();
+    let digest = Sha256::digest(&json);
+    let hex: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("\"{}\"", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polling::tests::test_session;
+
+    fn session(id: &str, status: SessionStatus, waiting_seconds: u64) -> Session {
+        let mut session = test_session(status, 0);
+        session.id = id.to_string();
+        session.idle_seconds = waiting_seconds;
+        session.needs_permission_seconds = waiting_seconds;
+        session
+    }
+
+    #[test]
+    fn test_summary_prefers_longest_permission_prompt() {
+        let sessions = vec![
+            session("idle", SessionStatus::WaitingForInput, 900),
+            session("asked-late", SessionStatus::NeedsPermission, 10),
+            session("asked-early", SessionStatus::NeedsPermission, 60),
+            session("busy", SessionStatus::Working, 0),
+        ];
+        let summary = summarize(&sessions);
+        assert_eq!(summary.counts.total, 4);
+        assert_eq!(summary.counts.needs_permission, 2);
+        assert_eq!(summary.most_urgent.unwrap().id, "asked-early");
+
+        let quiet = summarize(&sessions[3..]);
+        assert_eq!(quiet.most_urgent.as_ref().unwrap().id, "busy");
+        assert_eq!(etag(&quiet), etag(&summarize(&sessions[3..])));
+        assert_ne!(etag(&quiet), etag(&summarize(&sessions)));
+    }
+}
@@ -0,0 +1,168 @@
+use crate::popover::PixelRect;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, Window, WindowEvent};
+use tracing::warn;
+
+/// Window layout restored at launch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowState {
+    /// Main window position and size, in physical pixels
+    #[serde(default)]
+    pub main: Option<PixelRect>,
+    #[serde(default)]
+    pub popover_pinned: bool,
+}
+
+impl WindowState {
+    pub fn load() -> Self {
+        let path = Self::get_path();
+        if let Ok(content) = fs::read_to_string(path) {
+            serde_json::from_str(&content).unwrap_or_default()
+        } else {
+            Self::default()
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::get_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| e.to_string())
+    }
+
+    fn get_path() -> PathBuf {
+        let home = dirs::home_dir().expect("Failed to get home directory");
+        home.join(".claude").join("session-monitor-windows.json")
+    }
+}
+
+static STATE: OnceLock<Mutex<WindowState>> = OnceLock::new();
+
+fn state() -> &'static Mutex<WindowState> {
+    STATE.get_or_init(|| Mutex::new(WindowState::load()))
+}
+
+/// Record the popover pin and write it out straight away
+pub fn set_popover_pinned(pinned: bool) {
+    let mut state = state().lock().unwrap_or_else(|e| e.into_inner());
+    state.popover_pinned = pinned;
+    if let Err(e) = state.save() {
+        warn!("Failed to save window state: {}", e);
+    }
+}
+
+/// Apply the saved layout. Call from setup, before the windows are shown.
+pub fn restore(app: &AppHandle) {
+    let saved = state().lock().unwrap_or_else(|e| e.into_inner()).clone();
+
+    if saved.popover_pinned {
+        if let Err(e) = crate::popover::set_pinned(app, true) {
+            warn!("Failed to restore popover pin: {}", e);
+        }
+    }
+
+    let (Some(geometry), Some(window)) = (saved.main, app.get_webview_window("main")) else {
+        return;
+    };
+    let _ = window.set_size(PhysicalSize::new(geometry.width, geometry.height));
+
+    // A display that has since been unplugged would leave the window
+    // off-screen; keep the default centered position then
+    let monitors: Vec<PixelRect> = app
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|monitor| PixelRect {
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+        })
+        .collect();
+    if is_reachable(geometry, &monitors) {
+        let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+    }
+}
+
+/// Builder `on_window_event` hook: track the main window's geometry and
+/// write it out when the window closes (including on quit)
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    if window.label() != "main" {
+        return;
+    }
+    match event {
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
+            // Minimized windows report bogus positions (-32000 on Windows),
+            // and maximized ones should come back at their normal size
+            if window.is_minimized().unwrap_or(false) || window.is_maximized().unwrap_or(false) {
+                return;
+            }
+            let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+                return;
+            };
+            state().lock().unwrap_or_else(|e| e.into_inner()).main = Some(PixelRect {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            });
+        }
+        WindowEvent::CloseRequested { .. } | WindowEvent::Destroyed => {
+            let state = state().lock().unwrap_or_else(|e| e.into_inner());
+            if let Err(e) = state.save() {
+                warn!("Failed to save window state: {}", e);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether enough of the window's top edge (where it's dragged from) lands
+/// on some monitor for it to be moved back into view
+fn is_reachable(window: PixelRect, monitors: &[PixelRect]) -> bool {
+    // Grab area that must be visible, in physical pixels
+    const GRAB: i32 = 40;
+    let window_right = window.x + window.width as i32;
+
+    monitors.iter().any(|monitor| {
+        let monitor_right = monitor.x + monitor.width as i32;
+        let monitor_bottom = monitor.y + monitor.height as i32;
+        let overlap = window_right.min(monitor_right) - window.x.max(monitor.x);
+        overlap >= GRAB && window.y >= monitor.y && window.y + GRAB <= monitor_bottom
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_position_must_be_on_a_monitor() {
+        let primary = PixelRect {
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        };
+        let at = |x, y| PixelRect {
+            x,
+            y,
+            width: 900,
+            height: 600,
+        };
+
+        assert!(is_reachable(at(100, 100), &[primary]));
+        // Hanging off the right edge but still grabbable
+        assert!(is_reachable(at(1800, 100), &[primary]));
+        // Saved on a second display that's no longer connected
+        assert!(!is_reachable(at(2200, 100), &[primary]));
+        assert!(!is_reachable(at(100, -300), &[primary]));
+        assert!(!is_reachable(at(100, 100), &[]));
+    }
+}
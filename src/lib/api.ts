@@ -5,48 +5,132 @@
 
 import { invoke } from '@tauri-apps/api/core';
 import { get } from 'svelte/store';
-import type { Session, Conversation } from './types';
+import type {
+	Session,
+	Conversation,
+	ConversationFilter,
+	IgnoreList,
+	StatusTransition,
+	SessionEvent,
+	PermissionCheck,
+	PermissionRules,
+	SessionColor,
+	TodoItem,
+	SessionStats,
+	SearchHit,
+	FileChange,
+	ReportRange,
+	UsageReport,
+	HistoryVacuumResult,
+	FileParseStats,
+	SubscriptionTopic,
+	AppSettings,
+	ApiStatus,
+	SessionStatus,
+	SessionMatch,
+	ShareLink,
+	StopResult,
+	C9ErrorCode
+} from './types';
 import { isDemoMode } from './demo';
 import { getDemoSessions, demoConversations } from './demo/data';
 import { wsClient, useWebSocket } from './ws';
 
 /**
- * Get all active Claude Code sessions
+ * Message of an error thrown by an API call, whether it came over Tauri IPC
+ * (a plain `C9Error` object) or the WebSocket (an `Error` with a `code`)
  */
-export async function getSessions(): Promise<Session[]> {
+export function errorMessage(error: unknown): string {
+	if (error && typeof error === 'object' && 'message' in error) {
+		return String(error.message);
+	}
+	return String(error);
+}
+
+/** `C9Error` code of an error thrown by an API call, if it has one */
+export function errorCode(error: unknown): C9ErrorCode | undefined {
+	if (error && typeof error === 'object' && 'code' in error) {
+		return error.code as C9ErrorCode;
+	}
+	return undefined;
+}
+
+/**
+ * Get all active Claude Code sessions, as of the latest poll. `refresh`
+ * polls again first.
+ */
+export async function getSessions(refresh = false): Promise<Session[]> {
 	if (get(isDemoMode)) return getDemoSessions();
 
 	if (useWebSocket()) {
-		return await wsClient.request<Session[]>('getSessions');
+		return await wsClient.request<Session[]>('getSessions', { refresh });
+	}
+	return await invoke<Session[]>('get_sessions', { refresh });
+}
+
+/**
+ * Fuzzy-match running sessions for the quick switcher, best first (desktop/Tauri only)
+ */
+export async function querySessions(text: string): Promise<SessionMatch[]> {
+	if (get(isDemoMode) || useWebSocket()) return [];
+	return await invoke<SessionMatch[]>('query_sessions', { text });
+}
+
+/**
+ * Full-text search over every transcript, most recently active sessions first
+ */
+export async function searchConversations(query: string, limit?: number): Promise<SearchHit[]> {
+	if (get(isDemoMode)) return [];
+
+	if (useWebSocket()) {
+		return await wsClient.request<SearchHit[]>('searchConversations', { query, limit });
 	}
-	return await invoke<Session[]>('get_sessions');
+	return await invoke<SearchHit[]>('search_conversations', { query, limit });
 }
 
 /**
  * Get the full conversation history for a specific session
  */
-export async function getConversation(sessionId: string): Promise<Conversation> {
+export async function getConversation(
+	sessionId: string,
+	filter?: ConversationFilter
+): Promise<Conversation> {
 	if (get(isDemoMode)) {
 		return demoConversations[sessionId] ?? { sessionId, messages: [] };
 	}
 
 	if (useWebSocket()) {
-		return await wsClient.request<Conversation>('getConversation', { sessionId });
+		return await wsClient.request<Conversation>('getConversation', { sessionId, filter });
 	}
-	return await invoke<Conversation>('get_conversation', { sessionId });
+	return await invoke<Conversation>('get_conversation', { sessionId, filter });
 }
 
 /**
- * Stop a running session by sending SIGTERM
+ * Fetch the full content of a message that arrived cut short (see Message.fullLength).
+ * Tool results also pass their toolUseId, since one entry can hold several.
  */
-export async function stopSession(pid: number): Promise<void> {
-	if (get(isDemoMode)) return;
+export async function getMessageContent(
+	sessionId: string,
+	uuid: string,
+	toolUseId?: string
+): Promise<string> {
+	if (useWebSocket()) {
+		return await wsClient.request<string>('getMessageContent', { sessionId, uuid, toolUseId });
+	}
+	return await invoke<string>('get_message_content', { sessionId, uuid, toolUseId });
+}
+
+/**
+ * Stop a running session by sending SIGTERM and waiting for it to exit.
+ * With `force`, a process that ignores SIGTERM is sent SIGKILL.
+ */
+export async function stopSession(pid: number, force = false): Promise<StopResult | null> {
+	if (get(isDemoMode)) return null;
 
 	if (useWebSocket()) {
-		await wsClient.request('stopSession', { pid });
-		return;
+		return await wsClient.request<StopResult>('stopSession', { pid, force });
 	}
-	await invoke<void>('stop_session', { pid });
+	return await invoke<StopResult>('stop_session', { pid, force });
 }
 
 /**
@@ -62,6 +146,156 @@ export async function openSession(pid: number, projectPath: string): Promise<voi
 	await invoke<void>('open_session', { pid, projectPath });
 }
 
+/**
+ * Copy a session's transcript to the clipboard (desktop app only)
+ */
+export async function copyConversation(
+	sessionId: string,
+	format: 'markdown' | 'text' = 'markdown'
+): Promise<void> {
+	if (get(isDemoMode) || useWebSocket()) return;
+	await invoke<void>('copy_conversation', { sessionId, format });
+}
+
+/**
+ * Publish a session's transcript at a single-use, temporary link (desktop app only).
+ * `ttl` is in seconds; the server defaults to an hour.
+ */
+export async function shareConversation(sessionId: string, ttl?: number): Promise<ShareLink | null> {
+	if (get(isDemoMode) || useWebSocket()) return null;
+	return invoke<ShareLink>('share_conversation', { sessionId, ttl });
+}
+
+/**
+ * Open a session's JSONL transcript in the default editor (desktop app only)
+ */
+export async function openSessionFile(sessionId: string): Promise<void> {
+	if (get(isDemoMode) || useWebSocket()) return;
+	await invoke<void>('open_session_file', { sessionId });
+}
+
+/**
+ * Reveal a session's JSONL transcript in Finder/Explorer (desktop app only)
+ */
+export async function revealSessionFile(sessionId: string): Promise<void> {
+	if (get(isDemoMode) || useWebSocket()) return;
+	await invoke<void>('reveal_session_file', { sessionId });
+}
+
+/**
+ * Follow one session in its own small always-on-top window (desktop app only)
+ */
+export async function openSessionWindow(sessionId: string): Promise<void> {
+	if (get(isDemoMode) || useWebSocket()) return;
+	await invoke<void>('open_session_window', { sessionId });
+}
+
+/**
+ * Keep the tray popover open when it loses focus (desktop app only)
+ */
+export async function setPopoverPinned(pinned: boolean): Promise<void> {
+	if (get(isDemoMode) || useWebSocket()) return;
+	await invoke<void>('set_popover_pinned', { pinned });
+}
+
+/**
+ * Whether the tray popover is pinned (desktop app only)
+ */
+export async function getPopoverPinned(): Promise<boolean> {
+	if (get(isDemoMode) || useWebSocket()) return false;
+	return await invoke<boolean>('get_popover_pinned');
+}
+
+/**
+ * Answer a session's pending permission prompt from its terminal. toolName is
+ * the tool the prompt was shown for; the answer is refused if it has moved on.
+ */
+export async function respondToPermission(
+	pid: number,
+	approve: boolean,
+	toolName?: string | null
+): Promise<void> {
+	if (get(isDemoMode)) return;
+
+	if (useWebSocket()) {
+		await wsClient.request('respondToPermission', { pid, approve, toolName });
+		return;
+	}
+	await invoke<void>('respond_to_permission', { pid, approve, toolName });
+}
+
+/**
+ * Quit a session by typing /exit into its terminal, letting Claude Code
+ * clean up instead of being signaled. Only works while it waits at its prompt
+ */
+export async function quitSessionGracefully(pid: number): Promise<void> {
+	if (get(isDemoMode)) return;
+
+	if (useWebSocket()) {
+		await wsClient.request('quitSessionGracefully', { pid });
+		return;
+	}
+	await invoke<void>('quit_session_gracefully', { pid });
+}
+
+/**
+ * Show a status for a session instead of the detected one, until ttl seconds
+ * pass (default an hour) or the detected status changes. A ttl of 0 clears it.
+ */
+export async function overrideStatus(
+	sessionId: string,
+	status: SessionStatus,
+	ttl?: number
+): Promise<void> {
+	if (get(isDemoMode)) return;
+
+	if (useWebSocket()) {
+		await wsClient.request('overrideStatus', { sessionId, status, ttl });
+		return;
+	}
+	await invoke<void>('override_status', { sessionId, status, ttl });
+}
+
+/**
+ * Hold back a session's notifications for some minutes (0 wakes it).
+ * Returns when notifications resume, if known.
+ */
+export async function snoozeSession(sessionId: string, minutes: number): Promise<string | null> {
+	if (get(isDemoMode)) return null;
+
+	if (useWebSocket()) {
+		await wsClient.request('snoozeSession', { sessionId, minutes });
+		return null;
+	}
+	return await invoke<string | null>('snooze_session', { sessionId, minutes });
+}
+
+/**
+ * Attach a note to a session; blank text removes it
+ */
+export async function setNote(sessionId: string, text: string): Promise<void> {
+	if (get(isDemoMode)) return;
+
+	if (useWebSocket()) {
+		await wsClient.request('setNote', { sessionId, text });
+		return;
+	}
+	await invoke<void>('set_note', { sessionId, text });
+}
+
+/**
+ * Give a session a color label; null removes it
+ */
+export async function setSessionColor(sessionId: string, color: SessionColor | null): Promise<void> {
+	if (get(isDemoMode)) return;
+
+	if (useWebSocket()) {
+		await wsClient.request('setSessionColor', { sessionId, color });
+		return;
+	}
+	await invoke<void>('set_session_color', { sessionId, color });
+}
+
 /**
  * Rename a session title
  */
@@ -75,6 +309,203 @@ export async function renameSession(sessionId: string, newName: string): Promise
 	await invoke<void>('rename_session', { sessionId, newName });
 }
 
+/**
+ * Get the list of ignored sessions and project patterns (desktop/Tauri only)
+ */
+export async function getIgnoreList(): Promise<IgnoreList> {
+	return await invoke<IgnoreList>('get_ignore_list');
+}
+
+/**
+ * Replace the ignore list (desktop/Tauri only)
+ */
+export async function setIgnoreList(ignoreList: IgnoreList): Promise<void> {
+	await invoke<void>('set_ignore_list', { ignoreList });
+}
+
+/**
+ * Hide a session from the monitor and its notifications
+ */
+export async function ignoreSession(sessionId: string): Promise<void> {
+	if (get(isDemoMode)) return;
+
+	if (useWebSocket()) {
+		await wsClient.request('ignoreSession', { sessionId });
+		return;
+	}
+	await invoke<void>('ignore_session', { sessionId });
+}
+
+/**
+ * Get the status transitions recorded for a session (activity timeline)
+ */
+export async function getSessionTimeline(sessionId: string): Promise<StatusTransition[]> {
+	if (get(isDemoMode)) return [];
+
+	if (useWebSocket()) {
+		return await wsClient.request<StatusTransition[]>('getSessionTimeline', { sessionId });
+	}
+	return await invoke<StatusTransition[]>('get_session_timeline', { sessionId });
+}
+
+/**
+ * Get a session's current TODO list
+ */
+export async function getTodos(sessionId: string): Promise<TodoItem[]> {
+	if (get(isDemoMode)) {
+		return getDemoSessions().find((s) => s.id === sessionId)?.todos ?? [];
+	}
+
+	if (useWebSocket()) {
+		return await wsClient.request<TodoItem[]>('getTodos', { sessionId });
+	}
+	return await invoke<TodoItem[]>('get_todos', { sessionId });
+}
+
+/**
+ * Get message counts, token usage, duration and per-tool call stats for a session
+ */
+export async function getSessionStats(sessionId: string): Promise<SessionStats | null> {
+	if (get(isDemoMode)) return null;
+
+	if (useWebSocket()) {
+		return await wsClient.request<SessionStats>('getSessionStats', { sessionId });
+	}
+	return await invoke<SessionStats>('get_session_stats', { sessionId });
+}
+
+/**
+ * Get the files a session modified, most recently changed first
+ */
+export async function getSessionChanges(sessionId: string): Promise<FileChange[]> {
+	if (get(isDemoMode)) return [];
+
+	if (useWebSocket()) {
+		return await wsClient.request<FileChange[]>('getSessionChanges', { sessionId });
+	}
+	return await invoke<FileChange[]>('get_session_changes', { sessionId });
+}
+
+/**
+ * Get aggregate usage (sessions, working time, tokens, cost) per project per day
+ */
+export async function getUsageReport(range: ReportRange): Promise<UsageReport> {
+	if (useWebSocket()) {
+		return await wsClient.request<UsageReport>('getUsageReport', { range });
+	}
+	return await invoke<UsageReport>('get_usage_report', { range });
+}
+
+/**
+ * Apply the history retention settings now and compact the history file (desktop/Tauri only)
+ */
+export async function vacuumHistory(): Promise<HistoryVacuumResult> {
+	return await invoke<HistoryVacuumResult>('vacuum_history');
+}
+
+/**
+ * Get JSONL parse diagnostics (failures, lenient recoveries, unknown fields) per session file
+ */
+export async function getParseDiagnostics(): Promise<FileParseStats[]> {
+	if (get(isDemoMode)) return [];
+
+	if (useWebSocket()) {
+		return await wsClient.request<FileParseStats[]>('getParseDiagnostics');
+	}
+	return await invoke<FileParseStats[]>('get_parse_diagnostics');
+}
+
+/**
+ * Get the latest Anthropic status page reading (null until the first check)
+ */
+export async function getApiStatus(): Promise<ApiStatus | null> {
+	if (get(isDemoMode)) return null;
+
+	if (useWebSocket()) {
+		return await wsClient.request<ApiStatus | null>('getApiStatus');
+	}
+	return await invoke<ApiStatus | null>('get_api_status');
+}
+
+/**
+ * Dry-run a tool call against the loaded permission rules (desktop/Tauri only)
+ */
+export async function checkPermission(toolName: string, input: unknown): Promise<PermissionCheck> {
+	return await invoke<PermissionCheck>('check_permission', { toolName, input });
+}
+
+/**
+ * Get the allow/deny rules loaded from ~/.claude/settings.json (desktop/Tauri only)
+ */
+export async function getPermissionRules(): Promise<PermissionRules> {
+	return await invoke<PermissionRules>('get_permission_rules');
+}
+
+/**
+ * Append an allow rule such as `Bash(cargo test:*)` to ~/.claude/settings.json
+ * (desktop/Tauri only). Blanket Bash rules and shell operators are refused.
+ */
+export async function addAllowRule(pattern: string): Promise<PermissionRules> {
+	return await invoke<PermissionRules>('add_allow_rule', { pattern });
+}
+
+/**
+ * Get the latest status changes and notifications across all sessions, newest first
+ */
+export async function getRecentEvents(limit?: number): Promise<SessionEvent[]> {
+	if (get(isDemoMode)) return [];
+
+	if (useWebSocket()) {
+		return await wsClient.request<SessionEvent[]>('getRecentEvents', { limit });
+	}
+	return await invoke<SessionEvent[]>('get_recent_events', { limit });
+}
+
+/**
+ * Opt in to a WebSocket push stream. 'sessions' is on by default; 'conversation'
+ * pushes conversationUpdated events for one session. No-op over Tauri IPC.
+ */
+export async function subscribe(topic: SubscriptionTopic, sessionId?: string): Promise<void> {
+	if (get(isDemoMode) || !useWebSocket()) return;
+	await wsClient.request('subscribe', { topic, sessionId });
+}
+
+/**
+ * Opt out of a WebSocket push stream (e.g. the session list while viewing one conversation)
+ */
+export async function unsubscribe(topic: SubscriptionTopic, sessionId?: string): Promise<void> {
+	if (get(isDemoMode) || !useWebSocket()) return;
+	await wsClient.request('unsubscribe', { topic, sessionId });
+}
+
+/**
+ * Get app settings (desktop/Tauri only)
+ */
+export async function getSettings(): Promise<AppSettings> {
+	return await invoke<AppSettings>('get_settings');
+}
+
+/**
+ * Save app settings (desktop/Tauri only)
+ */
+export async function updateSettings(settings: AppSettings): Promise<void> {
+	await invoke<void>('update_settings', { settings });
+}
+
+/**
+ * Export settings, custom names/titles and ignore list as one JSON bundle (desktop/Tauri only)
+ */
+export async function exportSettings(): Promise<string> {
+	return await invoke<string>('export_settings');
+}
+
+/**
+ * Replace the current setup with a bundle from exportSettings (desktop/Tauri only)
+ */
+export async function importSettings(bundle: string): Promise<void> {
+	await invoke<void>('import_settings', { bundle });
+}
+
 /**
  * Server connection info (desktop/Tauri only)
  */
@@ -83,8 +514,131 @@ export interface ServerInfo {
 	port: number;
 	localIp: string;
 	wsUrl: string;
+	/** False when the server only listens on localhost */
+	mobileAccess: boolean;
 }
 
 export async function getServerInfo(): Promise<ServerInfo> {
 	return await invoke<ServerInfo>('get_server_info');
 }
+
+/**
+ * Restart the web server so changed bind/LAN settings take effect.
+ * Connected mobile clients are disconnected and reconnect.
+ */
+export async function restartServer(): Promise<ServerInfo> {
+	return await invoke<ServerInfo>('restart_server');
+}
+
+/**
+ * A network interface the desktop can advertise to mobile clients
+ */
+export interface NetworkInterface {
+	name: string;
+	address: string;
+	kind: 'wifi' | 'ethernet' | 'vpn' | 'other';
+	/** The address used when no interface is chosen in settings */
+	isDefault: boolean;
+}
+
+export async function listNetworkInterfaces(): Promise<NetworkInterface[]> {
+	return await invoke<NetworkInterface[]>('list_network_interfaces');
+}
+
+/**
+ * A connection or request the web server turned away (desktop/Tauri only)
+ */
+export interface RejectedAttempt {
+	ip: string;
+	path: string;
+	reason: 'notAllowed' | 'rateLimited' | 'backedOff' | 'invalidToken' | 'invalidPairingCode';
+	/** RFC 3339 */
+	at: string;
+}
+
+export async function getRejectedConnections(): Promise<RejectedAttempt[]> {
+	return await invoke<RejectedAttempt[]>('get_rejected_connections');
+}
+
+/**
+ * Last lines of the app's rotated log files, for bug reports (desktop/Tauri only)
+ */
+export async function getRecentLogs(lines?: number): Promise<string[]> {
+	return await invoke<string[]>('get_recent_logs', { lines });
+}
+
+/**
+ * The Claude Code installation c9watch is monitoring (desktop/Tauri only)
+ */
+export interface EnvironmentInfo {
+	appVersion: string;
+	os: string;
+	arch: string;
+	claudePath: string | null;
+	/** As reported by `claude --version`, e.g. "2.0.14" */
+	claudeVersion: string | null;
+	/** Claude Code version (major.minor) the session parser was built against */
+	schemaVersion: string;
+	/** The installed CLI is newer than the parser, so some details may be missing */
+	newerThanSchema: boolean;
+}
+
+export async function getEnvironmentInfo(): Promise<EnvironmentInfo> {
+	return await invoke<EnvironmentInfo>('get_environment_info');
+}
+
+/**
+ * One self-diagnostic check (desktop/Tauri only)
+ */
+export interface DiagnosticCheck {
+	id: string;
+	title: string;
+	status: 'pass' | 'warn' | 'fail';
+	detail: string;
+	/** How to resolve the problem, when the check didn't pass */
+	fix: string | null;
+}
+
+export interface DiagnosticsReport {
+	/** False if any check failed (warnings don't count) */
+	ok: boolean;
+	checks: DiagnosticCheck[];
+	/** RFC 3339 */
+	generatedAt: string;
+}
+
+export async function runDiagnostics(): Promise<DiagnosticsReport> {
+	return await invoke<DiagnosticsReport>('run_diagnostics');
+}
+
+/**
+ * One-time pairing code for a mobile device (desktop/Tauri only)
+ */
+export interface PairingCode {
+	code: string;
+	expiresInSeconds: number;
+	/** URL to encode in the QR code */
+	pairUrl: string;
+}
+
+export async function createPairingCode(): Promise<PairingCode> {
+	return await invoke<PairingCode>('create_pairing_code');
+}
+
+/**
+ * A device that has exchanged a pairing code for its own credential
+ */
+export interface PairedDevice {
+	id: string;
+	name: string;
+	pairedAt: string;
+	lastSeen: string | null;
+}
+
+export async function listPairedDevices(): Promise<PairedDevice[]> {
+	return await invoke<PairedDevice[]>('list_paired_devices');
+}
+
+export async function revokeDevice(deviceId: string): Promise<void> {
+	await invoke<void>('revoke_device', { deviceId });
+}
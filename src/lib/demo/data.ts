@@ -77,30 +77,74 @@ export function getDemoSessions(): Session[] {
 			pid: 90001,
 			sessionName: 'web-app',
 			customTitle: null,
+			note: null,
+			color: 'teal',
 			projectPath: '/Users/demo/projects/web-app',
 			gitBranch: 'feat/auth-flow',
+			gitStatus: { changedFiles: 4, ahead: 2, behind: 0 },
 			firstPrompt: 'Add OAuth2 login with Google and GitHub providers',
 			summary: 'Implementing OAuth2 authentication flow with multiple providers',
+			lineage: [],
 			messageCount: 34,
 			modified: minutesAgo(2),
 			status: SessionStatus.NeedsPermission,
+			statusOverridden: false,
+			snoozedUntil: null,
 			latestMessage: 'I need to write to src/auth/providers.ts — may I proceed?',
-			pendingToolName: 'Write'
+			pendingToolName: 'Write',
+			durationSeconds: 5400,
+			workingSeconds: 2400,
+			idleSeconds: 2700,
+			needsPermissionSeconds: 300,
+			contextPercent: 42,
+			cpuPercent: 1.2,
+			memoryBytes: 327155712,
+			agent: 'claude',
+			telemetry: null,
+			todos: [
+				{ content: 'Add OAuth callback route', status: 'completed', activeForm: 'Adding OAuth callback route' },
+				{ content: 'Store refresh tokens', status: 'in_progress', activeForm: 'Storing refresh tokens' },
+				{ content: 'Write integration tests', status: 'pending', activeForm: 'Writing integration tests' }
+			],
+			permissionMode: 'default',
+			model: 'opus-4.5',
+			pendingToolInput: '/Users/demo/projects/web-app/src/auth/providers.ts',
+			mcpServers: []
 		},
 		{
 			id: 'demo-2',
 			pid: 90002,
 			sessionName: 'web-app',
 			customTitle: null,
+			note: null,
+			color: null,
 			projectPath: '/Users/demo/projects/web-app',
 			gitBranch: 'fix/perf-regression',
+			gitStatus: { changedFiles: 1, ahead: 0, behind: 0 },
 			firstPrompt: 'Profile and fix the rendering performance regression in the dashboard',
 			summary: 'Investigating slow renders in dashboard table component',
+			lineage: [],
 			messageCount: 87,
 			modified: minutesAgo(5),
 			status: SessionStatus.Working,
+			statusOverridden: false,
+			snoozedUntil: null,
 			latestMessage: 'Running the profiler on the VirtualizedTable component to identify the bottleneck...',
-			pendingToolName: null
+			pendingToolName: null,
+			durationSeconds: 3600,
+			workingSeconds: 1900,
+			idleSeconds: 1500,
+			needsPermissionSeconds: 200,
+			contextPercent: null,
+			cpuPercent: 0.4,
+			memoryBytes: 281018368,
+			agent: 'claude',
+			telemetry: null,
+			todos: [],
+			permissionMode: 'plan',
+			model: 'sonnet-4.5',
+			pendingToolInput: null,
+			mcpServers: [{ name: 'github', calls: 4, errors: 0, tools: ['create_pull_request', 'get_issue'], lastErrorAt: null }]
 		},
 
 		// Project 2: api-server — NeedsPermission + Working
@@ -109,30 +153,70 @@ export function getDemoSessions(): Session[] {
 			pid: 90003,
 			sessionName: 'api-server',
 			customTitle: null,
+			note: null,
+			color: null,
 			projectPath: '/Users/demo/projects/api-server',
 			gitBranch: 'feat/rate-limiting',
+			gitStatus: { changedFiles: 6, ahead: null, behind: null },
 			firstPrompt: 'Implement token-bucket rate limiting middleware',
-			summary: 'Adding rate limiting with Redis-backed token bucket',
+			summary: null,
+			lineage: [],
 			messageCount: 21,
 			modified: minutesAgo(1),
 			status: SessionStatus.NeedsPermission,
-			latestMessage: 'I need to run `npm install ioredis` — allow?',
-			pendingToolName: 'Bash'
+			statusOverridden: false,
+			snoozedUntil: null,
+			latestMessage: 'Executing shell...',
+			pendingToolName: 'shell',
+			durationSeconds: 2700,
+			workingSeconds: 1300,
+			idleSeconds: 1200,
+			needsPermissionSeconds: 200,
+			contextPercent: 87,
+			cpuPercent: 8.7,
+			memoryBytes: 420478976,
+			agent: 'codex',
+			telemetry: null,
+			todos: [],
+			permissionMode: 'default',
+			model: 'gpt-5-codex',
+			pendingToolInput: 'cargo test --package api-server rate_limit',
+			mcpServers: []
 		},
 		{
 			id: 'demo-4',
 			pid: 90004,
 			sessionName: 'api-server',
 			customTitle: null,
+			note: null,
+			color: null,
 			projectPath: '/Users/demo/projects/api-server',
 			gitBranch: 'main',
+			gitStatus: { changedFiles: 0, ahead: 0, behind: 3 },
 			firstPrompt: 'Write integration tests for the payments webhook handler',
 			summary: 'Creating comprehensive test suite for Stripe webhook processing',
+			lineage: [],
 			messageCount: 156,
 			modified: minutesAgo(8),
 			status: SessionStatus.Working,
+			statusOverridden: false,
+			snoozedUntil: null,
 			latestMessage: 'Writing test case for subscription renewal webhook with idempotency check...',
-			pendingToolName: null
+			pendingToolName: null,
+			durationSeconds: 10800,
+			workingSeconds: 6100,
+			idleSeconds: 4300,
+			needsPermissionSeconds: 400,
+			contextPercent: 12,
+			cpuPercent: 0.0,
+			memoryBytes: 267386880,
+			agent: 'claude',
+			telemetry: null,
+			todos: [],
+			permissionMode: 'acceptEdits',
+			model: 'sonnet-4.5',
+			pendingToolInput: null,
+			mcpServers: [{ name: 'postgres', calls: 7, errors: 3, tools: ['query'], lastErrorAt: minutesAgo(2) }]
 		},
 
 		// Project 3: cli-tools — WaitingForInput x2
@@ -141,30 +225,70 @@ export function getDemoSessions(): Session[] {
 			pid: 90005,
 			sessionName: 'cli-tools',
 			customTitle: null,
+			note: null,
+			color: null,
 			projectPath: '/Users/demo/projects/cli-tools',
 			gitBranch: 'feat/config-wizard',
+			gitStatus: { changedFiles: 2, ahead: 1, behind: 0 },
 			firstPrompt: 'Build an interactive configuration wizard for first-time setup',
 			summary: 'Created interactive CLI wizard with prompts, validation, and config file generation',
+			lineage: [],
 			messageCount: 42,
 			modified: minutesAgo(15),
 			status: SessionStatus.WaitingForInput,
+			statusOverridden: false,
+			snoozedUntil: null,
 			latestMessage: 'Done! The wizard is at src/commands/init.ts. Run `cli-tools init` to try it.',
-			pendingToolName: null
+			pendingToolName: null,
+			durationSeconds: 1800,
+			workingSeconds: 900,
+			idleSeconds: 850,
+			needsPermissionSeconds: 50,
+			contextPercent: null,
+			cpuPercent: 0.3,
+			memoryBytes: 303038464,
+			agent: 'claude',
+			telemetry: null,
+			todos: [],
+			permissionMode: 'bypassPermissions',
+			model: 'haiku-4.5',
+			pendingToolInput: null,
+			mcpServers: []
 		},
 		{
 			id: 'demo-6',
 			pid: 90006,
 			sessionName: 'cli-tools',
 			customTitle: null,
+			note: null,
+			color: null,
 			projectPath: '/Users/demo/projects/cli-tools',
 			gitBranch: 'refactor/error-handling',
+			gitStatus: { changedFiles: 9, ahead: 5, behind: 0 },
 			firstPrompt: 'Refactor error handling to use typed Result pattern',
 			summary: 'Migrated all error handling from try/catch to Result<T, E> pattern',
+			lineage: [],
 			messageCount: 63,
 			modified: minutesAgo(60),
 			status: SessionStatus.WaitingForInput,
+			statusOverridden: false,
+			snoozedUntil: null,
 			latestMessage: 'All 14 command handlers have been migrated to the Result pattern. Tests pass.',
-			pendingToolName: null
+			pendingToolName: null,
+			durationSeconds: 7200,
+			workingSeconds: 3200,
+			idleSeconds: 3950,
+			needsPermissionSeconds: 50,
+			contextPercent: 63,
+			cpuPercent: 3.1,
+			memoryBytes: 360710144,
+			agent: 'claude',
+			telemetry: null,
+			todos: [],
+			permissionMode: 'default',
+			model: 'opus-4.5',
+			pendingToolInput: null,
+			mcpServers: []
 		}
 	];
 }
@@ -197,11 +321,13 @@ export const demoConversations: Record<string, Conversation> = {
 			{
 				timestamp: minutesAgo(28),
 				messageType: 'ToolUse',
-				content: 'Read src/auth/index.ts'
+				content: 'Read src/auth/index.ts',
+				toolCall: { id: 'toolu_demo_1', name: 'Read', input: { file_path: 'src/auth/index.ts' } }
 			},
 			{
 				timestamp: minutesAgo(28),
 				messageType: 'ToolResult',
+				toolResult: { toolUseId: 'toolu_demo_1', toolName: 'Read', isError: false },
 				content:
 					'```typescript\nimport { Router } from "express";\nimport { sessionMiddleware } from "./session";\n\nexport const authRouter = Router();\nauthRouter.use(sessionMiddleware);\n\n// TODO: Add OAuth providers\n```'
 			},
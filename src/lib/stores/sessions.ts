@@ -3,12 +3,13 @@
  */
 
 import { writable, derived, get } from 'svelte/store';
+import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { isPermissionGranted, requestPermission } from '@tauri-apps/plugin-notification';
-import type { Session, Conversation } from '../types';
+import type { Session, Conversation, ApiStatus } from '../types';
 import { SessionStatus } from '../types';
 import { isDemoMode } from '../demo';
-import { openSession } from '../api';
+import { openSession, getApiStatus } from '../api';
 import { wsClient, useWebSocket, getStoredWsUrl, isTauri } from '../ws';
 
 /**
@@ -31,6 +32,11 @@ export const currentConversation = writable<Conversation | null>(null);
  */
 export const notificationPermission = writable<'granted' | 'denied' | 'default'>('default');
 
+/**
+ * Latest Anthropic status page reading (null until checked or when disabled)
+ */
+export const apiStatus = writable<ApiStatus | null>(null);
+
 /**
  * In-app toast notifications (for web clients without Notification API)
  */
@@ -127,6 +133,9 @@ export async function initializeSessionListeners() {
 	} else {
 		await initTauriListeners();
 	}
+	getApiStatus()
+		.then((status) => apiStatus.set(status))
+		.catch((e) => console.error('[api-status] Failed to load:', e));
 }
 
 // ── WebSocket mode ──────────────────────────────────────────────────
@@ -151,10 +160,59 @@ async function initWebSocketListeners() {
 		}
 	});
 
-	wsClient.on('notification', (data: { title: string; body: string; sessionId: string; pid: number }) => {
+	wsClient.on('notification', (data: { title: string; body: string; sessionId?: string; sessionIds?: string[]; pid?: number }) => {
 		if (get(isDemoMode)) return;
 		showInAppNotification(data.title, data.body);
 	});
+
+	wsClient.on('alert', (data: { kind: string; status?: ApiStatus | null }) => {
+		if (data.kind === 'apiStatus') {
+			apiStatus.set(data.status ?? null);
+		}
+	});
+
+	// Mobile app: the Rust side keeps its own connection, which outlives the
+	// WebView being suspended
+	if (isTauri()) {
+		await initMobileConnection(wsUrl);
+	}
+}
+
+/** Latest state held by the mobile app's Rust-side connection */
+interface DesktopSnapshot {
+	connected: boolean;
+	sessions: Session[] | null;
+	updatedAt: string | null;
+}
+
+async function initMobileConnection(wsUrl: string) {
+	try {
+		await invoke('connect_desktop', { url: wsUrl });
+	} catch (e) {
+		console.error('[mobile] Native connection unavailable:', e);
+		return;
+	}
+
+	await listen<Session[]>('desktop-sessions-updated', (event) => {
+		if (!get(isDemoMode)) {
+			sessions.set(event.payload);
+		}
+	});
+
+	const onVisibilityChange = async () => {
+		const foreground = document.visibilityState === 'visible';
+		await invoke('set_app_foreground', { foreground }).catch(() => {});
+		if (!foreground || get(isDemoMode)) return;
+
+		// Repaint from what arrived while suspended; the WebView's own socket
+		// may still be reconnecting
+		const snapshot = await invoke<DesktopSnapshot>('get_desktop_snapshot').catch(() => null);
+		if (snapshot?.sessions) {
+			sessions.set(snapshot.sessions);
+		}
+	};
+	document.addEventListener('visibilitychange', onVisibilityChange);
+	await onVisibilityChange();
 }
 
 // ── Tauri IPC mode ──────────────────────────────────────────────────
@@ -166,10 +224,23 @@ async function initTauriListeners() {
 		}
 	});
 
+	await listen<ApiStatus | null>('api-status-changed', (event) => {
+		apiStatus.set(event.payload);
+	});
+
 	await listen<Conversation>('conversation-updated', (event) => {
 		currentConversation.set(event.payload);
 	});
 
+	// c9watch://session/<id> links
+	await listen<string>('open-session-link', (event) => {
+		expandedSessionId.set(event.payload);
+	});
+	const linkedSessionId = await invoke<string | null>('take_pending_session_link');
+	if (linkedSessionId) {
+		expandedSessionId.set(linkedSessionId);
+	}
+
 	await listen<NotificationMetadata>('notification-fired', (event) => {
 		if (!get(isDemoMode)) {
 			const metadata = event.payload;
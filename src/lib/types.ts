@@ -12,6 +12,11 @@ export enum SessionStatus {
   Connecting = 'Connecting'            // Session starting up
 }
 
+/**
+ * Color labels a session can be given with setSessionColor
+ */
+export type SessionColor = 'red' | 'orange' | 'yellow' | 'green' | 'teal' | 'blue' | 'purple' | 'pink';
+
 /**
  * A Claude Code session
  */
@@ -19,7 +24,10 @@ export interface Session {
   /** Session UUID */
   id: string;
 
-  /** Process ID of the running Claude instance */
+  /** Agent CLI running the session ("claude", "codex", ...) */
+  agent: string;
+
+  /** Process ID of the running agent instance */
   pid: number;
 
   /** Custom session name (defaults to project directory name) - shown as small badge */
@@ -28,18 +36,30 @@ export interface Session {
   /** Custom title override for the session - if set, shown instead of summary/firstPrompt */
   customTitle: string | null;
 
+  /** User's note on the session, e.g. "waiting on PR review" */
+  note: string | null;
+
+  /** User's color label, to pick the session out at a glance */
+  color: SessionColor | null;
+
   /** Full path to project directory */
   projectPath: string;
 
   /** Git branch name (if available) */
   gitBranch: string | null;
 
+  /** Uncommitted work and ahead/behind counts of the cwd's repository, null outside a repository */
+  gitStatus: GitStatus | null;
+
   /** Summary of the first prompt (shown in list view) */
   firstPrompt: string;
 
   /** AI-generated summary of the session (from sessions-index.json) */
   summary: string | null;
 
+  /** Earlier sessions this one was resumed from, oldest first */
+  lineage: string[];
+
   /** Total number of messages in the conversation */
   messageCount: number;
 
@@ -49,17 +69,229 @@ export interface Session {
   /** Current status of the session */
   status: SessionStatus;
 
+  /** Status was set by hand (overrideStatus) rather than detected */
+  statusOverridden: boolean;
+
+  /** When the session's notifications resume (ISO 8601), if snoozed */
+  snoozedUntil: string | null;
+
   /** Content of the latest message */
   latestMessage: string;
 
   /** Name of the tool currently awaiting user permission (if status is NeedsPermission) */
   pendingToolName: string | null;
+
+  /** What the pending tool wants to do: the command, file path or arguments (truncated) */
+  pendingToolInput: string | null;
+
+  /** Seconds since the Claude process started */
+  durationSeconds: number;
+
+  /** Seconds spent Working while c9watch was observing */
+  workingSeconds: number;
+
+  /** Seconds spent WaitingForInput while c9watch was observing */
+  idleSeconds: number;
+
+  /** Seconds spent in NeedsPermission while c9watch was observing */
+  needsPermissionSeconds: number;
+
+  /** Estimated context window usage (0-100), null if unknown */
+  contextPercent: number | null;
+
+  /** Exact figures from Claude Code's OpenTelemetry export, null unless the OTLP receiver is on */
+  telemetry: SessionTelemetry | null;
+
+  /** The session's TodoWrite list (empty if it has none) */
+  todos: TodoItem[];
+
+  /** Permission mode, null when the agent doesn't record one */
+  permissionMode: PermissionMode | null;
+
+  /** Model used for the latest reply (e.g. "opus-4.5"), null if unknown */
+  model: string | null;
+
+  /** MCP servers the session has called, by name */
+  mcpServers: McpServerUsage[];
+
+  /** CPU use of the agent process since the last poll, in percent of one core */
+  cpuPercent: number;
+
+  /** Resident memory of the agent process, in bytes */
+  memoryBytes: number;
+}
+
+/**
+ * One MCP server a session has called (tools named mcp__<server>__<tool>)
+ */
+export interface McpServerUsage {
+  name: string;
+  calls: number;
+
+  /** Calls whose result was an error */
+  errors: number;
+
+  /** Tools used on this server, sorted */
+  tools: string[];
+
+  /** ISO timestamp of the latest error, null if none */
+  lastErrorAt: string | null;
+}
+
+/**
+ * Working-tree state of a session's repository
+ */
+export interface GitStatus {
+  /** Tracked files with staged or unstaged changes */
+  changedFiles: number;
+
+  /** Commits not pushed upstream, null without an upstream */
+  ahead: number | null;
+
+  /** Upstream commits not merged, null without an upstream */
+  behind: number | null;
+}
+
+/**
+ * Claude Code permission mode (Shift+Tab / --permission-mode / --dangerously-skip-permissions)
+ */
+export type PermissionMode = 'default' | 'plan' | 'acceptEdits' | 'bypassPermissions';
+
+/**
+ * State of a TODO item
+ */
+export type TodoStatus = 'pending' | 'in_progress' | 'completed';
+
+/**
+ * One entry of a session's TODO list (written by Claude's TodoWrite tool)
+ */
+export interface TodoItem {
+  content: string;
+  status: TodoStatus;
+
+  /** Present-tense label shown while the item is in progress */
+  activeForm: string | null;
+}
+
+/**
+ * Call counts and durations for one tool, from the session transcript
+ */
+export interface ToolStats {
+  name: string;
+  calls: number;
+  /** Calls whose result was an error */
+  failures: number;
+  /** Calls still waiting for a result */
+  pending: number;
+  /** Time from tool call to result, over completed calls */
+  totalMs: number;
+  maxMs: number;
+}
+
+/**
+ * The same tool call (same input) failing repeatedly
+ */
+export interface RepeatedFailure {
+  toolName: string;
+  /** Summary of the input (command, file, arguments) */
+  input: string;
+  failures: number;
+  /** ISO timestamp of the latest failure */
+  lastFailedAt: string;
+}
+
+/**
+ * A file the agent modified, from its Edit/Write/NotebookEdit calls
+ */
+export interface FileChange {
+  path: string;
+  /** Successful Edit, MultiEdit and NotebookEdit calls */
+  edits: number;
+  /** Successful Write calls (file created or overwritten) */
+  writes: number;
+  /** ISO timestamp of the latest change */
+  lastChangedAt: string;
+}
+
+/**
+ * Transcript messages by kind, counted the way the conversation view splits them
+ */
+export interface MessageCounts {
+  user: number;
+  assistant: number;
+  thinking: number;
+  toolUse: number;
+  toolResult: number;
+  system: number;
+}
+
+/**
+ * Message, token and tool analytics for a session
+ */
+export interface SessionStats {
+  sessionId: string;
+  messages: MessageCounts;
+  tokens: TokenCounts;
+  /** From the first to the latest transcript entry */
+  durationSeconds: number;
+  /** API errors Claude Code recorded (overloaded, rate limited, ...) */
+  apiErrors: number;
+  toolCalls: number;
+  toolFailures: number;
+  /** Most used first */
+  tools: ToolStats[];
+  /** Most failures first */
+  repeatedFailures: RepeatedFailure[];
+}
+
+/**
+ * Per-tool call counts and timings from telemetry
+ */
+export interface ToolLatency {
+  calls: number;
+  failures: number;
+  totalMs: number;
+  maxMs: number;
+}
+
+/**
+ * A failed API request reported by telemetry
+ */
+export interface ApiError {
+  /** ISO 8601 */
+  timestamp: string;
+  message: string;
+  statusCode: string | null;
+  model: string | null;
+}
+
+/**
+ * What Claude Code's telemetry has reported for a session
+ */
+export interface SessionTelemetry {
+  inputTokens: number;
+  outputTokens: number;
+  cacheCreationInputTokens: number;
+  cacheReadInputTokens: number;
+
+  /** Cost reported by Claude Code, USD */
+  costUsd: number;
+
+  /** API requests made and total time spent waiting on them */
+  apiRequests: number;
+  apiDurationMs: number;
+
+  /** Most recent last */
+  apiErrors: ApiError[];
+
+  /** Keyed by tool name */
+  tools: Record<string, ToolLatency>;
 }
 
 /**
  * Message type in conversation
  */
-export type MessageType = 'User' | 'Assistant' | 'Thinking' | 'ToolUse' | 'ToolResult';
+export type MessageType = 'User' | 'Assistant' | 'Thinking' | 'ToolUse' | 'ToolResult' | 'System';
 
 /**
  * A message in a conversation
@@ -73,6 +305,74 @@ export interface Message {
 
   /** Message content text */
   content: string;
+
+  /** Structured diff for file edit tool calls; content then only names the file */
+  edit?: EditDiff;
+
+  /** Transcript entry the message came from (Claude sessions only) */
+  uuid?: string;
+
+  /** Entry this one follows in the conversation tree; absent at the root */
+  parentUuid?: string;
+
+  /** Full length in characters when content was cut short; see getMessageContent */
+  fullLength?: number;
+
+  /** Tokens used by the API response; only on the last message it produced */
+  usage?: MessageUsage;
+
+  /** Tool name and arguments, on ToolUse messages */
+  toolCall?: ToolCall;
+
+  /** Call answered and whether it failed, on ToolResult messages */
+  toolResult?: ToolResultInfo;
+}
+
+/**
+ * A tool call with its arguments as the model sent them
+ */
+export interface ToolCall {
+  id: string;
+  name: string;
+  /** Absent on file edits; see Message.edit */
+  input?: unknown;
+}
+
+/**
+ * Which tool call a result answers, and how it went
+ */
+export interface ToolResultInfo {
+  toolUseId: string;
+  /** Absent when the call isn't in the same transcript */
+  toolName?: string;
+  isError: boolean;
+}
+
+/**
+ * Token usage of one API response, as reported in the transcript
+ */
+export interface MessageUsage {
+  inputTokens: number | null;
+  outputTokens: number | null;
+  cacheCreationInputTokens: number | null;
+  cacheReadInputTokens: number | null;
+}
+
+/**
+ * One replacement made by a file edit tool call
+ */
+export interface EditHunk {
+  /** Empty when the file or cell is written from scratch */
+  oldString: string;
+  newString: string;
+}
+
+/**
+ * An Edit/MultiEdit/Write/NotebookEdit call, ready for diff rendering
+ */
+export interface EditDiff {
+  filePath: string;
+  hunks: EditHunk[];
 }
 
 /**
@@ -84,4 +384,442 @@ export interface Conversation {
 
   /** Array of messages in chronological order */
   messages: Message[];
+
+  /** Places the session was rewound; absent when it never was */
+  branches?: BranchPoint[];
+}
+
+/**
+ * A place where the conversation was rewound and taken another way
+ */
+export interface BranchPoint {
+  /** Entry the alternatives all follow */
+  parentUuid: string;
+
+  /** First entry uuid of each alternative, oldest first */
+  alternatives: string[];
+
+  /** Index into alternatives of the one shown; pass another as ConversationFilter.branch to see it */
+  selected: number;
 }
+
+/**
+ * Trimming options for getConversation, to keep small-screen payloads light
+ */
+export interface ConversationFilter {
+  excludeThinking?: boolean;
+  excludeToolResults?: boolean;
+
+  /** Longest message content to keep, in characters; longer ones end in "..." */
+  maxMessageLength?: number;
+
+  /** Any entry uuid on the branch to show; the session's current branch by default */
+  branch?: string;
+}
+
+/**
+ * Sessions and projects hidden from the monitor (and from notifications)
+ */
+export interface IgnoreList {
+  /** Exact session IDs to hide */
+  sessionIds: string[];
+
+  /** Glob patterns matched against the session's project path */
+  projectPatterns: string[];
+}
+
+/**
+ * A single status change in a session's activity timeline
+ */
+export interface StatusTransition {
+  /** When the new status was first observed (ISO 8601 string) */
+  timestamp: string;
+
+  /** Previous status (null for the first observation) */
+  from: SessionStatus | null;
+
+  /** New status */
+  to: SessionStatus;
+}
+
+/**
+ * Result of checkPermission: how c9watch would treat a tool call
+ */
+export interface PermissionCheck {
+  /** Whether the call runs without a permission prompt */
+  autoApproved: boolean;
+
+  /** Allow rules that match the call, as written in settings.json */
+  matchedRules: string[];
+
+  /** Why, in one sentence */
+  reason: string;
+}
+
+/**
+ * A rule from Claude Code's settings.json permissions block
+ */
+export interface PermissionRule {
+  /** As written in settings.json, e.g. "Bash(git add:*)" */
+  rule: string;
+
+  kind: 'bash' | 'tool' | 'mcp' | 'skill';
+}
+
+/**
+ * The permission rules c9watch loaded, from getPermissionRules
+ */
+export interface PermissionRules {
+  /** settings.json the rules came from, absent if there isn't one */
+  source?: string;
+
+  allow: PermissionRule[];
+  deny: PermissionRule[];
+}
+
+/**
+ * A status change or notification from any session, from getRecentEvents
+ */
+export interface SessionEvent {
+  /** ISO 8601 */
+  time: string;
+  kind: 'statusChanged' | 'notification';
+  sessionId: string;
+
+  /** Custom title if set, otherwise the session name */
+  sessionName: string;
+  projectPath: string;
+
+  /** Absent when the session just appeared */
+  from?: SessionStatus;
+
+  /** Absent once the session has ended */
+  to?: SessionStatus;
+
+  /** Notification body */
+  message?: string;
+}
+
+/**
+ * Time range for a usage report
+ */
+export type ReportRange = 'day' | 'week';
+
+/**
+ * Token counts summed over one or more assistant messages
+ */
+export interface TokenCounts {
+  inputTokens: number;
+  outputTokens: number;
+  cacheCreationInputTokens: number;
+  cacheReadInputTokens: number;
+}
+
+/**
+ * Usage for one project on one day
+ */
+export interface ProjectDayUsage extends TokenCounts {
+  /** Local date (YYYY-MM-DD) */
+  date: string;
+  projectPath: string;
+  sessionsRun: number;
+  workingSeconds: number;
+  /** Estimated cost at list prices */
+  costUsd: number;
+}
+
+/**
+ * Aggregate usage report over a date range
+ */
+export interface UsageReport extends TokenCounts {
+  startDate: string;
+  endDate: string;
+  sessionsRun: number;
+  workingSeconds: number;
+  costUsd: number;
+  /** Per-project, per-day breakdown */
+  days: ProjectDayUsage[];
+
+  /** Per-model breakdown, highest estimated cost first */
+  models: ModelUsage[];
+}
+
+/**
+ * Usage attributed to a single model over a report range
+ */
+export interface ModelUsage extends TokenCounts {
+  /** Model ID from the assistant message (e.g. claude-sonnet-4-5-20250929) */
+  model: string;
+
+  /** Number of assistant messages from this model */
+  messageCount: number;
+
+  /** Estimated cost at list prices */
+  costUsd: number;
+}
+
+/**
+ * User-configurable app settings
+ */
+export interface AppSettings {
+  /** Send a usage summary notification every Monday */
+  weeklyDigest: boolean;
+
+  /** Token budget for the current day (alerts at 80% and 100%) */
+  dailyTokenBudget?: number | null;
+
+  /** Token budget for the current week, starting Monday */
+  weeklyTokenBudget?: number | null;
+
+  /** Estimated cost budget in USD for the current day */
+  dailyCostBudget?: number | null;
+
+  /** Estimated cost budget in USD for the current week, starting Monday */
+  weeklyCostBudget?: number | null;
+
+  /** Let phones and other machines connect; when off the server listens on localhost only */
+  mobileAccess: boolean;
+
+  /** Specific interface address to listen on instead of all interfaces */
+  bindAddress?: string | null;
+
+  /** CIDR ranges allowed to connect (e.g. "192.168.1.0/24"); empty allows any peer */
+  allowedCidrs: string[];
+
+  /** Interface whose address goes in the QR code (e.g. "en0"); null uses the default route */
+  advertisedInterface?: string | null;
+
+  /** Run a local OTLP receiver (localhost:4318) for Claude Code telemetry; applies on next launch */
+  otlpReceiver?: boolean;
+
+  /** Statuspage summary.json polled for API incidents; empty turns the check off */
+  statusPageUrl?: string;
+
+  /** Skip the native notification when the session's terminal is already in front */
+  suppressWhenFocused?: boolean;
+
+  /** Hold back native notifications for every session (Shortcuts, AppleScript, c9watch://dnd) */
+  doNotDisturb?: boolean;
+
+  /** List sessions with no messages yet as Connecting instead of hiding them */
+  showEmptySessions?: boolean;
+
+  /** Set the terminal tab's title (iTerm2, kitty, WezTerm) when a session is renamed */
+  syncTerminalTitle?: boolean;
+
+  /** Alert when a session keeps a CPU core busy this many minutes; null turns it off */
+  cpuAlertMinutes?: number | null;
+
+  /** Drop usage history older than this many days (default 90); null keeps it forever */
+  historyRetentionDays?: number | null;
+
+  /** Drop the oldest usage history once the file passes this many MB (default 500); null lets it grow */
+  historyMaxMb?: number | null;
+
+  /** Mask likely secrets in everything sent to WS/mobile clients and share links (default on) */
+  redactSecrets?: boolean;
+
+  /** Extra regexes to redact; a `secret` named group masks just that part */
+  redactionPatterns?: string[];
+
+  /** Characters of the first prompt shown per session (default 100) */
+  promptPreviewChars?: number;
+
+  /** Characters of the latest message shown per session (default 200) */
+  messagePreviewChars?: number;
+
+  /** Characters of the first prompt used as a notification title (default 60) */
+  notificationTitleChars?: number;
+}
+
+/**
+ * Result of compacting the usage history file
+ */
+export interface HistoryVacuumResult {
+  /** Days of history dropped by the retention settings */
+  daysRemoved: number;
+  bytesBefore: number;
+  bytesAfter: number;
+}
+
+/**
+ * An unresolved incident on the Anthropic status page
+ */
+export interface Incident {
+  id: string;
+  name: string;
+
+  /** "investigating", "identified", "monitoring"... */
+  status: string;
+
+  /** "none" | "minor" | "major" | "critical" */
+  impact: string;
+
+  /** Incident page, if linked */
+  url: string | null;
+
+  /** ISO timestamp */
+  startedAt: string;
+}
+
+/**
+ * Anthropic API health from the status page
+ */
+export interface ApiStatus {
+  /** "none" when all systems are operational, otherwise "minor" | "major" | "critical" */
+  indicator: string;
+
+  /** e.g. "All Systems Operational" */
+  description: string;
+  incidents: Incident[];
+}
+
+/**
+ * Quick-switcher result from querySessions
+ */
+export interface SessionMatch {
+  session: Session;
+
+  /** Higher is better; results arrive sorted */
+  score: number;
+
+  /** Which part of the session matched best */
+  field: 'title' | 'name' | 'projectPath' | 'firstPrompt';
+}
+
+/**
+ * Part of a search snippet; matched spans are the query words
+ */
+export interface SnippetSpan {
+  text: string;
+  matched: boolean;
+}
+
+/**
+ * A session whose transcript matched searchConversations
+ */
+export interface SearchHit {
+  sessionId: string;
+  projectPath: string;
+
+  /** When the matching message was sent (RFC 3339) */
+  timestamp: string;
+
+  /** Text around the first match */
+  snippet: SnippetSpan[];
+}
+
+/**
+ * How a stopped session's process ended. `stillRunning` means it ignored
+ * SIGTERM; stopping again with `force` sends SIGKILL.
+ */
+export interface StopResult {
+  outcome: 'terminated' | 'killed' | 'stillRunning';
+
+  /** Milliseconds from SIGTERM until the process was gone, or until giving up */
+  elapsedMs: number;
+}
+
+/** Kind of failure reported by a command or WS request */
+export type C9ErrorCode =
+  | 'notFound'
+  | 'permissionDenied'
+  | 'parseError'
+  | 'processError'
+  | 'io'
+  | 'other';
+
+/**
+ * What a failed command rejects with (Tauri IPC), and the fields of a WS
+ * `error` message
+ */
+export interface C9Error {
+  code: C9ErrorCode;
+  message: string;
+}
+
+/**
+ * Temporary read-only transcript link served by the desktop's web server
+ */
+export interface ShareLink {
+  url: string;
+
+  /** ISO 8601 */
+  expiresAt: string;
+}
+
+/**
+ * A usage budget threshold that was crossed
+ */
+export interface BudgetAlert {
+  period: 'day' | 'week';
+  metric: 'tokens' | 'cost';
+
+  /** Threshold that was crossed (80 or 100) */
+  thresholdPercent: number;
+
+  /** Tokens or USD used so far in the period */
+  used: number;
+
+  /** Configured budget for the period */
+  limit: number;
+}
+
+/**
+ * A session whose process kept a CPU core busy for the configured minutes
+ */
+export interface CpuAlert {
+  sessionId: string;
+  sessionName: string;
+  pid: number;
+  cpuPercent: number;
+  minutes: number;
+}
+
+/**
+ * Alert pushed to WebSocket clients
+ */
+export interface Alert {
+  kind: 'budget' | 'cpu';
+  title: string;
+  body: string;
+  budget?: BudgetAlert;
+  cpu?: CpuAlert;
+}
+
+/**
+ * Result of the most recent parse of a session JSONL file
+ */
+export interface FileParseStats {
+  /** Path of the JSONL file */
+  path: string;
+
+  /** Lines that matched the expected schema */
+  parsed: number;
+
+  /** Lines that only parsed after lenient repair */
+  recovered: number;
+
+  /** Lines that could not be parsed and were dropped */
+  failures: number;
+
+  /** Entry types not recognised by c9watch, with counts */
+  unknownTypes: Record<string, number>;
+
+  /** Unrecognised top-level fields on user/assistant/system entries, with counts */
+  unknownFields: Record<string, number>;
+
+  /** Error from the last failed line */
+  lastError: string | null;
+
+  /** Truncated copy of the last failed line */
+  lastErrorLine: string | null;
+
+  /** When the file was last parsed (ISO 8601 string) */
+  updatedAt: string;
+}
+
+/**
+ * WebSocket push streams a client can subscribe to
+ */
+export type SubscriptionTopic = 'sessions' | 'conversation';
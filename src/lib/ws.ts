@@ -4,6 +4,19 @@
 
 type EventCallback = (data: any) => void;
 
+/** WS protocol version this client speaks (see PROTOCOL_VERSION in web_server.rs) */
+const PROTOCOL_VERSION = 1;
+
+/** Optional protocol features this client understands */
+const CLIENT_CAPABILITIES = ['alerts', 'subscriptions', 'conversationUpdates'];
+
+/** Server side of the hello handshake */
+export interface ServerHello {
+	protocolVersion: number;
+	serverVersion: string;
+	capabilities: string[];
+}
+
 class WsClient {
 	private ws: WebSocket | null = null;
 	private url: string = '';
@@ -12,16 +25,28 @@ class WsClient {
 	private pendingReject: ((reason: any) => void) | null = null;
 	private listeners = new Map<string, Set<EventCallback>>();
 	private reconnectTimer: ReturnType<typeof setTimeout> | null = null;
+	private _server: ServerHello | null = null;
 
 	get isConnected() {
 		return this._connected;
 	}
 
+	/** Handshake result, or null for servers that predate the hello message */
+	get server() {
+		return this._server;
+	}
+
+	/** Whether the connected server advertised an optional protocol feature */
+	supports(capability: string): boolean {
+		return this._server?.capabilities.includes(capability) ?? false;
+	}
+
 	async connect(url: string): Promise<void> {
 		this.url = url;
 		return new Promise((resolve, reject) => {
 			try {
-				this.ws = new WebSocket(url);
+				const { url: bareUrl, protocols } = withTokenSubprotocol(url);
+				this.ws = new WebSocket(bareUrl, protocols);
 			} catch (e) {
 				reject(e);
 				return;
@@ -36,7 +61,7 @@ class WsClient {
 				clearTimeout(timeout);
 				this._connected = true;
 				console.log('[ws] Connected');
-				resolve();
+				this.handshake().finally(() => resolve());
 			};
 
 			this.ws.onerror = () => {
@@ -54,6 +79,18 @@ class WsClient {
 		});
 	}
 
+	/** Exchange protocol versions and capabilities; older servers reply with an error */
+	private async handshake() {
+		try {
+			this._server = await this.request<ServerHello>('hello', {
+				protocolVersion: PROTOCOL_VERSION,
+				capabilities: CLIENT_CAPABILITIES
+			});
+		} catch {
+			this._server = null;
+		}
+	}
+
 	disconnect() {
 		if (this.reconnectTimer) {
 			clearTimeout(this.reconnectTimer);
@@ -99,10 +136,19 @@ class WsClient {
 				this.emit('notification', msg.data);
 				return;
 			}
+			if (msg.type === 'alert') {
+				this.emit('alert', msg.data);
+				return;
+			}
+			if (msg.type === 'conversationUpdated') {
+				this.emit('conversationUpdated', { sessionId: msg.sessionId, conversation: msg.data });
+				return;
+			}
 
 			// Request-response: resolve or reject the pending promise
 			if (msg.type === 'error') {
-				this.pendingReject?.(new Error(msg.message));
+				// Same `code` as a rejected Tauri command (see C9Error)
+				this.pendingReject?.(Object.assign(new Error(msg.message), { code: msg.code }));
 			} else {
 				this.pendingResolve?.(msg.data ?? msg);
 			}
@@ -143,6 +189,22 @@ class WsClient {
 
 export const wsClient = new WsClient();
 
+/**
+ * Move the auth token from the URL query into the Sec-WebSocket-Protocol
+ * header so it doesn't end up in server or proxy logs.
+ */
+function withTokenSubprotocol(url: string): { url: string; protocols: string[] } {
+	try {
+		const parsed = new URL(url);
+		const token = parsed.searchParams.get('token');
+		if (!token) return { url, protocols: [] };
+		parsed.searchParams.delete('token');
+		return { url: parsed.toString(), protocols: ['c9watch', `c9watch-token.${token}`] };
+	} catch {
+		return { url, protocols: [] };
+	}
+}
+
 // ── Transport helpers ────────────────────────────────────────────────
 
 /** Check if running inside Tauri desktop (not just bundled JS with the property) */